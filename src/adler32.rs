@@ -0,0 +1,121 @@
+//! Provides the Adler-32 checksum (as used by zlib).
+
+use checksum::{Checksum, ChecksumReader, ChecksumWriter};
+
+const MODULUS: u32 = 65521;
+
+/// The largest number of bytes which can be summed before the sums may overflow.
+const NMAX: usize = 5552;
+
+/// Calculates the Adler-32 incrementally.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::adler32::Adler32;
+///
+/// let mut adler = Adler32::new();
+/// adler.update(b"Wiki");
+/// adler.update(b"pedia");
+/// assert_eq!(0x11E60398, adler.digest());
+///
+/// ```
+///
+/// Calculates the checksum while reading.
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::adler32::Adler32Reader;
+/// use mm_binary_io::binary_read::BinaryRead;
+///
+/// let mut reader = Adler32Reader::new(io::Cursor::new(b"123456789".to_vec()));
+/// reader.read_byte_array(9).unwrap();
+/// assert_eq!(0x091E01DE, reader.digest());
+///
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    /// Creates a new `Adler32` with no data.
+    pub fn new() -> Adler32 {
+        Adler32 { a: 1, b: 0 }
+    }
+
+    /// Updates the checksum with `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        let mut a = self.a;
+        let mut b = self.b;
+        for chunk in bytes.chunks(NMAX) {
+            for &x in chunk {
+                a += x as u32;
+                b += a;
+            }
+            a %= MODULUS;
+            b %= MODULUS;
+        }
+        self.a = a;
+        self.b = b;
+    }
+
+    /// Returns the checksum of the data so far.
+    pub fn digest(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+
+    /// Resets the checksum to the initial state.
+    pub fn reset(&mut self) {
+        *self = Adler32::new();
+    }
+}
+
+impl Default for Adler32 {
+    fn default() -> Adler32 {
+        Adler32::new()
+    }
+}
+
+impl Checksum for Adler32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        Adler32::update(self, bytes)
+    }
+
+    fn digest(&self) -> u32 {
+        Adler32::digest(self)
+    }
+
+    fn reset(&mut self) {
+        Adler32::reset(self)
+    }
+}
+
+/// The reader which calculates the Adler-32 of the bytes read.
+pub type Adler32Reader<TRead> = ChecksumReader<TRead, Adler32>;
+
+/// The writer which calculates the Adler-32 of the bytes written.
+pub type Adler32Writer<TWrite> = ChecksumWriter<TWrite, Adler32>;
+
+/// Calculates the Adler-32 of `bytes`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::adler32;
+///
+/// assert_eq!(0x091E01DE, adler32::adler32(b"123456789"));
+/// assert_eq!(1, adler32::adler32(b""));
+/// assert_eq!(0x149A302C, adler32::adler32(&vec![0xFF; 100_000]));
+///
+/// ```
+///
+pub fn adler32(bytes: &[u8]) -> u32 {
+    let mut adler = Adler32::new();
+    adler.update(bytes);
+    adler.digest()
+}
@@ -0,0 +1,400 @@
+//! Provides the traits and the futures shared by `async_tokio` and `async_futures`, so that each
+//! of them only polls its runtime's reader.
+
+use std::io;
+use std::mem;
+
+use endian::Endian;
+use read_integer::ReadInteger;
+
+/// The byte count of the integers staged in `IntegerBuf` without allocation.
+const INLINE_INTEGER_LEN: usize = 16;
+
+/// The buffer of an integer being read, sized by its type, so that the wider `ReadInteger`
+/// implementations fit.
+pub(crate) enum IntegerBuf {
+    Inline([u8; INLINE_INTEGER_LEN]),
+    Heap(Vec<u8>),
+}
+
+impl IntegerBuf {
+    /// Creates a new buffer of the size of `TInt`.
+    pub(crate) fn new<TInt>() -> IntegerBuf {
+        let len = mem::size_of::<TInt>();
+        if len <= INLINE_INTEGER_LEN {
+            IntegerBuf::Inline([0; INLINE_INTEGER_LEN])
+        } else {
+            IntegerBuf::Heap(vec![0; len])
+        }
+    }
+
+    /// Returns the buffer of the size of `TInt`.
+    pub(crate) fn get_mut<TInt>(&mut self) -> &mut [u8] {
+        match *self {
+            IntegerBuf::Inline(ref mut buf) => &mut buf[..mem::size_of::<TInt>()],
+            IntegerBuf::Heap(ref mut buf) => buf,
+        }
+    }
+}
+
+/// Decodes a `TInt` from the head of `buf`, which is at least `mem::size_of::<TInt>()` bytes.
+pub(crate) fn integer<TEndian, TInt>(buf: &[u8]) -> io::Result<TInt>
+    where TEndian: Endian,
+          TInt: ReadInteger<OutputType=TInt> {
+    TInt::read_integer::<TEndian>(&mut &buf[..mem::size_of::<TInt>()])
+}
+
+/// Returns the byte count of an array of `element_count` `TInt`, or None if it overflows.
+pub(crate) fn integer_array_len<TInt>(element_count: usize) -> Option<usize> {
+    element_count.checked_mul(mem::size_of::<TInt>())
+}
+
+/// Returns the error of an array whose byte count overflows.
+pub(crate) fn integer_array_too_long() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "integer array is too long")
+}
+
+/// Decodes the `TInt` array of `bytes`.
+pub(crate) fn integer_array<TEndian, TInt>(bytes: &[u8]) -> io::Result<Vec<TInt>>
+    where TEndian: Endian,
+          TInt: ReadInteger<OutputType=TInt> {
+    let mut reader = bytes;
+    let mut result = Vec::with_capacity(bytes.len() / mem::size_of::<TInt>().max(1));
+    while !reader.is_empty() {
+        result.push(TInt::read_integer::<TEndian>(&mut reader)?);
+    }
+    Ok(result)
+}
+
+/// Decodes the UTF-8 string of `bytes`.
+pub(crate) fn string(bytes: Vec<u8>) -> io::Result<String> {
+    String::from_utf8(bytes)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8 string"))
+}
+
+/// Encodes the bytes written by the future of a write method.
+pub(crate) fn encode<TEncode>(encode: TEncode) -> io::Result<Vec<u8>>
+    where TEncode: FnOnce(&mut Vec<u8>) -> io::Result<()> {
+    let mut buf = vec![];
+    encode(&mut buf)?;
+    Ok(buf)
+}
+
+/// Defines `AsyncBinaryRead`, `AsyncBinaryWrite` and their futures over the runtime's `$read` and
+/// `$write`, with the doc attributes of the traits, which differ in the runtime of the examples.
+///
+/// The names used by the items, and `poll_fill` which fills a buffer by the runtime's
+/// `poll_read`, must be in scope where it is invoked.
+macro_rules! async_binary_io {
+    (read($read:ident) { $(#[$read_attr:meta])* } write($write:ident) { $(#[$write_attr:meta])* }) => {
+        $(#[$read_attr])*
+        pub trait AsyncBinaryRead: $read + Unpin {
+            /// Reads an integer.
+            ///
+            /// # Errors
+            ///
+            /// If the stream ends before the integer, the future outputs Err(io::Error) of
+            /// `UnexpectedEof`.
+            ///
+            fn read_integer<TEndian, TInt>(&mut self) -> ReadIntegerFuture<'_, Self, TEndian, TInt>
+                where TEndian: Endian,
+                      TInt: ReadInteger<OutputType=TInt> {
+                ReadIntegerFuture {
+                    reader: self,
+                    buf: async_common::IntegerBuf::new::<TInt>(),
+                    filled: 0,
+                    _integer: PhantomData,
+                }
+            }
+
+            /// Reads byte array.
+            ///
+            /// # Arguments
+            ///
+            /// * byte_count - the byte count of the array.
+            ///
+            fn read_byte_array(&mut self, byte_count: usize) -> ReadByteArrayFuture<'_, Self> {
+                ReadByteArrayFuture {
+                    reader: self,
+                    buf: vec![0; byte_count],
+                    filled: 0,
+                }
+            }
+
+            /// Reads an integer array.
+            ///
+            /// # Arguments
+            ///
+            /// * element_count - the number of elements in the array.
+            ///
+            fn read_integer_array<TEndian, TInt>(&mut self, element_count: usize) -> ReadIntegerArrayFuture<'_, Self, TEndian, TInt>
+                where TEndian: Endian,
+                      TInt: ReadInteger<OutputType=TInt> {
+                let byte_count = async_common::integer_array_len::<TInt>(element_count);
+                ReadIntegerArrayFuture {
+                    bytes: self.read_byte_array(byte_count.unwrap_or(0)),
+                    too_long: byte_count.is_none(),
+                    _integer: PhantomData,
+                }
+            }
+
+            /// Reads a UTF-8 string of `byte_count` bytes.
+            ///
+            /// # Errors
+            ///
+            /// If the bytes are not valid UTF-8, the future outputs Err(io::Error) of `InvalidData`.
+            ///
+            fn read_string(&mut self, byte_count: usize) -> ReadStringFuture<'_, Self> {
+                ReadStringFuture {
+                    bytes: self.read_byte_array(byte_count),
+                }
+            }
+
+            /// Reads an unsigned LEB128 variable-length integer.
+            fn read_varint_u64(&mut self) -> ReadVarintFuture<'_, Self> {
+                ReadVarintFuture {
+                    reader: self,
+                    buf: [0; MAX_VARINT_LEN],
+                    len: 0,
+                }
+            }
+
+            /// Reads a ZigZag encoded signed LEB128 variable-length integer.
+            fn read_varint_i64(&mut self) -> ReadVarintI64Future<'_, Self> {
+                ReadVarintI64Future {
+                    varint: self.read_varint_u64(),
+                }
+            }
+        }
+
+        impl<T> AsyncBinaryRead for T
+            where T: $read + Unpin + ?Sized {}
+
+        $(#[$write_attr])*
+        pub trait AsyncBinaryWrite: $write + Unpin {
+            /// Writes the `value`.
+            fn write_integer<TEndian, TInt>(&mut self, value: TInt) -> WriteBytesFuture<'_, Self>
+                where TEndian: Endian,
+                      TInt: WriteInteger {
+                WriteBytesFuture::new(self, encode(|buf| BinaryWrite::write_integer::<TEndian, _>(buf, value)))
+            }
+
+            /// Writes the integer array.
+            fn write_integer_array<TEndian, TInt>(&mut self, values: &[TInt]) -> WriteBytesFuture<'_, Self>
+                where TEndian: Endian,
+                      TInt: WriteInteger {
+                WriteBytesFuture::new(self, encode(|buf| BinaryWrite::write_integer_array::<TEndian, _>(buf, values)))
+            }
+
+            /// Writes the UTF-8 bytes of `value`.
+            fn write_string(&mut self, value: &str) -> WriteBytesFuture<'_, Self> {
+                WriteBytesFuture::new(self, Ok(value.as_bytes().to_vec()))
+            }
+
+            /// Writes a length-prefixed frame of `payload`.
+            ///
+            /// # Errors
+            ///
+            /// If the payload length does not fit in `TLen`, the future outputs Err(io::Error) of
+            /// `InvalidInput`.
+            ///
+            fn write_frame<TEndian, TLen>(&mut self, payload: &[u8]) -> WriteBytesFuture<'_, Self>
+                where TEndian: Endian,
+                      TLen: WriteInteger + TryFrom<u64> {
+                WriteBytesFuture::new(self, encode(|buf| BinaryWrite::write_frame::<TEndian, TLen>(buf, payload)))
+            }
+
+            /// Writes an unsigned LEB128 variable-length integer.
+            fn write_varint_u64(&mut self, value: u64) -> WriteBytesFuture<'_, Self> {
+                WriteBytesFuture::new(self, encode(|buf| varint::write_u64(buf, value)))
+            }
+
+            /// Writes a ZigZag encoded signed LEB128 variable-length integer.
+            fn write_varint_i64(&mut self, value: i64) -> WriteBytesFuture<'_, Self> {
+                WriteBytesFuture::new(self, encode(|buf| varint::write_i64(buf, value)))
+            }
+        }
+
+        impl<T> AsyncBinaryWrite for T
+            where T: $write + Unpin + ?Sized {}
+
+        /// The future of `AsyncBinaryRead::read_integer`.
+        pub struct ReadIntegerFuture<'a, TRead, TEndian, TInt>
+            where TRead: $read + Unpin + ?Sized + 'a {
+            reader: &'a mut TRead,
+            buf: async_common::IntegerBuf,
+            filled: usize,
+            _integer: PhantomData<fn() -> (TEndian, TInt)>,
+        }
+
+        impl<'a, TRead, TEndian, TInt> Future for ReadIntegerFuture<'a, TRead, TEndian, TInt>
+            where TRead: $read + Unpin + ?Sized,
+                  TEndian: Endian,
+                  TInt: ReadInteger<OutputType=TInt> {
+            type Output = io::Result<TInt>;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<TInt>> {
+                let this = self.get_mut();
+                let buf = this.buf.get_mut::<TInt>();
+                match poll_fill(&mut *this.reader, cx, buf, &mut this.filled) {
+                    Poll::Ready(Ok(())) => Poll::Ready(async_common::integer::<TEndian, TInt>(buf)),
+                    Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+                    Poll::Pending => Poll::Pending,
+                }
+            }
+        }
+
+        /// The future of `AsyncBinaryRead::read_byte_array`.
+        pub struct ReadByteArrayFuture<'a, TRead>
+            where TRead: $read + Unpin + ?Sized + 'a {
+            reader: &'a mut TRead,
+            buf: Vec<u8>,
+            filled: usize,
+        }
+
+        impl<'a, TRead> Future for ReadByteArrayFuture<'a, TRead>
+            where TRead: $read + Unpin + ?Sized {
+            type Output = io::Result<Vec<u8>>;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<Vec<u8>>> {
+                let this = self.get_mut();
+                match poll_fill(&mut *this.reader, cx, &mut this.buf, &mut this.filled) {
+                    Poll::Ready(Ok(())) => Poll::Ready(Ok(mem::take(&mut this.buf))),
+                    Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+                    Poll::Pending => Poll::Pending,
+                }
+            }
+        }
+
+        /// The future of `AsyncBinaryRead::read_integer_array`.
+        pub struct ReadIntegerArrayFuture<'a, TRead, TEndian, TInt>
+            where TRead: $read + Unpin + ?Sized + 'a {
+            bytes: ReadByteArrayFuture<'a, TRead>,
+            too_long: bool,
+            _integer: PhantomData<fn() -> (TEndian, TInt)>,
+        }
+
+        impl<'a, TRead, TEndian, TInt> Future for ReadIntegerArrayFuture<'a, TRead, TEndian, TInt>
+            where TRead: $read + Unpin + ?Sized,
+                  TEndian: Endian,
+                  TInt: ReadInteger<OutputType=TInt> {
+            type Output = io::Result<Vec<TInt>>;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<Vec<TInt>>> {
+                let this = self.get_mut();
+                if this.too_long {
+                    return Poll::Ready(Err(async_common::integer_array_too_long()));
+                }
+                Pin::new(&mut this.bytes).poll(cx)
+                    .map(|result| result.and_then(|bytes| async_common::integer_array::<TEndian, TInt>(&bytes)))
+            }
+        }
+
+        /// The future of `AsyncBinaryRead::read_string`.
+        pub struct ReadStringFuture<'a, TRead>
+            where TRead: $read + Unpin + ?Sized + 'a {
+            bytes: ReadByteArrayFuture<'a, TRead>,
+        }
+
+        impl<'a, TRead> Future for ReadStringFuture<'a, TRead>
+            where TRead: $read + Unpin + ?Sized {
+            type Output = io::Result<String>;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<String>> {
+                Pin::new(&mut self.get_mut().bytes).poll(cx)
+                    .map(|result| result.and_then(async_common::string))
+            }
+        }
+
+        /// The future of `AsyncBinaryRead::read_varint_u64`.
+        pub struct ReadVarintFuture<'a, TRead>
+            where TRead: $read + Unpin + ?Sized + 'a {
+            reader: &'a mut TRead,
+            buf: [u8; MAX_VARINT_LEN],
+            len: usize,
+        }
+
+        impl<'a, TRead> Future for ReadVarintFuture<'a, TRead>
+            where TRead: $read + Unpin + ?Sized {
+            type Output = io::Result<u64>;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<u64>> {
+                let this = self.get_mut();
+                // Reads a byte at a time so that no byte after the varint is consumed.
+                while this.len == 0 || (this.buf[this.len - 1] & 0x80 != 0 && this.len < MAX_VARINT_LEN) {
+                    let mut filled = 0;
+                    match poll_fill(&mut *this.reader, cx, &mut this.buf[this.len..this.len + 1], &mut filled) {
+                        Poll::Ready(Ok(())) => this.len += 1,
+                        Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                Poll::Ready(varint::read_u64(&mut &this.buf[..this.len]))
+            }
+        }
+
+        /// The future of `AsyncBinaryRead::read_varint_i64`.
+        pub struct ReadVarintI64Future<'a, TRead>
+            where TRead: $read + Unpin + ?Sized + 'a {
+            varint: ReadVarintFuture<'a, TRead>,
+        }
+
+        impl<'a, TRead> Future for ReadVarintI64Future<'a, TRead>
+            where TRead: $read + Unpin + ?Sized {
+            type Output = io::Result<i64>;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<i64>> {
+                Pin::new(&mut self.get_mut().varint).poll(cx)
+                    .map(|result| result.map(varint::zigzag_decode))
+            }
+        }
+
+        /// The future of the methods of `AsyncBinaryWrite`, which writes the encoded bytes.
+        pub struct WriteBytesFuture<'a, TWrite>
+            where TWrite: $write + Unpin + ?Sized + 'a {
+            writer: &'a mut TWrite,
+            error: Option<io::Error>,
+            buf: Vec<u8>,
+            written: usize,
+        }
+
+        impl<'a, TWrite> WriteBytesFuture<'a, TWrite>
+            where TWrite: $write + Unpin + ?Sized {
+            fn new(writer: &'a mut TWrite, encoded: io::Result<Vec<u8>>) -> WriteBytesFuture<'a, TWrite> {
+                let (buf, error) = match encoded {
+                    Ok(buf) => (buf, None),
+                    Err(e) => (vec![], Some(e)),
+                };
+                WriteBytesFuture {
+                    writer,
+                    error,
+                    buf,
+                    written: 0,
+                }
+            }
+        }
+
+        impl<'a, TWrite> Future for WriteBytesFuture<'a, TWrite>
+            where TWrite: $write + Unpin + ?Sized {
+            type Output = io::Result<()>;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+                let this = self.get_mut();
+                if let Some(e) = this.error.take() {
+                    return Poll::Ready(Err(e));
+                }
+                while this.written < this.buf.len() {
+                    match Pin::new(&mut *this.writer).poll_write(cx, &this.buf[this.written..]) {
+                        Poll::Ready(Ok(0)) => {
+                            return Poll::Ready(Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer")));
+                        }
+                        Poll::Ready(Ok(n)) => this.written += n,
+                        Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                Poll::Ready(Ok(()))
+            }
+        }
+    };
+}
@@ -0,0 +1,410 @@
+//! Provides the async counterparts of `FileRead` and `FileWrite` over `tokio::fs::File`.
+//!
+//! The streaming reads and writes are buffered, and the positional reads and writes run on the
+//! blocking thread pool of tokio, like `tokio::fs`. They need the tokio runtime.
+//!
+//! The positional IO uses a clone of the file handle. On unix it does not touch the cursor; on
+//! the other platforms the shared cursor is moved and then restored, so a positional read must
+//! not run concurrently with a streaming read of the same `AsyncFileRead`. `write_all_at`
+//! flushes the streaming writes before it starts.
+//!
+//! This module is available with the `tokio` feature.
+
+use std::fs;
+use std::future::Future;
+use std::io;
+use std::io::Seek;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncSeek, AsyncWrite, BufReader, BufWriter, ReadBuf};
+use tokio::task::{self, JoinHandle};
+
+use checksum::Checksum;
+use file::{self, FILE_BUFFER_SIZE};
+
+/// The buffered async file reader which supports positional reads.
+///
+/// `AsyncFileRead` implements `AsyncRead`, `AsyncBufRead` and `AsyncSeek`, so all
+/// `AsyncBinaryRead` methods can be used. The positional reads (`read_exact_at`,
+/// `checksum_range`) do not change the current position.
+///
+/// # Examples
+///
+/// ```
+/// extern crate mm_binary_io;
+/// extern crate tokio;
+///
+/// use std::env;
+/// use std::fs;
+/// use mm_binary_io::async_file::AsyncFileRead;
+/// use mm_binary_io::async_tokio::AsyncBinaryRead;
+/// use mm_binary_io::crc32::Crc32;
+/// use mm_binary_io::endian::BigEndian;
+///
+/// # fn main() {
+/// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+///
+/// let path = env::temp_dir().join("mm_binary_io_async_file_read_example.bin");
+/// fs::write(&path, b"\x12\x34123456789").unwrap();
+///
+/// let mut reader = runtime.block_on(AsyncFileRead::open(&path)).unwrap();
+/// assert_eq!(11, runtime.block_on(reader.len()).unwrap());
+/// assert_eq!(0x1234_u16, runtime.block_on(reader.read_integer::<BigEndian, u16>()).unwrap());
+///
+/// assert_eq!(b"234".to_vec(), runtime.block_on(reader.read_exact_at(3, 3)).unwrap());
+/// assert_eq!(0xCBF43926, runtime.block_on(reader.checksum_range(2, 9, Crc32::new())).unwrap());
+///
+/// assert_eq!(2, reader.position());
+/// assert_eq!("12", runtime.block_on(reader.read_string(2)).unwrap());
+///
+/// fs::remove_file(&path).unwrap();
+/// # }
+///
+/// ```
+///
+pub struct AsyncFileRead {
+    reader: BufReader<tokio::fs::File>,
+    file: Arc<fs::File>,
+    position: u64,
+}
+
+impl AsyncFileRead {
+    /// Opens the file at `path` for reading.
+    pub fn open<TPath>(path: TPath) -> BlockingFuture<AsyncFileRead>
+        where TPath: AsRef<Path> {
+        let path = path.as_ref().to_path_buf();
+        BlockingFuture::new(move || fs::File::open(path).and_then(AsyncFileRead::from_std))
+    }
+
+    /// Creates a new `AsyncFileRead` reading `file` from its current position.
+    pub fn from_std(file: fs::File) -> io::Result<AsyncFileRead> {
+        let mut file = file;
+        let position = file.stream_position()?;
+        let positional = Arc::new(file.try_clone()?);
+        Ok(AsyncFileRead {
+            reader: BufReader::with_capacity(FILE_BUFFER_SIZE, tokio::fs::File::from_std(file)),
+            file: positional,
+            position,
+        })
+    }
+
+    /// Returns the length of the file.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> BlockingFuture<u64> {
+        let file = self.file.clone();
+        BlockingFuture::new(move || file.metadata().map(|m| m.len()))
+    }
+
+    /// Returns the current position.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Reads exactly `len` bytes at `offset` without changing the current position.
+    ///
+    /// # Errors
+    ///
+    /// If the file ends before `len` bytes are read, the future outputs Err(io::Error) of
+    /// `UnexpectedEof`.
+    ///
+    pub fn read_exact_at(&self, offset: u64, len: usize) -> BlockingFuture<Vec<u8>> {
+        let file = self.file.clone();
+        BlockingFuture::new(move || {
+            let mut buf = vec![0_u8; len];
+            file::read_exact_at_file(&file, offset, &mut buf)?;
+            Ok(buf)
+        })
+    }
+
+    /// Calculates `checksum` over `len` bytes at `offset` without changing the current position.
+    ///
+    /// # Errors
+    ///
+    /// If the file ends before `len` bytes are read, the future outputs Err(io::Error) of
+    /// `UnexpectedEof`. If the range overflows `u64`, the future outputs Err(io::Error) of
+    /// `InvalidInput`.
+    ///
+    pub fn checksum_range<TChecksum>(&self, offset: u64, len: u64, checksum: TChecksum) -> BlockingFuture<TChecksum::Output>
+        where TChecksum: Checksum + Send + 'static,
+              TChecksum::Output: Send + 'static {
+        let file = self.file.clone();
+        BlockingFuture::new(move || {
+            let mut buf = vec![0_u8; len.min(FILE_BUFFER_SIZE as u64) as usize];
+            file::checksum_at(offset, len, checksum, &mut buf, |offset, buf| file::read_exact_at_file(&file, offset, buf))
+        })
+    }
+
+    /// Hints the OS that the file will be read sequentially, as `FileRead::advise_sequential`.
+    ///
+    /// The hint does not block, so it is given immediately.
+    pub fn advise_sequential(&self) -> io::Result<()> {
+        file::advise_file(&self.file, file::Advice::Sequential, 0, 0)
+    }
+
+    /// Hints the OS that the file will be read at random offsets, as `FileRead::advise_random`.
+    pub fn advise_random(&self) -> io::Result<()> {
+        file::advise_file(&self.file, file::Advice::Random, 0, 0)
+    }
+
+    /// Hints the OS that `len` bytes at `offset` will be read soon, as
+    /// `FileRead::advise_willneed`.
+    pub fn advise_willneed(&self, offset: u64, len: u64) -> io::Result<()> {
+        file::advise_file(&self.file, file::Advice::WillNeed, offset, len)
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn get_ref(&self) -> &tokio::fs::File {
+        self.reader.get_ref()
+    }
+
+    /// Unwraps this `AsyncFileRead`, returning the underlying file.
+    ///
+    /// The buffered data is lost, so the position of the file may be ahead of `position()`.
+    pub fn into_inner(self) -> tokio::fs::File {
+        self.reader.into_inner()
+    }
+}
+
+impl AsyncRead for AsyncFileRead {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let before = buf.filled().len();
+        let result = Pin::new(&mut this.reader).poll_read(cx, buf);
+        this.position += (buf.filled().len() - before) as u64;
+        result
+    }
+}
+
+impl AsyncBufRead for AsyncFileRead {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<&[u8]>> {
+        Pin::new(&mut self.get_mut().reader).poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+        this.position += amt as u64;
+        Pin::new(&mut this.reader).consume(amt)
+    }
+}
+
+impl AsyncSeek for AsyncFileRead {
+    fn start_seek(self: Pin<&mut Self>, position: io::SeekFrom) -> io::Result<()> {
+        Pin::new(&mut self.get_mut().reader).start_seek(position)
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        let result = Pin::new(&mut this.reader).poll_complete(cx);
+        if let Poll::Ready(Ok(position)) = result {
+            this.position = position;
+        }
+        result
+    }
+}
+
+/// The buffered async file writer which supports positional writes.
+///
+/// `AsyncFileWrite` implements `AsyncWrite` and `AsyncSeek`, so all `AsyncBinaryWrite` methods
+/// can be used. The positional writes (`write_all_at`) do not change the current position.
+///
+/// # Examples
+///
+/// ```
+/// extern crate mm_binary_io;
+/// extern crate tokio;
+///
+/// use std::env;
+/// use std::fs;
+/// use mm_binary_io::async_file::AsyncFileWrite;
+/// use mm_binary_io::async_tokio::AsyncBinaryWrite;
+/// use mm_binary_io::endian::BigEndian;
+///
+/// # fn main() {
+/// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+///
+/// let path = env::temp_dir().join("mm_binary_io_async_file_write_example.bin");
+///
+/// let mut writer = runtime.block_on(AsyncFileWrite::create(&path)).unwrap();
+/// runtime.block_on(writer.write_integer::<BigEndian, _>(0_u16)).unwrap();
+/// runtime.block_on(writer.write_integer::<BigEndian, _>(0x5678_u16)).unwrap();
+///
+/// // Fills the placeholder after the data is written.
+/// runtime.block_on(writer.write_all_at(0, vec![0x12, 0x34])).unwrap();
+/// assert_eq!(4, writer.position());
+/// runtime.block_on(writer.sync_all()).unwrap();
+///
+/// assert_eq!(vec![0x12, 0x34, 0x56, 0x78], fs::read(&path).unwrap());
+///
+/// fs::remove_file(&path).unwrap();
+/// # }
+///
+/// ```
+///
+pub struct AsyncFileWrite {
+    writer: BufWriter<tokio::fs::File>,
+    file: Arc<fs::File>,
+    position: u64,
+}
+
+impl AsyncFileWrite {
+    /// Creates a new file at `path` for writing, truncating the existing file.
+    pub fn create<TPath>(path: TPath) -> BlockingFuture<AsyncFileWrite>
+        where TPath: AsRef<Path> {
+        let path = path.as_ref().to_path_buf();
+        BlockingFuture::new(move || fs::File::create(path).and_then(AsyncFileWrite::from_std))
+    }
+
+    /// Creates a new `AsyncFileWrite` writing `file` from its current position.
+    pub fn from_std(file: fs::File) -> io::Result<AsyncFileWrite> {
+        let mut file = file;
+        let position = file.stream_position()?;
+        let positional = Arc::new(file.try_clone()?);
+        Ok(AsyncFileWrite {
+            writer: BufWriter::with_capacity(FILE_BUFFER_SIZE, tokio::fs::File::from_std(file)),
+            file: positional,
+            position,
+        })
+    }
+
+    /// Returns the current position.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Writes all of `buf` at `offset` without changing the current position.
+    ///
+    /// The buffered data is written before `buf`.
+    ///
+    pub fn write_all_at(&mut self, offset: u64, buf: Vec<u8>) -> FlushThenFuture<'_, ()> {
+        let file = self.file.clone();
+        FlushThenFuture {
+            writer: self,
+            flushed: false,
+            then: BlockingFuture::new(move || file::write_all_at_file(&file, offset, &buf)),
+        }
+    }
+
+    /// Writes the buffered data and synchronizes the file to the storage.
+    pub fn sync_all(&mut self) -> FlushThenFuture<'_, ()> {
+        let file = self.file.clone();
+        FlushThenFuture {
+            writer: self,
+            flushed: false,
+            then: BlockingFuture::new(move || file.sync_all()),
+        }
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn get_ref(&self) -> &tokio::fs::File {
+        self.writer.get_ref()
+    }
+
+    /// Unwraps this `AsyncFileWrite`, returning the underlying file.
+    ///
+    /// The buffered data is lost, so flush the writer before.
+    pub fn into_inner(self) -> tokio::fs::File {
+        self.writer.into_inner()
+    }
+}
+
+impl AsyncWrite for AsyncFileWrite {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let result = Pin::new(&mut this.writer).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = result {
+            this.position += n as u64;
+        }
+        result
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().writer).poll_shutdown(cx)
+    }
+}
+
+impl AsyncSeek for AsyncFileWrite {
+    fn start_seek(self: Pin<&mut Self>, position: io::SeekFrom) -> io::Result<()> {
+        Pin::new(&mut self.get_mut().writer).start_seek(position)
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        let result = Pin::new(&mut this.writer).poll_complete(cx);
+        if let Poll::Ready(Ok(position)) = result {
+            this.position = position;
+        }
+        result
+    }
+}
+
+/// The future of a blocking file operation, which runs on the blocking thread pool when it is
+/// first polled.
+pub struct BlockingFuture<T> {
+    task: Option<Box<dyn FnOnce() -> io::Result<T> + Send>>,
+    handle: Option<JoinHandle<io::Result<T>>>,
+}
+
+impl<T> BlockingFuture<T>
+    where T: Send + 'static {
+    fn new<TTask>(task: TTask) -> BlockingFuture<T>
+        where TTask: FnOnce() -> io::Result<T> + Send + 'static {
+        BlockingFuture {
+            task: Some(Box::new(task)),
+            handle: None,
+        }
+    }
+}
+
+impl<T> Future for BlockingFuture<T>
+    where T: Send + 'static {
+    type Output = io::Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<T>> {
+        let this = self.get_mut();
+        if let Some(task) = this.task.take() {
+            this.handle = Some(task::spawn_blocking(task));
+        }
+        let handle = match this.handle.as_mut() {
+            Some(handle) => handle,
+            None => return Poll::Ready(Err(io::Error::other("the future is polled after completion"))),
+        };
+        match Pin::new(handle).poll(cx) {
+            Poll::Ready(result) => {
+                this.handle = None;
+                Poll::Ready(result.unwrap_or_else(|e| Err(io::Error::from(e))))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// The future of an operation of `AsyncFileWrite`, which runs after the buffered data is written.
+pub struct FlushThenFuture<'a, T> {
+    writer: &'a mut AsyncFileWrite,
+    flushed: bool,
+    then: BlockingFuture<T>,
+}
+
+impl<'a, T> Future for FlushThenFuture<'a, T>
+    where T: Send + 'static {
+    type Output = io::Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<T>> {
+        let this = self.get_mut();
+        if !this.flushed {
+            match Pin::new(&mut *this.writer).poll_flush(cx) {
+                Poll::Ready(Ok(())) => this.flushed = true,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Pin::new(&mut this.then).poll(cx)
+    }
+}
@@ -0,0 +1,106 @@
+//! Provides the features to read and write binary data over tokio's `AsyncRead` and `AsyncWrite`.
+//!
+//! The methods mirror `BinaryRead` and `BinaryWrite`, and return futures which borrow the
+//! reader or the writer.
+//!
+//! This module is available with the `tokio` feature.
+
+use std::convert::TryFrom;
+use std::future::Future;
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use async_common::{self, encode};
+use binary_write::BinaryWrite;
+use endian::Endian;
+use read_integer::ReadInteger;
+use varint::{self, MAX_VARINT_LEN};
+use write_integer::WriteInteger;
+
+async_binary_io! {
+    read(AsyncRead) {
+        /// Provides the features to read binary data asynchronously.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// extern crate mm_binary_io;
+        /// extern crate tokio;
+        ///
+        /// use mm_binary_io::async_tokio::AsyncBinaryRead;
+        /// use mm_binary_io::endian::BigEndian;
+        ///
+        /// # fn main() {
+        /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        ///
+        /// let data = [0xFF_u8, 0xFE, 0x00, 0x01, 0x00, 0x02, b'a', b'b', 0xE5, 0x8E, 0x26, 0x03];
+        /// let mut reader = &data[..];
+        ///
+        /// assert_eq!(-2, runtime.block_on(reader.read_integer::<BigEndian, i16>()).unwrap());
+        /// assert_eq!(vec![1_u16, 2], runtime.block_on(reader.read_integer_array::<BigEndian, u16>(2)).unwrap());
+        /// assert_eq!("ab", runtime.block_on(reader.read_string(2)).unwrap());
+        /// assert_eq!(624_485, runtime.block_on(reader.read_varint_u64()).unwrap());
+        /// assert_eq!(-2, runtime.block_on(reader.read_varint_i64()).unwrap());
+        /// assert!(runtime.block_on(reader.read_byte_array(1)).is_err());
+        /// # }
+        ///
+        /// ```
+        ///
+    }
+    write(AsyncWrite) {
+        /// Provides the features to write binary data asynchronously.
+        ///
+        /// The data is encoded when the method is called, and written by the future.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// extern crate mm_binary_io;
+        /// extern crate tokio;
+        ///
+        /// use mm_binary_io::async_tokio::AsyncBinaryWrite;
+        /// use mm_binary_io::endian::LittleEndian;
+        ///
+        /// # fn main() {
+        /// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        ///
+        /// let mut writer = vec![];
+        /// runtime.block_on(writer.write_integer::<LittleEndian, _>(-2_i16)).unwrap();
+        /// runtime.block_on(writer.write_integer_array::<LittleEndian, _>(&[1_u16, 2])).unwrap();
+        /// runtime.block_on(writer.write_string("ab")).unwrap();
+        /// runtime.block_on(writer.write_varint_u64(624_485)).unwrap();
+        /// runtime.block_on(writer.write_varint_i64(-2)).unwrap();
+        /// runtime.block_on(writer.write_frame::<LittleEndian, u8>(&[0x12])).unwrap();
+        ///
+        /// assert_eq!(
+        ///     vec![0xFE_u8, 0xFF, 0x01, 0x00, 0x02, 0x00, b'a', b'b', 0xE5, 0x8E, 0x26, 0x03, 0x01, 0x12],
+        ///     writer);
+        /// # }
+        ///
+        /// ```
+        ///
+    }
+}
+
+fn poll_fill<TRead>(reader: &mut TRead, cx: &mut Context, buf: &mut [u8], filled: &mut usize) -> Poll<io::Result<()>>
+    where TRead: AsyncRead + Unpin + ?Sized {
+    while *filled < buf.len() {
+        let mut read_buf = ReadBuf::new(&mut buf[*filled..]);
+        match Pin::new(&mut *reader).poll_read(cx, &mut read_buf) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        }
+        let n = read_buf.filled().len();
+        if n == 0 {
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer")));
+        }
+        *filled += n;
+    }
+    Poll::Ready(Ok(()))
+}
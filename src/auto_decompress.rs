@@ -0,0 +1,174 @@
+//! Provides the reader which detects the compression of the input by its magic bytes, and
+//! decompresses it transparently, so that the loaders accept both the compressed and the raw
+//! inputs.
+//!
+//! gzip is decompressed with the `flate2` feature, Zstandard with the `zstd` feature, xz with the
+//! `xz` feature and the LZ4 frames with the `lz4` feature. The input without a known magic is
+//! read as it is.
+
+use std::io;
+
+#[cfg(feature = "flate2")]
+use flate::{FlateFormat, FlateReader};
+#[cfg(feature = "lz4")]
+use lz4::Lz4Reader;
+#[cfg(feature = "xz")]
+use xz2::read::XzDecoder;
+#[cfg(feature = "zstd")]
+use zstd::ZstdReader;
+
+/// The longest magic of the compression formats.
+const MAX_MAGIC_LEN: usize = 6;
+
+/// A compression format detected by its magic bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CompressionFormat {
+    /// gzip, of the magic `1F 8B`.
+    Gzip,
+    /// Zstandard, of the magic `28 B5 2F FD`.
+    Zstd,
+    /// xz, of the magic `FD 37 7A 58 5A 00`.
+    Xz,
+    /// The LZ4 frame, of the magic `04 22 4D 18`.
+    Lz4,
+}
+
+impl CompressionFormat {
+    /// Detects the format from the first bytes of the input, or returns None if they match no
+    /// magic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mm_binary_io::auto_decompress::CompressionFormat;
+    ///
+    /// assert_eq!(Some(CompressionFormat::Gzip), CompressionFormat::detect(&[0x1F, 0x8B, 0x08, 0x00]));
+    /// assert_eq!(Some(CompressionFormat::Zstd), CompressionFormat::detect(&[0x28, 0xB5, 0x2F, 0xFD]));
+    /// assert_eq!(None, CompressionFormat::detect(b"PK\x03\x04"));
+    /// assert_eq!(None, CompressionFormat::detect(&[0x1F]));
+    ///
+    /// ```
+    ///
+    pub fn detect(magic: &[u8]) -> Option<CompressionFormat> {
+        if magic.starts_with(&[0x1F, 0x8B]) {
+            Some(CompressionFormat::Gzip)
+        } else if magic.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Some(CompressionFormat::Zstd)
+        } else if magic.starts_with(&[0xFD, 0x37, 0x7A, 0x58, 0x5A, 0x00]) {
+            Some(CompressionFormat::Xz)
+        } else if magic.starts_with(&[0x04, 0x22, 0x4D, 0x18]) {
+            Some(CompressionFormat::Lz4)
+        } else {
+            None
+        }
+    }
+}
+
+/// The underlying reader with the magic bytes put back in front of it.
+type Peeked<TRead> = io::Chain<io::Cursor<Vec<u8>>, TRead>;
+
+enum Decoder<TRead>
+    where TRead: io::Read {
+    Raw(Peeked<TRead>),
+    #[cfg(feature = "flate2")]
+    Gzip(FlateReader<Peeked<TRead>>),
+    #[cfg(feature = "zstd")]
+    Zstd(ZstdReader<Peeked<TRead>>),
+    #[cfg(feature = "xz")]
+    Xz(XzDecoder<Peeked<TRead>>),
+    #[cfg(feature = "lz4")]
+    Lz4(Lz4Reader<Peeked<TRead>>),
+}
+
+/// Wraps a reader, and reads the data decompressed if it starts with a known magic, or as it is
+/// otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::auto_decompress::AutoDecompressReader;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+///
+/// let mut reader = AutoDecompressReader::new(&[0x00_u8, 0x00, 0x12, 0x34][..]).unwrap();
+/// assert_eq!(None, reader.format());
+/// assert_eq!(0x1234, reader.read_integer::<BigEndian, u32>().unwrap());
+///
+/// // An input shorter than the magics is read as it is too.
+/// let mut reader = AutoDecompressReader::new(&[0x1F_u8][..]).unwrap();
+/// assert_eq!(vec![0x1F], reader.read_byte_array(1).unwrap());
+///
+/// ```
+///
+pub struct AutoDecompressReader<TRead>
+    where TRead: io::Read {
+    decoder: Decoder<TRead>,
+    format: Option<CompressionFormat>,
+}
+
+impl<TRead> AutoDecompressReader<TRead>
+    where TRead: io::Read {
+    /// Creates a new `AutoDecompressReader`, reading the magic bytes from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// If the input is of a format whose feature is not enabled, returns Err(io::Error) of
+    /// `Unsupported`. If reading fails, returns Err(io::Error).
+    ///
+    pub fn new(reader: TRead) -> io::Result<AutoDecompressReader<TRead>> {
+        let mut reader = reader;
+        let mut magic = vec![0_u8; MAX_MAGIC_LEN];
+        let mut len = 0;
+        while len < magic.len() {
+            match reader.read(&mut magic[len..]) {
+                Ok(0) => break,
+                Ok(n) => len += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        magic.truncate(len);
+        let format = CompressionFormat::detect(&magic);
+        let peeked = io::Read::chain(io::Cursor::new(magic), reader);
+        let decoder = match format {
+            None => Decoder::Raw(peeked),
+            #[cfg(feature = "flate2")]
+            Some(CompressionFormat::Gzip) => Decoder::Gzip(FlateReader::new(peeked, FlateFormat::Gzip)),
+            #[cfg(feature = "zstd")]
+            Some(CompressionFormat::Zstd) => Decoder::Zstd(ZstdReader::new(peeked)?),
+            #[cfg(feature = "xz")]
+            Some(CompressionFormat::Xz) => Decoder::Xz(XzDecoder::new_multi_decoder(peeked)),
+            #[cfg(feature = "lz4")]
+            Some(CompressionFormat::Lz4) => Decoder::Lz4(Lz4Reader::new(peeked)),
+            #[allow(unreachable_patterns)]
+            Some(format) => {
+                let message = format!("{:?} decompression is not enabled", format);
+                return Err(io::Error::new(io::ErrorKind::Unsupported, message));
+            }
+        };
+        Ok(AutoDecompressReader { decoder, format })
+    }
+
+    /// Returns the detected compression format, or None for the raw input.
+    pub fn format(&self) -> Option<CompressionFormat> {
+        self.format
+    }
+}
+
+impl<TRead> io::Read for AutoDecompressReader<TRead>
+    where TRead: io::Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.decoder {
+            Decoder::Raw(ref mut r) => r.read(buf),
+            #[cfg(feature = "flate2")]
+            Decoder::Gzip(ref mut r) => r.read(buf),
+            #[cfg(feature = "zstd")]
+            Decoder::Zstd(ref mut r) => r.read(buf),
+            #[cfg(feature = "xz")]
+            Decoder::Xz(ref mut r) => r.read(buf),
+            #[cfg(feature = "lz4")]
+            Decoder::Lz4(ref mut r) => r.read(buf),
+        }
+    }
+}
@@ -0,0 +1,289 @@
+//! Provides the features to read and write the identifier and length octets of ASN.1 BER/DER.
+//!
+//! Only the octet layer is handled; the contents are left to the caller.
+
+use std::io;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::BigEndian;
+
+/// The class of a tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagClass {
+    /// Universal (`0b00`).
+    Universal,
+    /// Application (`0b01`).
+    Application,
+    /// Context-specific (`0b10`).
+    ContextSpecific,
+    /// Private (`0b11`).
+    Private,
+}
+
+impl TagClass {
+    fn from_bits(bits: u8) -> TagClass {
+        match bits & 0b11 {
+            0b00 => TagClass::Universal,
+            0b01 => TagClass::Application,
+            0b10 => TagClass::ContextSpecific,
+            _ => TagClass::Private,
+        }
+    }
+
+    fn bits(self) -> u8 {
+        match self {
+            TagClass::Universal => 0b00,
+            TagClass::Application => 0b01,
+            TagClass::ContextSpecific => 0b10,
+            TagClass::Private => 0b11,
+        }
+    }
+}
+
+/// The identifier octets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Identifier {
+    /// The tag class.
+    pub class: TagClass,
+    /// True if the encoding is constructed.
+    pub constructed: bool,
+    /// The tag number.
+    pub number: u64,
+}
+
+impl Identifier {
+    /// Creates a new `Identifier`.
+    pub fn new(class: TagClass, constructed: bool, number: u64) -> Identifier {
+        Identifier {
+            class,
+            constructed,
+            number,
+        }
+    }
+
+    /// Returns true if this is the identifier of the end-of-contents octets.
+    pub fn is_end_of_contents(&self) -> bool {
+        self.class == TagClass::Universal && !self.constructed && self.number == 0
+    }
+}
+
+/// The length octets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Length {
+    /// The definite form.
+    Definite(u64),
+    /// The indefinite form, terminated by the end-of-contents octets.
+    Indefinite,
+}
+
+/// Reads the identifier octets, including the high-tag-number form.
+///
+/// # Errors
+///
+/// If the tag number is not minimally encoded or exceeds u64, returns Err(io::Error) of
+/// `InvalidData`.
+/// If reading fails, returns Err(io::Error).
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::ber::{self, Identifier, TagClass};
+///
+/// let mut reader = io::Cursor::new(vec![0x30_u8, 0x5F, 0x81, 0x49]);
+/// assert_eq!(Identifier::new(TagClass::Universal, true, 16), ber::read_identifier(&mut reader).unwrap());
+/// assert_eq!(Identifier::new(TagClass::Application, false, 201), ber::read_identifier(&mut reader).unwrap());
+///
+/// ```
+///
+pub fn read_identifier<TRead>(reader: &mut TRead) -> io::Result<Identifier>
+    where TRead: io::Read {
+    let first = reader.read_integer::<BigEndian, u8>()?;
+    let class = TagClass::from_bits(first >> 6);
+    let constructed = first & 0x20 != 0;
+    let mut number = (first & 0x1F) as u64;
+    if number == 0x1F {
+        number = 0;
+        loop {
+            let byte = reader.read_integer::<BigEndian, u8>()?;
+            if number == 0 && byte == 0x80 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "tag number is not minimally encoded"));
+            }
+            if number >> 57 != 0 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "tag number exceeds u64"));
+            }
+            number = number << 7 | (byte & 0x7F) as u64;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+    }
+    Ok(Identifier { class, constructed, number })
+}
+
+/// Writes the identifier octets, using the high-tag-number form for the numbers above 30.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::ber::{self, Identifier, TagClass};
+///
+/// let mut writer = vec![];
+/// ber::write_identifier(&mut writer, &Identifier::new(TagClass::ContextSpecific, true, 0)).unwrap();
+/// ber::write_identifier(&mut writer, &Identifier::new(TagClass::Application, false, 201)).unwrap();
+/// assert_eq!(vec![0xA0, 0x5F, 0x81, 0x49], writer);
+///
+/// ```
+///
+pub fn write_identifier<TWrite>(writer: &mut TWrite, identifier: &Identifier) -> io::Result<()>
+    where TWrite: io::Write {
+    let leading = identifier.class.bits() << 6 | if identifier.constructed { 0x20 } else { 0 };
+    if identifier.number < 0x1F {
+        return writer.write_integer::<BigEndian, _>(leading | identifier.number as u8);
+    }
+    let mut octets = vec![leading | 0x1F];
+    let groups = (64 - identifier.number.leading_zeros() as usize).div_ceil(7);
+    for i in (0..groups).rev() {
+        let group = (identifier.number >> (i * 7)) as u8 & 0x7F;
+        octets.push(if i == 0 { group } else { group | 0x80 });
+    }
+    writer.write_all(&octets)
+}
+
+/// Reads the length octets in BER, accepting the indefinite form and non-minimal encodings.
+///
+/// # Errors
+///
+/// If the length is the reserved form (`0xFF`) or exceeds u64, returns Err(io::Error) of
+/// `InvalidData`.
+/// If reading fails, returns Err(io::Error).
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::ber::{self, Length};
+///
+/// let mut reader = io::Cursor::new(vec![0x7F_u8, 0x82, 0x01, 0x2C, 0x80, 0x81, 0x05]);
+/// assert_eq!(Length::Definite(127), ber::read_length(&mut reader).unwrap());
+/// assert_eq!(Length::Definite(300), ber::read_length(&mut reader).unwrap());
+/// assert_eq!(Length::Indefinite, ber::read_length(&mut reader).unwrap());
+/// assert_eq!(Length::Definite(5), ber::read_length(&mut reader).unwrap());
+///
+/// ```
+///
+pub fn read_length<TRead>(reader: &mut TRead) -> io::Result<Length>
+    where TRead: io::Read {
+    read_length_octets(reader).map(|(length, _)| length)
+}
+
+/// Reads the length octets in DER, which must be the definite form and minimally encoded.
+///
+/// # Errors
+///
+/// If the length is not a valid DER length, returns Err(io::Error) of `InvalidData`.
+/// If reading fails, returns Err(io::Error).
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::ber;
+///
+/// assert_eq!(300, ber::read_der_length(&mut io::Cursor::new(vec![0x82_u8, 0x01, 0x2C])).unwrap());
+/// assert!(ber::read_der_length(&mut io::Cursor::new(vec![0x81_u8, 0x05])).is_err());
+/// assert!(ber::read_der_length(&mut io::Cursor::new(vec![0x80_u8])).is_err());
+///
+/// ```
+///
+pub fn read_der_length<TRead>(reader: &mut TRead) -> io::Result<u64>
+    where TRead: io::Read {
+    match read_length_octets(reader)? {
+        (Length::Definite(len), octets) if octets == length_octets_len(len) => Ok(len),
+        (Length::Definite(_), _) => Err(io::Error::new(io::ErrorKind::InvalidData, "DER length is not minimally encoded")),
+        (Length::Indefinite, _) => Err(io::Error::new(io::ErrorKind::InvalidData, "DER does not allow the indefinite length")),
+    }
+}
+
+/// Writes the length octets in the minimal (DER) encoding.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::ber::{self, Length};
+///
+/// let mut writer = vec![];
+/// ber::write_length(&mut writer, Length::Definite(127)).unwrap();
+/// ber::write_length(&mut writer, Length::Definite(128)).unwrap();
+/// ber::write_length(&mut writer, Length::Indefinite).unwrap();
+/// assert_eq!(vec![0x7F, 0x81, 0x80, 0x80], writer);
+///
+/// ```
+///
+pub fn write_length<TWrite>(writer: &mut TWrite, length: Length) -> io::Result<()>
+    where TWrite: io::Write {
+    let len = match length {
+        Length::Definite(len) => len,
+        Length::Indefinite => return writer.write_integer::<BigEndian, _>(0x80_u8),
+    };
+    if len < 0x80 {
+        return writer.write_integer::<BigEndian, _>(len as u8);
+    }
+    let n = length_octets_len(len) - 1;
+    writer.write_integer::<BigEndian, _>(0x80 | n as u8)?;
+    writer.write_all(&len.to_be_bytes()[8 - n..])
+}
+
+/// Reads the identifier and the length octets.
+pub fn read_header<TRead>(reader: &mut TRead) -> io::Result<(Identifier, Length)>
+    where TRead: io::Read {
+    let identifier = read_identifier(reader)?;
+    let length = read_length(reader)?;
+    Ok((identifier, length))
+}
+
+/// Writes the identifier and the length octets.
+pub fn write_header<TWrite>(writer: &mut TWrite, identifier: &Identifier, length: Length) -> io::Result<()>
+    where TWrite: io::Write {
+    write_identifier(writer, identifier)?;
+    write_length(writer, length)
+}
+
+/// Writes the end-of-contents octets which terminate the contents of the indefinite length.
+pub fn write_end_of_contents<TWrite>(writer: &mut TWrite) -> io::Result<()>
+    where TWrite: io::Write {
+    writer.write_all(&[0x00, 0x00])
+}
+
+fn read_length_octets<TRead>(reader: &mut TRead) -> io::Result<(Length, usize)>
+    where TRead: io::Read {
+    let first = reader.read_integer::<BigEndian, u8>()?;
+    if first < 0x80 {
+        return Ok((Length::Definite(first as u64), 1));
+    }
+    if first == 0x80 {
+        return Ok((Length::Indefinite, 1));
+    }
+    if first == 0xFF {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "reserved length octet"));
+    }
+    let n = (first & 0x7F) as usize;
+    let mut len = 0_u64;
+    for _ in 0..n {
+        let byte = reader.read_integer::<BigEndian, u8>()?;
+        if len >> 56 != 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "length exceeds u64"));
+        }
+        len = len << 8 | byte as u64;
+    }
+    Ok((Length::Definite(len), n + 1))
+}
+
+fn length_octets_len(len: u64) -> usize {
+    if len < 0x80 {
+        1
+    } else {
+        1 + (64 - len.leading_zeros() as usize).div_ceil(8)
+    }
+}
@@ -0,0 +1,137 @@
+//! Provides the growable buffer to build binary data, whose reserved fields are filled after the
+//! data they depend on is written, such as the lengths and the offsets in the headers.
+
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+
+use byte_view::ByteViewMut;
+use endian::Endian;
+use to_bytes::ToBytes;
+
+/// A `TInt` field reserved by `BinaryBuilder::reserve_slot`.
+pub struct Slot<TInt> {
+    offset: usize,
+    _integer: PhantomData<fn() -> TInt>,
+}
+
+impl<TInt> Slot<TInt> {
+    /// Returns the offset from the head of the built bytes.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<TInt> Clone for Slot<TInt> {
+    fn clone(&self) -> Slot<TInt> {
+        *self
+    }
+}
+
+impl<TInt> Copy for Slot<TInt> {}
+
+impl<TInt> fmt::Debug for Slot<TInt> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Slot").field("offset", &self.offset).finish()
+    }
+}
+
+/// A growable buffer which is written at the end.
+///
+/// Because `BinaryBuilder` implements `io::Write`, all `BinaryWrite` methods can be used.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::binary_builder::BinaryBuilder;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::BigEndian;
+///
+/// let mut builder = BinaryBuilder::new();
+/// builder.write_string("BLK").unwrap();
+/// let len = builder.reserve_slot::<u32>();
+///
+/// let start = builder.position();
+/// builder.write_integer_array::<BigEndian, u16>(&[1, 2, 3]).unwrap();
+/// let body_len = builder.position() - start;
+/// builder.fill_slot::<BigEndian, _>(len, body_len as u32);
+///
+/// assert_eq!(b"BLK\x00\x00\x00\x06\x00\x01\x00\x02\x00\x03".to_vec(), builder.into_bytes());
+///
+/// ```
+///
+#[derive(Clone, Debug, Default)]
+pub struct BinaryBuilder {
+    bytes: Vec<u8>,
+}
+
+impl BinaryBuilder {
+    /// Creates a new empty `BinaryBuilder`.
+    pub fn new() -> BinaryBuilder {
+        BinaryBuilder::default()
+    }
+
+    /// Creates a new empty `BinaryBuilder` of the capacity of `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> BinaryBuilder {
+        BinaryBuilder { bytes: Vec::with_capacity(capacity) }
+    }
+
+    /// Returns the byte count written, where the next write starts.
+    pub fn position(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns the byte count written.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns true if nothing is written.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns the bytes written.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Writes zeros for a `TInt` field, and returns the slot to fill it later.
+    pub fn reserve_slot<TInt>(&mut self) -> Slot<TInt>
+        where TInt: ToBytes {
+        let offset = self.bytes.len();
+        self.bytes.resize(offset + mem::size_of::<TInt>(), 0);
+        Slot { offset, _integer: PhantomData }
+    }
+
+    /// Writes `value` in `TEndian` into `slot`, without changing the position.
+    ///
+    /// # Panics
+    ///
+    /// If `slot` was not reserved by this builder, and is past the end, the function panics.
+    ///
+    pub fn fill_slot<TEndian, TInt>(&mut self, slot: Slot<TInt>, value: TInt)
+        where TEndian: Endian,
+              TInt: ToBytes {
+        ByteViewMut::<TEndian>::new(&mut self.bytes)
+            .set_at(slot.offset, value)
+            .expect("slot is past the end of the builder");
+    }
+
+    /// Unwraps this `BinaryBuilder`, returning the bytes written.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl io::Write for BinaryBuilder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
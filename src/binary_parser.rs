@@ -0,0 +1,313 @@
+//! Provides the parser context, which bundles a reader with the options of the format, so that
+//! the nested parse functions take one parameter and the limits apply to all of them.
+//!
+//! The options are the default byte order, the maximum byte count allocated by a read, the
+//! validation mode and the string encoding.
+
+use std::io;
+use std::mem;
+
+use binary_read::BinaryRead;
+use endian::{BigEndian, LittleEndian};
+use read_integer::ReadInteger;
+use schema::ByteOrder;
+use validation::Validation;
+
+/// The maximum byte count allocated by a read of the default `ParseOptions`.
+pub const DEFAULT_MAX_ALLOC: usize = 16 * 1024 * 1024;
+
+/// The encoding of the strings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StringEncoding {
+    /// UTF-8.
+    Utf8,
+    /// ISO 8859-1, in which each byte is the code point.
+    Latin1,
+    /// ASCII, in which a byte of 0x80 or more is invalid.
+    Ascii,
+}
+
+/// The options of a `BinaryParser`.
+///
+/// The default is little-endian, `DEFAULT_MAX_ALLOC`, the strict validation and UTF-8.
+#[derive(Clone)]
+pub struct ParseOptions {
+    byte_order: ByteOrder,
+    max_alloc: usize,
+    validation: Validation,
+    encoding: StringEncoding,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            byte_order: ByteOrder::Little,
+            max_alloc: DEFAULT_MAX_ALLOC,
+            validation: Validation::strict(),
+            encoding: StringEncoding::Utf8,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Sets the byte order of the integers.
+    pub fn with_byte_order(mut self, byte_order: ByteOrder) -> ParseOptions {
+        self.byte_order = byte_order;
+        self
+    }
+
+    /// Sets the maximum byte count allocated by a read.
+    pub fn with_max_alloc(mut self, max_alloc: usize) -> ParseOptions {
+        self.max_alloc = max_alloc;
+        self
+    }
+
+    /// Sets the validation mode.
+    pub fn with_validation(mut self, validation: Validation) -> ParseOptions {
+        self.validation = validation;
+        self
+    }
+
+    /// Sets the encoding of the strings.
+    pub fn with_encoding(mut self, encoding: StringEncoding) -> ParseOptions {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns the byte order of the integers.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// Returns the maximum byte count allocated by a read.
+    pub fn max_alloc(&self) -> usize {
+        self.max_alloc
+    }
+
+    /// Returns the validation mode.
+    pub fn validation(&self) -> &Validation {
+        &self.validation
+    }
+
+    /// Returns the encoding of the strings.
+    pub fn encoding(&self) -> StringEncoding {
+        self.encoding
+    }
+}
+
+/// A reader bundled with the `ParseOptions` of the format.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_parser::{BinaryParser, ParseOptions, StringEncoding};
+/// use mm_binary_io::schema::ByteOrder;
+///
+/// struct Entry { id: u16, name: String }
+///
+/// fn parse_entry<TRead: io::Read>(p: &mut BinaryParser<TRead>) -> io::Result<Entry> {
+///     let id = p.u16()?;
+///     let len = p.u8()?;
+///     let name = p.str(len as usize)?;
+///     Ok(Entry { id, name })
+/// }
+///
+/// let options = ParseOptions::default()
+///     .with_byte_order(ByteOrder::Big)
+///     .with_encoding(StringEncoding::Latin1)
+///     .with_max_alloc(16);
+/// let data = b"\x00\x02\x00\x07\x03caf\xE9\x00\x08\x40";
+/// let mut parser = BinaryParser::with_options(&data[..], options);
+///
+/// let count = parser.u16().unwrap();
+/// let entry = parse_entry(&mut parser).unwrap();
+/// assert_eq!((2, 7, "caf"), (count, entry.id, &entry.name[..]));
+/// assert_eq!("\u{E9}", parser.str(1).unwrap());
+///
+/// // The limit applies to the nested reads.
+/// let error = parse_entry(&mut parser).err().unwrap();
+/// assert_eq!(io::ErrorKind::InvalidData, error.kind());
+///
+/// ```
+///
+pub struct BinaryParser<TRead> {
+    reader: TRead,
+    options: ParseOptions,
+}
+
+macro_rules! impl_read {
+    ($($name:ident: $t:ty),*) => {
+        $(
+            #[doc = concat!("Reads a `", stringify!($t), "` in the byte order of the options.")]
+            pub fn $name(&mut self) -> io::Result<$t> {
+                self.read::<$t>()
+            }
+        )*
+    };
+}
+
+impl<TRead> BinaryParser<TRead>
+    where TRead: io::Read {
+    /// Creates a new `BinaryParser` of `reader` with the default options.
+    pub fn new(reader: TRead) -> BinaryParser<TRead> {
+        BinaryParser::with_options(reader, ParseOptions::default())
+    }
+
+    /// Creates a new `BinaryParser` of `reader` with `options`.
+    pub fn with_options(reader: TRead, options: ParseOptions) -> BinaryParser<TRead> {
+        BinaryParser { reader, options }
+    }
+
+    /// Returns the options.
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    /// Returns the validation mode of the options.
+    pub fn validation(&self) -> &Validation {
+        &self.options.validation
+    }
+
+    /// Changes the byte order of the options, for the formats declaring it in the header.
+    pub fn set_byte_order(&mut self, byte_order: ByteOrder) {
+        self.options.byte_order = byte_order;
+    }
+
+    /// Reads a `TInt` in the byte order of the options.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(TInt), otherwise Err(io::Error).
+    ///
+    pub fn read<TInt>(&mut self) -> io::Result<TInt>
+        where TInt: ReadInteger<OutputType=TInt> {
+        match self.options.byte_order {
+            ByteOrder::Big => self.reader.read_integer::<BigEndian, TInt>(),
+            ByteOrder::Little => self.reader.read_integer::<LittleEndian, TInt>(),
+        }
+    }
+
+    impl_read!(u8: u8, i8: i8, u16: u16, i16: i16, u32: u32, i32: i32, u64: u64, i64: i64);
+
+    /// Reads an array of `count` `TInt` in the byte order of the options.
+    ///
+    /// # Errors
+    ///
+    /// If the array exceeds the maximum allocation, returns Err(io::Error) of `InvalidData`
+    /// and reads nothing. If reading fails, returns Err(io::Error).
+    ///
+    pub fn array<TInt>(&mut self, count: usize) -> io::Result<Vec<TInt>>
+        where TInt: ReadInteger<OutputType=TInt> {
+        self.check_alloc(count.saturating_mul(mem::size_of::<TInt>()))?;
+        match self.options.byte_order {
+            ByteOrder::Big => self.reader.read_integer_array::<BigEndian, TInt>(count),
+            ByteOrder::Little => self.reader.read_integer_array::<LittleEndian, TInt>(count),
+        }
+    }
+
+    /// Reads `len` bytes.
+    ///
+    /// # Errors
+    ///
+    /// If `len` exceeds the maximum allocation, returns Err(io::Error) of `InvalidData` and
+    /// reads nothing. If reading fails, returns Err(io::Error).
+    ///
+    pub fn bytes(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        self.check_alloc(len)?;
+        self.reader.read_byte_array(len)
+    }
+
+    /// Reads a string of `len` bytes in the encoding of the options.
+    ///
+    /// # Errors
+    ///
+    /// If `len` exceeds the maximum allocation, returns Err(io::Error) of `InvalidData` and
+    /// reads nothing. If the bytes are invalid in the encoding, returns Err(io::Error) of
+    /// `InvalidData`. If reading fails, returns Err(io::Error).
+    ///
+    pub fn str(&mut self, len: usize) -> io::Result<String> {
+        let bytes = self.bytes(len)?;
+        decode_string(bytes, self.options.encoding)
+    }
+
+    /// Reads a string field of `len` bytes, removing the trailing `pad` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Same as `str`.
+    ///
+    pub fn str_padded(&mut self, len: usize, pad: u8) -> io::Result<String> {
+        let mut bytes = self.bytes(len)?;
+        let end = bytes.iter().rposition(|&b| b != pad).map_or(0, |i| i + 1);
+        bytes.truncate(end);
+        decode_string(bytes, self.options.encoding)
+    }
+
+    /// Reads a padding of `len` bytes, which must be `pad` in the strict validation.
+    ///
+    /// # Errors
+    ///
+    /// Same as `BinaryRead::read_padding`.
+    ///
+    pub fn padding(&mut self, len: usize, pad: u8) -> io::Result<()> {
+        self.reader.read_padding(len, pad, &self.options.validation)
+    }
+
+    /// Reads and discards `count` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Same as `BinaryRead::skip_bytes`.
+    ///
+    pub fn skip(&mut self, count: u64) -> io::Result<()> {
+        self.reader.skip_bytes(count)
+    }
+
+    /// Checks that `byte_count` does not exceed the maximum allocation, for the nested parse
+    /// functions which allocate by themselves.
+    ///
+    /// # Errors
+    ///
+    /// If `byte_count` exceeds the maximum allocation, returns Err(io::Error) of `InvalidData`.
+    ///
+    pub fn check_alloc(&self, byte_count: usize) -> io::Result<()> {
+        if byte_count > self.options.max_alloc {
+            trace_event!(DEBUG, byte_count, max_alloc = self.options.max_alloc, "allocation exceeds the limit");
+            let message = format!("allocation of {} bytes exceeds the limit of {} bytes", byte_count, self.options.max_alloc);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        Ok(())
+    }
+}
+
+impl<TRead> BinaryParser<TRead> {
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &TRead {
+        &self.reader
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut TRead {
+        &mut self.reader
+    }
+
+    /// Unwraps this `BinaryParser`, returning the underlying reader.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+}
+
+fn decode_string(bytes: Vec<u8>, encoding: StringEncoding) -> io::Result<String> {
+    match encoding {
+        StringEncoding::Utf8 => String::from_utf8(bytes)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8 string")),
+        StringEncoding::Latin1 => Ok(bytes.iter().map(|&b| b as char).collect()),
+        StringEncoding::Ascii => {
+            if !bytes.is_ascii() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid ASCII string"));
+            }
+            Ok(bytes.iter().map(|&b| b as char).collect())
+        }
+    }
+}
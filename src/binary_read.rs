@@ -1,7 +1,19 @@
+use std::any;
 use std::io;
+use std::mem;
+use std::str;
 
 use endian::Endian;
+use error::{self, BinaryIoError};
+use progress::{self, Progress};
 use read_integer::ReadInteger;
+use read_tuple::ReadTuple;
+use scratch;
+use validation::Validation;
+use varint;
+
+/// The byte count discarded by one read of `skip_bytes`.
+const SKIP_CHUNK_SIZE: usize = 64 * 1024;
 
 /// Provides the features to read binary data.
 ///
@@ -35,7 +47,9 @@ pub trait BinaryRead: io::Read {
     ///
     /// # Errors
     ///
-    /// If the function succeeds then Ok(TInt), otherwise Err(io::Error).
+    /// If the function succeeds then Ok(TInt), otherwise Err(io::Error), which carries
+    /// `error::BinaryIoError` with the name of `TInt` unless it is an OS error or carries an
+    /// inner error of its own.
     ///
     fn read_integer<TEndian, TInt>(&mut self) -> io::Result<TInt>
         where
@@ -53,15 +67,16 @@ pub trait BinaryRead: io::Read {
     /// If the function succeeds then Ok(TInt), otherwise Err(io::Error).
     ///
     fn read_byte_array(&mut self, byte_count: usize) -> io::Result<Vec<u8>> {
-        let mut buf = vec![0_u8; byte_count];
-        self.read_exact(&mut buf)
-            .and_then(|_| {
-                Ok(buf)
-            })
+        let mut buf = Vec::new();
+        read_append(self, &mut buf, byte_count).map_err(|e| error::with_type_name(e, "[u8]"))?;
+        Ok(buf)
     }
 
     /// Reads an integer array.
     ///
+    /// If `TEndian` is the native endianness, the whole array is read by one `read_exact`
+    /// without per-element conversion.
+    ///
     /// # Arguments
     ///
     /// * element_count - the number of elements in the array.
@@ -74,6 +89,435 @@ pub trait BinaryRead: io::Read {
         where
             TEndian: Endian,
             TInt: ReadInteger<OutputType=TInt>;
+
+    /// Reads an integer, or returns None if the stream ends cleanly before it.
+    ///
+    /// The record loops can tell the end of the records from a record cut off in the middle.
+    ///
+    /// # Errors
+    ///
+    /// If the stream ends in the middle of the integer, returns Err(io::Error) of `UnexpectedEof`.
+    /// If reading fails, returns Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use mm_binary_io::binary_read::BinaryRead;
+    /// use mm_binary_io::endian::BigEndian;
+    ///
+    /// let mut reader = io::Cursor::new(vec![0x12_u8, 0x34]);
+    /// assert_eq!(Some(0x1234), reader.try_read_integer::<BigEndian, u16>().unwrap());
+    /// assert_eq!(None, reader.try_read_integer::<BigEndian, u16>().unwrap());
+    ///
+    /// let mut reader = io::Cursor::new(vec![0x12_u8]);
+    /// assert_eq!(io::ErrorKind::UnexpectedEof, reader.try_read_integer::<BigEndian, u16>().unwrap_err().kind());
+    ///
+    /// ```
+    ///
+    fn try_read_integer<TEndian, TInt>(&mut self) -> io::Result<Option<TInt>>
+        where
+            TEndian: Endian,
+            TInt: ReadInteger<OutputType=TInt> {
+        // The buffer is sized by `TInt`, so that the wider `ReadInteger` implementations fit.
+        scratch::with_scratch(mem::size_of::<TInt>(), |buf| {
+            match read_fully(self, buf)? {
+                0 => Ok(None),
+                n if n < buf.len() => {
+                    let e = io::Error::new(io::ErrorKind::UnexpectedEof, "integer is cut off");
+                    Err(BinaryIoError::new(e).with_type_name(any::type_name::<TInt>()).into())
+                }
+                _ => TInt::read_integer::<TEndian>(&mut &buf[..]).map(Some),
+            }
+        })
+    }
+
+    /// Reads byte array, or returns None if the stream ends cleanly before it.
+    ///
+    /// If `byte_count` is 0, returns Ok(Some) of the empty array even at the end of the stream.
+    ///
+    /// # Errors
+    ///
+    /// If the stream ends in the middle of the array, returns Err(io::Error) of `UnexpectedEof`.
+    /// If reading fails, returns Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use mm_binary_io::binary_read::BinaryRead;
+    ///
+    /// let mut reader = io::Cursor::new(vec![0x01_u8, 0x02, 0x03]);
+    /// assert_eq!(Some(vec![0x01, 0x02]), reader.try_read_byte_array(2).unwrap());
+    /// assert_eq!(io::ErrorKind::UnexpectedEof, reader.try_read_byte_array(2).unwrap_err().kind());
+    /// assert_eq!(None, reader.try_read_byte_array(2).unwrap());
+    ///
+    /// ```
+    ///
+    fn try_read_byte_array(&mut self, byte_count: usize) -> io::Result<Option<Vec<u8>>> {
+        if byte_count == 0 {
+            return Ok(Some(vec![]));
+        }
+        let mut first = [0_u8; 1];
+        if read_fully(self, &mut first)? == 0 {
+            return Ok(None);
+        }
+        let mut buf = first.to_vec();
+        read_append(self, &mut buf, byte_count - 1).map_err(|e| error::with_type_name(e, "[u8]"))?;
+        Ok(Some(buf))
+    }
+
+    /// Reads a length-prefixed frame and returns its payload.
+    ///
+    /// The frame is a `TLen` length in `TEndian` followed by the payload.
+    ///
+    /// # Arguments
+    ///
+    /// * max_len - the maximum payload length, to prevent a broken or hostile length
+    ///   from exhausting the memory.
+    ///
+    /// # Errors
+    ///
+    /// If the length exceeds `max_len`, returns Err(io::Error) of `InvalidData`.
+    /// If reading fails, returns Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use mm_binary_io::binary_read::BinaryRead;
+    /// use mm_binary_io::endian::BigEndian;
+    ///
+    /// let mut reader = io::Cursor::new(vec![0x00_u8, 0x02, 0x12, 0x34, 0x00, 0x03]);
+    /// assert_eq!(vec![0x12, 0x34], reader.read_frame::<BigEndian, u16>(1024).unwrap());
+    /// assert_eq!(io::ErrorKind::InvalidData, reader.read_frame::<BigEndian, u16>(2).unwrap_err().kind());
+    ///
+    /// ```
+    ///
+    fn read_frame<TEndian, TLen>(&mut self, max_len: usize) -> io::Result<Vec<u8>>
+        where
+            Self: Sized,
+            TEndian: Endian,
+            TLen: ReadInteger<OutputType=TLen> + Into<u64> {
+        let len: u64 = self.read_integer::<TEndian, TLen>()?.into();
+        if len > max_len as u64 {
+            trace_event!(DEBUG, len, max_len, "frame is too long");
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "frame is too long"));
+        }
+        trace_event!(TRACE, len, "read frame");
+        self.read_byte_array(len as usize)
+    }
+
+    /// Reads the next `len` bytes into an independent cursor, which a sub-parser can read and
+    /// seek without reaching past the region.
+    ///
+    /// # Errors
+    ///
+    /// If the stream ends before `len` bytes, returns Err(io::Error) of `UnexpectedEof`.
+    /// If reading fails, returns Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{self, Seek, SeekFrom};
+    /// use mm_binary_io::binary_read::BinaryRead;
+    /// use mm_binary_io::endian::BigEndian;
+    ///
+    /// let mut reader = io::Cursor::new(vec![0x00_u8, 0x01, 0x00, 0x02, 0xFF, 0xFF]);
+    /// let mut region = reader.take_cursor(4).unwrap();
+    ///
+    /// region.seek(SeekFrom::Start(2)).unwrap();
+    /// assert_eq!(2, region.read_integer::<BigEndian, u16>().unwrap());
+    /// assert!(region.read_integer::<BigEndian, u16>().is_err());
+    /// assert_eq!(0xFFFF, reader.read_integer::<BigEndian, u16>().unwrap());
+    ///
+    /// ```
+    ///
+    fn take_cursor(&mut self, len: usize) -> io::Result<io::Cursor<Vec<u8>>>
+        where Self: Sized {
+        self.read_byte_array(len).map(io::Cursor::new)
+    }
+
+    /// Reads the components of `TTuple` in order, for destructuring a small fixed header in one
+    /// expression.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(TTuple), otherwise Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use mm_binary_io::binary_read::BinaryRead;
+    /// use mm_binary_io::endian::LittleEndian;
+    ///
+    /// let mut reader = io::Cursor::new(b"\x01\x00\x00\x00\x02\x00RIFF\x03".to_vec());
+    /// let (version, flags, magic) = reader.read_tuple::<LittleEndian, (u32, u16, [u8; 4])>().unwrap();
+    /// assert_eq!((1, 2, *b"RIFF"), (version, flags, magic));
+    ///
+    /// assert!(reader.read_tuple::<LittleEndian, (u8, u8)>().is_err());
+    ///
+    /// ```
+    ///
+    fn read_tuple<TEndian, TTuple>(&mut self) -> io::Result<TTuple>
+        where
+            Self: Sized,
+            TEndian: Endian,
+            TTuple: ReadTuple {
+        TTuple::read_tuple::<TEndian, Self>(self)
+    }
+
+    /// Reads an unsigned LEB128 variable-length integer.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(u64), otherwise Err(io::Error).
+    ///
+    fn read_varint_u64(&mut self) -> io::Result<u64>
+        where Self: Sized {
+        varint::read_u64(self)
+    }
+
+    /// Reads a ZigZag encoded signed LEB128 variable-length integer.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(i64), otherwise Err(io::Error).
+    ///
+    fn read_varint_i64(&mut self) -> io::Result<i64>
+        where Self: Sized {
+        varint::read_i64(self)
+    }
+
+    /// Reads a fixed-size field and returns the bytes before the padding.
+    ///
+    /// The value ends at the first NUL, and the trailing `pad` bytes are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * len - the byte count of the field.
+    /// * pad - the padding byte.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the value), otherwise Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use mm_binary_io::binary_read::BinaryRead;
+    ///
+    /// let mut reader = io::Cursor::new(b"abc\0\0xyz    ".to_vec());
+    /// assert_eq!(b"abc".to_vec(), reader.read_padded_bytes(5, 0).unwrap());
+    /// assert_eq!(b"xyz".to_vec(), reader.read_padded_bytes(7, b' ').unwrap());
+    ///
+    /// ```
+    ///
+    fn read_padded_bytes(&mut self, len: usize, pad: u8) -> io::Result<Vec<u8>> {
+        let mut value = self.read_byte_array(len)?;
+        if let Some(end) = value.iter().position(|&b| b == 0) {
+            value.truncate(end);
+        }
+        while value.last() == Some(&pad) {
+            value.pop();
+        }
+        Ok(value)
+    }
+
+    /// Reads a padding of `len` bytes, checking that all bytes are `pad`.
+    ///
+    /// # Errors
+    ///
+    /// If a byte is not `pad` in the strict mode of `validation`, returns Err(io::Error) of
+    /// `InvalidData`.
+    /// If reading fails, returns Err(io::Error).
+    ///
+    fn read_padding(&mut self, len: usize, pad: u8, validation: &Validation) -> io::Result<()> {
+        scratch::with_scratch(len, |buf| {
+            self.read_exact(buf)?;
+            validation.check_padding(buf, pad)
+        })
+    }
+
+    /// Reads a UTF-8 string of `byte_count` bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the bytes are not valid UTF-8, returns Err(io::Error) of `InvalidData`.
+    /// If reading fails, returns Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use mm_binary_io::binary_read::BinaryRead;
+    ///
+    /// let mut reader = io::Cursor::new(vec![0x61_u8, 0xC3, 0xA9, 0xFF]);
+    /// assert_eq!("a\u{E9}", reader.read_string(3).unwrap());
+    /// assert_eq!(io::ErrorKind::InvalidData, reader.read_string(1).unwrap_err().kind());
+    ///
+    /// ```
+    ///
+    fn read_string(&mut self, byte_count: usize) -> io::Result<String> {
+        if byte_count > scratch::MAX_RETAINED_SIZE {
+            return String::from_utf8(self.read_byte_array(byte_count)?)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8 string"));
+        }
+        // The bytes are validated in the reused thread-local scratch buffer, and copied out only
+        // if they are valid.
+        scratch::with_scratch(byte_count, |buf| {
+            self.read_exact(buf).map_err(|e| error::with_type_name(e, "String"))?;
+            str::from_utf8(buf)
+                .map(str::to_owned)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8 string"))
+        })
+    }
+
+    /// Reads and discards `count` bytes.
+    ///
+    /// The bytes are read into the thread-local scratch buffer, so that no memory is allocated.
+    ///
+    /// # Errors
+    ///
+    /// If the stream ends before `count` bytes, returns Err(io::Error) of `UnexpectedEof`.
+    /// If reading fails, returns Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use mm_binary_io::binary_read::BinaryRead;
+    ///
+    /// let mut reader = io::Cursor::new(vec![0x01_u8, 0x02, 0x03, 0x04]);
+    /// reader.skip_bytes(3).unwrap();
+    /// assert_eq!(vec![0x04], reader.read_byte_array(1).unwrap());
+    /// assert_eq!(io::ErrorKind::UnexpectedEof, reader.skip_bytes(1).unwrap_err().kind());
+    ///
+    /// ```
+    ///
+    fn skip_bytes(&mut self, count: u64) -> io::Result<()> {
+        let mut remaining = count;
+        while remaining > 0 {
+            let len = remaining.min(SKIP_CHUNK_SIZE as u64) as usize;
+            scratch::with_scratch(len, |buf| self.read_exact(buf))?;
+            remaining -= len as u64;
+        }
+        Ok(())
+    }
+
+    /// Reads byte array, reporting the progress to `progress`.
+    ///
+    /// The progress is reported every `progress::REPORT_INTERVAL` bytes and at the end. An empty
+    /// array is reported once as `(0, Some(0))`.
+    ///
+    /// # Arguments
+    ///
+    /// * byte_count - the byte count of the array.
+    /// * progress - the receiver of the progress.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(Vec<u8>), otherwise Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use mm_binary_io::binary_read::BinaryRead;
+    ///
+    /// let mut reader = io::Cursor::new(vec![0x01_u8, 0x02, 0x03]);
+    ///
+    /// let mut reports = Vec::new();
+    /// let result = reader.read_byte_array_with_progress(3, &mut |processed, total| {
+    ///     reports.push((processed, total));
+    /// }).unwrap();
+    ///
+    /// assert_eq!(vec![0x01, 0x02, 0x03], result);
+    /// assert_eq!(vec![(3, Some(3))], reports);
+    ///
+    /// reports.clear();
+    /// assert!(reader.read_byte_array_with_progress(0, &mut |processed, total| {
+    ///     reports.push((processed, total));
+    /// }).unwrap().is_empty());
+    /// assert_eq!(vec![(0, Some(0))], reports);
+    ///
+    /// ```
+    ///
+    fn read_byte_array_with_progress<TProgress>(&mut self, byte_count: usize, progress: &mut TProgress) -> io::Result<Vec<u8>>
+        where TProgress: Progress {
+        let total = byte_count as u64;
+        let mut buf = Vec::with_capacity(byte_count);
+        if byte_count == 0 {
+            progress.report(0, Some(0));
+        }
+        let mut processed = 0;
+        while processed < byte_count {
+            let end = byte_count.min(processed + progress::REPORT_INTERVAL as usize);
+            read_append(self, &mut buf, end - processed)?;
+            processed = end;
+            progress.report(processed as u64, Some(total));
+        }
+        Ok(buf)
+    }
+
+    /// Reads an integer array, reporting the progress to `progress`.
+    ///
+    /// The progress is reported in bytes, approximately every `progress::REPORT_INTERVAL` bytes and at the end.
+    /// An empty array is reported once as `(0, Some(0))`.
+    ///
+    /// # Arguments
+    ///
+    /// * element_count - the number of elements in the array.
+    /// * progress - the receiver of the progress.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(Vec<TInt>), otherwise Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use mm_binary_io::endian::LittleEndian;
+    /// use mm_binary_io::binary_read::BinaryRead;
+    ///
+    /// let mut reader = io::Cursor::new(vec![0x01_u8, 0x00, 0x02, 0x00]);
+    ///
+    /// let mut last = (0, None);
+    /// let result = reader.read_integer_array_with_progress::<LittleEndian, u16, _>(2, &mut |processed, total| {
+    ///     last = (processed, total);
+    /// }).unwrap();
+    ///
+    /// assert_eq!(vec![1_u16, 2], result);
+    /// assert_eq!((4, Some(4)), last);
+    ///
+    /// ```
+    ///
+    fn read_integer_array_with_progress<TEndian, TInt, TProgress>(&mut self, element_count: usize, progress: &mut TProgress) -> io::Result<Vec<TInt>>
+        where
+            Self: Sized,
+            TEndian: Endian,
+            TInt: ReadInteger<OutputType=TInt>,
+            TProgress: Progress {
+        let element_size = mem::size_of::<TInt>() as u64;
+        let total = element_size * element_count as u64;
+        let mut result = Vec::with_capacity(element_count);
+        if element_count == 0 {
+            progress.report(0, Some(0));
+        }
+        let mut reported = 0;
+        for i in 0..element_count {
+            result.push(self.read_integer::<TEndian, TInt>()?);
+            let processed = element_size * (i as u64 + 1);
+            if processed - reported >= progress::REPORT_INTERVAL || processed == total {
+                progress.report(processed, Some(total));
+                reported = processed;
+            }
+        }
+        Ok(result)
+    }
 }
 
 impl<T> BinaryRead for T
@@ -81,16 +525,55 @@ impl<T> BinaryRead for T
     fn read_integer<TEndian, TInt>(&mut self) -> io::Result<TInt> where
         TEndian: Endian,
         TInt: ReadInteger<OutputType=TInt> {
-        TInt::read_integer::<TEndian>(self)
+        TInt::read_integer::<TEndian>(self).map_err(|e| error::with_type_name(e, any::type_name::<TInt>()))
     }
 
     fn read_integer_array<TEndian, TInt>(&mut self, element_count: usize) -> io::Result<Vec<TInt>> where
         TEndian: Endian,
         TInt: ReadInteger<OutputType=TInt> {
-        let mut result = Vec::with_capacity(element_count);
-        for _i in 0..element_count {
-            result.push(self.read_integer::<TEndian, TInt>()?);
+        TInt::read_integer_array::<TEndian>(self, element_count)
+            .map_err(|e| error::with_type_name(e, any::type_name::<[TInt]>()))
+    }
+}
+
+/// Reads until `buf` is filled or the stream ends, and returns the byte count read.
+pub(crate) fn read_fully<TRead>(reader: &mut TRead, buf: &mut [u8]) -> io::Result<usize>
+    where TRead: io::Read + ?Sized {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
         }
-        Ok(result)
     }
+    Ok(filled)
+}
+
+/// Reads exactly `len` bytes and appends them to `buf`.
+///
+/// With the `nightly` feature, the bytes are read into the spare capacity by `read_buf`, so the
+/// buffer is not zero-initialized before being overwritten.
+#[cfg(feature = "nightly")]
+fn read_append<TRead>(reader: &mut TRead, buf: &mut Vec<u8>, len: usize) -> io::Result<()>
+    where TRead: io::Read + ?Sized {
+    buf.reserve(len);
+    let start = buf.len();
+    {
+        let mut borrowed = io::BorrowedBuf::from(&mut buf.spare_capacity_mut()[..len]);
+        reader.read_buf_exact(borrowed.unfilled())?;
+    }
+    // `read_buf_exact` succeeded, so all `len` bytes are initialized.
+    unsafe { buf.set_len(start + len); }
+    Ok(())
+}
+
+/// Reads exactly `len` bytes and appends them to `buf`.
+#[cfg(not(feature = "nightly"))]
+fn read_append<TRead>(reader: &mut TRead, buf: &mut Vec<u8>, len: usize) -> io::Result<()>
+    where TRead: io::Read + ?Sized {
+    let start = buf.len();
+    buf.resize(start + len, 0);
+    reader.read_exact(&mut buf[start..])
 }
@@ -0,0 +1,308 @@
+//! Provides the serializer context, which bundles a writer with the options of the format, the
+//! counterpart of `binary_parser`.
+//!
+//! The options are the default byte order, the fill byte of the paddings, the alignment policy
+//! and the string encoding.
+
+use std::io;
+use std::mem;
+
+use binary_parser::StringEncoding;
+use binary_write::BinaryWrite;
+use endian::{BigEndian, LittleEndian};
+use schema::ByteOrder;
+use write_integer::WriteInteger;
+
+/// The alignment policy of the integers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Alignment {
+    /// The integers are written with no padding.
+    Packed,
+    /// Each integer is padded to the offset of a multiple of its size, as the C structs are.
+    Natural,
+}
+
+/// The options of a `BinarySerializer`.
+///
+/// The default is little-endian, the fill byte 0, `Alignment::Packed` and UTF-8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SerializeOptions {
+    byte_order: ByteOrder,
+    fill: u8,
+    alignment: Alignment,
+    encoding: StringEncoding,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> SerializeOptions {
+        SerializeOptions {
+            byte_order: ByteOrder::Little,
+            fill: 0,
+            alignment: Alignment::Packed,
+            encoding: StringEncoding::Utf8,
+        }
+    }
+}
+
+impl SerializeOptions {
+    /// Sets the byte order of the integers.
+    pub fn with_byte_order(mut self, byte_order: ByteOrder) -> SerializeOptions {
+        self.byte_order = byte_order;
+        self
+    }
+
+    /// Sets the fill byte of the paddings.
+    pub fn with_fill(mut self, fill: u8) -> SerializeOptions {
+        self.fill = fill;
+        self
+    }
+
+    /// Sets the alignment policy.
+    pub fn with_alignment(mut self, alignment: Alignment) -> SerializeOptions {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Sets the encoding of the strings.
+    pub fn with_encoding(mut self, encoding: StringEncoding) -> SerializeOptions {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Returns the byte order of the integers.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// Returns the fill byte of the paddings.
+    pub fn fill(&self) -> u8 {
+        self.fill
+    }
+
+    /// Returns the alignment policy.
+    pub fn alignment(&self) -> Alignment {
+        self.alignment
+    }
+
+    /// Returns the encoding of the strings.
+    pub fn encoding(&self) -> StringEncoding {
+        self.encoding
+    }
+}
+
+/// A writer bundled with the `SerializeOptions` of the format.
+///
+/// The position, on which the alignment depends, is the byte count written by the serializer.
+/// The bytes written through `get_mut` are not counted.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_serializer::{Alignment, BinarySerializer, SerializeOptions};
+/// use mm_binary_io::schema::ByteOrder;
+///
+/// let options = SerializeOptions::default()
+///     .with_byte_order(ByteOrder::Big)
+///     .with_fill(b' ')
+///     .with_alignment(Alignment::Natural);
+/// let mut s = BinarySerializer::with_options(vec![], options);
+///
+/// s.u8(1).unwrap();
+/// s.u32(0x1234_5678).unwrap();
+/// s.str_padded("name", 6).unwrap();
+/// assert_eq!(14, s.position());
+/// assert_eq!(io::ErrorKind::InvalidInput, s.str_padded("too long", 6).unwrap_err().kind());
+///
+/// assert_eq!(b"\x01   \x12\x34\x56\x78name  ".to_vec(), s.into_inner());
+///
+/// ```
+///
+pub struct BinarySerializer<TWrite> {
+    writer: TWrite,
+    options: SerializeOptions,
+    position: u64,
+}
+
+macro_rules! impl_write {
+    ($($name:ident: $t:ty),*) => {
+        $(
+            #[doc = concat!("Writes the `", stringify!($t), "` `value` in the byte order of the options.")]
+            pub fn $name(&mut self, value: $t) -> io::Result<()> {
+                self.write(value)
+            }
+        )*
+    };
+}
+
+impl<TWrite> BinarySerializer<TWrite>
+    where TWrite: io::Write {
+    /// Creates a new `BinarySerializer` of `writer` with the default options.
+    pub fn new(writer: TWrite) -> BinarySerializer<TWrite> {
+        BinarySerializer::with_options(writer, SerializeOptions::default())
+    }
+
+    /// Creates a new `BinarySerializer` of `writer` with `options`.
+    pub fn with_options(writer: TWrite, options: SerializeOptions) -> BinarySerializer<TWrite> {
+        BinarySerializer { writer, options, position: 0 }
+    }
+
+    /// Returns the options.
+    pub fn options(&self) -> &SerializeOptions {
+        &self.options
+    }
+
+    /// Changes the byte order of the options.
+    pub fn set_byte_order(&mut self, byte_order: ByteOrder) {
+        self.options.byte_order = byte_order;
+    }
+
+    /// Returns the byte count written.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Writes the `TInt` `value` in the byte order of the options, aligned by the policy.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    pub fn write<TInt>(&mut self, value: TInt) -> io::Result<()>
+        where TInt: WriteInteger {
+        self.align_for::<TInt>()?;
+        match self.options.byte_order {
+            ByteOrder::Big => self.writer.write_integer::<BigEndian, TInt>(value)?,
+            ByteOrder::Little => self.writer.write_integer::<LittleEndian, TInt>(value)?,
+        }
+        self.position += mem::size_of::<TInt>() as u64;
+        Ok(())
+    }
+
+    impl_write!(u8: u8, i8: i8, u16: u16, i16: i16, u32: u32, i32: i32, u64: u64, i64: i64);
+
+    /// Writes `values` in the byte order of the options, aligned by the policy.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    pub fn array<TInt>(&mut self, values: &[TInt]) -> io::Result<()>
+        where TInt: WriteInteger {
+        self.align_for::<TInt>()?;
+        match self.options.byte_order {
+            ByteOrder::Big => self.writer.write_integer_array::<BigEndian, TInt>(values)?,
+            ByteOrder::Little => self.writer.write_integer_array::<LittleEndian, TInt>(values)?,
+        }
+        self.position += mem::size_of_val(values) as u64;
+        Ok(())
+    }
+
+    /// Writes `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    pub fn bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.writer.write_all(bytes)?;
+        self.position += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Writes `value` in the encoding of the options.
+    ///
+    /// # Errors
+    ///
+    /// If `value` cannot be encoded, returns Err(io::Error) of `InvalidInput` and writes
+    /// nothing. If writing fails, returns Err(io::Error).
+    ///
+    pub fn str(&mut self, value: &str) -> io::Result<()> {
+        let bytes = encode_string(value, self.options.encoding)?;
+        self.bytes(&bytes)
+    }
+
+    /// Writes `value` in the encoding of the options, padded with the fill byte to `len` bytes.
+    ///
+    /// # Errors
+    ///
+    /// If `value` cannot be encoded, or is longer than `len` bytes, returns Err(io::Error) of
+    /// `InvalidInput` and writes nothing. If writing fails, returns Err(io::Error).
+    ///
+    pub fn str_padded(&mut self, value: &str, len: usize) -> io::Result<()> {
+        let bytes = encode_string(value, self.options.encoding)?;
+        self.writer.write_padded_bytes(&bytes, len, self.options.fill)?;
+        self.position += len as u64;
+        Ok(())
+    }
+
+    /// Writes `len` fill bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    pub fn padding(&mut self, len: usize) -> io::Result<()> {
+        let fill = vec![self.options.fill; len];
+        self.bytes(&fill)
+    }
+
+    /// Writes the fill bytes up to the position of a multiple of `boundary`.
+    ///
+    /// # Panics
+    ///
+    /// If `boundary` is 0, the function panics.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    pub fn align(&mut self, boundary: usize) -> io::Result<()> {
+        assert!(boundary > 0, "alignment boundary must not be 0");
+        let rest = (self.position % boundary as u64) as usize;
+        if rest == 0 {
+            return Ok(());
+        }
+        self.padding(boundary - rest)
+    }
+
+    fn align_for<TInt>(&mut self) -> io::Result<()> {
+        match self.options.alignment {
+            Alignment::Packed => Ok(()),
+            Alignment::Natural => self.align(mem::size_of::<TInt>()),
+        }
+    }
+}
+
+impl<TWrite> BinarySerializer<TWrite> {
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &TWrite {
+        &self.writer
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut TWrite {
+        &mut self.writer
+    }
+
+    /// Unwraps this `BinarySerializer`, returning the underlying writer.
+    pub fn into_inner(self) -> TWrite {
+        self.writer
+    }
+}
+
+fn encode_string(value: &str, encoding: StringEncoding) -> io::Result<Vec<u8>> {
+    match encoding {
+        StringEncoding::Utf8 => Ok(value.as_bytes().to_vec()),
+        StringEncoding::Latin1 => value.chars()
+            .map(|c| if (c as u32) < 0x100 { Ok(c as u8) } else { Err(invalid_char(c, "Latin-1")) })
+            .collect(),
+        StringEncoding::Ascii => value.chars()
+            .map(|c| if c.is_ascii() { Ok(c as u8) } else { Err(invalid_char(c, "ASCII")) })
+            .collect(),
+    }
+}
+
+fn invalid_char(c: char, encoding: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{:?} is not in {}", c, encoding))
+}
@@ -1,8 +1,12 @@
 //! Provides the features to write binary data.
 
+use std::convert::TryFrom;
 use std::io;
+use std::mem;
 
 use endian::Endian;
+use progress::{self, Progress};
+use varint;
 use write_integer::WriteInteger;
 
 /// Provides the features to write binary data.
@@ -95,6 +99,127 @@ pub trait BinaryWrite: io::Write {
     fn write_integer_array<TEndian, TInt>(&mut self, values: &[TInt]) -> io::Result<()>
         where TEndian: Endian,
               TInt: WriteInteger;
+
+    /// Writes a length-prefixed frame of `payload`.
+    ///
+    /// The frame is a `TLen` length in `TEndian` followed by the payload.
+    ///
+    /// # Errors
+    ///
+    /// If the payload length does not fit in `TLen`, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mm_binary_io::binary_write::BinaryWrite;
+    /// use mm_binary_io::endian::LittleEndian;
+    ///
+    /// let mut writer = vec![];
+    /// writer.write_frame::<LittleEndian, u32>(&[0x12, 0x34]).unwrap();
+    /// assert_eq!(vec![0x02, 0x00, 0x00, 0x00, 0x12, 0x34], writer);
+    ///
+    /// assert!(writer.write_frame::<LittleEndian, u8>(&[0; 256]).is_err());
+    ///
+    /// ```
+    fn write_frame<TEndian, TLen>(&mut self, payload: &[u8]) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian,
+              TLen: WriteInteger + TryFrom<u64> {
+        let len = TLen::try_from(payload.len() as u64)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame is too long"))?;
+        self.write_integer::<TEndian, _>(len)?;
+        self.write_all(payload)
+    }
+
+    /// Writes an unsigned LEB128 variable-length integer.
+    fn write_varint_u64(&mut self, value: u64) -> io::Result<()>
+        where Self: Sized {
+        varint::write_u64(self, value)
+    }
+
+    /// Writes a ZigZag encoded signed LEB128 variable-length integer.
+    fn write_varint_i64(&mut self, value: i64) -> io::Result<()>
+        where Self: Sized {
+        varint::write_i64(self, value)
+    }
+
+    /// Writes `value` as a fixed-size field padded with `pad`.
+    ///
+    /// # Errors
+    ///
+    /// If `value` is longer than `len`, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mm_binary_io::binary_write::BinaryWrite;
+    ///
+    /// let mut writer = vec![];
+    /// writer.write_padded_bytes(b"abc", 5, 0).unwrap();
+    /// writer.write_padded_bytes(b"xyz", 4, b' ').unwrap();
+    /// assert_eq!(b"abc\0\0xyz ".to_vec(), writer);
+    ///
+    /// assert!(writer.write_padded_bytes(b"abc", 2, 0).is_err());
+    ///
+    /// ```
+    fn write_padded_bytes(&mut self, value: &[u8], len: usize, pad: u8) -> io::Result<()> {
+        if value.len() > len {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "value is longer than the field"));
+        }
+        self.write_all(value)?;
+        self.write_all(&vec![pad; len - value.len()])
+    }
+
+    /// Writes the UTF-8 bytes of `value`.
+    fn write_string(&mut self, value: &str) -> io::Result<()> {
+        self.write_all(value.as_bytes())
+    }
+
+    /// Writes the integer array, reporting the progress to `progress`.
+    ///
+    /// The progress is reported in bytes, approximately every `progress::REPORT_INTERVAL` bytes and at the end.
+    /// An empty array is reported once as `(0, Some(0))`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use mm_binary_io::endian::BigEndian;
+    /// use mm_binary_io::binary_write::BinaryWrite;
+    ///
+    /// let mut writer = io::Cursor::new(vec![]);
+    /// let mut last = (0, None);
+    /// writer.write_integer_array_with_progress::<BigEndian, _, _>(&[0x1234_u16, 0x5678], &mut |processed, total| {
+    ///     last = (processed, total);
+    /// }).unwrap();
+    ///
+    /// assert_eq!(vec![0x12, 0x34, 0x56, 0x78], writer.into_inner());
+    /// assert_eq!((4, Some(4)), last);
+    ///
+    /// ```
+    fn write_integer_array_with_progress<TEndian, TInt, TProgress>(&mut self, values: &[TInt], progress: &mut TProgress) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian,
+              TInt: WriteInteger,
+              TProgress: Progress {
+        let element_size = mem::size_of::<TInt>() as u64;
+        let total = element_size * values.len() as u64;
+        if values.is_empty() {
+            progress.report(0, Some(0));
+        }
+        let mut reported = 0;
+        for (i, x) in values.iter().enumerate() {
+            x.write_integer::<TEndian>(self)?;
+            let processed = element_size * (i as u64 + 1);
+            if processed - reported >= progress::REPORT_INTERVAL || processed == total {
+                progress.report(processed, Some(total));
+                reported = processed;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<T> BinaryWrite for T
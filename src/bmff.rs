@@ -0,0 +1,214 @@
+//! Provides the features to walk ISO base media file format (MP4, QuickTime, HEIF) boxes.
+
+use std::io;
+use std::io::{Read, SeekFrom};
+
+use binary_read::BinaryRead;
+use endian::BigEndian;
+
+/// The header of a box.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoxHeader {
+    /// The box type (e.g. `*b"moov"`).
+    pub box_type: [u8; 4],
+    /// The user type of a `uuid` box.
+    pub user_type: Option<[u8; 16]>,
+    /// The stream offset of the box.
+    pub offset: u64,
+    /// The size of the box including the header.
+    pub size: u64,
+    /// The size of the header.
+    pub header_len: u64,
+}
+
+impl BoxHeader {
+    /// Returns the stream offset of the payload.
+    pub fn payload_offset(&self) -> u64 {
+        self.offset + self.header_len
+    }
+
+    /// Returns the size of the payload.
+    pub fn payload_len(&self) -> u64 {
+        self.size - self.header_len
+    }
+
+    /// Returns the stream offset just after the box.
+    pub fn end(&self) -> u64 {
+        self.offset + self.size
+    }
+}
+
+/// Walks the boxes in a range of a seekable stream.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::bmff::BoxReader;
+///
+/// let data = vec![
+///     0, 0, 0, 12, b'f', b't', b'y', b'p', b'i', b's', b'o', b'm',
+///     0, 0, 0, 24, b'm', b'o', b'o', b'v',
+///           0, 0, 0, 1, b'f', b'r', b'e', b'e', 0, 0, 0, 0, 0, 0, 0, 16,
+///     0, 0, 0, 25, b'u', b'u', b'i', b'd', 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 0xAB,
+///     0, 0, 0, 0, b'm', b'd', b'a', b't', 0xCD, 0xEF];
+///
+/// let mut boxes = BoxReader::new(io::Cursor::new(data)).unwrap();
+///
+/// let ftyp = boxes.next_box().unwrap().unwrap();
+/// assert_eq!(*b"ftyp", ftyp.box_type);
+/// assert_eq!(b"isom".to_vec(), boxes.read_payload(&ftyp).unwrap());
+///
+/// let moov = boxes.next_box().unwrap().unwrap();
+/// assert_eq!(*b"moov", moov.box_type);
+/// {
+///     let mut children = boxes.children(&moov).unwrap();
+///     let free = children.next_box().unwrap().unwrap();
+///     assert_eq!((*b"free", 16, 16), (free.box_type, free.size, free.header_len));
+///     assert_eq!(None, children.next_box().unwrap());
+/// }
+///
+/// let uuid = boxes.next_box().unwrap().unwrap();
+/// assert_eq!(Some([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]), uuid.user_type);
+/// assert_eq!(vec![0xAB], boxes.read_payload(&uuid).unwrap());
+///
+/// // A box of size 0 extends to the end of the stream.
+/// let mdat = boxes.next_box().unwrap().unwrap();
+/// assert_eq!(2, mdat.payload_len());
+///
+/// assert_eq!(None, boxes.next_box().unwrap());
+///
+/// ```
+///
+pub struct BoxReader<TRead>
+    where TRead: io::Read + io::Seek {
+    reader: TRead,
+    next: u64,
+    end: u64,
+}
+
+impl<TRead> BoxReader<TRead>
+    where TRead: io::Read + io::Seek {
+    /// Creates a new `BoxReader` walking from the current position to the end of the stream.
+    pub fn new(reader: TRead) -> io::Result<BoxReader<TRead>> {
+        let mut reader = reader;
+        let start = reader.stream_position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        Ok(BoxReader::with_range(reader, start, end))
+    }
+
+    /// Creates a new `BoxReader` walking the boxes in `start..end` of the stream.
+    pub fn with_range(reader: TRead, start: u64, end: u64) -> BoxReader<TRead> {
+        BoxReader {
+            reader,
+            next: start,
+            end,
+        }
+    }
+
+    /// Reads the header of the next box.
+    ///
+    /// The payload of the previous box is skipped, regardless of how much of it was read.
+    ///
+    /// # Errors
+    ///
+    /// If there is no more box in the range, returns Ok(None).
+    /// If the header is broken or the box exceeds the range, returns Err(io::Error) of `InvalidData`.
+    ///
+    pub fn next_box(&mut self) -> io::Result<Option<BoxHeader>> {
+        if self.next >= self.end {
+            return Ok(None);
+        }
+        let offset = self.next;
+        self.reader.seek(SeekFrom::Start(offset))?;
+
+        let mut size = self.reader.read_integer::<BigEndian, u32>()? as u64;
+        let mut box_type = [0_u8; 4];
+        self.reader.read_exact(&mut box_type)?;
+        let mut header_len = 8;
+        if size == 1 {
+            size = self.reader.read_integer::<BigEndian, u64>()?;
+            header_len += 8;
+        } else if size == 0 {
+            size = self.end - offset;
+        }
+        let user_type = if box_type == *b"uuid" {
+            let mut user_type = [0_u8; 16];
+            self.reader.read_exact(&mut user_type)?;
+            header_len += 16;
+            Some(user_type)
+        } else {
+            None
+        };
+
+        trace_event!(TRACE, box_type = %String::from_utf8_lossy(&box_type), offset, size, "read ISO BMFF box");
+        if size < header_len || size > self.end - offset {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid box size"));
+        }
+        self.next = offset + size;
+        Ok(Some(BoxHeader {
+            box_type,
+            user_type,
+            offset,
+            size,
+            header_len,
+        }))
+    }
+
+    /// Returns a reader bounded to the payload of `header`.
+    pub fn payload(&mut self, header: &BoxHeader) -> io::Result<io::Take<&mut TRead>> {
+        self.reader.seek(SeekFrom::Start(header.payload_offset()))?;
+        Ok((&mut self.reader).take(header.payload_len()))
+    }
+
+    /// Reads the whole payload of `header`.
+    pub fn read_payload(&mut self, header: &BoxHeader) -> io::Result<Vec<u8>> {
+        let len = header.payload_len();
+        let mut payload = vec![];
+        self.payload(header)?.read_to_end(&mut payload)?;
+        if (payload.len() as u64) < len {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "box payload is cut off"));
+        }
+        Ok(payload)
+    }
+
+    /// Returns a `BoxReader` walking the boxes nested in the payload of the container `header`.
+    pub fn children(&mut self, header: &BoxHeader) -> io::Result<BoxReader<&mut TRead>> {
+        self.children_after(header, 0)
+    }
+
+    /// Returns a `BoxReader` walking the boxes nested in the payload of `header` after `skip` bytes.
+    ///
+    /// This is useful for the containers with fields before the children (e.g. `meta` has
+    /// 4 bytes of version and flags).
+    pub fn children_after(&mut self, header: &BoxHeader, skip: u64) -> io::Result<BoxReader<&mut TRead>> {
+        if skip > header.payload_len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "skip exceeds the payload"));
+        }
+        Ok(BoxReader::with_range(&mut self.reader, header.payload_offset() + skip, header.end()))
+    }
+
+    /// Unwraps this `BoxReader`, returning the underlying reader.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+}
+
+/// Reads the version and the flags of a full box from the beginning of its payload.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::bmff;
+///
+/// let mut reader = io::Cursor::new(vec![0x01_u8, 0x00, 0x00, 0x03]);
+/// assert_eq!((1, 3), bmff::read_full_box_header(&mut reader).unwrap());
+///
+/// ```
+///
+pub fn read_full_box_header<TRead>(reader: &mut TRead) -> io::Result<(u8, u32)>
+    where TRead: io::Read {
+    let value = reader.read_integer::<BigEndian, u32>()?;
+    Ok(((value >> 24) as u8, value & 0x00FF_FFFF))
+}
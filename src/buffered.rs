@@ -0,0 +1,467 @@
+//! Provides the buffered readers and writers tuned for many small field reads and writes.
+//!
+//! On raw handles such as `File` and `TcpStream`, each `read_integer` costs a syscall. The
+//! buffered types serve them from memory, and refill in large chunks.
+
+use std::io;
+use std::mem;
+
+use error::{self, BinaryIoError};
+use metrics::IoMetrics;
+
+/// The default buffer size, large enough for the typical headers.
+pub const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+/// Reads binary data through an internal buffer.
+///
+/// The small reads are served from the buffer, which is refilled by one large read of the inner
+/// reader. The reads larger than the buffer bypass it when it is empty. `BinaryRead` is
+/// available through the blanket implementation.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Seek};
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::buffered::BufferedBinaryReader;
+/// use mm_binary_io::endian::BigEndian;
+///
+/// let data = vec![0x12_u8, 0x34, 0x56, 0x78, 0x9A, 0xBC];
+/// let mut reader = BufferedBinaryReader::with_capacity(4, io::Cursor::new(data));
+///
+/// assert_eq!(0x1234, reader.read_integer::<BigEndian, u16>().unwrap());
+/// assert_eq!(2, reader.position());
+/// assert_eq!(&[0x56, 0x78], reader.buffer());
+///
+/// assert_eq!(vec![0x56, 0x78, 0x9A], reader.read_byte_array(3).unwrap());
+/// assert_eq!(5, reader.position());
+///
+/// reader.seek(io::SeekFrom::Current(-4)).unwrap();
+/// assert_eq!(0x34, reader.read_integer::<BigEndian, u8>().unwrap());
+/// assert_eq!(2, reader.position());
+///
+/// ```
+///
+pub struct BufferedBinaryReader<TRead>
+    where TRead: io::Read {
+    reader: TRead,
+    buffer: Box<[u8]>,
+    start: usize,
+    end: usize,
+    position: u64,
+    metrics: Option<IoMetrics>,
+}
+
+impl<TRead> BufferedBinaryReader<TRead>
+    where TRead: io::Read {
+    /// Creates a new `BufferedBinaryReader` with the buffer of `DEFAULT_CAPACITY`.
+    pub fn new(reader: TRead) -> BufferedBinaryReader<TRead> {
+        BufferedBinaryReader::with_capacity(DEFAULT_CAPACITY, reader)
+    }
+
+    /// Creates a new `BufferedBinaryReader` with the buffer of `capacity` bytes.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0, the function panics.
+    ///
+    pub fn with_capacity(capacity: usize, reader: TRead) -> BufferedBinaryReader<TRead> {
+        assert!(capacity > 0, "capacity must not be 0");
+        BufferedBinaryReader {
+            reader,
+            buffer: vec![0; capacity].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            position: 0,
+            metrics: None,
+        }
+    }
+
+    /// Returns the byte count read through this reader, or the position in the inner reader
+    /// after a seek.
+    ///
+    /// The bytes buffered but not read yet are not counted.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the bytes buffered but not read yet.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer[self.start..self.end]
+    }
+
+    /// Starts recording the IO statistics, resetting them.
+    pub fn enable_metrics(&mut self) {
+        self.metrics = Some(IoMetrics::default());
+    }
+
+    /// Returns the IO statistics, or None if they are not enabled.
+    pub fn metrics(&self) -> Option<IoMetrics> {
+        self.metrics
+    }
+
+    /// Returns the size of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns a reference to the inner reader.
+    pub fn get_ref(&self) -> &TRead {
+        &self.reader
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    ///
+    /// Reading from it directly skips the buffered bytes.
+    pub fn get_mut(&mut self) -> &mut TRead {
+        &mut self.reader
+    }
+
+    /// Unwraps this `BufferedBinaryReader`, returning the inner reader.
+    ///
+    /// The buffered bytes are lost.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+
+    fn discard_buffer(&mut self) {
+        self.start = 0;
+        self.end = 0;
+    }
+
+    fn fill_buffer(&mut self) -> io::Result<&[u8]> {
+        if self.start == self.end {
+            self.end = self.reader.read(&mut self.buffer)?;
+            self.start = 0;
+        }
+        Ok(&self.buffer[self.start..self.end])
+    }
+
+    fn consume_buffer(&mut self, amt: usize) -> usize {
+        let amt = amt.min(self.end - self.start);
+        self.start += amt;
+        self.position += amt as u64;
+        amt
+    }
+
+    fn read_buffered(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.start == self.end && buf.len() >= self.buffer.len() {
+            let len = self.reader.read(buf)?;
+            self.position += len as u64;
+            return Ok(len);
+        }
+        let len = {
+            let available = self.fill_buffer()?;
+            let len = available.len().min(buf.len());
+            buf[..len].copy_from_slice(&available[..len]);
+            len
+        };
+        Ok(self.consume_buffer(len))
+    }
+}
+
+impl<TRead> io::Read for BufferedBinaryReader<TRead>
+    where TRead: io::Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let start = IoMetrics::start(&self.metrics);
+        let result = self.read_buffered(buf);
+        IoMetrics::end_read(&mut self.metrics, start, *result.as_ref().unwrap_or(&0));
+        result
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        let offset = self.position;
+        let mut filled = 0;
+        while filled < buf.len() {
+            match io::Read::read(self, &mut buf[filled..]) {
+                Ok(0) => {
+                    let e = io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer");
+                    return Err(BinaryIoError::new(e).with_offset(offset).into());
+                }
+                Ok(n) => filled += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(error::with_offset(e, offset)),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<TRead> io::BufRead for BufferedBinaryReader<TRead>
+    where TRead: io::Read {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let start = IoMetrics::start(&self.metrics);
+        self.fill_buffer()?;
+        IoMetrics::end_read(&mut self.metrics, start, 0);
+        Ok(&self.buffer[self.start..self.end])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = self.consume_buffer(amt);
+        IoMetrics::consume(&mut self.metrics, amt);
+    }
+}
+
+impl<TRead> io::Seek for BufferedBinaryReader<TRead>
+    where TRead: io::Read + io::Seek {
+    /// Seeks the inner reader, and discards the buffer.
+    ///
+    /// `SeekFrom::Current` is relative to the position read through this reader. The position
+    /// is set to the new position of the inner reader.
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            io::SeekFrom::Current(offset) => {
+                let buffered = (self.end - self.start) as i64;
+                io::SeekFrom::Current(offset.checked_sub(buffered).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "seek offset overflows")
+                })?)
+            }
+            pos => pos,
+        };
+        let position = self.reader.seek(pos)?;
+        self.discard_buffer();
+        self.position = position;
+        Ok(position)
+    }
+}
+
+/// Writes binary data through an internal buffer.
+///
+/// The small writes are collected in the buffer, which is written to the inner writer when it is
+/// full. The writes larger than the buffer bypass it. `BinaryWrite` is available through the
+/// blanket implementation.
+///
+/// Call `finish()` at the end to write the buffer and get the error. When dropped without
+/// `finish()`, the buffer is written, but the error is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::buffered::BufferedBinaryWriter;
+/// use mm_binary_io::endian::BigEndian;
+///
+/// let mut writer = BufferedBinaryWriter::with_capacity(4, vec![]);
+///
+/// writer.write_integer::<BigEndian, _>(0x1234_u16).unwrap();
+/// assert_eq!(2, writer.position());
+/// assert_eq!(&[0x12, 0x34], writer.buffer());
+/// assert!(writer.get_ref().is_empty());
+///
+/// writer.write_integer::<BigEndian, _>(0x56_u8).unwrap();
+/// writer.write_integer::<BigEndian, _>(0x789A_u16).unwrap();
+/// assert_eq!(5, writer.position());
+/// assert_eq!(vec![0x12, 0x34, 0x56], *writer.get_ref());
+///
+/// writer.flush().unwrap();
+/// assert!(writer.buffer().is_empty());
+///
+/// writer.write_integer::<BigEndian, _>(0xBC_u8).unwrap();
+/// assert_eq!(vec![0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC], writer.finish().unwrap());
+///
+/// ```
+///
+pub struct BufferedBinaryWriter<TWrite>
+    where TWrite: io::Write {
+    writer: Option<TWrite>,
+    buffer: Vec<u8>,
+    capacity: usize,
+    position: u64,
+    metrics: Option<IoMetrics>,
+}
+
+impl<TWrite> BufferedBinaryWriter<TWrite>
+    where TWrite: io::Write {
+    /// Creates a new `BufferedBinaryWriter` with the buffer of `DEFAULT_CAPACITY`.
+    pub fn new(writer: TWrite) -> BufferedBinaryWriter<TWrite> {
+        BufferedBinaryWriter::with_capacity(DEFAULT_CAPACITY, writer)
+    }
+
+    /// Creates a new `BufferedBinaryWriter` with the buffer of `capacity` bytes.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0, the function panics.
+    ///
+    pub fn with_capacity(capacity: usize, writer: TWrite) -> BufferedBinaryWriter<TWrite> {
+        assert!(capacity > 0, "capacity must not be 0");
+        BufferedBinaryWriter {
+            writer: Some(writer),
+            buffer: Vec::with_capacity(capacity),
+            capacity,
+            position: 0,
+            metrics: None,
+        }
+    }
+
+    /// Returns the byte count written through this writer, or the position in the inner writer
+    /// after a seek.
+    ///
+    /// The bytes buffered but not written to the inner writer yet are counted.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the bytes buffered but not written to the inner writer yet.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Starts recording the IO statistics, resetting them.
+    pub fn enable_metrics(&mut self) {
+        self.metrics = Some(IoMetrics::default());
+    }
+
+    /// Returns the IO statistics, or None if they are not enabled.
+    pub fn metrics(&self) -> Option<IoMetrics> {
+        self.metrics
+    }
+
+    /// Returns the size of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns a reference to the inner writer.
+    pub fn get_ref(&self) -> &TWrite {
+        self.writer.as_ref().expect("the writer is present until finished")
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    ///
+    /// Writing to it directly puts the bytes before the buffered bytes.
+    pub fn get_mut(&mut self) -> &mut TWrite {
+        self.writer.as_mut().expect("the writer is present until finished")
+    }
+
+    /// Writes the buffer, flushes the inner writer, and returns it.
+    ///
+    /// # Errors
+    ///
+    /// If writing or flushing fails, returns Err(io::Error). The bytes not written are written
+    /// again when this writer is dropped, ignoring the error, so call `flush` to retry them
+    /// before `finish`.
+    ///
+    pub fn finish(mut self) -> io::Result<TWrite> {
+        io::Write::flush(&mut self)?;
+        Ok(self.writer.take().expect("the writer is present until finished"))
+    }
+
+    fn write_buffer(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        // The bytes written are removed even on an error, and the rest are kept to be retried.
+        let buffer = mem::take(&mut self.buffer);
+        let mut written = 0;
+        let mut result = Ok(());
+        while written < buffer.len() {
+            match self.get_mut().write(&buffer[written..]) {
+                Ok(0) => {
+                    result = Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write the buffered data"));
+                    break;
+                }
+                Ok(n) => written += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        self.buffer = buffer;
+        self.buffer.drain(..written);
+        result
+    }
+
+    fn write_buffered(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.buffer.len() + buf.len() > self.capacity {
+            self.write_buffer()?;
+        }
+        let len = if buf.len() >= self.capacity {
+            self.get_mut().write(buf)?
+        } else {
+            self.buffer.extend_from_slice(buf);
+            buf.len()
+        };
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl<TWrite> io::Write for BufferedBinaryWriter<TWrite>
+    where TWrite: io::Write {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let start = IoMetrics::start(&self.metrics);
+        let result = self.write_buffered(buf);
+        IoMetrics::end_write(&mut self.metrics, start, *result.as_ref().unwrap_or(&0));
+        result
+    }
+
+    /// Writes the buffer, and flushes the inner writer.
+    ///
+    /// If writing fails, the bytes not written are kept in the buffer, and written by the next
+    /// flush.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{self, Write};
+    /// use mm_binary_io::buffered::BufferedBinaryWriter;
+    ///
+    /// // Accepts 2 bytes, and then fails once.
+    /// struct Flaky { bytes: Vec<u8>, failed: bool }
+    ///
+    /// impl Write for Flaky {
+    ///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    ///         if self.bytes.len() == 2 && !self.failed {
+    ///             self.failed = true;
+    ///             return Err(io::Error::new(io::ErrorKind::Other, "disk full"));
+    ///         }
+    ///         let n = buf.len().min(2);
+    ///         self.bytes.extend_from_slice(&buf[..n]);
+    ///         Ok(n)
+    ///     }
+    ///     fn flush(&mut self) -> io::Result<()> { Ok(()) }
+    /// }
+    ///
+    /// let mut writer = BufferedBinaryWriter::new(Flaky { bytes: vec![], failed: false });
+    /// writer.write_all(b"abcde").unwrap();
+    ///
+    /// assert!(writer.flush().is_err());
+    /// assert_eq!(b"cde", writer.buffer());
+    /// writer.flush().unwrap();
+    /// assert_eq!(b"abcde".to_vec(), writer.finish().unwrap().bytes);
+    ///
+    /// ```
+    ///
+    fn flush(&mut self) -> io::Result<()> {
+        let start = IoMetrics::start(&self.metrics);
+        let result = self.write_buffer().and_then(|_| self.get_mut().flush());
+        IoMetrics::end_write(&mut self.metrics, start, 0);
+        result
+    }
+}
+
+impl<TWrite> io::Seek for BufferedBinaryWriter<TWrite>
+    where TWrite: io::Write + io::Seek {
+    /// Writes the buffer, and seeks the inner writer.
+    ///
+    /// The position is set to the new position of the inner writer.
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.write_buffer()?;
+        let position = self.get_mut().seek(pos)?;
+        self.position = position;
+        Ok(position)
+    }
+}
+
+impl<TWrite> Drop for BufferedBinaryWriter<TWrite>
+    where TWrite: io::Write {
+    fn drop(&mut self) {
+        if self.writer.is_some() {
+            let _ = self.write_buffer();
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Provides the bounds-checked random access to the integers of a byte slice, for inspecting and
+//! patching the headers of an in-memory buffer without a cursor.
+//!
+//! This module works without `std` and `alloc`.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+
+use endian::Endian;
+use from_bytes::FromBytes;
+use slice::OutOfBounds;
+use to_bytes::ToBytes;
+
+/// A view of a byte slice, whose integers are read at offsets in `TEndian`.
+///
+/// An access past the end is an error of `OutOfBounds`, not a panic.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::byte_view::ByteView;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::slice::OutOfBounds;
+///
+/// let header = b"\x7FELF\x02\x01\x01\x00\x00\x00\x3E\x00\x01\x00\x00\x00";
+/// let view = ByteView::<LittleEndian>::new(header);
+///
+/// assert_eq!(Ok(&b"\x7FELF"[..]), view.slice(0, 4));
+/// assert_eq!(Ok(0x3E), view.get_u16_at(10));
+/// assert_eq!(Ok(1), view.get_u32_at(12));
+/// assert_eq!(Err(OutOfBounds { required: 8, remaining: 4 }), view.get_u64_at(12));
+/// assert_eq!(Err(OutOfBounds { required: 2, remaining: 0 }), view.get_u16_at(100));
+///
+/// ```
+///
+pub struct ByteView<'a, TEndian> {
+    bytes: &'a [u8],
+    _endian: PhantomData<fn() -> TEndian>,
+}
+
+macro_rules! impl_get_at {
+    ($($name:ident: $t:ty),*) => {
+        $(
+            #[doc = concat!("Returns the `", stringify!($t), "` at `offset`.")]
+            pub fn $name(&self, offset: usize) -> Result<$t, OutOfBounds> {
+                self.get_at::<$t>(offset)
+            }
+        )*
+    };
+}
+
+macro_rules! impl_set_at {
+    ($($name:ident: $t:ty),*) => {
+        $(
+            #[doc = concat!("Writes the `", stringify!($t), "` `value` at `offset`.")]
+            pub fn $name(&mut self, offset: usize, value: $t) -> Result<(), OutOfBounds> {
+                self.set_at(offset, value)
+            }
+        )*
+    };
+}
+
+impl<'a, TEndian> ByteView<'a, TEndian>
+    where TEndian: Endian {
+    /// Creates a new `ByteView` of `bytes`.
+    pub fn new(bytes: &'a [u8]) -> ByteView<'a, TEndian> {
+        ByteView { bytes, _endian: PhantomData }
+    }
+
+    /// Returns the byte count.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns true if the view is of 0 bytes.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns the bytes viewed.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the `TInt` at `offset`.
+    pub fn get_at<TInt>(&self, offset: usize) -> Result<TInt, OutOfBounds>
+        where TInt: FromBytes<OutputType=TInt> {
+        self.slice(offset, mem::size_of::<TInt>()).map(TInt::from_bytes::<TEndian>)
+    }
+
+    impl_get_at!(get_u8_at: u8, get_i8_at: i8, get_u16_at: u16, get_i16_at: i16,
+                 get_u32_at: u32, get_i32_at: i32, get_u64_at: u64, get_i64_at: i64);
+
+    /// Returns the `len` bytes at `offset`, borrowing them from the slice.
+    pub fn slice(&self, offset: usize, len: usize) -> Result<&'a [u8], OutOfBounds> {
+        range(self.bytes.len(), offset, len).map(|end| &self.bytes[offset..end])
+    }
+
+    /// Returns the view of the `len` bytes at `offset`.
+    pub fn view(&self, offset: usize, len: usize) -> Result<ByteView<'a, TEndian>, OutOfBounds> {
+        self.slice(offset, len).map(ByteView::new)
+    }
+}
+
+impl<'a, TEndian> Clone for ByteView<'a, TEndian> {
+    fn clone(&self) -> ByteView<'a, TEndian> {
+        *self
+    }
+}
+
+impl<'a, TEndian> Copy for ByteView<'a, TEndian> {}
+
+impl<'a, TEndian> fmt::Debug for ByteView<'a, TEndian> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ByteView").field(&self.bytes).finish()
+    }
+}
+
+/// A view of a mutable byte slice, whose integers are read and written at offsets in `TEndian`.
+///
+/// An access past the end is an error of `OutOfBounds`, and a failed write writes nothing.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::byte_view::ByteViewMut;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::slice::OutOfBounds;
+///
+/// let mut header = *b"IMG1\x00\x00\x00\x00\x00\x10";
+/// let mut view = ByteViewMut::<BigEndian>::new(&mut header);
+///
+/// view.set_bytes_at(0, b"IMG2").unwrap();
+/// view.set_u32_at(4, 0x0102_0304).unwrap();
+/// let width = view.as_view().get_u16_at(8).unwrap();
+/// view.set_u16_at(8, width * 2).unwrap();
+/// assert_eq!(Err(OutOfBounds { required: 4, remaining: 2 }), view.set_u32_at(8, 0));
+///
+/// assert_eq!(b"IMG2\x01\x02\x03\x04\x00\x20", &header);
+///
+/// ```
+///
+pub struct ByteViewMut<'a, TEndian> {
+    bytes: &'a mut [u8],
+    _endian: PhantomData<fn() -> TEndian>,
+}
+
+impl<'a, TEndian> ByteViewMut<'a, TEndian>
+    where TEndian: Endian {
+    /// Creates a new `ByteViewMut` of `bytes`.
+    pub fn new(bytes: &'a mut [u8]) -> ByteViewMut<'a, TEndian> {
+        ByteViewMut { bytes, _endian: PhantomData }
+    }
+
+    /// Returns the byte count.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns true if the view is of 0 bytes.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns the read-only view of the bytes.
+    pub fn as_view(&self) -> ByteView<'_, TEndian> {
+        ByteView::new(self.bytes)
+    }
+
+    /// Writes the `TInt` `value` at `offset`.
+    pub fn set_at<TInt>(&mut self, offset: usize, value: TInt) -> Result<(), OutOfBounds>
+        where TInt: ToBytes {
+        self.slice_mut(offset, mem::size_of::<TInt>()).map(|destination| value.to_bytes::<TEndian>(destination))
+    }
+
+    impl_set_at!(set_u8_at: u8, set_i8_at: i8, set_u16_at: u16, set_i16_at: i16,
+                 set_u32_at: u32, set_i32_at: i32, set_u64_at: u64, set_i64_at: i64);
+
+    /// Writes `bytes` at `offset`.
+    pub fn set_bytes_at(&mut self, offset: usize, bytes: &[u8]) -> Result<(), OutOfBounds> {
+        self.slice_mut(offset, bytes.len()).map(|destination| destination.copy_from_slice(bytes))
+    }
+
+    /// Returns the `len` bytes at `offset`, borrowing them mutably.
+    pub fn slice_mut(&mut self, offset: usize, len: usize) -> Result<&mut [u8], OutOfBounds> {
+        let end = range(self.bytes.len(), offset, len)?;
+        Ok(&mut self.bytes[offset..end])
+    }
+
+    /// Returns the mutable view of the `len` bytes at `offset`.
+    pub fn view_mut(&mut self, offset: usize, len: usize) -> Result<ByteViewMut<'_, TEndian>, OutOfBounds> {
+        self.slice_mut(offset, len).map(ByteViewMut::new)
+    }
+
+    /// Unwraps this `ByteViewMut`, returning the bytes viewed.
+    pub fn into_bytes(self) -> &'a mut [u8] {
+        self.bytes
+    }
+}
+
+impl<'a, TEndian> fmt::Debug for ByteViewMut<'a, TEndian> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ByteViewMut").field(&self.bytes).finish()
+    }
+}
+
+/// Returns the end of the `len` bytes at `offset` in `total` bytes.
+fn range(total: usize, offset: usize, len: usize) -> Result<usize, OutOfBounds> {
+    let remaining = total.saturating_sub(offset);
+    if len > remaining {
+        return Err(OutOfBounds { required: len, remaining });
+    }
+    Ok(offset + len)
+}
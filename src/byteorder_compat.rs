@@ -0,0 +1,350 @@
+//! Provides the extension methods with the same names as `byteorder::ReadBytesExt` and
+//! `byteorder::WriteBytesExt`, to migrate from `byteorder` without rewriting every call site.
+//!
+//! This module is available with the `byteorder_compat` feature. Replacing
+//! `use byteorder::{BigEndian, ReadBytesExt};` with
+//! `use mm_binary_io::{endian::BigEndian, byteorder_compat::ReadBytesExt};` is usually enough.
+
+use std::io;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::{self, BigEndian, Endian};
+
+/// Extends `io::Read` with the methods of `byteorder::ReadBytesExt`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::byteorder_compat::ReadBytesExt;
+/// use mm_binary_io::endian::{BigEndian, LittleEndian};
+///
+/// let mut reader = io::Cursor::new(vec![0x02_u8, 0x05, 0x03, 0x00, 0x12, 0x34, 0x56, 0x00, 0x00, 0xC0, 0x3F, 0xFE, 0xFF]);
+/// assert_eq!(0x0205, reader.read_u16::<BigEndian>().unwrap());
+/// assert_eq!(3, reader.read_u16::<LittleEndian>().unwrap());
+/// assert_eq!(0x123456, reader.read_uint::<BigEndian>(3).unwrap());
+/// assert_eq!(1.5, reader.read_f32::<LittleEndian>().unwrap());
+/// assert_eq!(-2, reader.read_i16::<LittleEndian>().unwrap());
+///
+/// ```
+///
+pub trait ReadBytesExt: io::Read {
+    /// Reads a u8.
+    fn read_u8(&mut self) -> io::Result<u8>;
+
+    /// Reads an i8.
+    fn read_i8(&mut self) -> io::Result<i8>;
+
+    /// Reads a u16.
+    fn read_u16<TEndian>(&mut self) -> io::Result<u16>
+        where TEndian: Endian;
+
+    /// Reads an i16.
+    fn read_i16<TEndian>(&mut self) -> io::Result<i16>
+        where TEndian: Endian;
+
+    /// Reads a 24-bit unsigned integer.
+    fn read_u24<TEndian>(&mut self) -> io::Result<u32>
+        where TEndian: Endian;
+
+    /// Reads a u32.
+    fn read_u32<TEndian>(&mut self) -> io::Result<u32>
+        where TEndian: Endian;
+
+    /// Reads an i32.
+    fn read_i32<TEndian>(&mut self) -> io::Result<i32>
+        where TEndian: Endian;
+
+    /// Reads a u64.
+    fn read_u64<TEndian>(&mut self) -> io::Result<u64>
+        where TEndian: Endian;
+
+    /// Reads an i64.
+    fn read_i64<TEndian>(&mut self) -> io::Result<i64>
+        where TEndian: Endian;
+
+    /// Reads an unsigned integer of `nbytes` bytes (1 to 8).
+    ///
+    /// # Panics
+    ///
+    /// If `nbytes` is 0 or greater than 8, the function panics.
+    ///
+    fn read_uint<TEndian>(&mut self, nbytes: usize) -> io::Result<u64>
+        where TEndian: Endian;
+
+    /// Reads a sign-extended integer of `nbytes` bytes (1 to 8).
+    ///
+    /// # Panics
+    ///
+    /// If `nbytes` is 0 or greater than 8, the function panics.
+    ///
+    fn read_int<TEndian>(&mut self, nbytes: usize) -> io::Result<i64>
+        where TEndian: Endian;
+
+    /// Reads an IEEE 754 single-precision float.
+    fn read_f32<TEndian>(&mut self) -> io::Result<f32>
+        where TEndian: Endian;
+
+    /// Reads an IEEE 754 double-precision float.
+    fn read_f64<TEndian>(&mut self) -> io::Result<f64>
+        where TEndian: Endian;
+
+    /// Reads u16 values to fill `destination`.
+    fn read_u16_into<TEndian>(&mut self, destination: &mut [u16]) -> io::Result<()>
+        where TEndian: Endian;
+
+    /// Reads u32 values to fill `destination`.
+    fn read_u32_into<TEndian>(&mut self, destination: &mut [u32]) -> io::Result<()>
+        where TEndian: Endian;
+
+    /// Reads u64 values to fill `destination`.
+    fn read_u64_into<TEndian>(&mut self, destination: &mut [u64]) -> io::Result<()>
+        where TEndian: Endian;
+}
+
+impl<T> ReadBytesExt for T
+    where T: io::Read {
+    fn read_u8(&mut self) -> io::Result<u8> {
+        self.read_integer::<BigEndian, u8>()
+    }
+
+    fn read_i8(&mut self) -> io::Result<i8> {
+        self.read_integer::<BigEndian, i8>()
+    }
+
+    fn read_u16<TEndian>(&mut self) -> io::Result<u16>
+        where TEndian: Endian {
+        self.read_integer::<TEndian, u16>()
+    }
+
+    fn read_i16<TEndian>(&mut self) -> io::Result<i16>
+        where TEndian: Endian {
+        self.read_integer::<TEndian, i16>()
+    }
+
+    fn read_u24<TEndian>(&mut self) -> io::Result<u32>
+        where TEndian: Endian {
+        self.read_uint::<TEndian>(3).map(|x| x as u32)
+    }
+
+    fn read_u32<TEndian>(&mut self) -> io::Result<u32>
+        where TEndian: Endian {
+        self.read_integer::<TEndian, u32>()
+    }
+
+    fn read_i32<TEndian>(&mut self) -> io::Result<i32>
+        where TEndian: Endian {
+        self.read_integer::<TEndian, i32>()
+    }
+
+    fn read_u64<TEndian>(&mut self) -> io::Result<u64>
+        where TEndian: Endian {
+        self.read_integer::<TEndian, u64>()
+    }
+
+    fn read_i64<TEndian>(&mut self) -> io::Result<i64>
+        where TEndian: Endian {
+        self.read_integer::<TEndian, i64>()
+    }
+
+    fn read_uint<TEndian>(&mut self, nbytes: usize) -> io::Result<u64>
+        where TEndian: Endian {
+        assert!((1..=8).contains(&nbytes), "nbytes must be 1 to 8");
+        let mut buf = [0_u8; 8];
+        if endian::is_big_endian::<TEndian>() {
+            self.read_exact(&mut buf[8 - nbytes..])?;
+        } else {
+            self.read_exact(&mut buf[..nbytes])?;
+        }
+        Ok(TEndian::u64_from_bytes(&buf))
+    }
+
+    fn read_int<TEndian>(&mut self, nbytes: usize) -> io::Result<i64>
+        where TEndian: Endian {
+        let shift = 64 - 8 * nbytes as u32;
+        self.read_uint::<TEndian>(nbytes).map(|x| ((x << shift) as i64) >> shift)
+    }
+
+    fn read_f32<TEndian>(&mut self) -> io::Result<f32>
+        where TEndian: Endian {
+        self.read_integer::<TEndian, u32>().map(f32::from_bits)
+    }
+
+    fn read_f64<TEndian>(&mut self) -> io::Result<f64>
+        where TEndian: Endian {
+        self.read_integer::<TEndian, u64>().map(f64::from_bits)
+    }
+
+    fn read_u16_into<TEndian>(&mut self, destination: &mut [u16]) -> io::Result<()>
+        where TEndian: Endian {
+        destination.iter_mut().try_for_each(|x| self.read_integer::<TEndian, u16>().map(|v| *x = v))
+    }
+
+    fn read_u32_into<TEndian>(&mut self, destination: &mut [u32]) -> io::Result<()>
+        where TEndian: Endian {
+        destination.iter_mut().try_for_each(|x| self.read_integer::<TEndian, u32>().map(|v| *x = v))
+    }
+
+    fn read_u64_into<TEndian>(&mut self, destination: &mut [u64]) -> io::Result<()>
+        where TEndian: Endian {
+        destination.iter_mut().try_for_each(|x| self.read_integer::<TEndian, u64>().map(|v| *x = v))
+    }
+}
+
+/// Extends `io::Write` with the methods of `byteorder::WriteBytesExt`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::byteorder_compat::WriteBytesExt;
+/// use mm_binary_io::endian::{BigEndian, LittleEndian};
+///
+/// let mut writer = vec![];
+/// writer.write_u16::<BigEndian>(0x0205).unwrap();
+/// writer.write_i32::<LittleEndian>(-2).unwrap();
+/// writer.write_uint::<LittleEndian>(0x123456, 3).unwrap();
+/// writer.write_f32::<BigEndian>(1.5).unwrap();
+/// assert_eq!(vec![0x02, 0x05, 0xFE, 0xFF, 0xFF, 0xFF, 0x56, 0x34, 0x12, 0x3F, 0xC0, 0x00, 0x00], writer);
+///
+/// ```
+///
+pub trait WriteBytesExt: io::Write {
+    /// Writes a u8.
+    fn write_u8(&mut self, value: u8) -> io::Result<()>;
+
+    /// Writes an i8.
+    fn write_i8(&mut self, value: i8) -> io::Result<()>;
+
+    /// Writes a u16.
+    fn write_u16<TEndian>(&mut self, value: u16) -> io::Result<()>
+        where TEndian: Endian;
+
+    /// Writes an i16.
+    fn write_i16<TEndian>(&mut self, value: i16) -> io::Result<()>
+        where TEndian: Endian;
+
+    /// Writes a 24-bit unsigned integer.
+    fn write_u24<TEndian>(&mut self, value: u32) -> io::Result<()>
+        where TEndian: Endian;
+
+    /// Writes a u32.
+    fn write_u32<TEndian>(&mut self, value: u32) -> io::Result<()>
+        where TEndian: Endian;
+
+    /// Writes an i32.
+    fn write_i32<TEndian>(&mut self, value: i32) -> io::Result<()>
+        where TEndian: Endian;
+
+    /// Writes a u64.
+    fn write_u64<TEndian>(&mut self, value: u64) -> io::Result<()>
+        where TEndian: Endian;
+
+    /// Writes an i64.
+    fn write_i64<TEndian>(&mut self, value: i64) -> io::Result<()>
+        where TEndian: Endian;
+
+    /// Writes the lower `nbytes` bytes (1 to 8) of an unsigned integer.
+    ///
+    /// # Panics
+    ///
+    /// If `nbytes` is 0 or greater than 8, or `value` does not fit in `nbytes` bytes,
+    /// the function panics.
+    ///
+    fn write_uint<TEndian>(&mut self, value: u64, nbytes: usize) -> io::Result<()>
+        where TEndian: Endian;
+
+    /// Writes the lower `nbytes` bytes (1 to 8) of a signed integer.
+    ///
+    /// # Panics
+    ///
+    /// If `nbytes` is 0 or greater than 8, or `value` does not fit in `nbytes` bytes,
+    /// the function panics.
+    ///
+    fn write_int<TEndian>(&mut self, value: i64, nbytes: usize) -> io::Result<()>
+        where TEndian: Endian;
+
+    /// Writes an IEEE 754 single-precision float.
+    fn write_f32<TEndian>(&mut self, value: f32) -> io::Result<()>
+        where TEndian: Endian;
+
+    /// Writes an IEEE 754 double-precision float.
+    fn write_f64<TEndian>(&mut self, value: f64) -> io::Result<()>
+        where TEndian: Endian;
+}
+
+impl<T> WriteBytesExt for T
+    where T: io::Write {
+    fn write_u8(&mut self, value: u8) -> io::Result<()> {
+        self.write_integer::<BigEndian, _>(value)
+    }
+
+    fn write_i8(&mut self, value: i8) -> io::Result<()> {
+        self.write_integer::<BigEndian, _>(value)
+    }
+
+    fn write_u16<TEndian>(&mut self, value: u16) -> io::Result<()>
+        where TEndian: Endian {
+        self.write_integer::<TEndian, _>(value)
+    }
+
+    fn write_i16<TEndian>(&mut self, value: i16) -> io::Result<()>
+        where TEndian: Endian {
+        self.write_integer::<TEndian, _>(value)
+    }
+
+    fn write_u24<TEndian>(&mut self, value: u32) -> io::Result<()>
+        where TEndian: Endian {
+        self.write_uint::<TEndian>(value as u64, 3)
+    }
+
+    fn write_u32<TEndian>(&mut self, value: u32) -> io::Result<()>
+        where TEndian: Endian {
+        self.write_integer::<TEndian, _>(value)
+    }
+
+    fn write_i32<TEndian>(&mut self, value: i32) -> io::Result<()>
+        where TEndian: Endian {
+        self.write_integer::<TEndian, _>(value)
+    }
+
+    fn write_u64<TEndian>(&mut self, value: u64) -> io::Result<()>
+        where TEndian: Endian {
+        self.write_integer::<TEndian, _>(value)
+    }
+
+    fn write_i64<TEndian>(&mut self, value: i64) -> io::Result<()>
+        where TEndian: Endian {
+        self.write_integer::<TEndian, _>(value)
+    }
+
+    fn write_uint<TEndian>(&mut self, value: u64, nbytes: usize) -> io::Result<()>
+        where TEndian: Endian {
+        assert!((1..=8).contains(&nbytes), "nbytes must be 1 to 8");
+        assert!(nbytes == 8 || value >> (8 * nbytes) == 0, "value does not fit in nbytes");
+        let mut buf = [0_u8; 8];
+        TEndian::u64_to_bytes(value, &mut buf);
+        if endian::is_big_endian::<TEndian>() {
+            self.write_all(&buf[8 - nbytes..])
+        } else {
+            self.write_all(&buf[..nbytes])
+        }
+    }
+
+    fn write_int<TEndian>(&mut self, value: i64, nbytes: usize) -> io::Result<()>
+        where TEndian: Endian {
+        let shift = 64 - 8 * nbytes as u32;
+        assert!((value << shift) >> shift == value, "value does not fit in nbytes");
+        self.write_uint::<TEndian>(value as u64 & (u64::MAX >> shift), nbytes)
+    }
+
+    fn write_f32<TEndian>(&mut self, value: f32) -> io::Result<()>
+        where TEndian: Endian {
+        self.write_integer::<TEndian, _>(value.to_bits())
+    }
+
+    fn write_f64<TEndian>(&mut self, value: f64) -> io::Result<()>
+        where TEndian: Endian {
+        self.write_integer::<TEndian, _>(value.to_bits())
+    }
+}
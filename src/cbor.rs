@@ -0,0 +1,197 @@
+//! Provides the features to read and write CBOR (RFC 8949) data item headers.
+//!
+//! A header is the initial byte (the major type and the additional information) followed by a
+//! 1, 2, 4 or 8-byte big-endian argument. The contents are left to the caller.
+
+use std::io;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::BigEndian;
+
+/// The additional information of the indefinite length (or the "break" stop code).
+pub const INDEFINITE: u8 = 31;
+
+/// The "break" stop code which ends an indefinite-length item.
+pub const BREAK: u8 = 0xFF;
+
+/// The major type of a data item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MajorType {
+    /// 0: an unsigned integer.
+    UnsignedInt,
+    /// 1: a negative integer (-1 - argument).
+    NegativeInt,
+    /// 2: a byte string.
+    ByteString,
+    /// 3: a UTF-8 text string.
+    TextString,
+    /// 4: an array.
+    Array,
+    /// 5: a map.
+    Map,
+    /// 6: a tagged data item.
+    Tag,
+    /// 7: a simple value or a floating-point number.
+    Simple,
+}
+
+impl MajorType {
+    /// Returns the major type of the upper 3 bits of `initial_byte`.
+    pub fn from_initial_byte(initial_byte: u8) -> MajorType {
+        match initial_byte >> 5 {
+            0 => MajorType::UnsignedInt,
+            1 => MajorType::NegativeInt,
+            2 => MajorType::ByteString,
+            3 => MajorType::TextString,
+            4 => MajorType::Array,
+            5 => MajorType::Map,
+            6 => MajorType::Tag,
+            _ => MajorType::Simple,
+        }
+    }
+
+    /// Returns the 3-bit value of the major type.
+    pub fn bits(self) -> u8 {
+        match self {
+            MajorType::UnsignedInt => 0,
+            MajorType::NegativeInt => 1,
+            MajorType::ByteString => 2,
+            MajorType::TextString => 3,
+            MajorType::Array => 4,
+            MajorType::Map => 5,
+            MajorType::Tag => 6,
+            MajorType::Simple => 7,
+        }
+    }
+
+    /// Returns true if the major type allows the indefinite length.
+    pub fn allows_indefinite(self) -> bool {
+        matches!(self, MajorType::ByteString | MajorType::TextString | MajorType::Array | MajorType::Map)
+    }
+}
+
+/// The header of a data item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    /// The major type.
+    pub major_type: MajorType,
+    /// The lower 5 bits of the initial byte.
+    pub additional_info: u8,
+    /// The argument, or None for the indefinite length and "break".
+    pub argument: Option<u64>,
+}
+
+impl Header {
+    /// Returns true if this is the "break" stop code.
+    pub fn is_break(&self) -> bool {
+        self.major_type == MajorType::Simple && self.additional_info == INDEFINITE
+    }
+}
+
+/// Reads the header of a data item.
+///
+/// # Errors
+///
+/// If the additional information is reserved (28 to 30), or is 31 for a major type which does
+/// not allow the indefinite length, returns Err(io::Error) of `InvalidData`.
+/// If reading fails, returns Err(io::Error).
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::cbor::{self, Header, MajorType};
+///
+/// let mut reader = io::Cursor::new(vec![0x19_u8, 0x01, 0xF4, 0x65, 0x9F, 0xF9, 0x3E, 0x00, 0xFF]);
+///
+/// let header = cbor::read_header(&mut reader).unwrap();
+/// assert_eq!((MajorType::UnsignedInt, Some(500)), (header.major_type, header.argument));
+///
+/// let header = cbor::read_header(&mut reader).unwrap();
+/// assert_eq!((MajorType::TextString, Some(5)), (header.major_type, header.argument));
+///
+/// let header = cbor::read_header(&mut reader).unwrap();
+/// assert_eq!((MajorType::Array, None), (header.major_type, header.argument));
+///
+/// // A half-precision float: the argument holds the bits, and the additional information the width.
+/// assert_eq!(
+///     Header { major_type: MajorType::Simple, additional_info: 25, argument: Some(0x3E00) },
+///     cbor::read_header(&mut reader).unwrap());
+///
+/// assert!(cbor::read_header(&mut reader).unwrap().is_break());
+///
+/// ```
+///
+pub fn read_header<TRead>(reader: &mut TRead) -> io::Result<Header>
+    where TRead: io::Read {
+    let initial_byte = reader.read_integer::<BigEndian, u8>()?;
+    let major_type = MajorType::from_initial_byte(initial_byte);
+    let additional_info = initial_byte & 0x1F;
+    let argument = match additional_info {
+        0..=23 => Some(additional_info as u64),
+        24 => Some(reader.read_integer::<BigEndian, u8>()? as u64),
+        25 => Some(reader.read_integer::<BigEndian, u16>()? as u64),
+        26 => Some(reader.read_integer::<BigEndian, u32>()? as u64),
+        27 => Some(reader.read_integer::<BigEndian, u64>()?),
+        INDEFINITE if major_type.allows_indefinite() || major_type == MajorType::Simple => None,
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid CBOR additional information")),
+    };
+    Ok(Header { major_type, additional_info, argument })
+}
+
+/// Writes the header of a data item with `argument` in the shortest form.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::cbor::{self, MajorType};
+///
+/// let mut writer = vec![];
+/// cbor::write_header(&mut writer, MajorType::UnsignedInt, 500).unwrap();
+/// cbor::write_header(&mut writer, MajorType::NegativeInt, 0).unwrap();
+/// cbor::write_header(&mut writer, MajorType::ByteString, 24).unwrap();
+/// assert_eq!(vec![0x19, 0x01, 0xF4, 0x20, 0x58, 0x18], writer);
+///
+/// ```
+///
+pub fn write_header<TWrite>(writer: &mut TWrite, major_type: MajorType, argument: u64) -> io::Result<()>
+    where TWrite: io::Write {
+    let major = major_type.bits() << 5;
+    if argument < 24 {
+        writer.write_integer::<BigEndian, _>(major | argument as u8)
+    } else if argument <= u8::MAX as u64 {
+        writer.write_integer::<BigEndian, _>(major | 24)?;
+        writer.write_integer::<BigEndian, _>(argument as u8)
+    } else if argument <= u16::MAX as u64 {
+        writer.write_integer::<BigEndian, _>(major | 25)?;
+        writer.write_integer::<BigEndian, _>(argument as u16)
+    } else if argument <= u32::MAX as u64 {
+        writer.write_integer::<BigEndian, _>(major | 26)?;
+        writer.write_integer::<BigEndian, _>(argument as u32)
+    } else {
+        writer.write_integer::<BigEndian, _>(major | 27)?;
+        writer.write_integer::<BigEndian, _>(argument)
+    }
+}
+
+/// Writes the header of an indefinite-length item, which must be ended by `write_break`.
+///
+/// # Errors
+///
+/// If `major_type` does not allow the indefinite length, returns Err(io::Error) of `InvalidInput`.
+/// If writing fails, returns Err(io::Error).
+///
+pub fn write_indefinite<TWrite>(writer: &mut TWrite, major_type: MajorType) -> io::Result<()>
+    where TWrite: io::Write {
+    if !major_type.allows_indefinite() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "major type does not allow the indefinite length"));
+    }
+    writer.write_integer::<BigEndian, _>(major_type.bits() << 5 | INDEFINITE)
+}
+
+/// Writes the "break" stop code.
+pub fn write_break<TWrite>(writer: &mut TWrite) -> io::Result<()>
+    where TWrite: io::Write {
+    writer.write_integer::<BigEndian, _>(BREAK)
+}
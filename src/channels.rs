@@ -0,0 +1,174 @@
+//! Provides the features to convert the multi-channel data between the interleaved and the
+//! planar layouts.
+//!
+//! The interleaved layout stores the samples of one frame together (`L R L R L R`), as WAV and
+//! most sensor captures do. The planar layout stores each channel contiguously (`L L L R R R`),
+//! which the filters and the analyses process. The conversions are also done while reading and
+//! writing the integer arrays.
+
+use std::io;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::Endian;
+use read_integer::ReadInteger;
+use write_integer::WriteInteger;
+
+/// Converts the `interleaved` samples of `channel_count` channels to the planar layout.
+///
+/// Returns None if `channel_count` is 0 or the length is not a multiple of it.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::channels;
+///
+/// let planar = channels::to_planar(&[1, 10, 2, 20, 3, 30], 2).unwrap();
+/// assert_eq!(vec![1, 2, 3, 10, 20, 30], planar);
+/// assert_eq!(Some(vec![1, 10, 2, 20, 3, 30]), channels::to_interleaved(&planar, 2));
+///
+/// assert_eq!(None, channels::to_planar(&[1, 10, 2], 2));
+///
+/// ```
+///
+pub fn to_planar<T>(interleaved: &[T], channel_count: usize) -> Option<Vec<T>>
+    where T: Copy {
+    let frame_count = frame_count(interleaved.len(), channel_count)?;
+    let mut planar = Vec::with_capacity(interleaved.len());
+    for channel in 0..channel_count {
+        planar.extend((0..frame_count).map(|frame| interleaved[frame * channel_count + channel]));
+    }
+    Some(planar)
+}
+
+/// Converts the `planar` samples of `channel_count` channels to the interleaved layout.
+///
+/// Returns None if `channel_count` is 0 or the length is not a multiple of it.
+pub fn to_interleaved<T>(planar: &[T], channel_count: usize) -> Option<Vec<T>>
+    where T: Copy {
+    let frame_count = frame_count(planar.len(), channel_count)?;
+    let mut interleaved = Vec::with_capacity(planar.len());
+    for frame in 0..frame_count {
+        interleaved.extend((0..channel_count).map(|channel| planar[channel * frame_count + frame]));
+    }
+    Some(interleaved)
+}
+
+/// Splits the `interleaved` samples of `channel_count` channels into a vector per channel.
+///
+/// Returns None if `channel_count` is 0 or the length is not a multiple of it.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::channels;
+///
+/// let split = channels::deinterleave(&[1, 10, 2, 20, 3, 30], 2).unwrap();
+/// assert_eq!(vec![vec![1, 2, 3], vec![10, 20, 30]], split);
+/// assert_eq!(Some(vec![1, 10, 2, 20, 3, 30]), channels::interleave(&split));
+///
+/// assert_eq!(None, channels::interleave(&[vec![1, 2], vec![10]]));
+///
+/// ```
+///
+pub fn deinterleave<T>(interleaved: &[T], channel_count: usize) -> Option<Vec<Vec<T>>>
+    where T: Copy {
+    frame_count(interleaved.len(), channel_count)?;
+    Some((0..channel_count)
+        .map(|channel| interleaved.iter().skip(channel).step_by(channel_count).cloned().collect())
+        .collect())
+}
+
+/// Joins the vectors of `channels` into the interleaved samples.
+///
+/// Returns None if the channels have different lengths.
+pub fn interleave<T, TChannel>(channels: &[TChannel]) -> Option<Vec<T>>
+    where T: Copy,
+          TChannel: AsRef<[T]> {
+    let frame_count = channels.first().map_or(0, |channel| channel.as_ref().len());
+    if channels.iter().any(|channel| channel.as_ref().len() != frame_count) {
+        return None;
+    }
+    let mut interleaved = Vec::with_capacity(frame_count * channels.len());
+    for frame in 0..frame_count {
+        interleaved.extend(channels.iter().map(|channel| channel.as_ref()[frame]));
+    }
+    Some(interleaved)
+}
+
+/// Provides the features to read the interleaved integer arrays by channel.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::channels::{ChannelRead, ChannelWrite};
+/// use mm_binary_io::endian::LittleEndian;
+///
+/// let channels = vec![vec![1_i16, 2, 3], vec![-1, -2, -3]];
+///
+/// let mut writer = vec![];
+/// writer.write_channels::<LittleEndian, i16, _>(&channels).unwrap();
+/// assert_eq!(vec![0x01, 0x00, 0xFF, 0xFF, 0x02, 0x00], writer[..6].to_vec());
+///
+/// let mut reader = io::Cursor::new(writer);
+/// assert_eq!(channels, reader.read_channels::<LittleEndian, i16>(3, 2).unwrap());
+///
+/// ```
+///
+pub trait ChannelRead: io::Read {
+    /// Reads `frame_count` frames of `channel_count` interleaved integers, and returns a vector
+    /// per channel.
+    ///
+    /// # Errors
+    ///
+    /// If `channel_count` is 0 or the sample count overflows, returns Err(io::Error) of
+    /// `InvalidInput`. If reading fails, returns Err(io::Error).
+    ///
+    fn read_channels<TEndian, TInt>(&mut self, frame_count: usize, channel_count: usize) -> io::Result<Vec<Vec<TInt>>>
+        where Self: Sized,
+              TEndian: Endian,
+              TInt: ReadInteger<OutputType=TInt> + Copy {
+        if channel_count == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "channel count is 0"));
+        }
+        let sample_count = frame_count.checked_mul(channel_count)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "sample count is too large"))?;
+        let interleaved = self.read_integer_array::<TEndian, TInt>(sample_count)?;
+        Ok(deinterleave(&interleaved, channel_count).unwrap_or_default())
+    }
+}
+
+impl<T> ChannelRead for T
+    where T: io::Read {}
+
+/// Provides the features to write the integer arrays of channels interleaved.
+pub trait ChannelWrite: io::Write {
+    /// Writes the vectors of `channels` as interleaved integers.
+    ///
+    /// # Errors
+    ///
+    /// If the channels have different lengths, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    fn write_channels<TEndian, TInt, TChannel>(&mut self, channels: &[TChannel]) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian,
+              TInt: WriteInteger + Copy,
+              TChannel: AsRef<[TInt]> {
+        let interleaved = interleave(channels)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "channels have different lengths"))?;
+        self.write_integer_array::<TEndian, TInt>(&interleaved)
+    }
+}
+
+impl<T> ChannelWrite for T
+    where T: io::Write {}
+
+/// Returns the frame count of `len` samples of `channel_count` channels.
+fn frame_count(len: usize, channel_count: usize) -> Option<usize> {
+    if channel_count == 0 || !len.is_multiple_of(channel_count) {
+        return None;
+    }
+    Some(len / channel_count)
+}
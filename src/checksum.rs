@@ -0,0 +1,195 @@
+//! Provides the features to calculate checksums while reading or writing binary data.
+
+use std::io;
+
+/// Provides the features of an incrementally updatable checksum.
+pub trait Checksum {
+    /// The checksum value type.
+    type Output;
+
+    /// Updates the checksum with `bytes`.
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Returns the checksum of the data so far.
+    fn digest(&self) -> Self::Output;
+
+    /// Resets the checksum to the initial state.
+    fn reset(&mut self);
+}
+
+/// Wraps a reader and updates a checksum with all bytes read through it.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::crc32::Crc32Reader;
+/// use mm_binary_io::endian::BigEndian;
+///
+/// let mut reader = Crc32Reader::new(io::Cursor::new(b"123456789\x00\x01".to_vec()));
+///
+/// assert_eq!(b"123456789".to_vec(), reader.read_byte_array(9).unwrap());
+/// assert_eq!(0xCBF43926, reader.digest());
+///
+/// reader.reset();
+/// assert_eq!(1_u16, reader.read_integer::<BigEndian, u16>().unwrap());
+/// assert_eq!(0x36DE2269, reader.digest());
+///
+/// ```
+///
+pub struct ChecksumReader<TRead, TChecksum>
+    where TRead: io::Read,
+          TChecksum: Checksum {
+    reader: TRead,
+    checksum: TChecksum,
+}
+
+impl<TRead, TChecksum> ChecksumReader<TRead, TChecksum>
+    where TRead: io::Read,
+          TChecksum: Checksum {
+    /// Creates a new `ChecksumReader` with the initial checksum.
+    pub fn new(reader: TRead) -> ChecksumReader<TRead, TChecksum>
+        where TChecksum: Default {
+        ChecksumReader::with_checksum(reader, TChecksum::default())
+    }
+
+    /// Creates a new `ChecksumReader` with the given checksum.
+    pub fn with_checksum(reader: TRead, checksum: TChecksum) -> ChecksumReader<TRead, TChecksum> {
+        ChecksumReader {
+            reader,
+            checksum,
+        }
+    }
+
+    /// Returns the checksum of the bytes read so far.
+    pub fn digest(&self) -> TChecksum::Output {
+        self.checksum.digest()
+    }
+
+    /// Resets the checksum to the initial state.
+    pub fn reset(&mut self) {
+        self.checksum.reset()
+    }
+
+    /// Gets a reference to the checksum.
+    pub fn checksum(&self) -> &TChecksum {
+        &self.checksum
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &TRead {
+        &self.reader
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// The bytes read directly from the underlying reader are not included in the checksum.
+    pub fn get_mut(&mut self) -> &mut TRead {
+        &mut self.reader
+    }
+
+    /// Unwraps this `ChecksumReader`, returning the underlying reader and the checksum.
+    pub fn into_inner(self) -> (TRead, TChecksum) {
+        (self.reader, self.checksum)
+    }
+}
+
+impl<TRead, TChecksum> io::Read for ChecksumReader<TRead, TChecksum>
+    where TRead: io::Read,
+          TChecksum: Checksum {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.checksum.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Wraps a writer and updates a checksum with all bytes written through it.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::crc32::Crc32Writer;
+/// use mm_binary_io::endian::BigEndian;
+///
+/// let mut writer = Crc32Writer::new(vec![]);
+/// writer.write_integer::<BigEndian, _>(0x31323334_u32).unwrap();
+/// writer.write_integer_array::<BigEndian, _>(&[0x35_u8, 0x36, 0x37, 0x38, 0x39]).unwrap();
+///
+/// assert_eq!(0xCBF43926, writer.digest());
+/// assert_eq!(b"123456789".to_vec(), writer.into_inner().0);
+///
+/// ```
+///
+pub struct ChecksumWriter<TWrite, TChecksum>
+    where TWrite: io::Write,
+          TChecksum: Checksum {
+    writer: TWrite,
+    checksum: TChecksum,
+}
+
+impl<TWrite, TChecksum> ChecksumWriter<TWrite, TChecksum>
+    where TWrite: io::Write,
+          TChecksum: Checksum {
+    /// Creates a new `ChecksumWriter` with the initial checksum.
+    pub fn new(writer: TWrite) -> ChecksumWriter<TWrite, TChecksum>
+        where TChecksum: Default {
+        ChecksumWriter::with_checksum(writer, TChecksum::default())
+    }
+
+    /// Creates a new `ChecksumWriter` with the given checksum.
+    pub fn with_checksum(writer: TWrite, checksum: TChecksum) -> ChecksumWriter<TWrite, TChecksum> {
+        ChecksumWriter {
+            writer,
+            checksum,
+        }
+    }
+
+    /// Returns the checksum of the bytes written so far.
+    pub fn digest(&self) -> TChecksum::Output {
+        self.checksum.digest()
+    }
+
+    /// Resets the checksum to the initial state.
+    pub fn reset(&mut self) {
+        self.checksum.reset()
+    }
+
+    /// Gets a reference to the checksum.
+    pub fn checksum(&self) -> &TChecksum {
+        &self.checksum
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &TWrite {
+        &self.writer
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    ///
+    /// The bytes written directly to the underlying writer are not included in the checksum.
+    pub fn get_mut(&mut self) -> &mut TWrite {
+        &mut self.writer
+    }
+
+    /// Unwraps this `ChecksumWriter`, returning the underlying writer and the checksum.
+    pub fn into_inner(self) -> (TWrite, TChecksum) {
+        (self.writer, self.checksum)
+    }
+}
+
+impl<TWrite, TChecksum> io::Write for ChecksumWriter<TWrite, TChecksum>
+    where TWrite: io::Write,
+          TChecksum: Checksum {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.writer.write(buf)?;
+        self.checksum.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,171 @@
+//! Provides the features to read and write checksum-verified frames.
+//!
+//! A frame is a length prefix, the payload, and the checksum of the payload.
+
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use checksum::Checksum;
+use endian::Endian;
+use read_integer::ReadInteger;
+use scratch;
+use write_integer::WriteInteger;
+
+/// The error returned when the checksum of a frame does not match.
+///
+/// It is returned as the inner error of an `io::Error` of `InvalidData`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChecksumMismatch<TOutput> {
+    /// The checksum stored in the frame.
+    pub expected: TOutput,
+    /// The checksum calculated from the payload.
+    pub actual: TOutput,
+}
+
+impl<TOutput> fmt::Display for ChecksumMismatch<TOutput>
+    where TOutput: fmt::LowerHex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "checksum mismatch: expected {:#x}, actual {:#x}", self.expected, self.actual)
+    }
+}
+
+impl<TOutput> error::Error for ChecksumMismatch<TOutput>
+    where TOutput: fmt::Debug + fmt::LowerHex {}
+
+/// Reads and writes frames of a `TLen` length prefix, the payload, and a `TChecksum` checksum
+/// of the payload, all integers in `TEndian`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::checksum_frame::{ChecksumFrame, ChecksumMismatch};
+/// use mm_binary_io::crc32::Crc32;
+/// use mm_binary_io::endian::BigEndian;
+///
+/// let mut frame = ChecksumFrame::<BigEndian, u16, Crc32>::new(1024);
+///
+/// let mut writer = io::Cursor::new(vec![]);
+/// frame.write_frame(&mut writer, b"123456789").unwrap();
+/// let mut data = writer.into_inner();
+/// assert_eq!(vec![0x00, 0x09], data[0..2].to_vec());
+/// assert_eq!(vec![0xCB, 0xF4, 0x39, 0x26], data[11..15].to_vec());
+///
+/// let mut reader = io::Cursor::new(data.clone());
+/// assert_eq!(b"123456789".to_vec(), frame.read_frame(&mut reader).unwrap());
+///
+/// // Corrupts the payload.
+/// data[2] = b'0';
+/// let mut reader = io::Cursor::new(data);
+/// let error = frame.read_frame(&mut reader).unwrap_err();
+/// assert_eq!(io::ErrorKind::InvalidData, error.kind());
+///
+/// let mismatch = error.get_ref().unwrap().downcast_ref::<ChecksumMismatch<u32>>().unwrap();
+/// assert_eq!(0xCBF43926, mismatch.expected);
+///
+/// ```
+///
+pub struct ChecksumFrame<TEndian, TLen, TChecksum>
+    where TEndian: Endian,
+          TChecksum: Checksum {
+    checksum: TChecksum,
+    max_len: usize,
+    _marker: PhantomData<(TEndian, TLen)>,
+}
+
+impl<TEndian, TLen, TChecksum> ChecksumFrame<TEndian, TLen, TChecksum>
+    where TEndian: Endian,
+          TLen: ReadInteger<OutputType=TLen> + WriteInteger + Into<u64> + TryFrom<u64>,
+          TChecksum: Checksum,
+          TChecksum::Output: ReadInteger<OutputType=TChecksum::Output> + WriteInteger
+          + PartialEq + fmt::Debug + fmt::LowerHex + Send + Sync + 'static {
+    /// Creates a new `ChecksumFrame` with the initial checksum.
+    ///
+    /// # Arguments
+    ///
+    /// * max_len - the maximum payload length accepted by `read_frame`.
+    ///
+    pub fn new(max_len: usize) -> ChecksumFrame<TEndian, TLen, TChecksum>
+        where TChecksum: Default {
+        ChecksumFrame::with_checksum(TChecksum::default(), max_len)
+    }
+
+    /// Creates a new `ChecksumFrame` with the given initial checksum.
+    ///
+    /// The checksum is reset before each frame is processed.
+    pub fn with_checksum(checksum: TChecksum, max_len: usize) -> ChecksumFrame<TEndian, TLen, TChecksum> {
+        ChecksumFrame {
+            checksum,
+            max_len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the maximum payload length accepted by `read_frame`.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Reads a frame and returns the verified payload.
+    ///
+    /// # Errors
+    ///
+    /// If the length exceeds the maximum, returns Err(io::Error) of `InvalidData`.
+    /// If the checksum does not match, returns Err(io::Error) of `InvalidData`
+    /// whose inner error is `ChecksumMismatch<TChecksum::Output>`.
+    /// If reading fails, returns Err(io::Error).
+    ///
+    pub fn read_frame<TRead>(&mut self, reader: &mut TRead) -> io::Result<Vec<u8>>
+        where TRead: io::Read {
+        let len: u64 = reader.read_integer::<TEndian, TLen>()?.into();
+        trace_span!(TRACE, "read checksum frame", len);
+        if len > self.max_len as u64 {
+            trace_event!(DEBUG, len, max_len = self.max_len, "frame is too long");
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "frame is too long"));
+        }
+        // The payload is verified in the reused thread-local scratch buffer, and copied out only if
+        // it matches.
+        scratch::with_scratch(len as usize, |payload| {
+            reader.read_exact(payload)?;
+            let expected = reader.read_integer::<TEndian, TChecksum::Output>()?;
+            self.verify(payload, expected)?;
+            Ok(payload.to_vec())
+        })
+    }
+
+    /// Writes a frame of `payload`.
+    ///
+    /// # Errors
+    ///
+    /// If the payload length does not fit in `TLen`, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    pub fn write_frame<TWrite>(&mut self, writer: &mut TWrite, payload: &[u8]) -> io::Result<()>
+        where TWrite: io::Write {
+        let len = TLen::try_from(payload.len() as u64)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame is too long"))?;
+        writer.write_integer::<TEndian, _>(len)?;
+        writer.write_all(payload)?;
+        writer.write_integer::<TEndian, _>(self.calculate(payload))
+    }
+
+    pub(crate) fn calculate(&mut self, payload: &[u8]) -> TChecksum::Output {
+        self.checksum.reset();
+        self.checksum.update(payload);
+        self.checksum.digest()
+    }
+
+    pub(crate) fn verify(&mut self, payload: &[u8], expected: TChecksum::Output) -> io::Result<()> {
+        let actual = self.calculate(payload);
+        if actual != expected {
+            trace_event!(DEBUG, len = payload.len(), "checksum frame mismatch");
+            return Err(io::Error::new(io::ErrorKind::InvalidData, ChecksumMismatch { expected, actual }));
+        }
+        Ok(())
+    }
+}
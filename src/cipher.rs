@@ -0,0 +1,259 @@
+//! Provides the reader and the writer decrypting and encrypting with a RustCrypto stream cipher,
+//! such as AES-CTR or ChaCha20, for the encrypted container formats.
+//!
+//! The keystream position follows the stream offset, so the wrappers keep `io::Seek` of the
+//! underlying reader or writer by seeking the cipher too.
+//!
+//! This module is available with the `cipher` feature.
+
+use std::io;
+
+use cipher_crate::{StreamCipher, StreamCipherSeek};
+
+use scratch;
+
+/// Wraps a reader of the encrypted data, and reads the decrypted data.
+///
+/// The keystream position 0 is at the offset `start` of the underlying reader, 0 unless it is
+/// created by `with_start`.
+///
+/// # Examples
+///
+/// ```
+/// extern crate aes;
+/// extern crate ctr;
+/// extern crate mm_binary_io;
+///
+/// use std::io::{self, Seek, SeekFrom};
+/// use ctr::cipher::KeyIvInit;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::cipher::{CipherReader, CipherWriter};
+/// use mm_binary_io::endian::BigEndian;
+///
+/// type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+///
+/// # fn main() {
+/// let (key, iv) = ([0x42_u8; 16], [0x24_u8; 16]);
+///
+/// let mut writer = CipherWriter::new(vec![], Aes128Ctr::new(&key.into(), &iv.into()));
+/// writer.write_integer_array::<BigEndian, u32>(&[1, 2, 3, 4, 5]).unwrap();
+/// let encrypted = writer.into_inner().0;
+/// assert_ne!(&[0, 0, 0, 1], &encrypted[..4]);
+///
+/// let mut reader = CipherReader::new(io::Cursor::new(encrypted), Aes128Ctr::new(&key.into(), &iv.into()));
+/// reader.seek(SeekFrom::Start(12)).unwrap();
+/// assert_eq!(4, reader.read_integer::<BigEndian, u32>().unwrap());
+/// reader.seek(SeekFrom::Start(0)).unwrap();
+/// assert_eq!(vec![1, 2, 3, 4, 5], reader.read_integer_array::<BigEndian, u32>(5).unwrap());
+/// # }
+///
+/// ```
+///
+pub struct CipherReader<TRead, TCipher>
+    where TRead: io::Read,
+          TCipher: StreamCipher + StreamCipherSeek {
+    reader: TRead,
+    cipher: TCipher,
+    start: u64,
+}
+
+impl<TRead, TCipher> CipherReader<TRead, TCipher>
+    where TRead: io::Read,
+          TCipher: StreamCipher + StreamCipherSeek {
+    /// Creates a new `CipherReader`, whose keystream starts at the offset 0 of `reader`.
+    ///
+    /// `cipher` must be at the keystream position 0.
+    pub fn new(reader: TRead, cipher: TCipher) -> CipherReader<TRead, TCipher> {
+        CipherReader::with_start(reader, cipher, 0)
+    }
+
+    /// Creates a new `CipherReader`, whose keystream starts at the offset `start` of `reader`,
+    /// such as an encrypted region after a plain header.
+    ///
+    /// `reader` must be at the offset `start`, and `cipher` at the keystream position 0.
+    pub fn with_start(reader: TRead, cipher: TCipher, start: u64) -> CipherReader<TRead, TCipher> {
+        CipherReader { reader, cipher, start }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &TRead {
+        &self.reader
+    }
+
+    /// Unwraps this `CipherReader`, returning the underlying reader and the cipher.
+    pub fn into_inner(self) -> (TRead, TCipher) {
+        (self.reader, self.cipher)
+    }
+}
+
+impl<TRead, TCipher> io::Read for CipherReader<TRead, TCipher>
+    where TRead: io::Read,
+          TCipher: StreamCipher + StreamCipherSeek {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.cipher.try_apply_keystream(&mut buf[..n]).map_err(keystream_error)?;
+        Ok(n)
+    }
+}
+
+impl<TRead, TCipher> io::Seek for CipherReader<TRead, TCipher>
+    where TRead: io::Read + io::Seek,
+          TCipher: StreamCipher + StreamCipherSeek {
+    /// Seeks the underlying reader and the keystream.
+    ///
+    /// Seeking before `start` is an error of `InvalidInput`, after which the underlying reader
+    /// is at the offset but the keystream is not.
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let offset = self.reader.seek(pos)?;
+        seek_cipher(&mut self.cipher, offset, self.start)?;
+        Ok(offset)
+    }
+}
+
+/// Wraps a writer, and writes the data encrypted.
+///
+/// The keystream position 0 is at the offset `start` of the underlying writer, 0 unless it is
+/// created by `with_start`. The keystream only advances over the bytes the underlying writer
+/// accepts, so a failed or short write can be retried.
+///
+/// # Examples
+///
+/// ```
+/// extern crate aes;
+/// extern crate ctr;
+/// extern crate mm_binary_io;
+///
+/// use std::io::{self, Read, Write};
+/// use ctr::cipher::KeyIvInit;
+/// use mm_binary_io::cipher::{CipherReader, CipherWriter};
+///
+/// type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+///
+/// // A writer which is interrupted once.
+/// struct Interrupted {
+///     bytes: Vec<u8>,
+///     interrupted: bool,
+/// }
+///
+/// impl Write for Interrupted {
+///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+///         if !self.interrupted {
+///             self.interrupted = true;
+///             return Err(io::ErrorKind::Interrupted.into());
+///         }
+///         self.bytes.write(buf)
+///     }
+///
+///     fn flush(&mut self) -> io::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// # fn main() {
+/// let (key, iv) = ([0x42_u8; 16], [0x24_u8; 16]);
+///
+/// let inner = Interrupted { bytes: vec![], interrupted: false };
+/// let mut writer = CipherWriter::new(inner, Aes128Ctr::new(&key.into(), &iv.into()));
+/// writer.write_all(b"retried safely").unwrap();
+/// let encrypted = writer.into_inner().0.bytes;
+///
+/// let mut reader = CipherReader::new(&encrypted[..], Aes128Ctr::new(&key.into(), &iv.into()));
+/// let mut decrypted = vec![];
+/// reader.read_to_end(&mut decrypted).unwrap();
+/// assert_eq!(b"retried safely".to_vec(), decrypted);
+/// # }
+///
+/// ```
+///
+pub struct CipherWriter<TWrite, TCipher>
+    where TWrite: io::Write,
+          TCipher: StreamCipher + StreamCipherSeek {
+    writer: TWrite,
+    cipher: TCipher,
+    start: u64,
+}
+
+impl<TWrite, TCipher> CipherWriter<TWrite, TCipher>
+    where TWrite: io::Write,
+          TCipher: StreamCipher + StreamCipherSeek {
+    /// Creates a new `CipherWriter`, whose keystream starts at the offset 0 of `writer`.
+    ///
+    /// `cipher` must be at the keystream position 0.
+    pub fn new(writer: TWrite, cipher: TCipher) -> CipherWriter<TWrite, TCipher> {
+        CipherWriter::with_start(writer, cipher, 0)
+    }
+
+    /// Creates a new `CipherWriter`, whose keystream starts at the offset `start` of `writer`.
+    ///
+    /// `writer` must be at the offset `start`, and `cipher` at the keystream position 0.
+    pub fn with_start(writer: TWrite, cipher: TCipher, start: u64) -> CipherWriter<TWrite, TCipher> {
+        CipherWriter { writer, cipher, start }
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &TWrite {
+        &self.writer
+    }
+
+    /// Unwraps this `CipherWriter`, returning the underlying writer and the cipher.
+    pub fn into_inner(self) -> (TWrite, TCipher) {
+        (self.writer, self.cipher)
+    }
+}
+
+impl<TWrite, TCipher> io::Write for CipherWriter<TWrite, TCipher>
+    where TWrite: io::Write,
+          TCipher: StreamCipher + StreamCipherSeek {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let position: u64 = self.cipher.try_current_pos().map_err(keystream_error)?;
+        let (cipher, writer) = (&mut self.cipher, &mut self.writer);
+        let result = scratch::with_scratch(buf.len(), |staged| {
+            cipher.try_apply_keystream_b2b(buf, staged).map_err(keystream_error)?;
+            writer.write(staged)
+        });
+        // The keystream is rewound to the end of the bytes accepted by the underlying writer, so
+        // that the rest is encrypted again by the retry, e.g. after `Interrupted`.
+        let n = match result {
+            Ok(n) => n,
+            Err(e) => {
+                self.cipher.try_seek(position).map_err(keystream_error)?;
+                return Err(e);
+            }
+        };
+        if n < buf.len() {
+            self.cipher.try_seek(position + n as u64).map_err(keystream_error)?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<TWrite, TCipher> io::Seek for CipherWriter<TWrite, TCipher>
+    where TWrite: io::Write + io::Seek,
+          TCipher: StreamCipher + StreamCipherSeek {
+    /// Seeks the underlying writer and the keystream.
+    ///
+    /// Seeking before `start` is an error of `InvalidInput`, after which the underlying writer
+    /// is at the offset but the keystream is not.
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let offset = self.writer.seek(pos)?;
+        seek_cipher(&mut self.cipher, offset, self.start)?;
+        Ok(offset)
+    }
+}
+
+/// Seeks `cipher` to the keystream position of the stream `offset`.
+fn seek_cipher<TCipher>(cipher: &mut TCipher, offset: u64, start: u64) -> io::Result<()>
+    where TCipher: StreamCipherSeek {
+    let position = offset.checked_sub(start)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "offset is before the keystream start"))?;
+    cipher.try_seek(position).map_err(keystream_error)
+}
+
+fn keystream_error<TError>(_: TError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "keystream is exhausted")
+}
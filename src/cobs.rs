@@ -0,0 +1,313 @@
+//! Provides the features to read and write COBS (Consistent Overhead Byte Stuffing) frames.
+//!
+//! COBS removes all zero bytes from a frame, so a zero byte can be used as the frame delimiter.
+
+use std::io;
+
+use delimited::{self, FrameDecoder};
+
+/// The maximum length of a block between two code bytes.
+const MAX_BLOCK_LEN: usize = 254;
+
+/// Returns the maximum encoded length of `len` bytes, excluding the delimiter.
+pub fn max_encoded_len(len: usize) -> usize {
+    len + len / MAX_BLOCK_LEN + 1
+}
+
+/// Encodes `data` without the delimiter.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::cobs;
+///
+/// assert_eq!(vec![0x03, 0x11, 0x22, 0x02, 0x33], cobs::encode(&[0x11, 0x22, 0x00, 0x33]));
+/// assert_eq!(vec![0x01, 0x01], cobs::encode(&[0x00]));
+///
+/// ```
+///
+pub fn encode(data: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(max_encoded_len(data.len()));
+    let mut encoder = Encoder::new();
+    for &byte in data {
+        encoder.push(&mut encoded, byte).unwrap();
+    }
+    encoder.finish(&mut encoded).unwrap();
+    encoded
+}
+
+/// Decodes a frame, with or without the trailing delimiter.
+///
+/// # Errors
+///
+/// If the frame contains a zero byte or is cut off, returns Err(io::Error) of `InvalidData`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::cobs;
+///
+/// assert_eq!(vec![0x11, 0x22, 0x00, 0x33], cobs::decode(&[0x03, 0x11, 0x22, 0x02, 0x33]).unwrap());
+/// assert_eq!(vec![0x11, 0x22, 0x00, 0x33], cobs::decode(&[0x03, 0x11, 0x22, 0x02, 0x33, 0x00]).unwrap());
+/// assert!(cobs::decode(&[0x03, 0x11]).is_err());
+///
+/// ```
+///
+pub fn decode(data: &[u8]) -> io::Result<Vec<u8>> {
+    let data = match data.split_last() {
+        Some((&0, rest)) => rest,
+        _ => data,
+    };
+    let mut decoder = Decoder::new();
+    for &byte in data {
+        if byte == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected zero in COBS frame"));
+        }
+        decoder.push(byte);
+    }
+    decoder.finish()
+}
+
+/// Encodes the written bytes as COBS frames.
+///
+/// The bytes written until `end_frame` form a frame.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+/// use mm_binary_io::cobs::CobsWriter;
+///
+/// let mut writer = CobsWriter::new(vec![]);
+/// writer.write_all(&[0x11, 0x00]).unwrap();
+/// writer.end_frame().unwrap();
+/// writer.end_frame().unwrap();
+///
+/// assert_eq!(vec![0x02, 0x11, 0x01, 0x00, 0x01, 0x00], writer.into_inner());
+///
+/// ```
+///
+pub struct CobsWriter<TWrite>
+    where TWrite: io::Write {
+    writer: TWrite,
+    encoder: Encoder,
+    delimiter: bool,
+}
+
+impl<TWrite> CobsWriter<TWrite>
+    where TWrite: io::Write {
+    /// Creates a new `CobsWriter` which writes a zero delimiter after each frame.
+    pub fn new(writer: TWrite) -> CobsWriter<TWrite> {
+        CobsWriter {
+            writer,
+            encoder: Encoder::new(),
+            delimiter: true,
+        }
+    }
+
+    /// Creates a new `CobsWriter` which does not write the delimiters.
+    pub fn without_delimiter(writer: TWrite) -> CobsWriter<TWrite> {
+        CobsWriter {
+            writer,
+            encoder: Encoder::new(),
+            delimiter: false,
+        }
+    }
+
+    /// Ends the current frame.
+    pub fn end_frame(&mut self) -> io::Result<()> {
+        self.encoder.finish(&mut self.writer)?;
+        if self.delimiter {
+            self.writer.write_all(&[0])?;
+        }
+        Ok(())
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &TWrite {
+        &self.writer
+    }
+
+    /// Unwraps this `CobsWriter`, returning the underlying writer.
+    ///
+    /// The bytes written after the last `end_frame` are lost.
+    pub fn into_inner(self) -> TWrite {
+        self.writer
+    }
+}
+
+impl<TWrite> io::Write for CobsWriter<TWrite>
+    where TWrite: io::Write {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            self.encoder.push(&mut self.writer, byte)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Reads zero-delimited COBS frames from a stream.
+///
+/// Empty frames (consecutive delimiters) are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::cobs::CobsReader;
+///
+/// let data = vec![0x02_u8, 0x11, 0x01, 0x00, 0x00, 0x03, 0x22, 0x33, 0x00, 0x05, 0x44];
+/// let mut reader = CobsReader::new(io::Cursor::new(data), 16);
+///
+/// assert_eq!(Some(vec![0x11, 0x00]), reader.read_frame().unwrap());
+/// assert_eq!(Some(vec![0x22, 0x33]), reader.read_frame().unwrap());
+/// assert_eq!(io::ErrorKind::UnexpectedEof, reader.read_frame().unwrap_err().kind());
+///
+/// ```
+///
+pub struct CobsReader<TRead>
+    where TRead: io::BufRead {
+    reader: TRead,
+    max_len: usize,
+    finished: bool,
+}
+
+impl<TRead> CobsReader<TRead>
+    where TRead: io::BufRead {
+    /// Creates a new `CobsReader`.
+    ///
+    /// # Arguments
+    ///
+    /// * max_len - the maximum length of a decoded frame.
+    pub fn new(reader: TRead, max_len: usize) -> CobsReader<TRead> {
+        CobsReader {
+            reader,
+            max_len,
+            finished: false,
+        }
+    }
+
+    /// Reads and decodes the next frame.
+    ///
+    /// # Errors
+    ///
+    /// If the stream ends cleanly before the frame, returns Ok(None).
+    /// If the frame is broken or longer than `max_len`, the frame is skipped and
+    /// Err(io::Error) of `InvalidData` is returned.
+    /// If the stream ends in the middle of the frame, returns Err(io::Error) of `UnexpectedEof`.
+    ///
+    pub fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        delimited::read_frame(&mut self.reader, 0, self.max_len, Decoder::new(), "COBS")
+    }
+
+    /// Unwraps this `CobsReader`, returning the underlying reader.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+}
+
+impl<TRead> Iterator for CobsReader<TRead>
+    where TRead: io::BufRead {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.read_frame() {
+            Ok(Some(frame)) => Some(Ok(frame)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = e.kind() != io::ErrorKind::InvalidData;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+struct Encoder {
+    block: Vec<u8>,
+}
+
+impl Encoder {
+    fn new() -> Encoder {
+        Encoder {
+            block: Vec::with_capacity(MAX_BLOCK_LEN),
+        }
+    }
+
+    fn push<TWrite>(&mut self, writer: &mut TWrite, byte: u8) -> io::Result<()>
+        where TWrite: io::Write {
+        if self.block.len() == MAX_BLOCK_LEN {
+            self.write_block(writer)?;
+        }
+        if byte == 0 {
+            self.write_block(writer)
+        } else {
+            self.block.push(byte);
+            Ok(())
+        }
+    }
+
+    fn finish<TWrite>(&mut self, writer: &mut TWrite) -> io::Result<()>
+        where TWrite: io::Write {
+        self.write_block(writer)
+    }
+
+    fn write_block<TWrite>(&mut self, writer: &mut TWrite) -> io::Result<()>
+        where TWrite: io::Write {
+        writer.write_all(&[self.block.len() as u8 + 1])?;
+        writer.write_all(&self.block)?;
+        self.block.clear();
+        Ok(())
+    }
+}
+
+struct Decoder {
+    frame: Vec<u8>,
+    remaining: u8,
+    pending_zero: bool,
+}
+
+impl Decoder {
+    fn new() -> Decoder {
+        Decoder {
+            frame: vec![],
+            remaining: 0,
+            pending_zero: false,
+        }
+    }
+}
+
+impl FrameDecoder for Decoder {
+    fn push(&mut self, byte: u8) {
+        if self.remaining == 0 {
+            if self.pending_zero {
+                self.frame.push(0);
+            }
+            self.remaining = byte - 1;
+            self.pending_zero = byte != 0xFF;
+        } else {
+            self.frame.push(byte);
+            self.remaining -= 1;
+        }
+    }
+
+    fn decoded_len(&self) -> usize {
+        self.frame.len()
+    }
+
+    fn finish(self) -> io::Result<Vec<u8>> {
+        if self.remaining != 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "COBS frame is cut off"));
+        }
+        Ok(self.frame)
+    }
+}
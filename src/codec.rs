@@ -0,0 +1,370 @@
+//! Provides the `tokio_util` codecs of the frame formats of this crate, so that they drop into
+//! `Framed` transports.
+//!
+//! The decoders wait for the whole frame in the buffer, and check its length against the
+//! maximum before reserving the space for it.
+//!
+//! This module is available with the `tokio-codec` feature.
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use checksum::Checksum;
+use checksum_frame::ChecksumFrame;
+use endian::Endian;
+use read_integer::ReadInteger;
+use tlv::{Tlv, TlvFormat};
+use write_integer::WriteInteger;
+
+/// The codec of the frames of a `TLen` length prefix in `TEndian` and the payload, as
+/// `BinaryRead::read_frame` and `BinaryWrite::write_frame`.
+///
+/// # Examples
+///
+/// ```
+/// extern crate bytes;
+/// extern crate tokio_util;
+/// extern crate mm_binary_io;
+///
+/// use bytes::BytesMut;
+/// use tokio_util::codec::{Decoder, Encoder};
+/// use mm_binary_io::codec::LengthPrefixedCodec;
+/// use mm_binary_io::endian::BigEndian;
+///
+/// # fn main() {
+/// let mut codec = LengthPrefixedCodec::<BigEndian, u16>::new(1024);
+/// let mut buf = BytesMut::new();
+/// codec.encode(&b"hello"[..], &mut buf).unwrap();
+/// assert_eq!(&b"\x00\x05hello"[..], &buf[..]);
+///
+/// // The frame is decoded when it has arrived whole.
+/// let mut received = BytesMut::from(&buf[..4]);
+/// assert_eq!(None, codec.decode(&mut received).unwrap());
+/// received.extend_from_slice(&buf[4..]);
+/// assert_eq!(&b"hello"[..], &codec.decode(&mut received).unwrap().unwrap()[..]);
+/// assert!(received.is_empty());
+/// # }
+///
+/// ```
+///
+pub struct LengthPrefixedCodec<TEndian, TLen>
+    where TEndian: Endian {
+    max_len: usize,
+    _marker: PhantomData<(TEndian, TLen)>,
+}
+
+impl<TEndian, TLen> LengthPrefixedCodec<TEndian, TLen>
+    where TEndian: Endian {
+    /// Creates a new `LengthPrefixedCodec`.
+    ///
+    /// # Arguments
+    ///
+    /// * max_len - the maximum payload length accepted by `decode`.
+    ///
+    pub fn new(max_len: usize) -> LengthPrefixedCodec<TEndian, TLen> {
+        LengthPrefixedCodec { max_len, _marker: PhantomData }
+    }
+
+    /// Returns the maximum payload length accepted by `decode`.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+}
+
+impl<TEndian, TLen> Decoder for LengthPrefixedCodec<TEndian, TLen>
+    where TEndian: Endian,
+          TLen: ReadInteger<OutputType=TLen> + Into<u64> {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    /// Decodes a frame, and returns the payload.
+    ///
+    /// If the length exceeds the maximum, returns Err(io::Error) of `InvalidData`.
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        let len = match decode_header(src, |reader| reader.read_integer::<TEndian, TLen>())? {
+            Some(len) => len.into(),
+            None => return Ok(None),
+        };
+        let payload_len = check_len(len, self.max_len)?;
+        let header_len = mem::size_of::<TLen>();
+        if !reserve_frame(src, header_len + payload_len) {
+            return Ok(None);
+        }
+        src.advance(header_len);
+        Ok(Some(src.split_to(payload_len)))
+    }
+}
+
+impl<TEndian, TLen, TPayload> Encoder<TPayload> for LengthPrefixedCodec<TEndian, TLen>
+    where TEndian: Endian,
+          TLen: WriteInteger + TryFrom<u64>,
+          TPayload: AsRef<[u8]> {
+    type Error = io::Error;
+
+    /// Encodes a frame of the payload.
+    ///
+    /// If the payload length does not fit in `TLen`, returns Err(io::Error) of `InvalidInput`.
+    fn encode(&mut self, payload: TPayload, dst: &mut BytesMut) -> io::Result<()> {
+        let payload = payload.as_ref();
+        dst.reserve(mem::size_of::<TLen>() + payload.len());
+        BufMut::writer(dst).write_frame::<TEndian, TLen>(payload)
+    }
+}
+
+/// The codec of the frames of `ChecksumFrame`, a `TLen` length prefix, the payload, and a
+/// `TChecksum` checksum of the payload.
+///
+/// # Examples
+///
+/// ```
+/// extern crate bytes;
+/// extern crate tokio_util;
+/// extern crate mm_binary_io;
+///
+/// use std::io;
+/// use bytes::BytesMut;
+/// use tokio_util::codec::{Decoder, Encoder};
+/// use mm_binary_io::codec::ChecksumFrameCodec;
+/// use mm_binary_io::crc32::Crc32;
+/// use mm_binary_io::endian::BigEndian;
+///
+/// # fn main() {
+/// let mut codec = ChecksumFrameCodec::<BigEndian, u16, Crc32>::new(1024);
+/// let mut buf = BytesMut::new();
+/// codec.encode(&b"123456789"[..], &mut buf).unwrap();
+/// assert_eq!(&[0xCB, 0xF4, 0x39, 0x26], &buf[11..]);
+///
+/// let mut corrupted = buf.clone();
+/// corrupted[2] = b'0';
+/// assert_eq!(&b"123456789"[..], &codec.decode(&mut buf).unwrap().unwrap()[..]);
+/// assert_eq!(io::ErrorKind::InvalidData, codec.decode(&mut corrupted).unwrap_err().kind());
+/// # }
+///
+/// ```
+///
+pub struct ChecksumFrameCodec<TEndian, TLen, TChecksum>
+    where TEndian: Endian,
+          TChecksum: Checksum {
+    frame: ChecksumFrame<TEndian, TLen, TChecksum>,
+}
+
+impl<TEndian, TLen, TChecksum> ChecksumFrameCodec<TEndian, TLen, TChecksum>
+    where TEndian: Endian,
+          TLen: ReadInteger<OutputType=TLen> + WriteInteger + Into<u64> + TryFrom<u64>,
+          TChecksum: Checksum,
+          TChecksum::Output: ReadInteger<OutputType=TChecksum::Output> + WriteInteger
+          + PartialEq + fmt::Debug + fmt::LowerHex + Send + Sync + 'static {
+    /// Creates a new `ChecksumFrameCodec` with the initial checksum.
+    ///
+    /// # Arguments
+    ///
+    /// * max_len - the maximum payload length accepted by `decode`.
+    ///
+    pub fn new(max_len: usize) -> ChecksumFrameCodec<TEndian, TLen, TChecksum>
+        where TChecksum: Default {
+        ChecksumFrameCodec { frame: ChecksumFrame::new(max_len) }
+    }
+
+    /// Creates a new `ChecksumFrameCodec` with the given initial checksum.
+    pub fn with_checksum(checksum: TChecksum, max_len: usize) -> ChecksumFrameCodec<TEndian, TLen, TChecksum> {
+        ChecksumFrameCodec { frame: ChecksumFrame::with_checksum(checksum, max_len) }
+    }
+
+    /// Returns the maximum payload length accepted by `decode`.
+    pub fn max_len(&self) -> usize {
+        self.frame.max_len()
+    }
+}
+
+impl<TEndian, TLen, TChecksum> Decoder for ChecksumFrameCodec<TEndian, TLen, TChecksum>
+    where TEndian: Endian,
+          TLen: ReadInteger<OutputType=TLen> + WriteInteger + Into<u64> + TryFrom<u64>,
+          TChecksum: Checksum,
+          TChecksum::Output: ReadInteger<OutputType=TChecksum::Output> + WriteInteger
+          + PartialEq + fmt::Debug + fmt::LowerHex + Send + Sync + 'static {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    /// Decodes a frame, and returns the verified payload.
+    ///
+    /// If the length exceeds the maximum, returns Err(io::Error) of `InvalidData`. If the
+    /// checksum does not match, returns Err(io::Error) of `InvalidData` whose inner error is
+    /// `ChecksumMismatch<TChecksum::Output>`, and the frame is consumed.
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<BytesMut>> {
+        let len = match decode_header(src, |reader| reader.read_integer::<TEndian, TLen>())? {
+            Some(len) => len.into(),
+            None => return Ok(None),
+        };
+        let payload_len = check_len(len, self.frame.max_len())?;
+        let header_len = mem::size_of::<TLen>();
+        let checksum_len = mem::size_of::<TChecksum::Output>();
+        if !reserve_frame(src, header_len + payload_len + checksum_len) {
+            return Ok(None);
+        }
+        src.advance(header_len);
+        let payload = src.split_to(payload_len);
+        let expected = (&src.split_to(checksum_len)[..]).read_integer::<TEndian, TChecksum::Output>()?;
+        self.frame.verify(&payload, expected)?;
+        Ok(Some(payload))
+    }
+}
+
+impl<TEndian, TLen, TChecksum, TPayload> Encoder<TPayload> for ChecksumFrameCodec<TEndian, TLen, TChecksum>
+    where TEndian: Endian,
+          TLen: ReadInteger<OutputType=TLen> + WriteInteger + Into<u64> + TryFrom<u64>,
+          TChecksum: Checksum,
+          TChecksum::Output: ReadInteger<OutputType=TChecksum::Output> + WriteInteger
+          + PartialEq + fmt::Debug + fmt::LowerHex + Send + Sync + 'static,
+          TPayload: AsRef<[u8]> {
+    type Error = io::Error;
+
+    /// Encodes a frame of the payload.
+    ///
+    /// If the payload length does not fit in `TLen`, returns Err(io::Error) of `InvalidInput`.
+    fn encode(&mut self, payload: TPayload, dst: &mut BytesMut) -> io::Result<()> {
+        let payload = payload.as_ref();
+        dst.reserve(mem::size_of::<TLen>() + payload.len() + mem::size_of::<TChecksum::Output>());
+        self.frame.write_frame(&mut BufMut::writer(dst), payload)
+    }
+}
+
+/// The codec of the TLV records of `TlvFormat`.
+///
+/// The nested records are decoded as the values of their parents, which `Tlv::children` reads.
+///
+/// # Examples
+///
+/// ```
+/// extern crate bytes;
+/// extern crate tokio_util;
+/// extern crate mm_binary_io;
+///
+/// use bytes::BytesMut;
+/// use tokio_util::codec::{Decoder, Encoder};
+/// use mm_binary_io::codec::TlvCodec;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::tlv::{Tlv, TlvFormat, Width};
+///
+/// # fn main() {
+/// let format = TlvFormat::<BigEndian>::new(Width::U8, Width::Varint);
+/// let mut codec = TlvCodec::new(format, 1024);
+/// let mut buf = BytesMut::new();
+/// codec.encode(Tlv { tag: 1, value: vec![0xAB, 0xCD] }, &mut buf).unwrap();
+/// assert_eq!(&[0x01, 0x02, 0xAB, 0xCD], &buf[..]);
+///
+/// let mut received = BytesMut::from(&buf[..1]);
+/// assert_eq!(None, codec.decode(&mut received).unwrap());
+/// received.extend_from_slice(&buf[1..]);
+/// assert_eq!(Some(Tlv { tag: 1, value: vec![0xAB, 0xCD] }), codec.decode(&mut received).unwrap());
+/// # }
+///
+/// ```
+///
+pub struct TlvCodec<TEndian>
+    where TEndian: Endian {
+    format: TlvFormat<TEndian>,
+    max_len: usize,
+}
+
+impl<TEndian> TlvCodec<TEndian>
+    where TEndian: Endian {
+    /// Creates a new `TlvCodec` of `format`.
+    ///
+    /// # Arguments
+    ///
+    /// * max_len - the maximum value length accepted by `decode`.
+    ///
+    pub fn new(format: TlvFormat<TEndian>, max_len: usize) -> TlvCodec<TEndian> {
+        TlvCodec { format, max_len }
+    }
+
+    /// Returns the layout of the records.
+    pub fn format(&self) -> TlvFormat<TEndian> {
+        self.format
+    }
+
+    /// Returns the maximum value length accepted by `decode`.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+}
+
+impl<TEndian> Decoder for TlvCodec<TEndian>
+    where TEndian: Endian {
+    type Item = Tlv;
+    type Error = io::Error;
+
+    /// Decodes a record.
+    ///
+    /// If the length exceeds the maximum, returns Err(io::Error) of `InvalidData`.
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Tlv>> {
+        let format = self.format;
+        let header = decode_header(src, |reader| {
+            let header = format.read_header(reader)?;
+            Ok((header, reader.position() as usize))
+        })?;
+        let ((tag, len), header_len) = match header {
+            Some((Some(header), header_len)) => (header, header_len),
+            _ => return Ok(None),
+        };
+        let value_len = check_len(len, self.max_len)?;
+        if !reserve_frame(src, header_len + value_len) {
+            return Ok(None);
+        }
+        src.advance(header_len);
+        Ok(Some(Tlv { tag, value: src.split_to(value_len).to_vec() }))
+    }
+}
+
+impl<TEndian> Encoder<Tlv> for TlvCodec<TEndian>
+    where TEndian: Endian {
+    type Error = io::Error;
+
+    /// Encodes a record.
+    ///
+    /// If the tag or the length does not fit in its width, returns Err(io::Error) of
+    /// `InvalidInput`.
+    fn encode(&mut self, record: Tlv, dst: &mut BytesMut) -> io::Result<()> {
+        let mut writer = BufMut::writer(dst);
+        self.format.write_header(&mut writer, record.tag, record.value.len() as u64)?;
+        io::Write::write_all(&mut writer, &record.value)
+    }
+}
+
+/// Parses the header at the head of `src` without consuming it, or returns Ok(None) if it has
+/// not arrived whole.
+fn decode_header<T, F>(src: &BytesMut, parse: F) -> io::Result<Option<T>>
+    where F: FnOnce(&mut io::Cursor<&[u8]>) -> io::Result<T> {
+    match parse(&mut io::Cursor::new(&src[..])) {
+        Ok(header) => Ok(Some(header)),
+        Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Checks the length of a frame against `max_len`.
+fn check_len(len: u64, max_len: usize) -> io::Result<usize> {
+    if len > max_len as u64 {
+        trace_event!(DEBUG, len, max_len, "frame is too long");
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame is too long"));
+    }
+    Ok(len as usize)
+}
+
+/// Returns true if the frame of `frame_len` bytes has arrived whole, or reserves the space of
+/// the rest otherwise.
+fn reserve_frame(src: &mut BytesMut, frame_len: usize) -> bool {
+    if src.len() < frame_len {
+        src.reserve(frame_len - src.len());
+        return false;
+    }
+    true
+}
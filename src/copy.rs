@@ -0,0 +1,237 @@
+//! Provides the features to copy binary data from a reader to a writer.
+
+use std::fs;
+use std::io;
+
+use progress::{NoProgress, Progress};
+use scratch;
+
+/// The size of the buffer used by the copy functions.
+pub const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The maximum number of bytes copied by a single zero-copy system call.
+#[cfg(target_os = "linux")]
+const ZERO_COPY_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Copies all bytes from `reader` to `writer`.
+///
+/// # Errors
+///
+/// If the function succeeds then Ok(the number of bytes copied), otherwise Err(io::Error).
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::copy;
+///
+/// let mut reader = io::Cursor::new(vec![0x12_u8, 0x34, 0x56]);
+/// let mut writer = io::Cursor::new(vec![]);
+///
+/// assert_eq!(3, copy::copy(&mut reader, &mut writer).unwrap());
+/// assert_eq!(vec![0x12, 0x34, 0x56], writer.into_inner());
+///
+/// ```
+///
+pub fn copy<TRead, TWrite>(reader: &mut TRead, writer: &mut TWrite) -> io::Result<u64>
+    where TRead: io::Read,
+          TWrite: io::Write {
+    copy_with_progress(reader, writer, None, &mut NoProgress {})
+}
+
+/// Copies all bytes from `reader` to `writer`, reporting the progress to `progress`.
+///
+/// The progress is reported after each chunk of `COPY_BUFFER_SIZE` bytes at most.
+///
+/// # Arguments
+///
+/// * total - the total number of bytes to copy, if known. It is only passed to `progress`.
+/// * progress - the receiver of the progress.
+///
+/// # Errors
+///
+/// If the function succeeds then Ok(the number of bytes copied), otherwise Err(io::Error).
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::copy;
+///
+/// let mut reader = io::Cursor::new(vec![0_u8; 100_000]);
+/// let mut writer = io::Cursor::new(vec![]);
+///
+/// let mut reports = vec![];
+/// copy::copy_with_progress(&mut reader, &mut writer, Some(100_000), &mut |processed, total| {
+///     reports.push((processed, total));
+/// }).unwrap();
+///
+/// assert_eq!(Some(&(100_000, Some(100_000))), reports.last());
+///
+/// ```
+///
+pub fn copy_with_progress<TRead, TWrite, TProgress>(reader: &mut TRead,
+                                                   writer: &mut TWrite,
+                                                   total: Option<u64>,
+                                                   progress: &mut TProgress) -> io::Result<u64>
+    where TRead: io::Read,
+          TWrite: io::Write,
+          TProgress: Progress {
+    scratch::with_scratch(COPY_BUFFER_SIZE, |buf| {
+        let mut processed = 0_u64;
+        loop {
+            let n = match reader.read(buf) {
+                Ok(0) => return Ok(processed),
+                Ok(n) => n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            writer.write_all(&buf[..n])?;
+            processed += n as u64;
+            progress.report(processed, total);
+        }
+    })
+}
+
+/// Copies all bytes from the current position of `reader` to the current position of `writer`.
+///
+/// On Linux, the data is copied in the kernel by `copy_file_range` or `sendfile`
+/// without passing through userspace buffers. If they are not supported by the kernel or
+/// the file systems, or copy nothing at first as for the procfs files, or on other platforms,
+/// falls back to `copy`.
+///
+/// # Errors
+///
+/// If the function succeeds then Ok(the number of bytes copied), otherwise Err(io::Error).
+///
+/// # Examples
+///
+/// ```
+/// use std::env;
+/// use std::fs;
+/// use std::io::{Read, Write};
+/// use mm_binary_io::copy;
+///
+/// let source_path = env::temp_dir().join("mm_binary_io_copy_file_example.src");
+/// let destination_path = env::temp_dir().join("mm_binary_io_copy_file_example.dst");
+/// fs::File::create(&source_path).unwrap().write_all(&[0x12, 0x34, 0x56]).unwrap();
+///
+/// let mut reader = fs::File::open(&source_path).unwrap();
+/// let mut writer = fs::File::create(&destination_path).unwrap();
+/// assert_eq!(3, copy::copy_file(&mut reader, &mut writer).unwrap());
+///
+/// let mut result = vec![];
+/// fs::File::open(&destination_path).unwrap().read_to_end(&mut result).unwrap();
+/// assert_eq!(vec![0x12, 0x34, 0x56], result);
+///
+/// fs::remove_file(&source_path).unwrap();
+/// fs::remove_file(&destination_path).unwrap();
+///
+/// ```
+///
+pub fn copy_file(reader: &mut fs::File, writer: &mut fs::File) -> io::Result<u64> {
+    copy_file_with_progress(reader, writer, None, &mut NoProgress {})
+}
+
+/// Copies all bytes from the current position of `reader` to the current position of `writer`,
+/// reporting the progress to `progress`.
+///
+/// See `copy_file` for the copy strategy.
+///
+/// # Arguments
+///
+/// * total - the total number of bytes to copy, if known. It is only passed to `progress`.
+/// * progress - the receiver of the progress.
+///
+/// # Errors
+///
+/// If the function succeeds then Ok(the number of bytes copied), otherwise Err(io::Error).
+///
+pub fn copy_file_with_progress<TProgress>(reader: &mut fs::File,
+                                          writer: &mut fs::File,
+                                          total: Option<u64>,
+                                          progress: &mut TProgress) -> io::Result<u64>
+    where TProgress: Progress {
+    let mut processed = 0_u64;
+    if zero_copy(reader, writer, &mut processed, total, progress)? {
+        return Ok(processed);
+    }
+
+    // Continues from the current positions, which the system calls have advanced.
+    let mut offset_progress = |copied: u64, total: Option<u64>| {
+        progress.report(processed + copied, total);
+    };
+    copy_with_progress(reader, writer, total, &mut offset_progress)
+        .map(|copied| processed + copied)
+}
+
+/// Copies by the zero-copy system calls.
+///
+/// Returns Ok(true) if all bytes are copied, or Ok(false) if the caller must copy the rest,
+/// which is also returned if no system call copied a byte.
+#[cfg(target_os = "linux")]
+fn zero_copy<TProgress>(reader: &mut fs::File,
+                        writer: &mut fs::File,
+                        processed: &mut u64,
+                        total: Option<u64>,
+                        progress: &mut TProgress) -> io::Result<bool>
+    where TProgress: Progress {
+    use std::os::unix::io::AsRawFd;
+    use std::ptr;
+
+    let in_fd = reader.as_raw_fd();
+    let out_fd = writer.as_raw_fd();
+    let mut use_copy_file_range = true;
+    loop {
+        let result = if use_copy_file_range {
+            unsafe {
+                ::libc::copy_file_range(in_fd, ptr::null_mut(), out_fd, ptr::null_mut(), ZERO_COPY_CHUNK_SIZE, 0)
+            }
+        } else {
+            unsafe {
+                ::libc::sendfile(out_fd, in_fd, ptr::null_mut(), ZERO_COPY_CHUNK_SIZE)
+            }
+        };
+        match result {
+            // Some files, such as procfs, sysfs and some FUSE files, report 0 bytes at first,
+            // so that the other ways are tried before the end of the file.
+            0 if *processed == 0 => {
+                if use_copy_file_range {
+                    use_copy_file_range = false;
+                } else {
+                    return Ok(false);
+                }
+            }
+            0 => return Ok(true),
+            n if n > 0 => {
+                *processed += n as u64;
+                progress.report(*processed, total);
+            }
+            _ => {
+                let e = io::Error::last_os_error();
+                match e.raw_os_error() {
+                    Some(::libc::EINTR) => {}
+                    Some(::libc::ENOSYS) | Some(::libc::EXDEV) | Some(::libc::EINVAL)
+                    | Some(::libc::EPERM) | Some(::libc::EOPNOTSUPP) | Some(::libc::EBADF) => {
+                        if use_copy_file_range {
+                            use_copy_file_range = false;
+                        } else {
+                            return Ok(false);
+                        }
+                    }
+                    _ => return Err(e),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn zero_copy<TProgress>(_reader: &mut fs::File,
+                        _writer: &mut fs::File,
+                        _processed: &mut u64,
+                        _total: Option<u64>,
+                        _progress: &mut TProgress) -> io::Result<bool>
+    where TProgress: Progress {
+    Ok(false)
+}
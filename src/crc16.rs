@@ -0,0 +1,199 @@
+//! Provides the common CRC-16 checksums (as used by embedded and serial protocols).
+
+use checksum::{Checksum, ChecksumReader, ChecksumWriter};
+
+/// The parameters of a CRC-16 algorithm.
+pub struct Crc16Algorithm {
+    reflected: bool,
+    init: u16,
+    xor_out: u16,
+    table: [u16; 256],
+}
+
+impl Crc16Algorithm {
+    /// Creates a new `Crc16Algorithm`.
+    ///
+    /// # Arguments
+    ///
+    /// * polynomial - the generator polynomial in the normal (MSB-first) form.
+    /// * init - the initial register value.
+    /// * reflected - true if the input and output are reflected (LSB-first).
+    /// * xor_out - the value XORed to the final register value.
+    ///
+    pub const fn new(polynomial: u16, init: u16, reflected: bool, xor_out: u16) -> Crc16Algorithm {
+        Crc16Algorithm {
+            reflected,
+            init,
+            xor_out,
+            table: make_table(polynomial, reflected),
+        }
+    }
+}
+
+const fn make_table(polynomial: u16, reflected: bool) -> [u16; 256] {
+    let mut table = [0_u16; 256];
+    let reversed = polynomial.reverse_bits();
+    let mut i = 0;
+    while i < 256 {
+        let mut value = if reflected { i as u16 } else { (i as u16) << 8 };
+        let mut bit = 0;
+        while bit < 8 {
+            value = if reflected {
+                if value & 1 != 0 { (value >> 1) ^ reversed } else { value >> 1 }
+            } else if value & 0x8000 != 0 {
+                (value << 1) ^ polynomial
+            } else {
+                value << 1
+            };
+            bit += 1;
+        }
+        table[i] = value;
+        i += 1;
+    }
+    table
+}
+
+/// CRC-16/X-25 (also known as CRC-16/IBM-SDLC and CRC-CCITT as used by HDLC and X.25).
+pub static X25: Crc16Algorithm = Crc16Algorithm::new(0x1021, 0xFFFF, true, 0xFFFF);
+
+/// CRC-16/MODBUS.
+pub static MODBUS: Crc16Algorithm = Crc16Algorithm::new(0x8005, 0xFFFF, true, 0x0000);
+
+/// CRC-16/XMODEM (also known as CRC-16/ACORN and CRC-16/LTE).
+pub static XMODEM: Crc16Algorithm = Crc16Algorithm::new(0x1021, 0x0000, false, 0x0000);
+
+/// CRC-16/KERMIT (also known as CRC-16/CCITT-TRUE).
+pub static KERMIT: Crc16Algorithm = Crc16Algorithm::new(0x1021, 0x0000, true, 0x0000);
+
+/// CRC-16/IBM-3740 (also known as CRC-16/CCITT-FALSE).
+pub static CCITT_FALSE: Crc16Algorithm = Crc16Algorithm::new(0x1021, 0xFFFF, false, 0x0000);
+
+/// Calculates a CRC-16 incrementally.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::crc16::{self, Crc16};
+///
+/// let mut crc = Crc16::modbus();
+/// crc.update(b"1234");
+/// crc.update(b"56789");
+/// assert_eq!(0x4B37, crc.digest());
+///
+/// let mut crc = Crc16::new(&crc16::KERMIT);
+/// crc.update(b"123456789");
+/// assert_eq!(0x2189, crc.digest());
+///
+/// ```
+///
+/// Calculates the checksum while reading.
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::crc16::{Crc16, Crc16Reader};
+///
+/// let mut reader = Crc16Reader::with_checksum(io::Cursor::new(b"123456789".to_vec()), Crc16::xmodem());
+/// reader.read_byte_array(9).unwrap();
+/// assert_eq!(0x31C3, reader.digest());
+///
+/// ```
+///
+#[derive(Clone, Copy)]
+pub struct Crc16 {
+    algorithm: &'static Crc16Algorithm,
+    state: u16,
+}
+
+impl Crc16 {
+    /// Creates a new `Crc16` of `algorithm` with no data.
+    pub fn new(algorithm: &'static Crc16Algorithm) -> Crc16 {
+        Crc16 {
+            algorithm,
+            state: algorithm.init,
+        }
+    }
+
+    /// Creates a new CRC-16/X-25 with no data.
+    pub fn x25() -> Crc16 {
+        Crc16::new(&X25)
+    }
+
+    /// Creates a new CRC-16/MODBUS with no data.
+    pub fn modbus() -> Crc16 {
+        Crc16::new(&MODBUS)
+    }
+
+    /// Creates a new CRC-16/XMODEM with no data.
+    pub fn xmodem() -> Crc16 {
+        Crc16::new(&XMODEM)
+    }
+
+    /// Updates the checksum with `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        let table = &self.algorithm.table;
+        let mut state = self.state;
+        if self.algorithm.reflected {
+            for &b in bytes {
+                state = table[((state ^ b as u16) & 0xFF) as usize] ^ (state >> 8);
+            }
+        } else {
+            for &b in bytes {
+                state = table[(((state >> 8) ^ b as u16) & 0xFF) as usize] ^ (state << 8);
+            }
+        }
+        self.state = state;
+    }
+
+    /// Returns the checksum of the data so far.
+    pub fn digest(&self) -> u16 {
+        self.state ^ self.algorithm.xor_out
+    }
+
+    /// Resets the checksum to the initial state.
+    pub fn reset(&mut self) {
+        self.state = self.algorithm.init;
+    }
+}
+
+impl Checksum for Crc16 {
+    type Output = u16;
+
+    fn update(&mut self, bytes: &[u8]) {
+        Crc16::update(self, bytes)
+    }
+
+    fn digest(&self) -> u16 {
+        Crc16::digest(self)
+    }
+
+    fn reset(&mut self) {
+        Crc16::reset(self)
+    }
+}
+
+/// The reader which calculates a CRC-16 of the bytes read.
+pub type Crc16Reader<TRead> = ChecksumReader<TRead, Crc16>;
+
+/// The writer which calculates a CRC-16 of the bytes written.
+pub type Crc16Writer<TWrite> = ChecksumWriter<TWrite, Crc16>;
+
+/// Calculates the CRC-16 of `bytes` by `algorithm`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::crc16;
+///
+/// assert_eq!(0x906E, crc16::crc16(&crc16::X25, b"123456789"));
+/// assert_eq!(0x4B37, crc16::crc16(&crc16::MODBUS, b"123456789"));
+/// assert_eq!(0x31C3, crc16::crc16(&crc16::XMODEM, b"123456789"));
+/// assert_eq!(0x29B1, crc16::crc16(&crc16::CCITT_FALSE, b"123456789"));
+///
+/// ```
+///
+pub fn crc16(algorithm: &'static Crc16Algorithm, bytes: &[u8]) -> u16 {
+    let mut crc = Crc16::new(algorithm);
+    crc.update(bytes);
+    crc.digest()
+}
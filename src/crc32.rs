@@ -0,0 +1,118 @@
+//! Provides the IEEE CRC-32 checksum (as used by PNG, ZIP and gzip).
+
+use checksum::{Checksum, ChecksumReader, ChecksumWriter};
+
+const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+const TABLE: [u32; 256] = make_table();
+
+const fn make_table() -> [u32; 256] {
+    let mut table = [0_u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut value = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 1 != 0 {
+                (value >> 1) ^ POLYNOMIAL
+            } else {
+                value >> 1
+            };
+            bit += 1;
+        }
+        table[i] = value;
+        i += 1;
+    }
+    table
+}
+
+/// Calculates the IEEE CRC-32 incrementally.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::crc32::Crc32;
+///
+/// let mut crc = Crc32::new();
+/// crc.update(b"1234");
+/// crc.update(b"56789");
+/// assert_eq!(0xCBF43926, crc.digest());
+///
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    /// Creates a new `Crc32` with no data.
+    pub fn new() -> Crc32 {
+        Crc32 { state: 0xFFFF_FFFF }
+    }
+
+    /// Updates the checksum with `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        let mut state = self.state;
+        for &b in bytes {
+            state = TABLE[((state ^ b as u32) & 0xFF) as usize] ^ (state >> 8);
+        }
+        self.state = state;
+    }
+
+    /// Returns the checksum of the data so far.
+    pub fn digest(&self) -> u32 {
+        !self.state
+    }
+
+    /// Resets the checksum to the initial state.
+    pub fn reset(&mut self) {
+        self.state = 0xFFFF_FFFF;
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Crc32 {
+        Crc32::new()
+    }
+}
+
+impl Checksum for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        Crc32::update(self, bytes)
+    }
+
+    fn digest(&self) -> u32 {
+        Crc32::digest(self)
+    }
+
+    fn reset(&mut self) {
+        Crc32::reset(self)
+    }
+}
+
+/// The reader which calculates the IEEE CRC-32 of the bytes read.
+pub type Crc32Reader<TRead> = ChecksumReader<TRead, Crc32>;
+
+/// The writer which calculates the IEEE CRC-32 of the bytes written.
+pub type Crc32Writer<TWrite> = ChecksumWriter<TWrite, Crc32>;
+
+/// Calculates the IEEE CRC-32 of `bytes`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::crc32;
+///
+/// assert_eq!(0xCBF43926, crc32::crc32(b"123456789"));
+/// assert_eq!(0, crc32::crc32(b""));
+///
+/// ```
+///
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.digest()
+}
@@ -0,0 +1,183 @@
+//! Provides the CRC-64 checksums (as used by xz and large-file integrity checks).
+
+use checksum::{Checksum, ChecksumReader, ChecksumWriter};
+
+/// The parameters of a CRC-64 algorithm.
+pub struct Crc64Algorithm {
+    reflected: bool,
+    init: u64,
+    xor_out: u64,
+    table: [u64; 256],
+}
+
+impl Crc64Algorithm {
+    /// Creates a new `Crc64Algorithm`.
+    ///
+    /// # Arguments
+    ///
+    /// * polynomial - the generator polynomial in the normal (MSB-first) form.
+    /// * init - the initial register value.
+    /// * reflected - true if the input and output are reflected (LSB-first).
+    /// * xor_out - the value XORed to the final register value.
+    ///
+    pub const fn new(polynomial: u64, init: u64, reflected: bool, xor_out: u64) -> Crc64Algorithm {
+        Crc64Algorithm {
+            reflected,
+            init,
+            xor_out,
+            table: make_table(polynomial, reflected),
+        }
+    }
+}
+
+const fn make_table(polynomial: u64, reflected: bool) -> [u64; 256] {
+    let mut table = [0_u64; 256];
+    let reversed = polynomial.reverse_bits();
+    let mut i = 0;
+    while i < 256 {
+        let mut value = if reflected { i as u64 } else { (i as u64) << 56 };
+        let mut bit = 0;
+        while bit < 8 {
+            value = if reflected {
+                if value & 1 != 0 { (value >> 1) ^ reversed } else { value >> 1 }
+            } else if value & 0x8000_0000_0000_0000 != 0 {
+                (value << 1) ^ polynomial
+            } else {
+                value << 1
+            };
+            bit += 1;
+        }
+        table[i] = value;
+        i += 1;
+    }
+    table
+}
+
+/// CRC-64/XZ (also known as CRC-64/GO-ECMA).
+pub static XZ: Crc64Algorithm = Crc64Algorithm::new(0x42F0_E1EB_A9EA_3693, 0xFFFF_FFFF_FFFF_FFFF, true, 0xFFFF_FFFF_FFFF_FFFF);
+
+/// CRC-64/ECMA-182.
+pub static ECMA_182: Crc64Algorithm = Crc64Algorithm::new(0x42F0_E1EB_A9EA_3693, 0x0000_0000_0000_0000, false, 0x0000_0000_0000_0000);
+
+/// Calculates a CRC-64 incrementally.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::crc64::{self, Crc64};
+///
+/// let mut crc = Crc64::xz();
+/// crc.update(b"1234");
+/// crc.update(b"56789");
+/// assert_eq!(0x995DC9BBDF1939FA, crc.digest());
+///
+/// let mut crc = Crc64::new(&crc64::ECMA_182);
+/// crc.update(b"123456789");
+/// assert_eq!(0x6C40DF5F0B497347, crc.digest());
+///
+/// ```
+///
+/// Calculates the checksum while writing.
+///
+/// ```
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::crc64::{Crc64, Crc64Writer};
+/// use mm_binary_io::endian::BigEndian;
+///
+/// let mut writer = Crc64Writer::with_checksum(vec![], Crc64::xz());
+/// writer.write_integer_array::<BigEndian, _>(b"123456789").unwrap();
+/// assert_eq!(0x995DC9BBDF1939FA, writer.digest());
+///
+/// ```
+///
+#[derive(Clone, Copy)]
+pub struct Crc64 {
+    algorithm: &'static Crc64Algorithm,
+    state: u64,
+}
+
+impl Crc64 {
+    /// Creates a new `Crc64` of `algorithm` with no data.
+    pub fn new(algorithm: &'static Crc64Algorithm) -> Crc64 {
+        Crc64 {
+            algorithm,
+            state: algorithm.init,
+        }
+    }
+
+    /// Creates a new CRC-64/XZ with no data.
+    pub fn xz() -> Crc64 {
+        Crc64::new(&XZ)
+    }
+
+    /// Creates a new CRC-64/ECMA-182 with no data.
+    pub fn ecma_182() -> Crc64 {
+        Crc64::new(&ECMA_182)
+    }
+
+    /// Updates the checksum with `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        let table = &self.algorithm.table;
+        let mut state = self.state;
+        if self.algorithm.reflected {
+            for &b in bytes {
+                state = table[((state ^ b as u64) & 0xFF) as usize] ^ (state >> 8);
+            }
+        } else {
+            for &b in bytes {
+                state = table[(((state >> 56) ^ b as u64) & 0xFF) as usize] ^ (state << 8);
+            }
+        }
+        self.state = state;
+    }
+
+    /// Returns the checksum of the data so far.
+    pub fn digest(&self) -> u64 {
+        self.state ^ self.algorithm.xor_out
+    }
+
+    /// Resets the checksum to the initial state.
+    pub fn reset(&mut self) {
+        self.state = self.algorithm.init;
+    }
+}
+
+impl Checksum for Crc64 {
+    type Output = u64;
+
+    fn update(&mut self, bytes: &[u8]) {
+        Crc64::update(self, bytes)
+    }
+
+    fn digest(&self) -> u64 {
+        Crc64::digest(self)
+    }
+
+    fn reset(&mut self) {
+        Crc64::reset(self)
+    }
+}
+
+/// The reader which calculates a CRC-64 of the bytes read.
+pub type Crc64Reader<TRead> = ChecksumReader<TRead, Crc64>;
+
+/// The writer which calculates a CRC-64 of the bytes written.
+pub type Crc64Writer<TWrite> = ChecksumWriter<TWrite, Crc64>;
+
+/// Calculates the CRC-64 of `bytes` by `algorithm`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::crc64;
+///
+/// assert_eq!(0x995DC9BBDF1939FA, crc64::crc64(&crc64::XZ, b"123456789"));
+/// assert_eq!(0x6C40DF5F0B497347, crc64::crc64(&crc64::ECMA_182, b"123456789"));
+///
+/// ```
+///
+pub fn crc64(algorithm: &'static Crc64Algorithm, bytes: &[u8]) -> u64 {
+    let mut crc = Crc64::new(algorithm);
+    crc.update(bytes);
+    crc.digest()
+}
@@ -0,0 +1,70 @@
+//! Provides the reader loop shared by the delimiter-framed encodings, `slip` and `cobs`.
+
+use std::io;
+
+/// Decodes the bytes of one frame, which never contain the delimiter.
+pub(crate) trait FrameDecoder {
+    /// Decodes the next byte of the frame.
+    fn push(&mut self, byte: u8);
+
+    /// Returns the length of the frame decoded so far.
+    fn decoded_len(&self) -> usize;
+
+    /// Finishes the frame at the delimiter.
+    fn finish(self) -> io::Result<Vec<u8>>;
+}
+
+/// Reads the bytes up to the next `delimiter` into `decoder`, skipping the empty frames.
+///
+/// `name` names the encoding in the error messages.
+///
+/// # Errors
+///
+/// If the stream ends cleanly before the frame, returns Ok(None).
+/// If the frame is broken or longer than `max_len`, the frame is skipped and
+/// Err(io::Error) of `InvalidData` is returned.
+/// If the stream ends in the middle of the frame, returns Err(io::Error) of `UnexpectedEof`.
+///
+pub(crate) fn read_frame<TRead, TDecoder>(reader: &mut TRead, delimiter: u8, max_len: usize, decoder: TDecoder, name: &str) -> io::Result<Option<Vec<u8>>>
+    where
+        TRead: io::BufRead,
+        TDecoder: FrameDecoder {
+    let mut decoder = decoder;
+    let mut started = false;
+    let mut too_long = false;
+    loop {
+        let (used, delimited) = {
+            let buf = match reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if buf.is_empty() {
+                if !started {
+                    return Ok(None);
+                }
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, format!("{} frame is cut off", name)));
+            }
+            let (used, delimited) = match buf.iter().position(|&b| b == delimiter) {
+                Some(i) => (i, true),
+                None => (buf.len(), false),
+            };
+            for &byte in &buf[..used] {
+                started = true;
+                if !too_long {
+                    decoder.push(byte);
+                    too_long = decoder.decoded_len() > max_len;
+                }
+            }
+            (used, delimited)
+        };
+        reader.consume(used + delimited as usize);
+        if delimited && started {
+            break;
+        }
+    }
+    if too_long {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} frame is too long", name)));
+    }
+    decoder.finish().map(Some)
+}
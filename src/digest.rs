@@ -0,0 +1,95 @@
+//! Provides the features to calculate RustCrypto hashes (SHA-256, BLAKE2, ...) while reading or
+//! writing binary data.
+//!
+//! This module is available with the `digest` feature.
+
+use digest_crate::{Digest, Output};
+
+use checksum::{Checksum, ChecksumReader, ChecksumWriter};
+
+/// Adapts a `digest::Digest` to `Checksum`.
+///
+/// # Examples
+///
+/// ```
+/// extern crate sha2;
+/// extern crate mm_binary_io;
+///
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::digest::{DigestReader, DigestWriter};
+/// use mm_binary_io::endian::BigEndian;
+///
+/// # fn main() {
+/// let mut writer = DigestWriter::<_, sha2::Sha256>::new(vec![]);
+/// writer.write_integer::<BigEndian, _>(0x00616263_u32).unwrap();
+/// let digest = writer.digest();
+///
+/// let mut reader = DigestReader::<_, sha2::Sha256>::new(io::Cursor::new(writer.into_inner().0));
+/// assert_eq!(0x616263, reader.read_integer::<BigEndian, u32>().unwrap());
+/// assert_eq!(digest, reader.digest());
+///
+/// let mut reader = DigestReader::<_, sha2::Sha256>::new(io::Cursor::new(b"abc".to_vec()));
+/// reader.read_byte_array(3).unwrap();
+/// assert_eq!(
+///     [0xBA, 0x78, 0x16, 0xBF, 0x8F, 0x01, 0xCF, 0xEA, 0x41, 0x41, 0x40, 0xDE, 0x5D, 0xAE, 0x22, 0x23,
+///      0xB0, 0x03, 0x61, 0xA3, 0x96, 0x17, 0x7A, 0x9C, 0xB4, 0x10, 0xFF, 0x61, 0xF2, 0x00, 0x15, 0xAD],
+///     reader.digest().as_slice());
+/// # }
+///
+/// ```
+///
+#[derive(Clone)]
+pub struct DigestChecksum<TDigest>
+    where TDigest: Digest + Clone {
+    digest: TDigest,
+}
+
+impl<TDigest> DigestChecksum<TDigest>
+    where TDigest: Digest + Clone {
+    /// Creates a new `DigestChecksum` with no data.
+    pub fn new() -> DigestChecksum<TDigest> {
+        DigestChecksum::with_digest(TDigest::new())
+    }
+
+    /// Creates a new `DigestChecksum` which continues from `digest`.
+    pub fn with_digest(digest: TDigest) -> DigestChecksum<TDigest> {
+        DigestChecksum { digest }
+    }
+
+    /// Unwraps this `DigestChecksum`, returning the underlying digest.
+    pub fn into_inner(self) -> TDigest {
+        self.digest
+    }
+}
+
+impl<TDigest> Default for DigestChecksum<TDigest>
+    where TDigest: Digest + Clone {
+    fn default() -> DigestChecksum<TDigest> {
+        DigestChecksum::new()
+    }
+}
+
+impl<TDigest> Checksum for DigestChecksum<TDigest>
+    where TDigest: Digest + Clone {
+    type Output = Output<TDigest>;
+
+    fn update(&mut self, bytes: &[u8]) {
+        Digest::update(&mut self.digest, bytes)
+    }
+
+    fn digest(&self) -> Output<TDigest> {
+        self.digest.clone().finalize()
+    }
+
+    fn reset(&mut self) {
+        self.digest = TDigest::new();
+    }
+}
+
+/// The reader which calculates a RustCrypto hash of the bytes read.
+pub type DigestReader<TRead, TDigest> = ChecksumReader<TRead, DigestChecksum<TDigest>>;
+
+/// The writer which calculates a RustCrypto hash of the bytes written.
+pub type DigestWriter<TWrite, TDigest> = ChecksumWriter<TWrite, DigestChecksum<TDigest>>;
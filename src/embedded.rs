@@ -0,0 +1,281 @@
+//! Provides the features to read and write binary data over `embedded_io::Read` and
+//! `embedded_io::Write`, so that the parsing code can be shared with microcontroller firmware.
+//!
+//! The methods mirror `BinaryRead` and `BinaryWrite`, returning `Error` which wraps the error of
+//! the underlying reader or writer. The async traits of `embedded-io-async` are not supported, as
+//! implementing them needs `async fn`.
+//!
+//! This module is available with the `embedded-io` feature, which does not need `std`. The methods
+//! returning `Vec` and `String` need the `alloc` feature, and the others allocate nothing.
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::mem;
+
+use embedded_io::{self, ErrorKind, Read, ReadExactError, Write};
+
+use endian::Endian;
+use from_bytes::FromBytes;
+use to_bytes::ToBytes;
+use varint::{self, MAX_VARINT_LEN};
+
+/// The widest integer read and written, which is staged on the stack so that no allocation is
+/// needed.
+const MAX_INTEGER_LEN: usize = 16;
+
+/// The error of `EmbeddedBinaryRead` and `EmbeddedBinaryWrite`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    /// The reader ended before the value.
+    UnexpectedEof,
+    /// The data is broken.
+    InvalidData(&'static str),
+    /// The argument is not valid.
+    InvalidInput(&'static str),
+    /// The error of the underlying reader or writer.
+    Other(E),
+}
+
+impl<E> From<ReadExactError<E>> for Error<E> {
+    fn from(e: ReadExactError<E>) -> Error<E> {
+        match e {
+            ReadExactError::UnexpectedEof => Error::UnexpectedEof,
+            ReadExactError::Other(e) => Error::Other(e),
+        }
+    }
+}
+
+impl<E> fmt::Display for Error<E>
+    where E: fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::UnexpectedEof => write!(f, "failed to fill whole buffer"),
+            Error::InvalidData(msg) | Error::InvalidInput(msg) => write!(f, "{}", msg),
+            Error::Other(ref e) => write!(f, "{:?}", e),
+        }
+    }
+}
+
+impl<E> error::Error for Error<E>
+    where E: fmt::Debug {}
+
+impl<E> embedded_io::Error for Error<E>
+    where E: embedded_io::Error {
+    fn kind(&self) -> ErrorKind {
+        match *self {
+            // `embedded_io::ErrorKind` has no variant of the end of the stream, so match on
+            // `Error::UnexpectedEof` itself to tell it from the other errors.
+            Error::UnexpectedEof => ErrorKind::Other,
+            Error::InvalidData(_) => ErrorKind::InvalidData,
+            Error::InvalidInput(_) => ErrorKind::InvalidInput,
+            Error::Other(ref e) => e.kind(),
+        }
+    }
+}
+
+/// Provides the features to read binary data from `embedded_io::Read`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::embedded::{EmbeddedBinaryRead, Error};
+/// use mm_binary_io::endian::BigEndian;
+///
+/// let data = [0xFF_u8, 0xFE, 0x00, 0x01, 0x00, 0x02, b'a', b'b', 0xE5, 0x8E, 0x26, 0x03];
+/// let mut reader = &data[..];
+///
+/// assert_eq!(Ok(-2), reader.read_integer::<BigEndian, i16>());
+/// assert_eq!(Ok(vec![1_u16, 2]), reader.read_integer_array::<BigEndian, u16>(2));
+/// assert_eq!(Ok("ab".to_string()), reader.read_string(2));
+/// assert_eq!(Ok(624_485), reader.read_varint_u64());
+/// assert_eq!(Ok(-2), reader.read_varint_i64());
+/// assert_eq!(Err(Error::UnexpectedEof), reader.read_byte_array(1));
+///
+/// ```
+///
+pub trait EmbeddedBinaryRead: Read {
+    /// Reads an integer.
+    ///
+    /// # Errors
+    ///
+    /// If `TInt` is wider than 16 bytes, returns Err(Error::InvalidInput) and reads nothing.
+    ///
+    fn read_integer<TEndian, TInt>(&mut self) -> Result<TInt, Error<Self::Error>>
+        where TEndian: Endian,
+              TInt: FromBytes<OutputType=TInt> {
+        let mut buf = [0_u8; MAX_INTEGER_LEN];
+        let buf = integer_buf::<TInt, _>(&mut buf)?;
+        self.read_exact(buf)?;
+        Ok(TInt::from_bytes::<TEndian>(buf))
+    }
+
+    /// Reads byte array.
+    ///
+    /// # Arguments
+    ///
+    /// * byte_count - the byte count of the array.
+    ///
+    #[cfg(feature = "alloc")]
+    fn read_byte_array(&mut self, byte_count: usize) -> Result<Vec<u8>, Error<Self::Error>> {
+        let mut buf = ::alloc::vec![0_u8; byte_count];
+        self.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Reads an integer array.
+    ///
+    /// # Arguments
+    ///
+    /// * element_count - the number of elements in the array.
+    ///
+    #[cfg(feature = "alloc")]
+    fn read_integer_array<TEndian, TInt>(&mut self, element_count: usize) -> Result<Vec<TInt>, Error<Self::Error>>
+        where TEndian: Endian,
+              TInt: FromBytes<OutputType=TInt> {
+        let mut result = Vec::with_capacity(element_count);
+        for _i in 0..element_count {
+            result.push(self.read_integer::<TEndian, TInt>()?);
+        }
+        Ok(result)
+    }
+
+    /// Reads a UTF-8 string of `byte_count` bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the bytes are not valid UTF-8, returns Err(Error::InvalidData).
+    ///
+    #[cfg(feature = "alloc")]
+    fn read_string(&mut self, byte_count: usize) -> Result<String, Error<Self::Error>> {
+        String::from_utf8(self.read_byte_array(byte_count)?)
+            .map_err(|_| Error::InvalidData("invalid UTF-8 string"))
+    }
+
+    /// Reads an unsigned LEB128 variable-length integer.
+    ///
+    /// # Errors
+    ///
+    /// If the encoded integer exceeds u64, returns Err(Error::InvalidData).
+    ///
+    fn read_varint_u64(&mut self) -> Result<u64, Error<Self::Error>> {
+        let mut buf = [0_u8; MAX_VARINT_LEN];
+        let mut len = 0;
+        while len == 0 || (buf[len - 1] & 0x80 != 0 && len < MAX_VARINT_LEN) {
+            self.read_exact(&mut buf[len..len + 1])?;
+            len += 1;
+        }
+        varint::decode_u64(&buf[..len]).ok_or(Error::InvalidData("varint overflows u64"))
+    }
+
+    /// Reads a ZigZag encoded signed LEB128 variable-length integer.
+    fn read_varint_i64(&mut self) -> Result<i64, Error<Self::Error>> {
+        self.read_varint_u64().map(varint::zigzag_decode)
+    }
+}
+
+impl<T> EmbeddedBinaryRead for T
+    where T: Read + ?Sized {}
+
+/// Provides the features to write binary data to `embedded_io::Write`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::embedded::{EmbeddedBinaryWrite, Error};
+/// use mm_binary_io::endian::LittleEndian;
+///
+/// let mut buf = [0_u8; 14];
+/// {
+///     let mut writer = &mut buf[..];
+///     writer.write_integer::<LittleEndian, _>(-2_i16).unwrap();
+///     writer.write_integer_array::<LittleEndian, _>(&[1_u16, 2]).unwrap();
+///     writer.write_string("ab").unwrap();
+///     writer.write_varint_u64(624_485).unwrap();
+///     writer.write_varint_i64(-2).unwrap();
+///     writer.write_frame::<LittleEndian, u8>(&[0x12]).unwrap();
+///     assert_eq!(Err(Error::InvalidInput("frame is too long")), writer.write_frame::<LittleEndian, u8>(&[0; 256]));
+/// }
+///
+/// assert_eq!([0xFE_u8, 0xFF, 0x01, 0x00, 0x02, 0x00, b'a', b'b', 0xE5, 0x8E, 0x26, 0x03, 0x01, 0x12], buf);
+///
+/// ```
+///
+pub trait EmbeddedBinaryWrite: Write {
+    /// Writes the `value`.
+    ///
+    /// # Errors
+    ///
+    /// If `TInt` is wider than 16 bytes, returns Err(Error::InvalidInput) and writes nothing.
+    ///
+    fn write_integer<TEndian, TInt>(&mut self, value: TInt) -> Result<(), Error<Self::Error>>
+        where TEndian: Endian,
+              TInt: ToBytes {
+        write_integer::<TEndian, _, _>(self, &value)
+    }
+
+    /// Writes the integer array.
+    fn write_integer_array<TEndian, TInt>(&mut self, values: &[TInt]) -> Result<(), Error<Self::Error>>
+        where TEndian: Endian,
+              TInt: ToBytes {
+        for value in values {
+            write_integer::<TEndian, _, _>(self, value)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the UTF-8 bytes of `value`.
+    fn write_string(&mut self, value: &str) -> Result<(), Error<Self::Error>> {
+        self.write_all(value.as_bytes()).map_err(Error::Other)
+    }
+
+    /// Writes a length-prefixed frame of `payload`.
+    ///
+    /// # Errors
+    ///
+    /// If the payload length does not fit in `TLen`, returns Err(Error::InvalidInput).
+    ///
+    fn write_frame<TEndian, TLen>(&mut self, payload: &[u8]) -> Result<(), Error<Self::Error>>
+        where TEndian: Endian,
+              TLen: ToBytes + TryFrom<u64> {
+        let len = TLen::try_from(payload.len() as u64)
+            .map_err(|_| Error::InvalidInput("frame is too long"))?;
+        self.write_integer::<TEndian, _>(len)?;
+        self.write_all(payload).map_err(Error::Other)
+    }
+
+    /// Writes an unsigned LEB128 variable-length integer.
+    fn write_varint_u64(&mut self, value: u64) -> Result<(), Error<Self::Error>> {
+        let mut buf = [0_u8; MAX_VARINT_LEN];
+        let len = varint::encode_u64(value, &mut buf);
+        self.write_all(&buf[..len]).map_err(Error::Other)
+    }
+
+    /// Writes a ZigZag encoded signed LEB128 variable-length integer.
+    fn write_varint_i64(&mut self, value: i64) -> Result<(), Error<Self::Error>> {
+        self.write_varint_u64(varint::zigzag_encode(value))
+    }
+}
+
+impl<T> EmbeddedBinaryWrite for T
+    where T: Write + ?Sized {}
+
+fn write_integer<TEndian, TInt, TWrite>(writer: &mut TWrite, value: &TInt) -> Result<(), Error<TWrite::Error>>
+    where TEndian: Endian,
+          TInt: ToBytes,
+          TWrite: Write + ?Sized {
+    let mut buf = [0_u8; MAX_INTEGER_LEN];
+    let buf = integer_buf::<TInt, _>(&mut buf)?;
+    value.to_bytes::<TEndian>(buf);
+    writer.write_all(buf).map_err(Error::Other)
+}
+
+/// Returns the head of `buf` of the size of `TInt`.
+fn integer_buf<TInt, E>(buf: &mut [u8; MAX_INTEGER_LEN]) -> Result<&mut [u8], Error<E>> {
+    buf.get_mut(..mem::size_of::<TInt>())
+        .ok_or(Error::InvalidInput("integer is wider than 16 bytes"))
+}
@@ -126,6 +126,22 @@ pub trait Endian {
 
     /// Converts i64 to bytes.
     fn i64_to_bytes(value: i64, destination: &mut [u8]);
+
+    /// Returns whether this is the endianness of the target, so the bytes need no conversion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mm_binary_io::endian::{Endian, BigEndian, LittleEndian};
+    ///
+    /// assert_eq!(cfg!(target_endian = "big"), BigEndian::is_native());
+    /// assert_eq!(cfg!(target_endian = "little"), LittleEndian::is_native());
+    ///
+    /// ```
+    ///
+    fn is_native() -> bool {
+        Self::u16_from_bytes(&1_u16.to_ne_bytes()) == 1
+    }
 }
 
 /// Provides functions to convert a byte array to integer, and vice versa for Big Endian.
@@ -233,6 +249,22 @@ impl Endian for BigEndian {
 }
 
 /// Provides functions to convert a byte array to integer, and vice versa for Little Endian.
+///
+/// # Examples
+///
+/// The signed integers are decoded in Little Endian as the unsigned ones are.
+///
+/// ```
+///
+/// use mm_binary_io::endian::{Endian, LittleEndian};
+///
+/// assert_eq!(-2, LittleEndian::i16_from_bytes(&[0xFE, 0xFF]));
+/// assert_eq!(0x1234, LittleEndian::i16_from_bytes(&[0x34, 0x12]));
+/// assert_eq!(-3, LittleEndian::i32_from_bytes(&[0xFD, 0xFF, 0xFF, 0xFF]));
+/// assert_eq!(-4, LittleEndian::i64_from_bytes(&[0xFC, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]));
+///
+/// ```
+///
 pub struct LittleEndian {}
 
 impl Endian for LittleEndian {
@@ -250,7 +282,7 @@ impl Endian for LittleEndian {
     }
 
     fn i16_from_bytes(bytes: &[u8]) -> i16 {
-        BigEndian::u16_from_bytes(bytes) as i16
+        LittleEndian::u16_from_bytes(bytes) as i16
     }
 
     fn u32_from_bytes(bytes: &[u8]) -> u32 {
@@ -261,7 +293,7 @@ impl Endian for LittleEndian {
     }
 
     fn i32_from_bytes(bytes: &[u8]) -> i32 {
-        BigEndian::u32_from_bytes(bytes) as i32
+        LittleEndian::u32_from_bytes(bytes) as i32
     }
 
     fn u64_from_bytes(bytes: &[u8]) -> u64 {
@@ -276,7 +308,7 @@ impl Endian for LittleEndian {
     }
 
     fn i64_from_bytes(bytes: &[u8]) -> i64 {
-        BigEndian::u64_from_bytes(bytes) as i64
+        LittleEndian::u64_from_bytes(bytes) as i64
     }
 
     fn u8_to_bytes(value: u8, destination: &mut [u8]) {
@@ -335,3 +367,10 @@ impl Endian for LittleEndian {
         destination[0] = ((value >> 0) & 0xFF_u64) as u8;
     }
 }
+
+/// Returns whether `TEndian` stores the most significant byte first.
+#[cfg(feature = "std")]
+pub(crate) fn is_big_endian<TEndian>() -> bool
+    where TEndian: Endian {
+    TEndian::u16_from_bytes(&[0x00, 0x01]) == 1
+}
@@ -0,0 +1,288 @@
+//! Provides the error which tells where and what a failed read was reading.
+//!
+//! The read methods still return `io::Error`, so that they work with `?` in any IO code. When a
+//! read fails, `BinaryIoError` is attached as the inner error, with the type being read, and the
+//! byte offset if the reader knows its position. `BinaryIoError::find` extracts it, and the
+//! `io::ErrorKind` of the original error is kept.
+//!
+//! The details are attached only to the errors which carry neither an OS error code nor an inner
+//! error, such as the end of the stream. The other errors are returned as they are, so that
+//! `raw_os_error` and the downcast of the inner error work on them.
+//!
+//! The offsets are known by `BufferedBinaryReader`, and by the positional reads of `FileRead`.
+//! The field names are attached by `Context::ctx`, which builds the path of the field through
+//! the nested structures.
+
+use std::error;
+use std::fmt;
+use std::io;
+
+/// The error of a failed read, with the byte offset, the type being read and the field name.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::buffered::BufferedBinaryReader;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::error::BinaryIoError;
+///
+/// let mut reader = BufferedBinaryReader::new(io::Cursor::new(vec![0_u8; 6]));
+/// reader.read_integer::<BigEndian, u32>().unwrap();
+///
+/// let error = reader.read_integer::<BigEndian, u32>().unwrap_err();
+/// assert_eq!(io::ErrorKind::UnexpectedEof, error.kind());
+///
+/// let detail = BinaryIoError::find(&error).unwrap();
+/// assert_eq!(Some(4), detail.offset());
+/// assert_eq!(Some("u32"), detail.type_name());
+/// assert_eq!("failed to read u32 at offset 4: failed to fill whole buffer", error.to_string());
+///
+/// let error: io::Error = BinaryIoError::new(io::ErrorKind::InvalidData.into())
+///     .with_field("header.width")
+///     .into();
+/// assert_eq!(io::ErrorKind::InvalidData, error.kind());
+/// assert_eq!(Some("header.width"), BinaryIoError::find(&error).unwrap().field());
+///
+/// // The OS errors are returned as they are.
+/// struct Failing;
+/// impl io::Read for Failing {
+///     fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+///         Err(io::Error::from_raw_os_error(5))
+///     }
+/// }
+///
+/// let error = Failing.read_integer::<BigEndian, u32>().unwrap_err();
+/// assert_eq!(Some(5), error.raw_os_error());
+/// assert!(BinaryIoError::find(&error).is_none());
+///
+/// ```
+///
+#[derive(Debug)]
+pub struct BinaryIoError {
+    source: io::Error,
+    offset: Option<u64>,
+    type_name: Option<&'static str>,
+    field: Option<String>,
+}
+
+impl BinaryIoError {
+    /// Creates a new `BinaryIoError` of `source` without the details.
+    ///
+    /// If `source` already carries a `BinaryIoError`, it is returned instead.
+    pub fn new(source: io::Error) -> BinaryIoError {
+        match source.downcast::<BinaryIoError>() {
+            Ok(e) => e,
+            Err(source) => BinaryIoError { source, offset: None, type_name: None, field: None },
+        }
+    }
+
+    /// Sets the byte offset of the stream where the failed read started.
+    pub fn with_offset(mut self, offset: u64) -> BinaryIoError {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Sets the name of the type being read.
+    pub fn with_type_name(mut self, type_name: &'static str) -> BinaryIoError {
+        self.type_name = Some(type_name);
+        self
+    }
+
+    /// Sets the name of the field or the section being read.
+    pub fn with_field<TField>(mut self, field: TField) -> BinaryIoError
+        where TField: Into<String> {
+        self.field = Some(field.into());
+        self
+    }
+
+    /// Returns the kind of the original error.
+    pub fn kind(&self) -> io::ErrorKind {
+        self.source.kind()
+    }
+
+    /// Returns the byte offset where the failed read started, if known.
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+
+    /// Returns the name of the type being read, if known.
+    pub fn type_name(&self) -> Option<&'static str> {
+        self.type_name
+    }
+
+    /// Returns the name of the field or the section being read, if known.
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    /// Returns the original error.
+    pub fn get_ref(&self) -> &io::Error {
+        &self.source
+    }
+
+    /// Unwraps this `BinaryIoError`, returning the original error.
+    pub fn into_inner(self) -> io::Error {
+        self.source
+    }
+
+    /// Returns the `BinaryIoError` carried by `error`, if any.
+    pub fn find(error: &io::Error) -> Option<&BinaryIoError> {
+        error.get_ref().and_then(|e| e.downcast_ref::<BinaryIoError>())
+    }
+}
+
+impl fmt::Display for BinaryIoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed to read")?;
+        if let Some(type_name) = self.type_name {
+            write!(f, " {}", type_name)?;
+        }
+        if let Some(ref field) = self.field {
+            write!(f, " `{}`", field)?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " at offset {}", offset)?;
+        }
+        write!(f, ": {}", self.source)
+    }
+}
+
+impl error::Error for BinaryIoError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<io::Error> for BinaryIoError {
+    fn from(e: io::Error) -> BinaryIoError {
+        BinaryIoError::new(e)
+    }
+}
+
+impl From<BinaryIoError> for io::Error {
+    fn from(e: BinaryIoError) -> io::Error {
+        io::Error::new(e.kind(), e)
+    }
+}
+
+/// Attaches the field names to the errors of the reads, building the path of the failed field.
+///
+/// Each level of a nested structure adds its name in front of the path, so that a failure deep
+/// in the structure reports e.g. `header.chunks[3].len`. The names starting with `[` are joined
+/// without a dot.
+///
+/// The errors carrying an OS error code or an inner error of another type are returned
+/// unchanged, so that the code and the inner error stay reachable.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::error::{BinaryIoError, Context};
+///
+/// fn read_chunk_len<TRead: io::Read>(reader: &mut TRead) -> io::Result<u32> {
+///     reader.read_integer::<BigEndian, u32>().ctx("len")
+/// }
+///
+/// fn read_header<TRead: io::Read>(reader: &mut TRead) -> io::Result<Vec<u32>> {
+///     let count = reader.read_integer::<BigEndian, u8>().ctx("count")?;
+///     (0..count)
+///         .map(|i| read_chunk_len(reader).with_ctx(|| format!("chunks[{}]", i)))
+///         .collect()
+/// }
+///
+/// let mut reader = io::Cursor::new(vec![0x05_u8, 0, 0, 0, 1, 0, 0]);
+/// let error = read_header(&mut reader).ctx("header").unwrap_err();
+///
+/// assert_eq!(io::ErrorKind::UnexpectedEof, error.kind());
+/// assert_eq!(Some("header.chunks[1].len"), BinaryIoError::find(&error).unwrap().field());
+/// assert_eq!("failed to read u32 `header.chunks[1].len`: failed to fill whole buffer", error.to_string());
+///
+/// let os_error = Err::<(), _>(io::Error::from_raw_os_error(2)).ctx("header").unwrap_err();
+/// assert_eq!(Some(2), os_error.raw_os_error());
+///
+/// ```
+///
+pub trait Context<T> {
+    /// Adds `field` in front of the field path of the error.
+    fn ctx<TField>(self, field: TField) -> io::Result<T>
+        where TField: Into<String>;
+
+    /// Adds the name returned by `f` in front of the field path of the error.
+    ///
+    /// `f` is called only on an error, so that the successful reads format nothing.
+    fn with_ctx<TField, F>(self, f: F) -> io::Result<T>
+        where
+            TField: Into<String>,
+            F: FnOnce() -> TField;
+}
+
+impl<T> Context<T> for io::Result<T> {
+    fn ctx<TField>(self, field: TField) -> io::Result<T>
+        where TField: Into<String> {
+        self.map_err(|e| with_parent_field(e, field.into()))
+    }
+
+    fn with_ctx<TField, F>(self, f: F) -> io::Result<T>
+        where
+            TField: Into<String>,
+            F: FnOnce() -> TField {
+        self.map_err(|e| with_parent_field(e, f().into()))
+    }
+}
+
+/// Adds `parent` in front of the field path of `error`, unless `error` is not attachable.
+fn with_parent_field(error: io::Error, parent: String) -> io::Error {
+    if !is_attachable(&error) {
+        return error;
+    }
+    let mut e = BinaryIoError::new(error);
+    e.field = Some(match e.field.take() {
+        Some(ref field) if field.starts_with('[') => parent + field,
+        Some(field) => parent + "." + &field,
+        None => parent,
+    });
+    e.into()
+}
+
+/// Returns true if the details can be attached to `error` without hiding its OS error code or
+/// its inner error.
+fn is_attachable(error: &io::Error) -> bool {
+    if error.raw_os_error().is_some() {
+        return false;
+    }
+    match error.get_ref() {
+        Some(inner) => inner.is::<BinaryIoError>(),
+        None => true,
+    }
+}
+
+/// Attaches the type name to `error`, unless a nested read already attached one or `error` is
+/// not attachable.
+pub(crate) fn with_type_name(error: io::Error, type_name: &'static str) -> io::Error {
+    if !is_attachable(&error) {
+        return error;
+    }
+    let e = BinaryIoError::new(error);
+    if e.type_name.is_some() {
+        return e.into();
+    }
+    e.with_type_name(type_name).into()
+}
+
+/// Attaches the offset to `error`, unless a nested reader already attached one or `error` is
+/// not attachable.
+pub(crate) fn with_offset(error: io::Error, offset: u64) -> io::Error {
+    if !is_attachable(&error) {
+        return error;
+    }
+    let e = BinaryIoError::new(error);
+    if e.offset.is_some() {
+        return e.into();
+    }
+    e.with_offset(offset).into()
+}
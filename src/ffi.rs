@@ -0,0 +1,421 @@
+//! Provides the C-callable functions over opaque reader and writer handles, so that C and C++
+//! projects can link the parsing core of this crate.
+//!
+//! The functions are `extern "C"` with `#[no_mangle]`, and use only pointers, fixed-width
+//! integers and `MmStatus`, so the header can be generated by cbindgen. Build the crate as a
+//! `staticlib` or `cdylib` (e.g. `cargo rustc --features ffi --crate-type staticlib`) to link it.
+//!
+//! A handle is created by `mm_reader_new` or `mm_writer_new`, and must be released exactly once
+//! by `mm_reader_free` or `mm_writer_free`.
+//!
+//! This module is available with the `ffi` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use mm_binary_io::ffi::*;
+//!
+//! unsafe {
+//!     let writer = mm_writer_new();
+//!     assert_eq!(MmStatus::Ok, mm_writer_write_u32_be(writer, 0x1234_5678));
+//!     assert_eq!(MmStatus::Ok, mm_writer_write_frame_u32_le(writer, b"ab".as_ptr(), 2));
+//!
+//!     let mut len = 0;
+//!     let data = mm_writer_data(writer, &mut len);
+//!     let reader = mm_reader_new(data, len);
+//!     mm_writer_free(writer);
+//!
+//!     let mut value = 0;
+//!     assert_eq!(MmStatus::Ok, mm_reader_read_u32_be(reader, &mut value));
+//!     assert_eq!(0x1234_5678, value);
+//!
+//!     let mut payload = [0_u8; 4];
+//!     let mut payload_len = 0;
+//!     assert_eq!(MmStatus::Ok, mm_reader_read_frame_u32_le(reader, payload.as_mut_ptr(), payload.len(), &mut payload_len));
+//!     assert_eq!(b"ab", &payload[..payload_len]);
+//!
+//!     // A frame longer than the buffer is left unread, with its length.
+//!     let writer = mm_writer_new();
+//!     assert_eq!(MmStatus::Ok, mm_writer_write_frame_u32_le(writer, b"abcdef".as_ptr(), 6));
+//!     let mut len = 0;
+//!     let data = mm_writer_data(writer, &mut len);
+//!     let frames = mm_reader_new(data, len);
+//!     mm_writer_free(writer);
+//!     assert_eq!(MmStatus::InvalidData, mm_reader_read_frame_u32_le(frames, payload.as_mut_ptr(), payload.len(), &mut payload_len));
+//!     assert_eq!((6, 0), (payload_len, mm_reader_position(frames)));
+//!     mm_reader_free(frames);
+//!
+//!     assert_eq!(MmStatus::UnexpectedEof, mm_reader_read_u32_be(reader, &mut value));
+//!     assert_eq!(MmStatus::NullPointer, mm_reader_read_u32_be(reader, std::ptr::null_mut()));
+//!     mm_reader_free(reader);
+//! }
+//!
+//! ```
+
+use std::io;
+use std::ptr;
+use std::slice;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::{BigEndian, LittleEndian};
+
+/// The result of the functions.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MmStatus {
+    /// The function succeeded.
+    Ok = 0,
+    /// A required pointer is null.
+    NullPointer = 1,
+    /// The reader ended before the value.
+    UnexpectedEof = 2,
+    /// The data is broken.
+    InvalidData = 3,
+    /// The argument is not valid.
+    InvalidInput = 4,
+    /// Any other error.
+    Other = 5,
+}
+
+impl From<io::Error> for MmStatus {
+    fn from(e: io::Error) -> MmStatus {
+        match e.kind() {
+            io::ErrorKind::UnexpectedEof => MmStatus::UnexpectedEof,
+            io::ErrorKind::InvalidData => MmStatus::InvalidData,
+            io::ErrorKind::InvalidInput => MmStatus::InvalidInput,
+            _ => MmStatus::Other,
+        }
+    }
+}
+
+/// The opaque reader handle, holding a copy of the bytes to read.
+pub struct MmReader {
+    inner: io::Cursor<Vec<u8>>,
+}
+
+/// The opaque writer handle, collecting the bytes written.
+pub struct MmWriter {
+    inner: Vec<u8>,
+}
+
+/// Creates a reader of a copy of the `len` bytes at `data`.
+///
+/// Returns null if `data` is null and `len` is not 0.
+///
+/// # Safety
+///
+/// `data` must be valid for reading `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mm_reader_new(data: *const u8, len: usize) -> *mut MmReader {
+    let bytes = match bytes_from_raw(data, len) {
+        Some(bytes) => bytes.to_vec(),
+        None => return ptr::null_mut(),
+    };
+    Box::into_raw(Box::new(MmReader { inner: io::Cursor::new(bytes) }))
+}
+
+/// Releases the `reader`. Does nothing if it is null.
+///
+/// # Safety
+///
+/// `reader` must be null or a handle returned by `mm_reader_new`, which is not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn mm_reader_free(reader: *mut MmReader) {
+    if !reader.is_null() {
+        drop(Box::from_raw(reader));
+    }
+}
+
+/// Returns the byte count read, or 0 if `reader` is null.
+///
+/// # Safety
+///
+/// `reader` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn mm_reader_position(reader: *const MmReader) -> u64 {
+    reader.as_ref().map_or(0, |reader| reader.inner.position())
+}
+
+/// Returns the byte count left, or 0 if `reader` is null.
+///
+/// # Safety
+///
+/// `reader` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn mm_reader_remaining(reader: *const MmReader) -> u64 {
+    reader.as_ref().map_or(0, |reader| {
+        (reader.inner.get_ref().len() as u64).saturating_sub(reader.inner.position())
+    })
+}
+
+/// Reads `len` bytes into `out`.
+///
+/// # Safety
+///
+/// `reader` must be null or a live handle, and `out` must be valid for writing `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mm_reader_read_bytes(reader: *mut MmReader, out: *mut u8, len: usize) -> MmStatus {
+    let reader = match reader.as_mut() {
+        Some(reader) => reader,
+        None => return MmStatus::NullPointer,
+    };
+    let out = match bytes_from_raw_mut(out, len) {
+        Some(out) => out,
+        None => return MmStatus::NullPointer,
+    };
+    status(io::Read::read_exact(&mut reader.inner, out))
+}
+
+macro_rules! reader_read_integer {
+    ($($name:ident, $t:ty, $endian:ty;)*) => {
+        $(
+            #[doc = concat!("Reads a `", stringify!($t), "` in `", stringify!($endian), "` into `out`.")]
+            ///
+            /// Nothing is stored to `out` if the function fails.
+            ///
+            /// # Safety
+            ///
+            /// `reader` must be null or a live handle, and `out` must be null or valid for writing.
+            #[no_mangle]
+            pub unsafe extern "C" fn $name(reader: *mut MmReader, out: *mut $t) -> MmStatus {
+                let reader = match reader.as_mut() {
+                    Some(reader) => reader,
+                    None => return MmStatus::NullPointer,
+                };
+                if out.is_null() {
+                    return MmStatus::NullPointer;
+                }
+                match reader.inner.read_integer::<$endian, $t>() {
+                    Ok(value) => {
+                        *out = value;
+                        MmStatus::Ok
+                    }
+                    Err(e) => e.into(),
+                }
+            }
+        )*
+    }
+}
+
+reader_read_integer! {
+    mm_reader_read_u8, u8, BigEndian;
+    mm_reader_read_i8, i8, BigEndian;
+    mm_reader_read_u16_be, u16, BigEndian;
+    mm_reader_read_u16_le, u16, LittleEndian;
+    mm_reader_read_i16_be, i16, BigEndian;
+    mm_reader_read_i16_le, i16, LittleEndian;
+    mm_reader_read_u32_be, u32, BigEndian;
+    mm_reader_read_u32_le, u32, LittleEndian;
+    mm_reader_read_i32_be, i32, BigEndian;
+    mm_reader_read_i32_le, i32, LittleEndian;
+    mm_reader_read_u64_be, u64, BigEndian;
+    mm_reader_read_u64_le, u64, LittleEndian;
+    mm_reader_read_i64_be, i64, BigEndian;
+    mm_reader_read_i64_le, i64, LittleEndian;
+}
+
+macro_rules! reader_read_frame {
+    ($($name:ident, $endian:ty;)*) => {
+        $(
+            #[doc = concat!("Reads a frame of a `u32` length in `", stringify!($endian), "` and the payload.")]
+            ///
+            /// The payload is copied to `out`, and its length is stored to `out_len`. If the
+            /// payload is longer than `capacity`, its length is stored to `out_len` and returns
+            /// `MmStatus::InvalidData`, so that the caller can retry with a larger buffer.
+            ///
+            /// If the function fails, the position of the reader is not changed.
+            ///
+            /// # Safety
+            ///
+            /// `reader` must be null or a live handle, `out` must be valid for writing `capacity`
+            /// bytes, and `out_len` must be null or valid for writing.
+            #[no_mangle]
+            pub unsafe extern "C" fn $name(reader: *mut MmReader, out: *mut u8, capacity: usize, out_len: *mut usize) -> MmStatus {
+                let reader = match reader.as_mut() {
+                    Some(reader) => reader,
+                    None => return MmStatus::NullPointer,
+                };
+                let out = match bytes_from_raw_mut(out, capacity) {
+                    Some(out) => out,
+                    None => return MmStatus::NullPointer,
+                };
+                if out_len.is_null() {
+                    return MmStatus::NullPointer;
+                }
+                let position = reader.inner.position();
+                let result = reader.inner.read_integer::<$endian, u32>().and_then(|len| {
+                    let len = len as usize;
+                    if len > capacity {
+                        *out_len = len;
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame is longer than the capacity"));
+                    }
+                    io::Read::read_exact(&mut reader.inner, &mut out[..len])?;
+                    *out_len = len;
+                    Ok(())
+                });
+                if result.is_err() {
+                    reader.inner.set_position(position);
+                }
+                status(result)
+            }
+        )*
+    }
+}
+
+reader_read_frame! {
+    mm_reader_read_frame_u32_be, BigEndian;
+    mm_reader_read_frame_u32_le, LittleEndian;
+}
+
+/// Creates an empty writer.
+#[no_mangle]
+pub extern "C" fn mm_writer_new() -> *mut MmWriter {
+    Box::into_raw(Box::new(MmWriter { inner: Vec::new() }))
+}
+
+/// Releases the `writer`. Does nothing if it is null.
+///
+/// # Safety
+///
+/// `writer` must be null or a handle returned by `mm_writer_new`, which is not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn mm_writer_free(writer: *mut MmWriter) {
+    if !writer.is_null() {
+        drop(Box::from_raw(writer));
+    }
+}
+
+/// Returns the bytes written, storing their length to `out_len`.
+///
+/// The pointer is valid until the next write to or the release of the `writer`. Returns null if
+/// `writer` is null.
+///
+/// # Safety
+///
+/// `writer` must be null or a live handle, and `out_len` must be null or valid for writing.
+#[no_mangle]
+pub unsafe extern "C" fn mm_writer_data(writer: *const MmWriter, out_len: *mut usize) -> *const u8 {
+    let writer = match writer.as_ref() {
+        Some(writer) => writer,
+        None => return ptr::null(),
+    };
+    if !out_len.is_null() {
+        *out_len = writer.inner.len();
+    }
+    writer.inner.as_ptr()
+}
+
+/// Writes the `len` bytes at `data`.
+///
+/// # Safety
+///
+/// `writer` must be null or a live handle, and `data` must be valid for reading `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn mm_writer_write_bytes(writer: *mut MmWriter, data: *const u8, len: usize) -> MmStatus {
+    let writer = match writer.as_mut() {
+        Some(writer) => writer,
+        None => return MmStatus::NullPointer,
+    };
+    let data = match bytes_from_raw(data, len) {
+        Some(data) => data,
+        None => return MmStatus::NullPointer,
+    };
+    writer.inner.extend_from_slice(data);
+    MmStatus::Ok
+}
+
+macro_rules! writer_write_integer {
+    ($($name:ident, $t:ty, $endian:ty;)*) => {
+        $(
+            #[doc = concat!("Writes the `", stringify!($t), "` `value` in `", stringify!($endian), "`.")]
+            ///
+            /// # Safety
+            ///
+            /// `writer` must be null or a live handle.
+            #[no_mangle]
+            pub unsafe extern "C" fn $name(writer: *mut MmWriter, value: $t) -> MmStatus {
+                match writer.as_mut() {
+                    Some(writer) => status(writer.inner.write_integer::<$endian, $t>(value)),
+                    None => MmStatus::NullPointer,
+                }
+            }
+        )*
+    }
+}
+
+writer_write_integer! {
+    mm_writer_write_u8, u8, BigEndian;
+    mm_writer_write_i8, i8, BigEndian;
+    mm_writer_write_u16_be, u16, BigEndian;
+    mm_writer_write_u16_le, u16, LittleEndian;
+    mm_writer_write_i16_be, i16, BigEndian;
+    mm_writer_write_i16_le, i16, LittleEndian;
+    mm_writer_write_u32_be, u32, BigEndian;
+    mm_writer_write_u32_le, u32, LittleEndian;
+    mm_writer_write_i32_be, i32, BigEndian;
+    mm_writer_write_i32_le, i32, LittleEndian;
+    mm_writer_write_u64_be, u64, BigEndian;
+    mm_writer_write_u64_le, u64, LittleEndian;
+    mm_writer_write_i64_be, i64, BigEndian;
+    mm_writer_write_i64_le, i64, LittleEndian;
+}
+
+macro_rules! writer_write_frame {
+    ($($name:ident, $endian:ty;)*) => {
+        $(
+            #[doc = concat!("Writes a frame of a `u32` length in `", stringify!($endian), "` and the `len` bytes at `payload`.")]
+            ///
+            /// If the length does not fit in `u32`, returns `MmStatus::InvalidInput`.
+            ///
+            /// # Safety
+            ///
+            /// `writer` must be null or a live handle, and `payload` must be valid for reading
+            /// `len` bytes.
+            #[no_mangle]
+            pub unsafe extern "C" fn $name(writer: *mut MmWriter, payload: *const u8, len: usize) -> MmStatus {
+                let writer = match writer.as_mut() {
+                    Some(writer) => writer,
+                    None => return MmStatus::NullPointer,
+                };
+                let payload = match bytes_from_raw(payload, len) {
+                    Some(payload) => payload,
+                    None => return MmStatus::NullPointer,
+                };
+                status(writer.inner.write_frame::<$endian, u32>(payload))
+            }
+        )*
+    }
+}
+
+writer_write_frame! {
+    mm_writer_write_frame_u32_be, BigEndian;
+    mm_writer_write_frame_u32_le, LittleEndian;
+}
+
+fn status(result: io::Result<()>) -> MmStatus {
+    match result {
+        Ok(()) => MmStatus::Ok,
+        Err(e) => e.into(),
+    }
+}
+
+unsafe fn bytes_from_raw<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(data, len))
+    }
+}
+
+unsafe fn bytes_from_raw_mut<'a>(data: *mut u8, len: usize) -> Option<&'a mut [u8]> {
+    if len == 0 {
+        Some(&mut [])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts_mut(data, len))
+    }
+}
@@ -0,0 +1,498 @@
+//! Provides the features to read and write binary files.
+
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::io::Seek;
+use std::path::Path;
+
+use checksum::Checksum;
+use error;
+use metrics::IoMetrics;
+use scratch;
+
+/// The size of the buffer used by `FileRead` and `FileWrite`.
+pub const FILE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The buffered file reader which supports positional reads.
+///
+/// `FileRead` implements `io::Read` and `io::Seek`, so all `BinaryRead` methods can be used.
+/// The positional reads (`read_exact_at`, `checksum_range`) do not change the current position.
+///
+/// # Examples
+///
+/// ```
+/// use std::env;
+/// use std::fs;
+/// use std::io::Write;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::file::FileRead;
+///
+/// let path = env::temp_dir().join("mm_binary_io_file_read_example.bin");
+/// fs::File::create(&path).unwrap().write_all(&[0x12, 0x34, 0x56, 0x78]).unwrap();
+///
+/// let mut reader = FileRead::open(&path).unwrap();
+/// assert_eq!(4, reader.len().unwrap());
+/// assert_eq!(0x1234_u16, reader.read_integer::<BigEndian, u16>().unwrap());
+///
+/// let mut buf = [0_u8; 2];
+/// reader.read_exact_at(1, &mut buf).unwrap();
+/// assert_eq!([0x34, 0x56], buf);
+///
+/// assert_eq!(2, reader.position().unwrap());
+/// assert_eq!(0x5678_u16, reader.read_integer::<BigEndian, u16>().unwrap());
+///
+/// fs::remove_file(&path).unwrap();
+///
+/// ```
+///
+pub struct FileRead {
+    reader: io::BufReader<fs::File>,
+    metrics: Option<IoMetrics>,
+}
+
+impl FileRead {
+    /// Opens the file at `path` for reading.
+    pub fn open<TPath>(path: TPath) -> io::Result<FileRead>
+        where TPath: AsRef<Path> {
+        trace_event!(DEBUG, path = %path.as_ref().display(), "open file for reading");
+        fs::File::open(path).map(FileRead::from_file)
+    }
+
+    /// Creates a new `FileRead` reading `file` from its current position.
+    pub fn from_file(file: fs::File) -> FileRead {
+        FileRead {
+            reader: io::BufReader::with_capacity(FILE_BUFFER_SIZE, file),
+            metrics: None,
+        }
+    }
+
+    /// Returns the length of the file.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> io::Result<u64> {
+        self.reader.get_ref().metadata().map(|m| m.len())
+    }
+
+    /// Returns the current position.
+    pub fn position(&mut self) -> io::Result<u64> {
+        self.reader.stream_position()
+    }
+
+    /// Reads exactly `buf.len()` bytes at `offset` without changing the current position.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    /// If the file ends before `buf` is filled, returns Err(io::Error) of `UnexpectedEof`.
+    ///
+    pub fn read_exact_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let start = IoMetrics::start(&self.metrics);
+        trace_event!(TRACE, offset, len = buf.len(), "read file at offset");
+        let result = read_exact_at(&mut self.reader, offset, buf);
+        IoMetrics::end_read(&mut self.metrics, start, if result.is_ok() { buf.len() } else { 0 });
+        result.map_err(|e| error::with_offset(e, offset))
+    }
+
+    /// Calculates `checksum` over `len` bytes at `offset` without changing the current position.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the checksum), otherwise Err(io::Error).
+    /// If the file ends before `len` bytes are read, returns Err(io::Error) of `UnexpectedEof`.
+    /// If the range overflows `u64`, returns Err(io::Error) of `InvalidInput`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::env;
+    /// use std::fs;
+    /// use std::io::Write;
+    /// use mm_binary_io::adler32::Adler32;
+    /// use mm_binary_io::crc32::Crc32;
+    /// use mm_binary_io::file::FileRead;
+    ///
+    /// let path = env::temp_dir().join("mm_binary_io_checksum_range_example.bin");
+    /// fs::File::create(&path).unwrap().write_all(b"header123456789trailer").unwrap();
+    ///
+    /// let mut reader = FileRead::open(&path).unwrap();
+    /// assert_eq!(0xCBF43926, reader.checksum_range(6, 9, Crc32::new()).unwrap());
+    /// assert_eq!(0x091E01DE, reader.checksum_range(6, 9, Adler32::new()).unwrap());
+    /// assert_eq!(0, reader.position().unwrap());
+    ///
+    /// fs::remove_file(&path).unwrap();
+    ///
+    /// ```
+    ///
+    pub fn checksum_range<TChecksum>(&mut self, offset: u64, len: u64, checksum: TChecksum) -> io::Result<TChecksum::Output>
+        where TChecksum: Checksum {
+        scratch::with_scratch(len.min(FILE_BUFFER_SIZE as u64) as usize, |buf| {
+            checksum_at(offset, len, checksum, buf, |offset, buf| self.read_exact_at(offset, buf))
+        })
+    }
+
+    /// Hints the OS that the file will be read sequentially, so it reads ahead aggressively.
+    ///
+    /// The hint is ignored on the platforms other than Linux.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::env;
+    /// use std::fs;
+    /// use mm_binary_io::file::FileRead;
+    ///
+    /// let path = env::temp_dir().join("mm_binary_io_advise_example.bin");
+    /// fs::write(&path, &[0_u8; 16]).unwrap();
+    ///
+    /// let reader = FileRead::open(&path).unwrap();
+    /// reader.advise_sequential().unwrap();
+    /// reader.advise_random().unwrap();
+    /// reader.advise_willneed(4, 8).unwrap();
+    ///
+    /// fs::remove_file(&path).unwrap();
+    ///
+    /// ```
+    ///
+    pub fn advise_sequential(&self) -> io::Result<()> {
+        advise_file(self.get_ref(), Advice::Sequential, 0, 0)
+    }
+
+    /// Hints the OS that the file will be read at random offsets, so it does not read ahead.
+    ///
+    /// The hint is ignored on the platforms other than Linux.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    pub fn advise_random(&self) -> io::Result<()> {
+        advise_file(self.get_ref(), Advice::Random, 0, 0)
+    }
+
+    /// Hints the OS that `len` bytes at `offset` will be read soon, so it starts loading them.
+    ///
+    /// The hint is ignored on the platforms other than Linux.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    pub fn advise_willneed(&self, offset: u64, len: u64) -> io::Result<()> {
+        advise_file(self.get_ref(), Advice::WillNeed, offset, len)
+    }
+
+    /// Starts recording the IO statistics, resetting them.
+    pub fn enable_metrics(&mut self) {
+        self.metrics = Some(IoMetrics::default());
+    }
+
+    /// Returns the IO statistics, or None if they are not enabled.
+    pub fn metrics(&self) -> Option<IoMetrics> {
+        self.metrics
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn get_ref(&self) -> &fs::File {
+        self.reader.get_ref()
+    }
+
+    /// Unwraps this `FileRead`, returning the underlying file.
+    ///
+    /// The buffered data is lost, so the position of the file may be ahead of `position()`.
+    pub fn into_inner(self) -> fs::File {
+        self.reader.into_inner()
+    }
+}
+
+impl io::Read for FileRead {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let start = IoMetrics::start(&self.metrics);
+        let result = self.reader.read(buf);
+        IoMetrics::end_read(&mut self.metrics, start, *result.as_ref().unwrap_or(&0));
+        result
+    }
+}
+
+impl io::BufRead for FileRead {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let start = IoMetrics::start(&self.metrics);
+        let result = self.reader.fill_buf();
+        IoMetrics::end_read(&mut self.metrics, start, 0);
+        result
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = amt.min(self.reader.buffer().len());
+        self.reader.consume(amt);
+        IoMetrics::consume(&mut self.metrics, amt);
+    }
+}
+
+impl io::Seek for FileRead {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.reader.seek(pos)
+    }
+}
+
+/// The buffered file writer which supports positional writes.
+///
+/// `FileWrite` implements `io::Write` and `io::Seek`, so all `BinaryWrite` methods can be used.
+/// The positional writes (`write_all_at`) do not change the current position.
+///
+/// # Examples
+///
+/// ```
+/// use std::env;
+/// use std::fs;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::file::FileWrite;
+///
+/// let path = env::temp_dir().join("mm_binary_io_file_write_example.bin");
+///
+/// let mut writer = FileWrite::create(&path).unwrap();
+/// writer.write_integer::<BigEndian, _>(0_u16).unwrap();
+/// writer.write_integer::<BigEndian, _>(0x5678_u16).unwrap();
+///
+/// // Fills the placeholder after the data is written.
+/// writer.write_all_at(0, &[0x12, 0x34]).unwrap();
+/// assert_eq!(4, writer.position().unwrap());
+/// writer.sync_all().unwrap();
+///
+/// assert_eq!(vec![0x12, 0x34, 0x56, 0x78], fs::read(&path).unwrap());
+///
+/// fs::remove_file(&path).unwrap();
+///
+/// ```
+///
+pub struct FileWrite {
+    writer: io::BufWriter<fs::File>,
+    metrics: Option<IoMetrics>,
+}
+
+impl FileWrite {
+    /// Creates a new file at `path` for writing, truncating the existing file.
+    pub fn create<TPath>(path: TPath) -> io::Result<FileWrite>
+        where TPath: AsRef<Path> {
+        trace_event!(DEBUG, path = %path.as_ref().display(), "create file for writing");
+        fs::File::create(path).map(FileWrite::from_file)
+    }
+
+    /// Creates a new `FileWrite` writing `file` from its current position.
+    pub fn from_file(file: fs::File) -> FileWrite {
+        FileWrite {
+            writer: io::BufWriter::with_capacity(FILE_BUFFER_SIZE, file),
+            metrics: None,
+        }
+    }
+
+    /// Returns the current position.
+    pub fn position(&mut self) -> io::Result<u64> {
+        self.writer.stream_position()
+    }
+
+    /// Writes all of `buf` at `offset` without changing the current position.
+    ///
+    /// The buffered data is written before `buf`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    pub fn write_all_at(&mut self, offset: u64, buf: &[u8]) -> io::Result<()> {
+        let start = IoMetrics::start(&self.metrics);
+        trace_event!(TRACE, offset, len = buf.len(), "write file at offset");
+        let result = write_all_at(&mut self.writer, offset, buf);
+        IoMetrics::end_write(&mut self.metrics, start, if result.is_ok() { buf.len() } else { 0 });
+        result
+    }
+
+    /// Writes the buffered data and synchronizes the file to the storage.
+    pub fn sync_all(&mut self) -> io::Result<()> {
+        trace_event!(DEBUG, "sync file");
+        io::Write::flush(&mut self.writer)?;
+        self.writer.get_ref().sync_all()
+    }
+
+    /// Starts recording the IO statistics, resetting them.
+    pub fn enable_metrics(&mut self) {
+        self.metrics = Some(IoMetrics::default());
+    }
+
+    /// Returns the IO statistics, or None if they are not enabled.
+    pub fn metrics(&self) -> Option<IoMetrics> {
+        self.metrics
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn get_ref(&self) -> &fs::File {
+        self.writer.get_ref()
+    }
+
+    /// Writes the buffered data and unwraps this `FileWrite`, returning the underlying file.
+    pub fn into_inner(self) -> io::Result<fs::File> {
+        self.writer.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+impl io::Write for FileWrite {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let start = IoMetrics::start(&self.metrics);
+        let result = self.writer.write(buf);
+        IoMetrics::end_write(&mut self.metrics, start, *result.as_ref().unwrap_or(&0));
+        result
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let start = IoMetrics::start(&self.metrics);
+        let result = self.writer.flush();
+        IoMetrics::end_write(&mut self.metrics, start, 0);
+        result
+    }
+}
+
+impl io::Seek for FileWrite {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.writer.seek(pos)
+    }
+}
+
+/// Calculates `checksum` over `len` bytes at `offset`, reading them by `read_at` into `buf` in
+/// chunks.
+pub(crate) fn checksum_at<TChecksum, F>(offset: u64, len: u64, checksum: TChecksum, buf: &mut [u8], mut read_at: F) -> io::Result<TChecksum::Output>
+    where TChecksum: Checksum,
+          F: FnMut(u64, &mut [u8]) -> io::Result<()> {
+    let mut checksum = checksum;
+    let mut processed = 0_u64;
+    while processed < len {
+        let position = offset.checked_add(processed)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "checksum range overflows"))?;
+        let n = (len - processed).min(buf.len() as u64) as usize;
+        read_at(position, &mut buf[..n])?;
+        checksum.update(&buf[..n]);
+        processed += n as u64;
+    }
+    Ok(checksum.digest())
+}
+
+#[cfg(unix)]
+fn read_exact_at(reader: &mut io::BufReader<fs::File>, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+    use std::os::unix::fs::FileExt;
+
+    reader.get_ref().read_exact_at(buf, offset)
+}
+
+#[cfg(not(unix))]
+fn read_exact_at(reader: &mut io::BufReader<fs::File>, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+    use std::io::Read;
+
+    let position = reader.stream_position()?;
+    reader.seek(io::SeekFrom::Start(offset))?;
+    let result = reader.read_exact(buf);
+    reader.seek(io::SeekFrom::Start(position))?;
+    result
+}
+
+#[cfg(unix)]
+fn write_all_at(writer: &mut io::BufWriter<fs::File>, offset: u64, buf: &[u8]) -> io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::FileExt;
+
+    writer.flush()?;
+    writer.get_ref().write_all_at(buf, offset)
+}
+
+#[cfg(not(unix))]
+fn write_all_at(writer: &mut io::BufWriter<fs::File>, offset: u64, buf: &[u8]) -> io::Result<()> {
+    use std::io::Write;
+
+    let position = writer.stream_position()?;
+    writer.seek(io::SeekFrom::Start(offset))?;
+    let result = writer.write_all(buf).and_then(|_| writer.flush());
+    writer.seek(io::SeekFrom::Start(position))?;
+    result
+}
+
+/// Reads exactly `buf.len()` bytes at `offset` of `file`.
+///
+/// On the platforms without positional reads, the file cursor is moved and then restored, so
+/// the read must not overlap with another operation on the handles sharing the cursor.
+#[cfg(unix)]
+pub(crate) fn read_exact_at_file(file: &fs::File, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+    use std::os::unix::fs::FileExt;
+
+    file.read_exact_at(buf, offset)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn read_exact_at_file(file: &fs::File, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+    use std::io::Read;
+
+    let mut file = file;
+    let position = file.stream_position()?;
+    file.seek(io::SeekFrom::Start(offset))?;
+    let result = file.read_exact(buf);
+    file.seek(io::SeekFrom::Start(position))?;
+    result
+}
+
+/// Writes all of `buf` at `offset` of `file`.
+///
+/// On the platforms without positional writes, the file cursor is moved and then restored, so
+/// the write must not overlap with another operation on the handles sharing the cursor.
+#[cfg(unix)]
+pub(crate) fn write_all_at_file(file: &fs::File, offset: u64, buf: &[u8]) -> io::Result<()> {
+    use std::os::unix::fs::FileExt;
+
+    file.write_all_at(buf, offset)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn write_all_at_file(file: &fs::File, offset: u64, buf: &[u8]) -> io::Result<()> {
+    use std::io::Write;
+
+    let mut file = file;
+    let position = file.stream_position()?;
+    file.seek(io::SeekFrom::Start(offset))?;
+    let result = file.write_all(buf);
+    file.seek(io::SeekFrom::Start(position))?;
+    result
+}
+
+/// The access pattern hinted by `advise_file`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Advice {
+    Sequential,
+    Random,
+    WillNeed,
+}
+
+/// Hints the OS the access pattern of `len` bytes at `offset` of `file`, or of the whole file if
+/// `len` is 0.
+#[cfg(target_os = "linux")]
+pub(crate) fn advise_file(file: &fs::File, advice: Advice, offset: u64, len: u64) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let advice = match advice {
+        Advice::Sequential => ::libc::POSIX_FADV_SEQUENTIAL,
+        Advice::Random => ::libc::POSIX_FADV_RANDOM,
+        Advice::WillNeed => ::libc::POSIX_FADV_WILLNEED,
+    };
+    let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "range is too large");
+    let offset = ::libc::off_t::try_from(offset).map_err(|_| too_large())?;
+    let len = ::libc::off_t::try_from(len).map_err(|_| too_large())?;
+    match unsafe { ::libc::posix_fadvise(file.as_raw_fd(), offset, len, advice) } {
+        0 => Ok(()),
+        errno => Err(io::Error::from_raw_os_error(errno)),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn advise_file(_file: &fs::File, _advice: Advice, _offset: u64, _len: u64) -> io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,246 @@
+//! Provides the adapters to read and write the zlib, the raw deflate and the gzip compressed data,
+//! so that the compressed sections of a format are parsed by the same field code.
+//!
+//! `FlateReader` is an `io::Read`, so `BinaryRead` reads the decompressed data from it. A
+//! compressed region of a known length is read by `FlateRead::flate_region`, which leaves the
+//! underlying reader at the end of the region.
+//!
+//! This module is available with the `flate2` feature.
+
+use std::io;
+
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+use flate2::Compression;
+
+/// The container of the deflate compressed data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FlateFormat {
+    /// The zlib format (RFC 1950), with a 2-byte header and an Adler-32 trailer.
+    Zlib,
+    /// The raw deflate data (RFC 1951), without a header.
+    Deflate,
+    /// The gzip format (RFC 1952), with a header and a CRC-32 trailer.
+    Gzip,
+}
+
+enum Decoder<TRead>
+    where TRead: io::Read {
+    Zlib(ZlibDecoder<TRead>),
+    Deflate(DeflateDecoder<TRead>),
+    Gzip(GzDecoder<TRead>),
+}
+
+enum Encoder<TWrite>
+    where TWrite: io::Write {
+    Zlib(ZlibEncoder<TWrite>),
+    Deflate(DeflateEncoder<TWrite>),
+    Gzip(GzEncoder<TWrite>),
+}
+
+/// Wraps a reader of the compressed data, and reads the decompressed data.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::flate::{FlateFormat, FlateReader, FlateWriter};
+///
+/// let mut writer = FlateWriter::new(vec![], FlateFormat::Zlib, 6);
+/// writer.write_integer::<BigEndian, _>(0x1234_u16).unwrap();
+/// writer.write_integer_array::<BigEndian, u32>(&[7; 100]).unwrap();
+/// let compressed = writer.finish().unwrap();
+/// assert!(compressed.len() < 402);
+///
+/// let mut reader = FlateReader::new(&compressed[..], FlateFormat::Zlib);
+/// assert_eq!(0x1234, reader.read_integer::<BigEndian, u16>().unwrap());
+/// assert_eq!(vec![7; 100], reader.read_integer_array::<BigEndian, u32>(100).unwrap());
+///
+/// ```
+///
+pub struct FlateReader<TRead>
+    where TRead: io::Read {
+    decoder: Decoder<TRead>,
+}
+
+impl<TRead> FlateReader<TRead>
+    where TRead: io::Read {
+    /// Creates a new `FlateReader` of the data of `format` read from `reader`.
+    pub fn new(reader: TRead, format: FlateFormat) -> FlateReader<TRead> {
+        let decoder = match format {
+            FlateFormat::Zlib => Decoder::Zlib(ZlibDecoder::new(reader)),
+            FlateFormat::Deflate => Decoder::Deflate(DeflateDecoder::new(reader)),
+            FlateFormat::Gzip => Decoder::Gzip(GzDecoder::new(reader)),
+        };
+        FlateReader { decoder }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &TRead {
+        match self.decoder {
+            Decoder::Zlib(ref d) => d.get_ref(),
+            Decoder::Deflate(ref d) => d.get_ref(),
+            Decoder::Gzip(ref d) => d.get_ref(),
+        }
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Reading from it directly corrupts the decompression.
+    pub fn get_mut(&mut self) -> &mut TRead {
+        match self.decoder {
+            Decoder::Zlib(ref mut d) => d.get_mut(),
+            Decoder::Deflate(ref mut d) => d.get_mut(),
+            Decoder::Gzip(ref mut d) => d.get_mut(),
+        }
+    }
+
+    /// Unwraps this `FlateReader`, returning the underlying reader.
+    ///
+    /// The decoder may have read the underlying reader ahead of the decompressed data.
+    pub fn into_inner(self) -> TRead {
+        match self.decoder {
+            Decoder::Zlib(d) => d.into_inner(),
+            Decoder::Deflate(d) => d.into_inner(),
+            Decoder::Gzip(d) => d.into_inner(),
+        }
+    }
+}
+
+impl<TRead> io::Read for FlateReader<TRead>
+    where TRead: io::Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.decoder {
+            Decoder::Zlib(ref mut d) => d.read(buf),
+            Decoder::Deflate(ref mut d) => d.read(buf),
+            Decoder::Gzip(ref mut d) => d.read(buf),
+        }
+    }
+}
+
+/// Wraps a writer, and writes the data compressed.
+///
+/// `finish` must be called to write the end of the compressed data.
+pub struct FlateWriter<TWrite>
+    where TWrite: io::Write {
+    encoder: Encoder<TWrite>,
+}
+
+impl<TWrite> FlateWriter<TWrite>
+    where TWrite: io::Write {
+    /// Creates a new `FlateWriter` of `format` writing to `writer`, with the compression `level`
+    /// from 0 (none) to 9 (best).
+    pub fn new(writer: TWrite, format: FlateFormat, level: u32) -> FlateWriter<TWrite> {
+        let level = Compression::new(level.min(9));
+        let encoder = match format {
+            FlateFormat::Zlib => Encoder::Zlib(ZlibEncoder::new(writer, level)),
+            FlateFormat::Deflate => Encoder::Deflate(DeflateEncoder::new(writer, level)),
+            FlateFormat::Gzip => Encoder::Gzip(GzEncoder::new(writer, level)),
+        };
+        FlateWriter { encoder }
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &TWrite {
+        match self.encoder {
+            Encoder::Zlib(ref e) => e.get_ref(),
+            Encoder::Deflate(ref e) => e.get_ref(),
+            Encoder::Gzip(ref e) => e.get_ref(),
+        }
+    }
+
+    /// Writes the rest of the compressed data and the trailer, and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the underlying writer), otherwise Err(io::Error).
+    ///
+    pub fn finish(self) -> io::Result<TWrite> {
+        match self.encoder {
+            Encoder::Zlib(e) => e.finish(),
+            Encoder::Deflate(e) => e.finish(),
+            Encoder::Gzip(e) => e.finish(),
+        }
+    }
+}
+
+impl<TWrite> io::Write for FlateWriter<TWrite>
+    where TWrite: io::Write {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.encoder {
+            Encoder::Zlib(ref mut e) => e.write(buf),
+            Encoder::Deflate(ref mut e) => e.write(buf),
+            Encoder::Gzip(ref mut e) => e.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.encoder {
+            Encoder::Zlib(ref mut e) => e.flush(),
+            Encoder::Deflate(ref mut e) => e.flush(),
+            Encoder::Gzip(ref mut e) => e.flush(),
+        }
+    }
+}
+
+/// Provides the features to read the compressed regions.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Write};
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::flate::{FlateFormat, FlateRead, FlateWriter};
+///
+/// let mut writer = FlateWriter::new(vec![], FlateFormat::Deflate, 9);
+/// writer.write_all(b"compressed section").unwrap();
+/// let compressed = writer.finish().unwrap();
+///
+/// // A chunk of a 32-bit length, the compressed data, and a trailing field.
+/// let mut data = (compressed.len() as u32).to_be_bytes().to_vec();
+/// data.extend(&compressed);
+/// data.extend(&[0xAB, 0xCD]);
+///
+/// let mut reader = io::Cursor::new(data);
+/// let len = reader.read_integer::<BigEndian, u32>().unwrap();
+/// let section = reader.flate_region(len as u64, FlateFormat::Deflate).read_byte_array(18).unwrap();
+/// assert_eq!(b"compressed section".to_vec(), section);
+/// assert_eq!(0xABCD, reader.read_integer::<BigEndian, u16>().unwrap());
+///
+/// ```
+///
+pub trait FlateRead: io::Read {
+    /// Returns a reader decompressing the next `compressed_len` bytes of `format`.
+    ///
+    /// The reader reads at most `compressed_len` bytes from this reader. Read the decompressed
+    /// data to the end to leave this reader at the end of the region.
+    fn flate_region(&mut self, compressed_len: u64, format: FlateFormat) -> FlateReader<io::Take<&mut Self>>
+        where Self: Sized {
+        FlateReader::new(io::Read::take(self, compressed_len), format)
+    }
+
+    /// Reads the next `compressed_len` bytes of `format`, and returns the decompressed data.
+    ///
+    /// The bytes of the region left after the compressed data are skipped.
+    ///
+    /// # Errors
+    ///
+    /// If the compressed data is broken, returns Err(io::Error) of `InvalidInput` or
+    /// `InvalidData`. If reading fails, returns Err(io::Error).
+    ///
+    fn read_flate_region(&mut self, compressed_len: u64, format: FlateFormat) -> io::Result<Vec<u8>>
+        where Self: Sized {
+        let mut region = self.flate_region(compressed_len, format);
+        let mut data = vec![];
+        io::Read::read_to_end(&mut region, &mut data)?;
+        io::copy(region.get_mut(), &mut io::sink())?;
+        Ok(data)
+    }
+}
+
+impl<T> FlateRead for T
+    where T: io::Read {}
@@ -0,0 +1,272 @@
+//! Provides the Fletcher-16 and Fletcher-32 checksums.
+
+use std::marker::PhantomData;
+
+use checksum::{Checksum, ChecksumReader, ChecksumWriter};
+use endian::Endian;
+
+/// The number of elements summed before the sums are reduced.
+const CHUNK_SIZE: usize = 4096;
+
+/// Calculates the Fletcher-16 incrementally.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::fletcher::Fletcher16;
+///
+/// let mut fletcher = Fletcher16::new();
+/// fletcher.update(b"abc");
+/// fletcher.update(b"de");
+/// assert_eq!(0xC8F0, fletcher.digest());
+///
+/// ```
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fletcher16 {
+    sum1: u32,
+    sum2: u32,
+}
+
+impl Fletcher16 {
+    /// Creates a new `Fletcher16` with no data.
+    pub fn new() -> Fletcher16 {
+        Fletcher16 { sum1: 0, sum2: 0 }
+    }
+
+    /// Updates the checksum with `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(CHUNK_SIZE) {
+            for &x in chunk {
+                self.sum1 += x as u32;
+                self.sum2 += self.sum1;
+            }
+            self.sum1 %= 255;
+            self.sum2 %= 255;
+        }
+    }
+
+    /// Returns the checksum of the data so far.
+    pub fn digest(&self) -> u16 {
+        ((self.sum2 << 8) | self.sum1) as u16
+    }
+
+    /// Resets the checksum to the initial state.
+    pub fn reset(&mut self) {
+        *self = Fletcher16::new();
+    }
+}
+
+impl Default for Fletcher16 {
+    fn default() -> Fletcher16 {
+        Fletcher16::new()
+    }
+}
+
+impl Checksum for Fletcher16 {
+    type Output = u16;
+
+    fn update(&mut self, bytes: &[u8]) {
+        Fletcher16::update(self, bytes)
+    }
+
+    fn digest(&self) -> u16 {
+        Fletcher16::digest(self)
+    }
+
+    fn reset(&mut self) {
+        Fletcher16::reset(self)
+    }
+}
+
+/// Calculates the Fletcher-32 incrementally.
+///
+/// The data is summed as 16-bit words in `TEndian`. An odd trailing byte is padded with zero.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::endian::{BigEndian, LittleEndian};
+/// use mm_binary_io::fletcher::Fletcher32;
+///
+/// let mut fletcher = Fletcher32::<LittleEndian>::new();
+/// fletcher.update(b"abc");
+/// fletcher.update(b"de");
+/// assert_eq!(0xF04FC729, fletcher.digest());
+///
+/// fletcher.update(b"f");
+/// assert_eq!(0x56502D2A, fletcher.digest());
+///
+/// let mut fletcher = Fletcher32::<BigEndian>::new();
+/// fletcher.update(b"abcdef");
+/// assert_eq!(0x50562A2D, fletcher.digest());
+///
+/// ```
+///
+/// Calculates the checksum while writing.
+///
+/// ```
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::fletcher::Fletcher32Writer;
+///
+/// let mut writer = Fletcher32Writer::<_, LittleEndian>::new(vec![]);
+/// writer.write_integer::<LittleEndian, _>(0x6261_u16).unwrap();
+/// writer.write_integer::<LittleEndian, _>(0x6463_u16).unwrap();
+/// writer.write_integer::<LittleEndian, _>(0x65_u8).unwrap();
+/// assert_eq!(0xF04FC729, writer.digest());
+///
+/// ```
+///
+pub struct Fletcher32<TEndian>
+    where TEndian: Endian {
+    sum1: u64,
+    sum2: u64,
+    pending: Option<u8>,
+    _endian: PhantomData<TEndian>,
+}
+
+impl<TEndian> Fletcher32<TEndian>
+    where TEndian: Endian {
+    /// Creates a new `Fletcher32` with no data.
+    pub fn new() -> Fletcher32<TEndian> {
+        Fletcher32 {
+            sum1: 0,
+            sum2: 0,
+            pending: None,
+            _endian: PhantomData,
+        }
+    }
+
+    /// Updates the checksum with `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        let mut bytes = bytes;
+        if let Some(first) = self.pending.take() {
+            if bytes.is_empty() {
+                self.pending = Some(first);
+                return;
+            }
+            self.add_word(TEndian::u16_from_bytes(&[first, bytes[0]]));
+            bytes = &bytes[1..];
+        }
+        for chunk in bytes.chunks(CHUNK_SIZE * 2) {
+            let mut words = chunk.chunks_exact(2);
+            for word in &mut words {
+                self.sum1 += TEndian::u16_from_bytes(word) as u64;
+                self.sum2 += self.sum1;
+            }
+            self.sum1 %= 65535;
+            self.sum2 %= 65535;
+            if let [last] = *words.remainder() {
+                self.pending = Some(last);
+            }
+        }
+    }
+
+    /// Returns the checksum of the data so far.
+    pub fn digest(&self) -> u32 {
+        let mut sum1 = self.sum1;
+        let mut sum2 = self.sum2;
+        if let Some(last) = self.pending {
+            sum1 = (sum1 + TEndian::u16_from_bytes(&[last, 0]) as u64) % 65535;
+            sum2 = (sum2 + sum1) % 65535;
+        }
+        ((sum2 << 16) | sum1) as u32
+    }
+
+    /// Resets the checksum to the initial state.
+    pub fn reset(&mut self) {
+        self.sum1 = 0;
+        self.sum2 = 0;
+        self.pending = None;
+    }
+
+    fn add_word(&mut self, word: u16) {
+        self.sum1 = (self.sum1 + word as u64) % 65535;
+        self.sum2 = (self.sum2 + self.sum1) % 65535;
+    }
+}
+
+impl<TEndian> Clone for Fletcher32<TEndian>
+    where TEndian: Endian {
+    fn clone(&self) -> Fletcher32<TEndian> {
+        Fletcher32 {
+            sum1: self.sum1,
+            sum2: self.sum2,
+            pending: self.pending,
+            _endian: PhantomData,
+        }
+    }
+}
+
+impl<TEndian> Default for Fletcher32<TEndian>
+    where TEndian: Endian {
+    fn default() -> Fletcher32<TEndian> {
+        Fletcher32::new()
+    }
+}
+
+impl<TEndian> Checksum for Fletcher32<TEndian>
+    where TEndian: Endian {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        Fletcher32::update(self, bytes)
+    }
+
+    fn digest(&self) -> u32 {
+        Fletcher32::digest(self)
+    }
+
+    fn reset(&mut self) {
+        Fletcher32::reset(self)
+    }
+}
+
+/// The reader which calculates the Fletcher-16 of the bytes read.
+pub type Fletcher16Reader<TRead> = ChecksumReader<TRead, Fletcher16>;
+
+/// The writer which calculates the Fletcher-16 of the bytes written.
+pub type Fletcher16Writer<TWrite> = ChecksumWriter<TWrite, Fletcher16>;
+
+/// The reader which calculates the Fletcher-32 of the bytes read.
+pub type Fletcher32Reader<TRead, TEndian> = ChecksumReader<TRead, Fletcher32<TEndian>>;
+
+/// The writer which calculates the Fletcher-32 of the bytes written.
+pub type Fletcher32Writer<TWrite, TEndian> = ChecksumWriter<TWrite, Fletcher32<TEndian>>;
+
+/// Calculates the Fletcher-16 of `bytes`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::fletcher;
+///
+/// assert_eq!(0x2057, fletcher::fletcher16(b"abcdef"));
+///
+/// ```
+///
+pub fn fletcher16(bytes: &[u8]) -> u16 {
+    let mut fletcher = Fletcher16::new();
+    fletcher.update(bytes);
+    fletcher.digest()
+}
+
+/// Calculates the Fletcher-32 of `bytes` summed as 16-bit words in `TEndian`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::fletcher;
+///
+/// assert_eq!(0xEBE19591, fletcher::fletcher32::<LittleEndian>(b"abcdefgh"));
+///
+/// ```
+///
+pub fn fletcher32<TEndian>(bytes: &[u8]) -> u32
+    where TEndian: Endian {
+    let mut fletcher = Fletcher32::<TEndian>::new();
+    fletcher.update(bytes);
+    fletcher.digest()
+}
@@ -0,0 +1,167 @@
+//! Provides the codec of the length-prefixed frames over a stream transport such as `TcpStream`.
+//!
+//! A frame is a `TLen` length prefix in `TEndian`, the payload, and optionally the CRC-32 of the
+//! payload. The length is checked against the maximum before the payload is allocated, so that a
+//! broken or hostile peer can not exhaust the memory.
+
+use std::convert::TryFrom;
+use std::io;
+use std::marker::PhantomData;
+
+use binary_read::BinaryRead;
+use checksum_frame::ChecksumMismatch;
+use crc32;
+use endian::Endian;
+use read_integer::ReadInteger;
+use write_integer::WriteInteger;
+
+/// Reads and writes the length-prefixed frames over `TStream`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::frame_codec::FrameCodec;
+///
+/// let mut codec = FrameCodec::<_, BigEndian, u16>::new(io::Cursor::new(vec![]), 1024).with_crc32();
+/// codec.write_frame(b"hello").unwrap();
+/// codec.write_frame(b"").unwrap();
+/// assert_eq!(vec![0x00, 0x05], codec.get_ref().get_ref()[..2].to_vec());
+/// assert_eq!(2 + 5 + 4 + 2 + 4, codec.get_ref().get_ref().len());
+///
+/// codec.get_mut().set_position(0);
+/// assert_eq!(Some(b"hello".to_vec()), codec.try_read_frame().unwrap());
+/// assert_eq!(Vec::<u8>::new(), codec.read_frame().unwrap());
+/// assert_eq!(None, codec.try_read_frame().unwrap());
+///
+/// // The frame longer than the maximum is rejected before its payload is read.
+/// let mut codec = FrameCodec::<_, BigEndian, u16>::new(&[0xFF_u8, 0xFF][..], 1024);
+/// assert_eq!(io::ErrorKind::InvalidData, codec.read_frame().unwrap_err().kind());
+///
+/// ```
+///
+pub struct FrameCodec<TStream, TEndian, TLen>
+    where TEndian: Endian {
+    stream: TStream,
+    max_len: usize,
+    crc32: bool,
+    _marker: PhantomData<(TEndian, TLen)>,
+}
+
+impl<TStream, TEndian, TLen> FrameCodec<TStream, TEndian, TLen>
+    where TEndian: Endian,
+          TLen: ReadInteger<OutputType=TLen> + WriteInteger + Into<u64> + TryFrom<u64> {
+    /// Creates a new `FrameCodec` of the frames without a CRC.
+    ///
+    /// # Arguments
+    ///
+    /// * max_len - the maximum payload length accepted by `read_frame`.
+    ///
+    pub fn new(stream: TStream, max_len: usize) -> FrameCodec<TStream, TEndian, TLen> {
+        FrameCodec {
+            stream,
+            max_len,
+            crc32: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Appends the CRC-32 of the payload in `TEndian` to the frames, and verifies it on reading.
+    pub fn with_crc32(mut self) -> FrameCodec<TStream, TEndian, TLen> {
+        self.crc32 = true;
+        self
+    }
+
+    /// Returns the maximum payload length accepted by `read_frame`.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Gets a reference to the underlying stream.
+    pub fn get_ref(&self) -> &TStream {
+        &self.stream
+    }
+
+    /// Gets a mutable reference to the underlying stream.
+    ///
+    /// Reading or writing it directly breaks the framing, unless it is done between the frames.
+    pub fn get_mut(&mut self) -> &mut TStream {
+        &mut self.stream
+    }
+
+    /// Unwraps this `FrameCodec`, returning the underlying stream.
+    pub fn into_inner(self) -> TStream {
+        self.stream
+    }
+
+    /// Reads a frame and returns the payload.
+    ///
+    /// # Errors
+    ///
+    /// If the length exceeds the maximum, returns Err(io::Error) of `InvalidData`.
+    /// If the CRC does not match, returns Err(io::Error) of `InvalidData` whose inner error is
+    /// `ChecksumMismatch<u32>`. If reading fails, returns Err(io::Error).
+    ///
+    pub fn read_frame(&mut self) -> io::Result<Vec<u8>>
+        where TStream: io::Read {
+        let len = self.stream.read_integer::<TEndian, TLen>()?;
+        self.read_payload(len.into())
+    }
+
+    /// Reads a frame and returns the payload, or returns None if the stream ends cleanly before
+    /// the frame, such as when the peer closes the connection.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the payload or None), otherwise Err(io::Error) as
+    /// `read_frame`.
+    ///
+    pub fn try_read_frame(&mut self) -> io::Result<Option<Vec<u8>>>
+        where TStream: io::Read {
+        match self.stream.try_read_integer::<TEndian, TLen>()? {
+            Some(len) => self.read_payload(len.into()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Writes a frame of `payload`.
+    ///
+    /// The frame is written by one `write_all`, so that a transport without buffering does not
+    /// send the prefix in a packet of its own.
+    ///
+    /// # Errors
+    ///
+    /// If the payload length does not fit in `TLen`, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    pub fn write_frame(&mut self, payload: &[u8]) -> io::Result<()>
+        where TStream: io::Write {
+        let len = TLen::try_from(payload.len() as u64)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame is too long"))?;
+        let mut frame = Vec::with_capacity(8 + payload.len() + 4);
+        len.write_integer::<TEndian>(&mut frame)?;
+        frame.extend_from_slice(payload);
+        if self.crc32 {
+            crc32::crc32(payload).write_integer::<TEndian>(&mut frame)?;
+        }
+        self.stream.write_all(&frame)
+    }
+
+    fn read_payload(&mut self, len: u64) -> io::Result<Vec<u8>>
+        where TStream: io::Read {
+        if len > self.max_len as u64 {
+            trace_event!(DEBUG, len, max_len = self.max_len, "frame is too long");
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "frame is too long"));
+        }
+        let payload = self.stream.read_byte_array(len as usize)?;
+        if self.crc32 {
+            let expected = self.stream.read_integer::<TEndian, u32>()?;
+            let actual = crc32::crc32(&payload);
+            if actual != expected {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, ChecksumMismatch { expected, actual }));
+            }
+        }
+        Ok(payload)
+    }
+}
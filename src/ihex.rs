@@ -0,0 +1,139 @@
+//! Provides the features to read and write Intel HEX files.
+//!
+//! The payload is exposed as a `MemoryImage`. The 32-bit addresses are supported by the
+//! extended segment (02) and the extended linear (04) address records.
+
+use std::io;
+
+use memory_image::{self, MemoryImage};
+
+/// The number of data bytes in a data record written by `write`.
+pub const RECORD_DATA_LEN: usize = 16;
+
+const INVALID_RECORD: &str = "invalid Intel HEX record";
+
+/// Reads an Intel HEX file.
+///
+/// The reading stops at the end-of-file (01) record. The start address (03, 05) records are
+/// stored as the entry point.
+///
+/// # Errors
+///
+/// If a record is broken, returns Err(io::Error) of `InvalidData`. If the checksum of a record does
+/// not match, the inner error is `ChecksumMismatch<u8>`.
+/// If the end-of-file record is missing, returns Err(io::Error) of `UnexpectedEof`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::ihex;
+/// use mm_binary_io::memory_image::Segment;
+///
+/// let text = ":020000040001F9\n:0400100001020304E2\n:04000005000100FFF7\n:00000001FF\n";
+/// let image = ihex::read(io::Cursor::new(text)).unwrap();
+///
+/// assert_eq!(&[Segment::new(0x0001_0010, vec![1, 2, 3, 4])], image.segments());
+/// assert_eq!(Some(0x0001_00FF), image.entry_point());
+///
+/// let mut written = vec![];
+/// ihex::write(&mut written, &image).unwrap();
+/// assert_eq!(text.as_bytes(), &written[..]);
+///
+/// ```
+///
+pub fn read<TRead>(reader: TRead) -> io::Result<MemoryImage>
+    where TRead: io::BufRead {
+    let mut image = MemoryImage::new();
+    let mut base = 0_u32;
+    let mut ended = false;
+    memory_image::read_records(reader, |record| {
+        if record[0] != b':' {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, INVALID_RECORD));
+        }
+        let bytes = memory_image::decode_hex(&record[1..], INVALID_RECORD)?;
+        if bytes.len() < 5 || bytes.len() != bytes[0] as usize + 5 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, INVALID_RECORD));
+        }
+        let (body, expected) = bytes.split_at(bytes.len() - 1);
+        let actual = checksum(body);
+        if actual != expected[0] {
+            return Err(memory_image::checksum_mismatch(expected[0], actual));
+        }
+
+        let offset = u16::from_be_bytes([body[1], body[2]]);
+        let data = &body[4..];
+        match (body[3], data.len()) {
+            (0x00, _) => {
+                let address = base.checked_add(offset as u32)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, INVALID_RECORD))?;
+                image.write_at(address, data).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, INVALID_RECORD))?;
+            }
+            (0x01, _) => {
+                ended = true;
+                return Ok(false);
+            }
+            (0x02, 2) => base = (u16::from_be_bytes([data[0], data[1]]) as u32) << 4,
+            (0x03, 4) => {
+                let segment = u16::from_be_bytes([data[0], data[1]]) as u32;
+                let offset = u16::from_be_bytes([data[2], data[3]]) as u32;
+                image.set_entry_point(Some((segment << 4) + offset));
+            }
+            (0x04, 2) => base = (u16::from_be_bytes([data[0], data[1]]) as u32) << 16,
+            (0x05, 4) => image.set_entry_point(Some(u32::from_be_bytes([data[0], data[1], data[2], data[3]]))),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, INVALID_RECORD)),
+        }
+        Ok(true)
+    })?;
+    if !ended {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Intel HEX end-of-file record is missing"));
+    }
+    Ok(image)
+}
+
+/// Writes `image` as an Intel HEX file.
+///
+/// The addresses above 64 KiB are written with the extended linear address (04) records, and
+/// the entry point is written as the start linear address (05) record.
+///
+pub fn write<TWrite>(writer: &mut TWrite, image: &MemoryImage) -> io::Result<()>
+    where TWrite: io::Write {
+    let mut upper = 0_u16;
+    for segment in image.segments() {
+        let mut address = segment.address;
+        let mut data = &segment.data[..];
+        while !data.is_empty() {
+            if (address >> 16) as u16 != upper {
+                upper = (address >> 16) as u16;
+                write_record(writer, 0x04, 0, &upper.to_be_bytes())?;
+            }
+            let room = 0x10000 - (address & 0xFFFF) as usize;
+            let n = RECORD_DATA_LEN.min(room).min(data.len());
+            write_record(writer, 0x00, address as u16, &data[..n])?;
+            address = address.wrapping_add(n as u32);
+            data = &data[n..];
+        }
+    }
+    if let Some(entry_point) = image.entry_point() {
+        write_record(writer, 0x05, 0, &entry_point.to_be_bytes())?;
+    }
+    write_record(writer, 0x01, 0, &[])
+}
+
+fn write_record<TWrite>(writer: &mut TWrite, record_type: u8, offset: u16, data: &[u8]) -> io::Result<()>
+    where TWrite: io::Write {
+    let mut body = vec![data.len() as u8];
+    body.extend_from_slice(&offset.to_be_bytes());
+    body.push(record_type);
+    body.extend_from_slice(data);
+    body.push(checksum(&body));
+
+    let mut line = String::from(":");
+    memory_image::encode_hex(&body, &mut line);
+    line.push('\n');
+    writer.write_all(line.as_bytes())
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0_u8, |sum, &b| sum.wrapping_add(b)).wrapping_neg()
+}
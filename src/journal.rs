@@ -0,0 +1,187 @@
+//! Provides a journal (write-ahead log) to make in-place file updates crash-consistent.
+//!
+//! The intended writes are first recorded in a separate journal file and synchronized to the disk,
+//! then applied to the target file. If the process crashes while applying them, `Journal::recover`
+//! replays the journal on the next start. If it crashes before the journal is complete,
+//! the target file has not been modified and the journal is discarded.
+//!
+//! The journal file is a `record_log` whose records are a little-endian u64 offset followed by
+//! the bytes to write, terminated by an empty commit record.
+
+use std::fs;
+use std::io;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use binary_write::BinaryWrite;
+use endian::{Endian, LittleEndian};
+use record_log::{RecordLogReader, RecordLogWriter};
+
+/// Records intended writes to a file and applies them crash-consistently.
+///
+/// # Examples
+///
+/// ```
+/// use std::env;
+/// use std::fs;
+/// use std::io::{Read, Write};
+/// use mm_binary_io::journal::Journal;
+///
+/// let dir = env::temp_dir();
+/// let target_path = dir.join("mm_binary_io_journal_example.bin");
+/// let journal_path = dir.join("mm_binary_io_journal_example.journal");
+///
+/// fs::File::create(&target_path).unwrap().write_all(&[0_u8; 8]).unwrap();
+/// let mut target = fs::OpenOptions::new().read(true).write(true).open(&target_path).unwrap();
+///
+/// // Replays the journal left by a previous crash, if any.
+/// Journal::recover(&journal_path, &mut target).unwrap();
+///
+/// let mut journal = Journal::new(&journal_path);
+/// journal.write_at(0, &[0x12, 0x34]);
+/// journal.write_at(6, &[0x56, 0x78]);
+/// journal.commit(&mut target).unwrap();
+///
+/// let mut result = vec![];
+/// fs::File::open(&target_path).unwrap().read_to_end(&mut result).unwrap();
+/// assert_eq!(vec![0x12, 0x34, 0, 0, 0, 0, 0x56, 0x78], result);
+/// assert!(!journal_path.exists());
+///
+/// fs::remove_file(&target_path).unwrap();
+///
+/// ```
+///
+pub struct Journal {
+    path: PathBuf,
+    entries: Vec<(u64, Vec<u8>)>,
+}
+
+impl Journal {
+    /// Creates a new empty `Journal` which will be stored at `path`.
+    pub fn new<TPath>(path: TPath) -> Journal
+        where TPath: AsRef<Path> {
+        Journal {
+            path: path.as_ref().to_path_buf(),
+            entries: vec![],
+        }
+    }
+
+    /// Records the intention to write `bytes` at `offset` of the target file.
+    ///
+    /// Nothing is written until `commit` is called.
+    pub fn write_at(&mut self, offset: u64, bytes: &[u8]) {
+        self.entries.push((offset, bytes.to_vec()));
+    }
+
+    /// Returns the number of the recorded writes.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no write is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Writes and synchronizes the journal, applies the recorded writes to `target`,
+    /// synchronizes `target`, and removes the journal.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    /// If the function fails after the journal was written, `recover` completes the update.
+    ///
+    pub fn commit(&mut self, target: &mut fs::File) -> io::Result<()> {
+        {
+            let file = fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&self.path)?;
+            let mut writer = RecordLogWriter::new(io::BufWriter::new(file));
+            let mut record = vec![];
+            for &(offset, ref bytes) in &self.entries {
+                record.clear();
+                record.write_integer::<LittleEndian, _>(offset)?;
+                record.extend_from_slice(bytes);
+                writer.append(&record)?;
+            }
+            writer.append(&[])?;
+            writer.flush()?;
+            writer.get_ref().get_ref().sync_all()?;
+        }
+        sync_parent(&self.path)?;
+
+        apply(&self.entries, target)?;
+        self.entries.clear();
+        fs::remove_file(&self.path)?;
+        sync_parent(&self.path)
+    }
+
+    /// Replays the journal at `path` to `target` if it is complete, and removes it.
+    ///
+    /// An incomplete journal means the crash happened before `target` was modified,
+    /// so it is removed without replaying.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(true if the journal was replayed), otherwise Err(io::Error).
+    ///
+    pub fn recover<TPath>(path: TPath, target: &mut fs::File) -> io::Result<bool>
+        where TPath: AsRef<Path> {
+        let path = path.as_ref();
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        let mut entries = vec![];
+        let mut committed = false;
+        for record in RecordLogReader::new(io::BufReader::new(file)) {
+            let record = match record {
+                Ok(record) => record,
+                // A damaged record can only be the result of an interrupted journal write.
+                Err(ref e) if e.kind() == io::ErrorKind::InvalidData => break,
+                Err(e) => return Err(e),
+            };
+            if record.is_empty() {
+                committed = true;
+                break;
+            }
+            if record.len() < 8 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "journal entry is too short"));
+            }
+            entries.push((LittleEndian::u64_from_bytes(&record[0..8]), record[8..].to_vec()));
+        }
+
+        if committed {
+            apply(&entries, target)?;
+        }
+        fs::remove_file(path)?;
+        sync_parent(path)?;
+        Ok(committed)
+    }
+}
+
+fn apply(entries: &[(u64, Vec<u8>)], target: &mut fs::File) -> io::Result<()> {
+    for &(offset, ref bytes) in entries {
+        target.seek(SeekFrom::Start(offset))?;
+        target.write_all(bytes)?;
+    }
+    target.sync_all()
+}
+
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => fs::File::open(".")?.sync_all(),
+        Some(parent) => fs::File::open(parent)?.sync_all(),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
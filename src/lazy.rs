@@ -0,0 +1,135 @@
+//! Provides the fields which are skipped in the first pass over a seekable stream, and read only
+//! when accessed, so that the loaders of the large files read only the sections they need.
+
+use std::io;
+
+use offset::{FollowRead, Offset};
+
+/// A field of `T` whose offset and byte count are recorded, and which is read on demand.
+///
+/// The value is read from a reader limited to the byte count, so that a decoder cannot read
+/// past the field.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::lazy::Lazy;
+///
+/// let data = b"\x00\x05large\x00\x02ok";
+/// let mut reader = io::Cursor::new(&data[..]);
+///
+/// // The first pass records the fields, reading only their lengths.
+/// let len = reader.read_integer::<BigEndian, u16>().unwrap();
+/// let mut skipped = Lazy::<String>::skip(&mut reader, len as u64).unwrap();
+/// let len = reader.read_integer::<BigEndian, u16>().unwrap();
+/// let small = Lazy::<String>::skip(&mut reader, len as u64).unwrap();
+/// assert_eq!((9, 2), (small.offset(), small.len()));
+///
+/// let decode = |r: &mut io::Take<&mut io::Cursor<&[u8]>>| r.read_string(r.limit() as usize);
+/// assert_eq!("ok", small.load(&mut reader, decode).unwrap());
+///
+/// // `get` reads the value once.
+/// assert!(!skipped.is_loaded());
+/// assert_eq!("large", skipped.get(&mut reader, decode).unwrap());
+/// assert!(skipped.is_loaded());
+///
+/// // The position is not changed by the reads.
+/// assert_eq!(11, reader.position());
+///
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct Lazy<T> {
+    offset: u64,
+    len: u64,
+    value: Option<T>,
+}
+
+impl<T> Lazy<T> {
+    /// Creates a new `Lazy` of the `len` bytes at `offset`.
+    pub fn new(offset: u64, len: u64) -> Lazy<T> {
+        Lazy { offset, len, value: None }
+    }
+
+    /// Records the `len` bytes at the current position of `reader`, and seeks past them.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(Lazy), otherwise Err(io::Error).
+    ///
+    pub fn skip<TRead>(reader: &mut TRead, len: u64) -> io::Result<Lazy<T>>
+        where TRead: io::Seek {
+        let offset = reader.stream_position()?;
+        let end = offset.checked_add(len)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "field end overflows"))?;
+        reader.seek(io::SeekFrom::Start(end))?;
+        Ok(Lazy::new(offset, len))
+    }
+
+    /// Returns the offset from the stream head.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the byte count.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if the field is of 0 bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if the value has been read by `get`.
+    pub fn is_loaded(&self) -> bool {
+        self.value.is_some()
+    }
+
+    /// Reads the value by `decode`, without keeping it.
+    ///
+    /// The position of `reader` is restored, even if `decode` fails.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(T), otherwise Err(io::Error).
+    ///
+    pub fn load<TRead, F>(&self, reader: &mut TRead, decode: F) -> io::Result<T>
+        where TRead: io::Read + io::Seek,
+              F: FnOnce(&mut io::Take<&mut TRead>) -> io::Result<T> {
+        trace_event!(TRACE, offset = self.offset, len = self.len, "load lazy field");
+        let len = self.len;
+        reader.follow(Offset::new(self.offset), |r| decode(&mut io::Read::take(r, len)))
+    }
+
+    /// Returns the value, reading it by `decode` on the first call.
+    ///
+    /// The position of `reader` is restored, even if `decode` fails.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the value), otherwise Err(io::Error), after which the
+    /// value is read again on the next call.
+    ///
+    pub fn get<TRead, F>(&mut self, reader: &mut TRead, decode: F) -> io::Result<&T>
+        where TRead: io::Read + io::Seek,
+              F: FnOnce(&mut io::Take<&mut TRead>) -> io::Result<T> {
+        if self.value.is_none() {
+            self.value = Some(self.load(reader, decode)?);
+        }
+        Ok(self.value.as_ref().unwrap())
+    }
+
+    /// Returns the value read by `get`, or None.
+    pub fn loaded(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    /// Unwraps this `Lazy`, returning the value read by `get`, or None.
+    pub fn into_loaded(self) -> Option<T> {
+        self.value
+    }
+}
@@ -1,9 +1,267 @@
 //! `mm_binary_io` provides the features for binary I/O.
+//!
+//! Without the default `std` feature, the crate is `no_std` and provides only `byte_view`,
+//! `endian`, `from_bytes`, `to_bytes`, `slice`, `typed_slice` and the slice encoding of `varint`,
+//! which need neither `std` nor `alloc`. The `embedded-io` feature adds `embedded`, whose
+//! methods allocating the arrays and the strings need the `alloc` feature.
+//!
+//! The `nightly` feature reads byte arrays into uninitialized buffers by `read_buf`, which needs
+//! a nightly compiler.
+//!
+//! The `tracing` feature emits the `tracing` spans and events of the frame reads, the section
+//! parses (PNG chunks, ISO BMFF boxes, TLV records, tar and ZIP headers, recovered records) and the
+//! file operations, with their offsets and sizes.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "nightly", feature(read_buf, core_io_borrowed_buf))]
+
+#[cfg(not(feature = "std"))]
+extern crate core as std;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "bytemuck")]
+extern crate bytemuck;
+#[cfg(feature = "tokio-codec")]
+extern crate bytes;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "cipher")]
+extern crate cipher as cipher_crate;
+#[cfg(feature = "digest")]
+extern crate digest as digest_crate;
+#[cfg(feature = "embedded-io")]
+extern crate embedded_io;
+#[cfg(feature = "flate2")]
+extern crate flate2;
+#[cfg(feature = "futures-io")]
+extern crate futures_io;
+#[cfg(feature = "heapless")]
+extern crate heapless;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+extern crate io_uring;
+#[cfg(feature = "wasm")]
+extern crate js_sys;
+#[cfg(target_os = "linux")]
+extern crate libc;
+#[cfg(feature = "lz4")]
+extern crate lz4_flex;
+#[cfg(feature = "test-helpers")]
+extern crate proptest;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "serde_binary")]
+extern crate serde;
+#[cfg(feature = "time")]
+extern crate time;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "tokio-codec")]
+extern crate tokio_util;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "xxhash")]
+extern crate xxhash_rust;
+#[cfg(feature = "xz")]
+extern crate xz2;
+#[cfg(feature = "zstd")]
+extern crate zstd as zstd_crate;
+
+/// Emits a `tracing` event of `$level` with the `tracing` feature, and nothing without it.
+#[cfg(feature = "std")]
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::event!(::tracing::Level::$level, $($arg)+);
+    };
+}
+
+/// Enters a `tracing` span of `$level` with the `tracing` feature until the end of the block,
+/// and does nothing without it.
+#[cfg(feature = "std")]
+macro_rules! trace_span {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        let _guard = ::tracing::span!(::tracing::Level::$level, $($arg)+).entered();
+    };
+}
+
+#[cfg(feature = "std")]
+pub mod adler32;
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+#[macro_use]
+mod async_common;
+#[cfg(feature = "tokio")]
+pub mod async_file;
+#[cfg(feature = "futures-io")]
+pub mod async_futures;
+#[cfg(feature = "tokio")]
+pub mod async_tokio;
+#[cfg(feature = "std")]
+pub mod auto_decompress;
+#[cfg(feature = "std")]
+pub mod ber;
+#[cfg(feature = "std")]
+pub mod binary_builder;
+#[cfg(feature = "std")]
+pub mod binary_parser;
+#[cfg(feature = "std")]
 pub mod binary_read;
+#[cfg(feature = "std")]
+pub mod binary_serializer;
+#[cfg(feature = "std")]
 pub mod binary_write;
+#[cfg(feature = "std")]
+pub mod bmff;
+#[cfg(feature = "std")]
+pub mod buffered;
+pub mod byte_view;
+#[cfg(feature = "byteorder_compat")]
+pub mod byteorder_compat;
+#[cfg(feature = "std")]
+pub mod cbor;
+#[cfg(feature = "std")]
+pub mod channels;
+#[cfg(feature = "std")]
+pub mod checksum;
+#[cfg(feature = "std")]
+pub mod checksum_frame;
+#[cfg(feature = "cipher")]
+pub mod cipher;
+#[cfg(feature = "tokio-codec")]
+pub mod codec;
+#[cfg(feature = "std")]
+pub mod cobs;
+#[cfg(feature = "std")]
+pub mod copy;
+#[cfg(feature = "std")]
+pub mod crc16;
+#[cfg(feature = "std")]
+pub mod crc32;
+#[cfg(feature = "std")]
+pub mod crc64;
+#[cfg(feature = "std")]
+mod delimited;
+#[cfg(feature = "digest")]
+pub mod digest;
+#[cfg(feature = "embedded-io")]
+pub mod embedded;
 pub mod endian;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod file;
+#[cfg(feature = "flate2")]
+pub mod flate;
+#[cfg(feature = "std")]
+pub mod frame_codec;
+#[cfg(feature = "std")]
+pub mod fletcher;
 pub mod from_bytes;
+#[cfg(feature = "std")]
+pub mod ihex;
+#[cfg(feature = "std")]
+pub mod journal;
+#[cfg(feature = "std")]
+pub mod lazy;
+#[cfg(feature = "lz4")]
+pub mod lz4;
+#[cfg(feature = "std")]
+pub mod memory_image;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+#[cfg(feature = "std")]
+pub mod net;
+#[cfg(feature = "std")]
+pub mod offset;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+#[cfg(feature = "std")]
+pub mod packet;
+#[cfg(feature = "std")]
+pub mod patch;
+#[cfg(feature = "std")]
+pub mod pcm;
+#[cfg(feature = "std")]
+pub mod pixels;
+#[cfg(feature = "std")]
+pub mod png_chunk;
+#[cfg(feature = "bytemuck")]
+pub mod pod;
+#[cfg(feature = "std")]
+pub mod prefetch;
+#[cfg(feature = "std")]
+pub mod progress;
+#[cfg(feature = "std")]
+pub mod push_parser;
+#[cfg(feature = "std")]
 pub mod read_integer;
+#[cfg(feature = "std")]
+pub mod read_tuple;
+#[cfg(feature = "std")]
+pub mod record_file;
+#[cfg(feature = "std")]
+pub mod recovery;
+#[cfg(feature = "std")]
+pub mod record_log;
+#[cfg(feature = "std")]
+pub mod retry;
+#[cfg(feature = "std")]
+pub mod ring_buffer;
+#[cfg(feature = "std")]
+pub mod rolling;
+#[cfg(feature = "std")]
+pub mod savepoint;
+#[cfg(feature = "std")]
+pub mod schema;
+#[cfg(feature = "std")]
+pub mod scratch;
+#[cfg(feature = "std")]
+pub mod search;
+#[cfg(feature = "std")]
+pub mod section_table;
+#[cfg(feature = "serde_binary")]
+pub mod serde_binary;
+pub mod slice;
+#[cfg(feature = "std")]
+pub mod slip;
+#[cfg(feature = "std")]
+pub mod srec;
+#[cfg(feature = "std")]
+pub mod struct_array;
+#[cfg(feature = "std")]
+pub mod synced_reader;
+#[cfg(feature = "std")]
+pub mod tar;
+#[cfg(feature = "std")]
+pub mod testing;
+#[cfg(feature = "std")]
+pub mod timeout;
+#[cfg(feature = "std")]
+pub mod timestamp;
+#[cfg(feature = "std")]
+pub mod tlv;
+pub mod to_bytes;
+#[cfg(feature = "std")]
+pub mod transform;
+pub mod typed_slice;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub mod uring;
+#[cfg(feature = "std")]
+pub mod uuid;
+#[cfg(feature = "std")]
+pub mod validation;
+pub mod varint;
+#[cfg(feature = "std")]
 pub mod write_integer;
-
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "xxhash")]
+pub mod xxhash;
+#[cfg(feature = "std")]
+pub mod zip;
+#[cfg(feature = "zstd")]
+pub mod zstd;
@@ -0,0 +1,193 @@
+//! Provides the adapters to read and write the LZ4 frames, and the LZ4 blocks stored inside the
+//! binary containers.
+//!
+//! `Lz4Reader` and `Lz4Writer` read and write the LZ4 frame format, which carries its own header
+//! and end mark. The raw LZ4 blocks have neither, so the container stores their lengths, and they
+//! are read by `Lz4Read::read_lz4_block`.
+//!
+//! This module is available with the `lz4` feature.
+
+use std::io;
+
+use lz4_flex::block;
+use lz4_flex::frame::{FrameDecoder, FrameEncoder};
+
+use binary_read::BinaryRead;
+
+/// Wraps a reader of the LZ4 frames, and reads the decompressed data.
+///
+/// The concatenated frames are read as one stream.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::lz4::{Lz4Reader, Lz4Writer};
+///
+/// let mut writer = Lz4Writer::new(vec![]);
+/// writer.write_integer_array::<LittleEndian, u16>(&[0xBEEF; 500]).unwrap();
+/// let compressed = writer.finish().unwrap();
+/// assert_eq!(&[0x04, 0x22, 0x4D, 0x18], &compressed[..4]);
+///
+/// let mut reader = Lz4Reader::new(&compressed[..]);
+/// assert_eq!(vec![0xBEEF; 500], reader.read_integer_array::<LittleEndian, u16>(500).unwrap());
+///
+/// ```
+///
+pub struct Lz4Reader<TRead>
+    where TRead: io::Read {
+    decoder: FrameDecoder<TRead>,
+}
+
+impl<TRead> Lz4Reader<TRead>
+    where TRead: io::Read {
+    /// Creates a new `Lz4Reader` of the frames read from `reader`.
+    pub fn new(reader: TRead) -> Lz4Reader<TRead> {
+        Lz4Reader { decoder: FrameDecoder::new(reader) }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &TRead {
+        self.decoder.get_ref()
+    }
+
+    /// Unwraps this `Lz4Reader`, returning the underlying reader.
+    pub fn into_inner(self) -> TRead {
+        self.decoder.into_inner()
+    }
+}
+
+impl<TRead> io::Read for Lz4Reader<TRead>
+    where TRead: io::Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.decoder.read(buf)
+    }
+}
+
+/// Wraps a writer, and writes the data as an LZ4 frame.
+///
+/// `finish` must be called to write the end of the frame.
+pub struct Lz4Writer<TWrite>
+    where TWrite: io::Write {
+    encoder: FrameEncoder<TWrite>,
+}
+
+impl<TWrite> Lz4Writer<TWrite>
+    where TWrite: io::Write {
+    /// Creates a new `Lz4Writer` writing a frame of the default settings to `writer`.
+    pub fn new(writer: TWrite) -> Lz4Writer<TWrite> {
+        Lz4Writer { encoder: FrameEncoder::new(writer) }
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &TWrite {
+        self.encoder.get_ref()
+    }
+
+    /// Writes the rest of the frame and the end mark, and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the underlying writer), otherwise Err(io::Error).
+    ///
+    pub fn finish(self) -> io::Result<TWrite> {
+        Ok(self.encoder.finish()?)
+    }
+}
+
+impl<TWrite> io::Write for Lz4Writer<TWrite>
+    where TWrite: io::Write {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.encoder.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.encoder.flush()
+    }
+}
+
+/// Compresses `data` into a raw LZ4 block.
+pub fn compress_block(data: &[u8]) -> Vec<u8> {
+    block::compress(data)
+}
+
+/// Decompresses the raw LZ4 block `compressed` of `decompressed_len` bytes.
+///
+/// # Errors
+///
+/// If the block is broken or its decompressed length is not `decompressed_len`, returns
+/// Err(io::Error) of `InvalidData`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::lz4;
+///
+/// let compressed = lz4::compress_block(b"abcabcabcabcabcabc");
+/// assert_eq!(b"abcabcabcabcabcabc".to_vec(), lz4::decompress_block(&compressed, 18).unwrap());
+/// assert!(lz4::decompress_block(&compressed, 17).is_err());
+///
+/// ```
+///
+pub fn decompress_block(compressed: &[u8], decompressed_len: usize) -> io::Result<Vec<u8>> {
+    let mut data = vec![0_u8; decompressed_len];
+    match block::decompress_into(compressed, &mut data) {
+        Ok(len) if len == decompressed_len => Ok(data),
+        Ok(_) => Err(io::Error::new(io::ErrorKind::InvalidData, "decompressed block is too short")),
+        Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+    }
+}
+
+/// Provides the features to read the LZ4 frames and blocks.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::lz4::{self, Lz4Read};
+///
+/// // A block header of the compressed and the decompressed lengths, followed by the block.
+/// let data = [7_u8; 300];
+/// let compressed = lz4::compress_block(&data);
+/// let mut writer = vec![];
+/// writer.write_integer::<LittleEndian, _>(compressed.len() as u32).unwrap();
+/// writer.write_integer::<LittleEndian, _>(data.len() as u32).unwrap();
+/// writer.extend(&compressed);
+///
+/// let mut reader = io::Cursor::new(writer);
+/// let compressed_len = reader.read_integer::<LittleEndian, u32>().unwrap();
+/// let decompressed_len = reader.read_integer::<LittleEndian, u32>().unwrap();
+/// let block = reader.read_lz4_block(compressed_len as usize, decompressed_len as usize).unwrap();
+/// assert_eq!(data.to_vec(), block);
+///
+/// ```
+///
+pub trait Lz4Read: io::Read {
+    /// Returns a reader decompressing the LZ4 frames of the next `compressed_len` bytes.
+    fn lz4_region(&mut self, compressed_len: u64) -> Lz4Reader<io::Take<&mut Self>>
+        where Self: Sized {
+        Lz4Reader::new(io::Read::take(self, compressed_len))
+    }
+
+    /// Reads a raw LZ4 block of `compressed_len` bytes, and returns the `decompressed_len` bytes
+    /// decompressed.
+    ///
+    /// # Errors
+    ///
+    /// If the block is broken or its decompressed length is not `decompressed_len`, returns
+    /// Err(io::Error) of `InvalidData`. If reading fails, returns Err(io::Error).
+    ///
+    fn read_lz4_block(&mut self, compressed_len: usize, decompressed_len: usize) -> io::Result<Vec<u8>>
+        where Self: Sized {
+        let compressed = self.read_byte_array(compressed_len)?;
+        decompress_block(&compressed, decompressed_len)
+    }
+}
+
+impl<T> Lz4Read for T
+    where T: io::Read {}
@@ -0,0 +1,252 @@
+//! Provides a sparse memory image made of addressed binary segments.
+//!
+//! `MemoryImage` is the payload of the firmware formats (`ihex`, `srec`). It implements
+//! `io::Read`, `io::Write` and `io::Seek` with the position as the address, so all `BinaryRead`
+//! and `BinaryWrite` methods can be used.
+
+use std::io;
+
+use checksum_frame::ChecksumMismatch;
+
+/// A contiguous run of bytes at an address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    /// The address of the first byte.
+    pub address: u32,
+    /// The bytes.
+    pub data: Vec<u8>,
+}
+
+impl Segment {
+    /// Creates a new `Segment`.
+    pub fn new(address: u32, data: Vec<u8>) -> Segment {
+        Segment {
+            address,
+            data,
+        }
+    }
+
+    /// Returns the address just after the segment.
+    pub fn end(&self) -> u64 {
+        self.address as u64 + self.data.len() as u64
+    }
+}
+
+/// A sparse memory image.
+///
+/// The segments are kept sorted, and overlapping or adjacent writes are merged.
+/// Reading stops at the end of a segment, so `read_exact` over a gap fails with `UnexpectedEof`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{Seek, SeekFrom};
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::memory_image::{MemoryImage, Segment};
+///
+/// let mut image = MemoryImage::new();
+/// image.write_at(0x1000, &[0x01, 0x02]).unwrap();
+/// image.write_at(0x2000, &[0xFF]).unwrap();
+/// image.seek(SeekFrom::Start(0x1002)).unwrap();
+/// image.write_integer::<LittleEndian, u16>(0x0403).unwrap();
+///
+/// assert_eq!(&[Segment::new(0x1000, vec![1, 2, 3, 4]), Segment::new(0x2000, vec![0xFF])], image.segments());
+///
+/// image.seek(SeekFrom::Start(0x1000)).unwrap();
+/// assert_eq!(0x04030201_u32, image.read_integer::<LittleEndian, u32>().unwrap());
+/// assert!(image.read_integer::<LittleEndian, u8>().is_err());
+///
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryImage {
+    segments: Vec<Segment>,
+    entry_point: Option<u32>,
+    position: u64,
+}
+
+impl MemoryImage {
+    /// Creates a new empty `MemoryImage`.
+    pub fn new() -> MemoryImage {
+        MemoryImage::default()
+    }
+
+    /// Returns the segments sorted by the address.
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Unwraps this `MemoryImage`, returning the segments.
+    pub fn into_segments(self) -> Vec<Segment> {
+        self.segments
+    }
+
+    /// Returns the entry point (start address), if any.
+    pub fn entry_point(&self) -> Option<u32> {
+        self.entry_point
+    }
+
+    /// Sets the entry point (start address).
+    pub fn set_entry_point(&mut self, entry_point: Option<u32>) {
+        self.entry_point = entry_point;
+    }
+
+    /// Returns the total number of bytes in the segments.
+    pub fn data_len(&self) -> u64 {
+        self.segments.iter().map(|s| s.data.len() as u64).sum()
+    }
+
+    /// Writes `data` at `address`, overwriting and merging the existing segments.
+    ///
+    /// # Errors
+    ///
+    /// If `data` exceeds the 32-bit address space, returns Err(io::Error) of `InvalidInput`.
+    ///
+    pub fn write_at(&mut self, address: u32, data: &[u8]) -> io::Result<()> {
+        let start = address as u64;
+        let end = start + data.len() as u64;
+        if end > u32::MAX as u64 + 1 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "data exceeds the address space"));
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+        let first = self.segments.partition_point(|s| s.end() < start);
+        let last = self.segments.partition_point(|s| s.address as u64 <= end);
+        if first == last {
+            self.segments.insert(first, Segment::new(address, data.to_vec()));
+            return Ok(());
+        }
+
+        let merged_start = start.min(self.segments[first].address as u64);
+        let merged_end = end.max(self.segments[last - 1].end());
+        let mut merged = vec![0_u8; (merged_end - merged_start) as usize];
+        for segment in &self.segments[first..last] {
+            let offset = (segment.address as u64 - merged_start) as usize;
+            merged[offset..offset + segment.data.len()].copy_from_slice(&segment.data);
+        }
+        let offset = (start - merged_start) as usize;
+        merged[offset..offset + data.len()].copy_from_slice(data);
+        self.segments.splice(first..last, Some(Segment::new(merged_start as u32, merged)));
+        Ok(())
+    }
+
+    /// Reads exactly `buf.len()` bytes at `address` without changing the current position.
+    ///
+    /// # Errors
+    ///
+    /// If the range is not covered by a segment, returns Err(io::Error) of `UnexpectedEof`.
+    ///
+    pub fn read_exact_at(&self, address: u32, buf: &mut [u8]) -> io::Result<()> {
+        let data = self.contiguous_at(address as u64);
+        if data.len() < buf.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "range is not covered by a segment"));
+        }
+        buf.copy_from_slice(&data[..buf.len()]);
+        Ok(())
+    }
+
+    fn contiguous_at(&self, address: u64) -> &[u8] {
+        let index = self.segments.partition_point(|s| s.end() <= address);
+        match self.segments.get(index) {
+            Some(segment) if segment.address as u64 <= address => {
+                &segment.data[(address - segment.address as u64) as usize..]
+            }
+            _ => &[],
+        }
+    }
+}
+
+impl io::Read for MemoryImage {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = {
+            let data = self.contiguous_at(self.position);
+            let n = data.len().min(buf.len());
+            buf[..n].copy_from_slice(&data[..n]);
+            n
+        };
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl io::Write for MemoryImage {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.position > u32::MAX as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "position exceeds the address space"));
+        }
+        self.write_at(self.position as u32, buf)?;
+        self.position += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl io::Seek for MemoryImage {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            io::SeekFrom::Start(offset) => Some(offset),
+            io::SeekFrom::End(offset) => {
+                let end = self.segments.last().map_or(0, |s| s.end());
+                end.checked_add_signed(offset)
+            }
+            io::SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position")),
+        }
+    }
+}
+
+/// Decodes the hex digits of a record into bytes.
+pub(crate) fn decode_hex(digits: &[u8], format: &'static str) -> io::Result<Vec<u8>> {
+    if !digits.len().is_multiple_of(2) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format));
+    }
+    digits.chunks(2).map(|pair| {
+        let high = (pair[0] as char).to_digit(16);
+        let low = (pair[1] as char).to_digit(16);
+        match (high, low) {
+            (Some(high), Some(low)) => Ok((high << 4 | low) as u8),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, format)),
+        }
+    }).collect()
+}
+
+/// Encodes bytes as uppercase hex digits.
+pub(crate) fn encode_hex(bytes: &[u8], line: &mut String) {
+    for byte in bytes {
+        line.push_str(&format!("{:02X}", byte));
+    }
+}
+
+/// Returns the error of a record checksum mismatch.
+pub(crate) fn checksum_mismatch(expected: u8, actual: u8) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, ChecksumMismatch { expected, actual })
+}
+
+/// Reads the records (non-empty trimmed lines) of a text firmware file.
+pub(crate) fn read_records<TRead, TFn>(reader: TRead, mut handle: TFn) -> io::Result<()>
+    where TRead: io::BufRead,
+          TFn: FnMut(&[u8]) -> io::Result<bool> {
+    for line in reader.split(b'\n') {
+        let line = line?;
+        let record = line.trim_ascii();
+        if record.is_empty() {
+            continue;
+        }
+        if !handle(record)? {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Provides the opt-in IO statistics of the buffered and file readers and writers.
+//!
+//! The metrics are disabled by default, so they cost nothing. Once enabled by `enable_metrics()`,
+//! `metrics()` returns the bytes and the calls through the reader or writer, and the time spent
+//! in them.
+
+use std::time::{Duration, Instant};
+
+/// The IO statistics of a reader or writer.
+///
+/// The calls are the calls of `read`, `fill_buf`, `write`, `flush` and the positional reads and
+/// writes. The bytes consumed from `fill_buf` are counted as read.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::buffered::BufferedBinaryReader;
+/// use mm_binary_io::endian::BigEndian;
+///
+/// let mut reader = BufferedBinaryReader::new(io::Cursor::new(vec![0_u8; 8]));
+/// assert_eq!(None, reader.metrics());
+///
+/// reader.enable_metrics();
+/// reader.read_integer::<BigEndian, u32>().unwrap();
+/// reader.read_integer::<BigEndian, u16>().unwrap();
+///
+/// let metrics = reader.metrics().unwrap();
+/// assert_eq!(6, metrics.bytes_read);
+/// assert_eq!(2, metrics.read_calls);
+/// assert_eq!(0, metrics.bytes_written);
+///
+/// ```
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IoMetrics {
+    /// The byte count read.
+    pub bytes_read: u64,
+    /// The byte count written.
+    pub bytes_written: u64,
+    /// The number of the read calls.
+    pub read_calls: u64,
+    /// The number of the write calls.
+    pub write_calls: u64,
+    /// The time spent in the calls.
+    pub elapsed: Duration,
+}
+
+impl IoMetrics {
+    /// Returns the bytes read and written per second of `elapsed`, or None if `elapsed` is 0.
+    pub fn throughput(&self) -> Option<f64> {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            Some((self.bytes_read + self.bytes_written) as f64 / secs)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the start time of a call if `metrics` is enabled.
+    pub(crate) fn start(metrics: &Option<IoMetrics>) -> Option<Instant> {
+        metrics.as_ref().map(|_| Instant::now())
+    }
+
+    /// Records a read call started at `start`, which read `len` bytes.
+    pub(crate) fn end_read(metrics: &mut Option<IoMetrics>, start: Option<Instant>, len: usize) {
+        if let (Some(metrics), Some(start)) = (metrics.as_mut(), start) {
+            metrics.elapsed += start.elapsed();
+            metrics.read_calls += 1;
+            metrics.bytes_read += len as u64;
+        }
+    }
+
+    /// Records a write call started at `start`, which wrote `len` bytes.
+    pub(crate) fn end_write(metrics: &mut Option<IoMetrics>, start: Option<Instant>, len: usize) {
+        if let (Some(metrics), Some(start)) = (metrics.as_mut(), start) {
+            metrics.elapsed += start.elapsed();
+            metrics.write_calls += 1;
+            metrics.bytes_written += len as u64;
+        }
+    }
+
+    /// Records `len` bytes consumed from `fill_buf`.
+    pub(crate) fn consume(metrics: &mut Option<IoMetrics>, len: usize) {
+        if let Some(metrics) = metrics.as_mut() {
+            metrics.bytes_read += len as u64;
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! Provides the features to read and write the MessagePack wire format as a dynamic `Value`.
+//!
+//! This module is available with the `msgpack` feature.
+
+use std::io;
+use std::io::Read;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::BigEndian;
+
+/// The maximum nesting depth of arrays and maps accepted by `read_value`.
+pub const MAX_DEPTH: usize = 256;
+
+/// A MessagePack value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// nil.
+    Nil,
+    /// true or false.
+    Bool(bool),
+    /// A negative integer, or an integer encoded as a signed type.
+    Int(i64),
+    /// A non-negative integer encoded as an unsigned type or a positive fixint.
+    UInt(u64),
+    /// float 32.
+    F32(f32),
+    /// float 64.
+    F64(f64),
+    /// A UTF-8 string.
+    Str(String),
+    /// A byte array.
+    Bin(Vec<u8>),
+    /// An array.
+    Array(Vec<Value>),
+    /// A map, in the encoded order.
+    Map(Vec<(Value, Value)>),
+    /// An extension type and its data.
+    Ext(i8, Vec<u8>),
+}
+
+/// Reads a value.
+///
+/// # Errors
+///
+/// If the data is broken (an unused marker, an invalid UTF-8 string, or nesting deeper than
+/// `MAX_DEPTH`), returns Err(io::Error) of `InvalidData`.
+/// If the stream ends in the middle of the value, returns Err(io::Error) of `UnexpectedEof`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::msgpack::{self, Value};
+///
+/// let data = vec![0x82_u8, 0xA1, b'a', 0x01, 0xA1, b'b', 0x92, 0xFF, 0xC3];
+/// assert_eq!(
+///     Value::Map(vec![
+///         (Value::Str("a".to_string()), Value::UInt(1)),
+///         (Value::Str("b".to_string()), Value::Array(vec![Value::Int(-1), Value::Bool(true)])),
+///     ]),
+///     msgpack::read_value(&mut io::Cursor::new(data)).unwrap());
+///
+/// ```
+///
+pub fn read_value<TRead>(reader: &mut TRead) -> io::Result<Value>
+    where TRead: io::Read {
+    read_nested(reader, 0)
+}
+
+/// Writes a value in the shortest encoding.
+///
+/// # Errors
+///
+/// If a string, a byte array, an array or a map is longer than `u32::MAX`, returns
+/// Err(io::Error) of `InvalidInput`.
+/// If writing fails, returns Err(io::Error).
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::msgpack::{self, Value};
+///
+/// let mut writer = vec![];
+/// msgpack::write_value(&mut writer, &Value::Array(vec![
+///     Value::UInt(300),
+///     Value::Int(-33),
+///     Value::Bin(vec![0xAB]),
+///     Value::F64(1.5),
+///     Value::Nil,
+/// ])).unwrap();
+/// assert_eq!(
+///     vec![0x95,
+///          0xCD, 0x01, 0x2C,
+///          0xD0, 0xDF,
+///          0xC4, 0x01, 0xAB,
+///          0xCB, 0x3F, 0xF8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+///          0xC0],
+///     writer);
+///
+/// ```
+///
+pub fn write_value<TWrite>(writer: &mut TWrite, value: &Value) -> io::Result<()>
+    where TWrite: io::Write {
+    match *value {
+        Value::Nil => writer.write_integer::<BigEndian, _>(0xC0_u8),
+        Value::Bool(false) => writer.write_integer::<BigEndian, _>(0xC2_u8),
+        Value::Bool(true) => writer.write_integer::<BigEndian, _>(0xC3_u8),
+        Value::Int(value) if value >= 0 => write_uint(writer, value as u64),
+        Value::Int(value) => write_negative_int(writer, value),
+        Value::UInt(value) => write_uint(writer, value),
+        Value::F32(value) => {
+            writer.write_integer::<BigEndian, _>(0xCA_u8)?;
+            writer.write_integer::<BigEndian, _>(value.to_bits())
+        }
+        Value::F64(value) => {
+            writer.write_integer::<BigEndian, _>(0xCB_u8)?;
+            writer.write_integer::<BigEndian, _>(value.to_bits())
+        }
+        Value::Str(ref value) => {
+            write_len(writer, value.len(), Some(0xA0), 0xD9, 0xDA, 0xDB)?;
+            writer.write_all(value.as_bytes())
+        }
+        Value::Bin(ref value) => {
+            write_len(writer, value.len(), None, 0xC4, 0xC5, 0xC6)?;
+            writer.write_all(value)
+        }
+        Value::Array(ref values) => {
+            write_collection_len(writer, values.len(), 0x90, 0xDC, 0xDD)?;
+            values.iter().try_for_each(|v| write_value(writer, v))
+        }
+        Value::Map(ref entries) => {
+            write_collection_len(writer, entries.len(), 0x80, 0xDE, 0xDF)?;
+            entries.iter().try_for_each(|(k, v)| {
+                write_value(writer, k)?;
+                write_value(writer, v)
+            })
+        }
+        Value::Ext(ext_type, ref data) => {
+            let marker = match data.len() {
+                1 => Some(0xD4_u8),
+                2 => Some(0xD5),
+                4 => Some(0xD6),
+                8 => Some(0xD7),
+                16 => Some(0xD8),
+                _ => None,
+            };
+            match marker {
+                Some(marker) => writer.write_integer::<BigEndian, _>(marker)?,
+                None => write_len(writer, data.len(), None, 0xC7, 0xC8, 0xC9)?,
+            }
+            writer.write_integer::<BigEndian, _>(ext_type)?;
+            writer.write_all(data)
+        }
+    }
+}
+
+fn read_nested<TRead>(reader: &mut TRead, depth: usize) -> io::Result<Value>
+    where TRead: io::Read {
+    let marker = reader.read_integer::<BigEndian, u8>()?;
+    let value = match marker {
+        0x00..=0x7F => Value::UInt(marker as u64),
+        0x80..=0x8F => read_map(reader, (marker & 0x0F) as usize, depth)?,
+        0x90..=0x9F => read_array(reader, (marker & 0x0F) as usize, depth)?,
+        0xA0..=0xBF => read_str(reader, (marker & 0x1F) as usize)?,
+        0xC0 => Value::Nil,
+        0xC2 => Value::Bool(false),
+        0xC3 => Value::Bool(true),
+        0xC4 => {
+            let len = reader.read_integer::<BigEndian, u8>()? as usize;
+            Value::Bin(read_bytes(reader, len)?)
+        }
+        0xC5 => {
+            let len = reader.read_integer::<BigEndian, u16>()? as usize;
+            Value::Bin(read_bytes(reader, len)?)
+        }
+        0xC6 => {
+            let len = reader.read_integer::<BigEndian, u32>()? as usize;
+            Value::Bin(read_bytes(reader, len)?)
+        }
+        0xC7 => {
+            let len = reader.read_integer::<BigEndian, u8>()? as usize;
+            read_ext(reader, len)?
+        }
+        0xC8 => {
+            let len = reader.read_integer::<BigEndian, u16>()? as usize;
+            read_ext(reader, len)?
+        }
+        0xC9 => {
+            let len = reader.read_integer::<BigEndian, u32>()? as usize;
+            read_ext(reader, len)?
+        }
+        0xCA => Value::F32(f32::from_bits(reader.read_integer::<BigEndian, u32>()?)),
+        0xCB => Value::F64(f64::from_bits(reader.read_integer::<BigEndian, u64>()?)),
+        0xCC => Value::UInt(reader.read_integer::<BigEndian, u8>()? as u64),
+        0xCD => Value::UInt(reader.read_integer::<BigEndian, u16>()? as u64),
+        0xCE => Value::UInt(reader.read_integer::<BigEndian, u32>()? as u64),
+        0xCF => Value::UInt(reader.read_integer::<BigEndian, u64>()?),
+        0xD0 => Value::Int(reader.read_integer::<BigEndian, i8>()? as i64),
+        0xD1 => Value::Int(reader.read_integer::<BigEndian, i16>()? as i64),
+        0xD2 => Value::Int(reader.read_integer::<BigEndian, i32>()? as i64),
+        0xD3 => Value::Int(reader.read_integer::<BigEndian, i64>()?),
+        0xD4 => read_ext(reader, 1)?,
+        0xD5 => read_ext(reader, 2)?,
+        0xD6 => read_ext(reader, 4)?,
+        0xD7 => read_ext(reader, 8)?,
+        0xD8 => read_ext(reader, 16)?,
+        0xD9 => {
+            let len = reader.read_integer::<BigEndian, u8>()? as usize;
+            read_str(reader, len)?
+        }
+        0xDA => {
+            let len = reader.read_integer::<BigEndian, u16>()? as usize;
+            read_str(reader, len)?
+        }
+        0xDB => {
+            let len = reader.read_integer::<BigEndian, u32>()? as usize;
+            read_str(reader, len)?
+        }
+        0xDC => {
+            let len = reader.read_integer::<BigEndian, u16>()? as usize;
+            read_array(reader, len, depth)?
+        }
+        0xDD => {
+            let len = reader.read_integer::<BigEndian, u32>()? as usize;
+            read_array(reader, len, depth)?
+        }
+        0xDE => {
+            let len = reader.read_integer::<BigEndian, u16>()? as usize;
+            read_map(reader, len, depth)?
+        }
+        0xDF => {
+            let len = reader.read_integer::<BigEndian, u32>()? as usize;
+            read_map(reader, len, depth)?
+        }
+        0xE0..=0xFF => Value::Int(marker as i8 as i64),
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "unused MessagePack marker")),
+    };
+    Ok(value)
+}
+
+fn read_bytes<TRead>(reader: &mut TRead, len: usize) -> io::Result<Vec<u8>>
+    where TRead: io::Read {
+    let mut bytes = vec![];
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "MessagePack value is cut off"));
+    }
+    Ok(bytes)
+}
+
+fn read_str<TRead>(reader: &mut TRead, len: usize) -> io::Result<Value>
+    where TRead: io::Read {
+    let bytes = read_bytes(reader, len)?;
+    String::from_utf8(bytes)
+        .map(Value::Str)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn read_ext<TRead>(reader: &mut TRead, len: usize) -> io::Result<Value>
+    where TRead: io::Read {
+    let ext_type = reader.read_integer::<BigEndian, i8>()?;
+    Ok(Value::Ext(ext_type, read_bytes(reader, len)?))
+}
+
+fn read_array<TRead>(reader: &mut TRead, len: usize, depth: usize) -> io::Result<Value>
+    where TRead: io::Read {
+    check_depth(depth)?;
+    let mut values = Vec::with_capacity(len.min(1024));
+    for _ in 0..len {
+        values.push(read_nested(reader, depth + 1)?);
+    }
+    Ok(Value::Array(values))
+}
+
+fn read_map<TRead>(reader: &mut TRead, len: usize, depth: usize) -> io::Result<Value>
+    where TRead: io::Read {
+    check_depth(depth)?;
+    let mut entries = Vec::with_capacity(len.min(1024));
+    for _ in 0..len {
+        let key = read_nested(reader, depth + 1)?;
+        let value = read_nested(reader, depth + 1)?;
+        entries.push((key, value));
+    }
+    Ok(Value::Map(entries))
+}
+
+fn check_depth(depth: usize) -> io::Result<()> {
+    if depth >= MAX_DEPTH {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "MessagePack value is nested too deeply"));
+    }
+    Ok(())
+}
+
+fn write_uint<TWrite>(writer: &mut TWrite, value: u64) -> io::Result<()>
+    where TWrite: io::Write {
+    if value < 0x80 {
+        writer.write_integer::<BigEndian, _>(value as u8)
+    } else if value <= u8::MAX as u64 {
+        writer.write_integer::<BigEndian, _>(0xCC_u8)?;
+        writer.write_integer::<BigEndian, _>(value as u8)
+    } else if value <= u16::MAX as u64 {
+        writer.write_integer::<BigEndian, _>(0xCD_u8)?;
+        writer.write_integer::<BigEndian, _>(value as u16)
+    } else if value <= u32::MAX as u64 {
+        writer.write_integer::<BigEndian, _>(0xCE_u8)?;
+        writer.write_integer::<BigEndian, _>(value as u32)
+    } else {
+        writer.write_integer::<BigEndian, _>(0xCF_u8)?;
+        writer.write_integer::<BigEndian, _>(value)
+    }
+}
+
+fn write_negative_int<TWrite>(writer: &mut TWrite, value: i64) -> io::Result<()>
+    where TWrite: io::Write {
+    if value >= -32 {
+        writer.write_integer::<BigEndian, _>(value as i8)
+    } else if value >= i8::MIN as i64 {
+        writer.write_integer::<BigEndian, _>(0xD0_u8)?;
+        writer.write_integer::<BigEndian, _>(value as i8)
+    } else if value >= i16::MIN as i64 {
+        writer.write_integer::<BigEndian, _>(0xD1_u8)?;
+        writer.write_integer::<BigEndian, _>(value as i16)
+    } else if value >= i32::MIN as i64 {
+        writer.write_integer::<BigEndian, _>(0xD2_u8)?;
+        writer.write_integer::<BigEndian, _>(value as i32)
+    } else {
+        writer.write_integer::<BigEndian, _>(0xD3_u8)?;
+        writer.write_integer::<BigEndian, _>(value)
+    }
+}
+
+fn write_len<TWrite>(writer: &mut TWrite, len: usize, fix: Option<u8>, marker8: u8, marker16: u8, marker32: u8) -> io::Result<()>
+    where TWrite: io::Write {
+    match fix {
+        Some(fix) if len < 32 => writer.write_integer::<BigEndian, _>(fix | len as u8),
+        _ if len <= u8::MAX as usize => {
+            writer.write_integer::<BigEndian, _>(marker8)?;
+            writer.write_integer::<BigEndian, _>(len as u8)
+        }
+        _ if len <= u16::MAX as usize => {
+            writer.write_integer::<BigEndian, _>(marker16)?;
+            writer.write_integer::<BigEndian, _>(len as u16)
+        }
+        _ if len as u64 <= u32::MAX as u64 => {
+            writer.write_integer::<BigEndian, _>(marker32)?;
+            writer.write_integer::<BigEndian, _>(len as u32)
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "MessagePack value is too long")),
+    }
+}
+
+fn write_collection_len<TWrite>(writer: &mut TWrite, len: usize, fix: u8, marker16: u8, marker32: u8) -> io::Result<()>
+    where TWrite: io::Write {
+    if len < 16 {
+        writer.write_integer::<BigEndian, _>(fix | len as u8)
+    } else if len <= u16::MAX as usize {
+        writer.write_integer::<BigEndian, _>(marker16)?;
+        writer.write_integer::<BigEndian, _>(len as u16)
+    } else if len as u64 <= u32::MAX as u64 {
+        writer.write_integer::<BigEndian, _>(marker32)?;
+        writer.write_integer::<BigEndian, _>(len as u32)
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidInput, "MessagePack value is too long"))
+    }
+}
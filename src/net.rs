@@ -0,0 +1,344 @@
+//! Provides the features to read and write the network address fields.
+//!
+//! The addresses and the ports are stored in the network byte order (big-endian), as in the
+//! packet headers and the socket address structures. `MacAddr` (EUI-48) and `MacAddr8` (EUI-64)
+//! are the hardware addresses of the network captures and the device protocols.
+
+use std::fmt;
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+use std::str;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::BigEndian;
+
+/// An EUI-48 MAC address.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::net::MacAddr;
+///
+/// let addr: MacAddr = "00-1A-2B-3C-4D-5E".parse().unwrap();
+/// assert_eq!(&[0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E], addr.octets());
+/// assert_eq!("00:1a:2b:3c:4d:5e", addr.to_string());
+/// assert!(!addr.is_multicast());
+/// assert_eq!("02:1a:2b:ff:fe:3c:4d:5e", addr.to_modified_eui64().to_string());
+/// assert!(MacAddr::BROADCAST.is_multicast());
+///
+/// ```
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddr([u8; 6]);
+
+/// An EUI-64 MAC address.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddr8([u8; 8]);
+
+impl MacAddr {
+    /// The broadcast address `ff:ff:ff:ff:ff:ff`.
+    pub const BROADCAST: MacAddr = MacAddr([0xFF; 6]);
+
+    /// Creates a new `MacAddr` of `octets`.
+    pub fn new(octets: [u8; 6]) -> MacAddr {
+        MacAddr(octets)
+    }
+
+    /// Returns the octets.
+    pub fn octets(&self) -> &[u8; 6] {
+        &self.0
+    }
+
+    /// Returns true if the group bit (the lowest bit of the first octet) is set.
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+
+    /// Returns true if the locally administered bit (the second lowest bit of the first octet)
+    /// is set.
+    pub fn is_local(&self) -> bool {
+        self.0[0] & 0x02 != 0
+    }
+
+    /// Returns the modified EUI-64 of the IPv6 interface identifiers, which inserts `ff:fe` in
+    /// the middle and flips the locally administered bit.
+    pub fn to_modified_eui64(&self) -> MacAddr8 {
+        let o = self.0;
+        MacAddr8([o[0] ^ 0x02, o[1], o[2], 0xFF, 0xFE, o[3], o[4], o[5]])
+    }
+}
+
+impl MacAddr8 {
+    /// Creates a new `MacAddr8` of `octets`.
+    pub fn new(octets: [u8; 8]) -> MacAddr8 {
+        MacAddr8(octets)
+    }
+
+    /// Returns the octets.
+    pub fn octets(&self) -> &[u8; 8] {
+        &self.0
+    }
+
+    /// Returns true if the group bit (the lowest bit of the first octet) is set.
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+
+    /// Returns true if the locally administered bit (the second lowest bit of the first octet)
+    /// is set.
+    pub fn is_local(&self) -> bool {
+        self.0[0] & 0x02 != 0
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_octets(&self.0, f)
+    }
+}
+
+impl fmt::Display for MacAddr8 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_octets(&self.0, f)
+    }
+}
+
+/// The error returned when a string is not a MAC address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseMacAddrError;
+
+impl fmt::Display for ParseMacAddrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid MAC address string")
+    }
+}
+
+impl ::std::error::Error for ParseMacAddrError {}
+
+impl str::FromStr for MacAddr {
+    type Err = ParseMacAddrError;
+
+    /// Parses the octets separated by `:` or `-`.
+    fn from_str(s: &str) -> Result<MacAddr, ParseMacAddrError> {
+        let mut octets = [0_u8; 6];
+        parse_octets(s, &mut octets)?;
+        Ok(MacAddr(octets))
+    }
+}
+
+impl str::FromStr for MacAddr8 {
+    type Err = ParseMacAddrError;
+
+    /// Parses the octets separated by `:` or `-`.
+    fn from_str(s: &str) -> Result<MacAddr8, ParseMacAddrError> {
+        let mut octets = [0_u8; 8];
+        parse_octets(s, &mut octets)?;
+        Ok(MacAddr8(octets))
+    }
+}
+
+fn format_octets(octets: &[u8], f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, octet) in octets.iter().enumerate() {
+        if i > 0 {
+            f.write_str(":")?;
+        }
+        write!(f, "{:02x}", octet)?;
+    }
+    Ok(())
+}
+
+fn parse_octets(s: &str, octets: &mut [u8]) -> Result<(), ParseMacAddrError> {
+    let mut parts = s.split([':', '-']);
+    for octet in octets.iter_mut() {
+        let part = parts.next().ok_or(ParseMacAddrError)?;
+        if part.len() != 2 {
+            return Err(ParseMacAddrError);
+        }
+        *octet = u8::from_str_radix(part, 16).map_err(|_| ParseMacAddrError)?;
+    }
+    match parts.next() {
+        Some(_) => Err(ParseMacAddrError),
+        None => Ok(()),
+    }
+}
+
+/// Provides the features to read the network addresses.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+/// use mm_binary_io::net::{MacAddr, NetRead, NetWrite};
+///
+/// let v4 = SocketAddrV4::new(Ipv4Addr::new(192, 168, 0, 1), 8080);
+/// let v6 = SocketAddrV6::new(Ipv6Addr::LOCALHOST, 443, 0, 0);
+/// let mac = MacAddr::new([0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E]);
+///
+/// let mut writer = vec![];
+/// writer.write_socket_addr_v4(&v4).unwrap();
+/// writer.write_socket_addr_v6(&v6).unwrap();
+/// writer.write_mac_addr(&mac).unwrap();
+/// assert_eq!(vec![192, 168, 0, 1, 0x1F, 0x90], writer[..6].to_vec());
+/// assert_eq!(6 + 18 + 6, writer.len());
+///
+/// let mut reader = io::Cursor::new(writer);
+/// assert_eq!(v4, reader.read_socket_addr_v4().unwrap());
+/// assert_eq!(v6, reader.read_socket_addr_v6().unwrap());
+/// assert_eq!(mac, reader.read_mac_addr().unwrap());
+///
+/// ```
+///
+pub trait NetRead: io::Read {
+    /// Reads an IPv4 address of 4 bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(Ipv4Addr), otherwise Err(io::Error).
+    ///
+    fn read_ipv4_addr(&mut self) -> io::Result<Ipv4Addr> {
+        let mut octets = [0_u8; 4];
+        self.read_exact(&mut octets)?;
+        Ok(Ipv4Addr::from(octets))
+    }
+
+    /// Reads an IPv6 address of 16 bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(Ipv6Addr), otherwise Err(io::Error).
+    ///
+    fn read_ipv6_addr(&mut self) -> io::Result<Ipv6Addr> {
+        let mut octets = [0_u8; 16];
+        self.read_exact(&mut octets)?;
+        Ok(Ipv6Addr::from(octets))
+    }
+
+    /// Reads an IPv4 address followed by a 16-bit port.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(SocketAddrV4), otherwise Err(io::Error).
+    ///
+    fn read_socket_addr_v4(&mut self) -> io::Result<SocketAddrV4>
+        where Self: Sized {
+        let ip = self.read_ipv4_addr()?;
+        let port = self.read_integer::<BigEndian, u16>()?;
+        Ok(SocketAddrV4::new(ip, port))
+    }
+
+    /// Reads an IPv6 address followed by a 16-bit port.
+    ///
+    /// The flow information and the scope ID are 0.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(SocketAddrV6), otherwise Err(io::Error).
+    ///
+    fn read_socket_addr_v6(&mut self) -> io::Result<SocketAddrV6>
+        where Self: Sized {
+        let ip = self.read_ipv6_addr()?;
+        let port = self.read_integer::<BigEndian, u16>()?;
+        Ok(SocketAddrV6::new(ip, port, 0, 0))
+    }
+
+    /// Reads an EUI-48 MAC address of 6 bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(MacAddr), otherwise Err(io::Error).
+    ///
+    fn read_mac_addr(&mut self) -> io::Result<MacAddr> {
+        let mut octets = [0_u8; 6];
+        self.read_exact(&mut octets)?;
+        Ok(MacAddr(octets))
+    }
+
+    /// Reads an EUI-64 MAC address of 8 bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(MacAddr8), otherwise Err(io::Error).
+    ///
+    fn read_mac_addr8(&mut self) -> io::Result<MacAddr8> {
+        let mut octets = [0_u8; 8];
+        self.read_exact(&mut octets)?;
+        Ok(MacAddr8(octets))
+    }
+}
+
+impl<T> NetRead for T
+    where T: io::Read {}
+
+/// Provides the features to write the network addresses.
+pub trait NetWrite: io::Write {
+    /// Writes an IPv4 address of 4 bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    fn write_ipv4_addr(&mut self, value: &Ipv4Addr) -> io::Result<()> {
+        self.write_all(&value.octets())
+    }
+
+    /// Writes an IPv6 address of 16 bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    fn write_ipv6_addr(&mut self, value: &Ipv6Addr) -> io::Result<()> {
+        self.write_all(&value.octets())
+    }
+
+    /// Writes an IPv4 address followed by a 16-bit port.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    fn write_socket_addr_v4(&mut self, value: &SocketAddrV4) -> io::Result<()>
+        where Self: Sized {
+        self.write_ipv4_addr(value.ip())?;
+        self.write_integer::<BigEndian, _>(value.port())
+    }
+
+    /// Writes an IPv6 address followed by a 16-bit port.
+    ///
+    /// The flow information and the scope ID are not written.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    fn write_socket_addr_v6(&mut self, value: &SocketAddrV6) -> io::Result<()>
+        where Self: Sized {
+        self.write_ipv6_addr(value.ip())?;
+        self.write_integer::<BigEndian, _>(value.port())
+    }
+
+    /// Writes an EUI-48 MAC address of 6 bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    fn write_mac_addr(&mut self, value: &MacAddr) -> io::Result<()> {
+        self.write_all(&value.0)
+    }
+
+    /// Writes an EUI-64 MAC address of 8 bytes.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    fn write_mac_addr8(&mut self, value: &MacAddr8) -> io::Result<()> {
+        self.write_all(&value.0)
+    }
+}
+
+impl<T> NetWrite for T
+    where T: io::Write {}
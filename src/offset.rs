@@ -0,0 +1,186 @@
+//! Provides the typed offsets stored in the binary data, and the following of them, for the
+//! formats chained by the offsets such as PE, ELF and the game assets.
+//!
+//! An `Offset<T, W>` is an offset from the stream head stored in the integer `W`, pointing at a
+//! `T`. Following it seeks there, reads the `T`, and returns to the previous position, so that the
+//! parser of the pointing structure reads on. `FollowRead::with_position_at` does the same for the
+//! untyped offsets.
+
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::Endian;
+use read_integer::ReadInteger;
+use write_integer::WriteInteger;
+
+/// An offset stored in `W`, pointing at a `T`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::offset::{FollowRead, Offset};
+///
+/// struct Name(String);
+///
+/// let data = b"\x08\x00\x00\x00\x2A\x00\x00\x00\x05asset";
+/// let mut reader = io::Cursor::new(&data[..]);
+///
+/// let name_offset = Offset::<Name, u32>::read::<LittleEndian, _>(&mut reader).unwrap();
+/// let name = reader.follow(name_offset, |r| {
+///     let len = r.read_integer::<LittleEndian, u8>()?;
+///     r.read_string(len as usize).map(Name)
+/// }).unwrap();
+/// assert_eq!("asset", name.0);
+///
+/// // The reading goes on after the offset.
+/// assert_eq!(42, reader.read_integer::<LittleEndian, u32>().unwrap());
+///
+/// ```
+///
+pub struct Offset<T, W> {
+    value: W,
+    _target: PhantomData<fn() -> T>,
+}
+
+impl<T, W> Offset<T, W>
+    where W: Copy + Into<u64> {
+    /// Creates a new `Offset` of `value`.
+    pub fn new(value: W) -> Offset<T, W> {
+        Offset { value, _target: PhantomData }
+    }
+
+    /// Returns the stored value.
+    pub fn value(&self) -> W {
+        self.value
+    }
+
+    /// Returns the offset from the stream head.
+    pub fn get(&self) -> u64 {
+        self.value.into()
+    }
+
+    /// Returns true if the offset is 0, which the formats use for no target.
+    pub fn is_null(&self) -> bool {
+        self.get() == 0
+    }
+
+    /// Reads an offset stored in `W` of `TEndian`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(Offset), otherwise Err(io::Error).
+    ///
+    pub fn read<TEndian, TRead>(reader: &mut TRead) -> io::Result<Offset<T, W>>
+        where TEndian: Endian,
+              TRead: io::Read,
+              W: ReadInteger<OutputType=W> {
+        reader.read_integer::<TEndian, W>().map(Offset::new)
+    }
+
+    /// Writes the stored value in `TEndian`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    pub fn write<TEndian, TWrite>(&self, writer: &mut TWrite) -> io::Result<()>
+        where TEndian: Endian,
+              TWrite: io::Write,
+              W: WriteInteger {
+        writer.write_integer::<TEndian, W>(self.value)
+    }
+}
+
+impl<T, W> Clone for Offset<T, W>
+    where W: Clone {
+    fn clone(&self) -> Offset<T, W> {
+        Offset { value: self.value.clone(), _target: PhantomData }
+    }
+}
+
+impl<T, W> Copy for Offset<T, W>
+    where W: Copy {}
+
+impl<T, W> fmt::Debug for Offset<T, W>
+    where W: fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Offset").field(&self.value).finish()
+    }
+}
+
+impl<T, W> PartialEq for Offset<T, W>
+    where W: PartialEq {
+    fn eq(&self, other: &Offset<T, W>) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T, W> Eq for Offset<T, W>
+    where W: Eq {}
+
+/// Provides the features to follow the offsets of a seekable stream.
+pub trait FollowRead: io::Read + io::Seek {
+    /// Seeks to `offset`, reads the target by `f`, and returns to the current position, even if
+    /// `f` fails.
+    ///
+    /// # Errors
+    ///
+    /// Same as `with_position_at`.
+    ///
+    fn follow<T, W, F>(&mut self, offset: Offset<T, W>, f: F) -> io::Result<T>
+        where Self: Sized,
+              W: Copy + Into<u64>,
+              F: FnOnce(&mut Self) -> io::Result<T> {
+        trace_event!(TRACE, offset = offset.get(), "follow offset");
+        self.with_position_at(offset.get(), f)
+    }
+
+    /// Seeks to `offset` from the stream head, calls `f`, and returns to the current position,
+    /// even if `f` fails.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the result of `f`), otherwise Err(io::Error). The error
+    /// of `f` is returned in preference to the error of the returning seek.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use mm_binary_io::binary_read::BinaryRead;
+    /// use mm_binary_io::endian::BigEndian;
+    /// use mm_binary_io::offset::FollowRead;
+    ///
+    /// let mut reader = io::Cursor::new(vec![0x00_u8, 0x01, 0x00, 0x02]);
+    /// reader.set_position(1);
+    ///
+    /// let value = reader.with_position_at(2, |r| r.read_integer::<BigEndian, u16>()).unwrap();
+    /// assert_eq!(2, value);
+    /// assert_eq!(1, reader.position());
+    ///
+    /// // The position is restored after an error too.
+    /// assert!(reader.with_position_at(3, |r| r.read_integer::<BigEndian, u16>()).is_err());
+    /// assert_eq!(1, reader.position());
+    ///
+    /// ```
+    ///
+    fn with_position_at<R, F>(&mut self, offset: u64, f: F) -> io::Result<R>
+        where Self: Sized,
+              F: FnOnce(&mut Self) -> io::Result<R> {
+        let position = self.stream_position()?;
+        let result = self.seek(io::SeekFrom::Start(offset)).and_then(|_| f(self));
+        let restored = self.seek(io::SeekFrom::Start(position));
+        let value = result?;
+        restored?;
+        Ok(value)
+    }
+}
+
+impl<T> FollowRead for T
+    where T: io::Read + io::Seek {}
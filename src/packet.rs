@@ -0,0 +1,235 @@
+//! Provides the builder and the parser of the datagrams in fixed buffers, such as the UDP packets.
+//!
+//! `PacketBuilder` writes into a buffer provided by the caller without allocating, and
+//! `PacketParser` reads a received datagram. They implement `io::Write` and `io::Read`, so the
+//! header code written on `BinaryWrite` and `BinaryRead` is shared with the stream transports.
+
+use std::io;
+
+use slice::OutOfBounds;
+
+/// Writes a datagram into a fixed buffer.
+///
+/// A write exceeding the buffer is an error of `WriteZero` whose inner error is `OutOfBounds`,
+/// and writes nothing, so that a packet is never sent with a truncated field. Seeking back over
+/// the written bytes patches them, such as a length field written before the payload.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Seek, SeekFrom, Write};
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::packet::PacketBuilder;
+///
+/// let mut buf = [0_u8; 8];
+/// let mut builder = PacketBuilder::new(&mut buf);
+/// builder.write_integer::<BigEndian, _>(0x0102_u16).unwrap();
+/// builder.write_integer::<BigEndian, _>(0_u16).unwrap();
+/// builder.write_all(b"abc").unwrap();
+///
+/// // Patches the payload length.
+/// builder.seek(SeekFrom::Start(2)).unwrap();
+/// builder.write_integer::<BigEndian, _>(3_u16).unwrap();
+/// builder.seek(SeekFrom::End(0)).unwrap();
+/// assert_eq!(&[0x01, 0x02, 0x00, 0x03, b'a', b'b', b'c'], builder.written());
+///
+/// // The 2 bytes do not fit in the last byte, and nothing is written.
+/// let error = builder.write_integer::<BigEndian, _>(0_u16).unwrap_err();
+/// assert_eq!(io::ErrorKind::WriteZero, error.kind());
+/// assert_eq!(7, builder.len());
+///
+/// ```
+///
+#[derive(Debug)]
+pub struct PacketBuilder<'a> {
+    bytes: &'a mut [u8],
+    position: usize,
+    len: usize,
+}
+
+impl<'a> PacketBuilder<'a> {
+    /// Creates a new `PacketBuilder` writing `bytes` from the head.
+    pub fn new(bytes: &'a mut [u8]) -> PacketBuilder<'a> {
+        PacketBuilder { bytes, position: 0, len: 0 }
+    }
+
+    /// Returns the position of the next write.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the byte count written.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if nothing is written.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the byte count of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns the byte count left after the position.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    /// Returns the bytes written.
+    pub fn written(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    /// Unwraps this `PacketBuilder`, returning the bytes written.
+    pub fn into_written(self) -> &'a mut [u8] {
+        &mut self.bytes[..self.len]
+    }
+}
+
+impl<'a> io::Write for PacketBuilder<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.remaining() {
+            trace_event!(DEBUG, required = buf.len(), remaining = self.remaining(), "packet buffer is full");
+            let error = OutOfBounds { required: buf.len(), remaining: self.remaining() };
+            return Err(io::Error::new(io::ErrorKind::WriteZero, error));
+        }
+        let start = self.position;
+        self.bytes[start..start + buf.len()].copy_from_slice(buf);
+        self.position += buf.len();
+        self.len = self.len.max(self.position);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> io::Seek for PacketBuilder<'a> {
+    /// Seeks within the bytes written, whose end is `SeekFrom::End(0)`.
+    ///
+    /// Seeking outside them is an error of `InvalidInput`.
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.position = seek_position(pos, self.position, self.len)?;
+        Ok(self.position as u64)
+    }
+}
+
+/// Reads a datagram from a byte slice.
+///
+/// The byte arrays of `read_bytes` are borrowed from the slice, so that the payload is not copied.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::packet::PacketParser;
+///
+/// let datagram = [0x01_u8, 0x02, 0x00, 0x03, b'a', b'b', b'c'];
+/// let mut parser = PacketParser::new(&datagram);
+/// assert_eq!(0x0102, parser.read_integer::<BigEndian, u16>().unwrap());
+/// let len = parser.read_integer::<BigEndian, u16>().unwrap();
+/// assert_eq!(&b"abc"[..], parser.read_bytes(len as usize).unwrap());
+/// assert_eq!(0, parser.remaining());
+///
+/// // A truncated datagram is an error of `UnexpectedEof`.
+/// let mut parser = PacketParser::new(&datagram[..3]);
+/// parser.read_integer::<BigEndian, u16>().unwrap();
+/// let error = parser.read_integer::<BigEndian, u16>().unwrap_err();
+/// assert_eq!(io::ErrorKind::UnexpectedEof, error.kind());
+///
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct PacketParser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PacketParser<'a> {
+    /// Creates a new `PacketParser` reading `bytes` from the head.
+    pub fn new(bytes: &'a [u8]) -> PacketParser<'a> {
+        PacketParser { bytes, position: 0 }
+    }
+
+    /// Returns the byte count read.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the byte count left.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    /// Returns the bytes left, without consuming them.
+    pub fn remaining_bytes(&self) -> &'a [u8] {
+        &self.bytes[self.position..]
+    }
+
+    /// Reads `len` bytes borrowed from the datagram.
+    ///
+    /// # Errors
+    ///
+    /// If the datagram is too short, returns Err(io::Error) of `UnexpectedEof` whose inner error
+    /// is `OutOfBounds`, and consumes nothing.
+    ///
+    pub fn read_bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if len > self.remaining() {
+            let error = OutOfBounds { required: len, remaining: self.remaining() };
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, error));
+        }
+        let start = self.position;
+        self.position += len;
+        Ok(&self.bytes[start..start + len])
+    }
+}
+
+impl<'a> io::Read for PacketParser<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.remaining());
+        buf[..n].copy_from_slice(&self.bytes[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+impl<'a> io::BufRead for PacketParser<'a> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.remaining_bytes())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position = (self.position + amt).min(self.bytes.len());
+    }
+}
+
+impl<'a> io::Seek for PacketParser<'a> {
+    /// Seeks within the datagram.
+    ///
+    /// Seeking outside it is an error of `InvalidInput`.
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.position = seek_position(pos, self.position, self.bytes.len())?;
+        Ok(self.position as u64)
+    }
+}
+
+/// Resolves `pos` against the `current` position, checking that it is within `0..=end`.
+fn seek_position(pos: io::SeekFrom, current: usize, end: usize) -> io::Result<usize> {
+    let (base, offset) = match pos {
+        io::SeekFrom::Start(offset) => (0, i128::from(offset)),
+        io::SeekFrom::End(offset) => (end, i128::from(offset)),
+        io::SeekFrom::Current(offset) => (current, i128::from(offset)),
+    };
+    let position = base as i128 + offset;
+    if position < 0 || position > end as i128 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "position is outside the packet"));
+    }
+    Ok(position as usize)
+}
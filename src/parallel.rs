@@ -0,0 +1,73 @@
+//! Provides the features to decode very large integer and float arrays from an in-memory byte
+//! slice in parallel by rayon.
+//!
+//! The slice is split into chunks of at least `MIN_CHUNK_ELEMENTS` elements, which are converted
+//! concurrently on the rayon thread pool, keeping the order of the elements.
+//!
+//! This module is available with the `rayon` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use mm_binary_io::endian::{BigEndian, LittleEndian};
+//! use mm_binary_io::parallel;
+//!
+//! let bytes: Vec<u8> = (0..200_000_u32).flat_map(|i| i.to_be_bytes().to_vec()).collect();
+//!
+//! let values = parallel::decode_integer_array::<BigEndian, u32>(&bytes).unwrap();
+//! assert_eq!(200_000, values.len());
+//! assert_eq!(123_456, values[123_456]);
+//!
+//! let values = parallel::decode_f32_array::<LittleEndian>(&[0x00, 0x00, 0x80, 0x3F]).unwrap();
+//! assert_eq!(vec![1.0], values);
+//!
+//! assert_eq!(None, parallel::decode_f64_array::<BigEndian>(&bytes[..7]));
+//!
+//! ```
+
+use std::mem;
+
+use rayon::prelude::*;
+
+use endian::Endian;
+use from_bytes::FromBytes;
+
+/// The minimum number of the elements converted by one task.
+pub const MIN_CHUNK_ELEMENTS: usize = 64 * 1024;
+
+/// Decodes `bytes` as an array of `TInt` in parallel.
+///
+/// Returns None if the length of `bytes` is not a multiple of the size of `TInt`.
+pub fn decode_integer_array<TEndian, TInt>(bytes: &[u8]) -> Option<Vec<TInt>>
+    where TEndian: Endian,
+          TInt: FromBytes<OutputType=TInt> + Send {
+    decode_array(bytes, mem::size_of::<TInt>(), TInt::from_bytes::<TEndian>)
+}
+
+/// Decodes `bytes` as an array of IEEE 754 `f32` in parallel.
+///
+/// Returns None if the length of `bytes` is not a multiple of 4.
+pub fn decode_f32_array<TEndian>(bytes: &[u8]) -> Option<Vec<f32>>
+    where TEndian: Endian {
+    decode_array(bytes, 4, |chunk| f32::from_bits(TEndian::u32_from_bytes(chunk)))
+}
+
+/// Decodes `bytes` as an array of IEEE 754 `f64` in parallel.
+///
+/// Returns None if the length of `bytes` is not a multiple of 8.
+pub fn decode_f64_array<TEndian>(bytes: &[u8]) -> Option<Vec<f64>>
+    where TEndian: Endian {
+    decode_array(bytes, 8, |chunk| f64::from_bits(TEndian::u64_from_bytes(chunk)))
+}
+
+fn decode_array<T, TDecode>(bytes: &[u8], element_size: usize, decode: TDecode) -> Option<Vec<T>>
+    where T: Send,
+          TDecode: Fn(&[u8]) -> T + Sync + Send {
+    if !bytes.len().is_multiple_of(element_size) {
+        return None;
+    }
+    Some(bytes.par_chunks_exact(element_size)
+        .with_min_len(MIN_CHUNK_ELEMENTS)
+        .map(decode)
+        .collect())
+}
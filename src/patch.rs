@@ -0,0 +1,192 @@
+//! Provides the byte-level diff of two streams, and the patches applied through `FileWrite`, for
+//! the firmware and asset patching.
+//!
+//! The diff compares the bytes at the same offsets, so a `Patch` records the overwritten runs and
+//! the length change, not the insertions and the deletions. It keeps the old bytes too, so that
+//! it can be checked and reverted.
+
+use std::cmp;
+use std::io;
+
+use binary_read::read_fully;
+use file::FileWrite;
+
+/// The byte count compared at a time.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A run of the bytes which differ.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hunk {
+    /// The offset of the run.
+    pub offset: u64,
+    /// The old bytes, shorter than `new` if the run is past the old end.
+    pub old: Vec<u8>,
+    /// The new bytes, shorter than `old` if the run is past the new end.
+    pub new: Vec<u8>,
+}
+
+/// The difference of two streams.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::patch::{self, Hunk};
+///
+/// let old = b"firmware v1.0\x00\x00";
+/// let new = b"firmware v1.2\x00";
+/// let patch = patch::diff(&mut &old[..], &mut &new[..]).unwrap();
+/// assert_eq!(vec![
+///     Hunk { offset: 12, old: b"0".to_vec(), new: b"2".to_vec() },
+///     Hunk { offset: 14, old: b"\x00".to_vec(), new: vec![] },
+/// ], patch.hunks);
+///
+/// let mut bytes = old.to_vec();
+/// patch.apply_to_vec(&mut bytes).unwrap();
+/// assert_eq!(new.to_vec(), bytes);
+/// patch.inverse().apply_to_vec(&mut bytes).unwrap();
+/// assert_eq!(old.to_vec(), bytes);
+///
+/// // The patch is of the old length.
+/// assert_eq!(io::ErrorKind::InvalidInput, patch.apply_to_vec(&mut vec![0; 3]).unwrap_err().kind());
+///
+/// ```
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Patch {
+    /// The length of the old stream.
+    pub old_len: u64,
+    /// The length of the new stream.
+    pub new_len: u64,
+    /// The runs of the bytes which differ, in the offset order.
+    pub hunks: Vec<Hunk>,
+}
+
+impl Patch {
+    /// Returns true if the streams are the same.
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty() && self.old_len == self.new_len
+    }
+
+    /// Returns the patch from the new stream to the old stream.
+    pub fn inverse(&self) -> Patch {
+        Patch {
+            old_len: self.new_len,
+            new_len: self.old_len,
+            hunks: self.hunks.iter()
+                .map(|hunk| Hunk { offset: hunk.offset, old: hunk.new.clone(), new: hunk.old.clone() })
+                .collect(),
+        }
+    }
+
+    /// Applies this patch to the file of `writer`, which must be of the old stream.
+    ///
+    /// The file is truncated if the new stream is shorter.
+    ///
+    /// # Errors
+    ///
+    /// If the file length is not `old_len`, returns Err(io::Error) of `InvalidInput` and writes
+    /// nothing. If writing fails, returns Err(io::Error), after which the file is patched partly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::env;
+    /// use std::fs;
+    /// use mm_binary_io::file::FileWrite;
+    /// use mm_binary_io::patch;
+    ///
+    /// let path = env::temp_dir().join("mm_binary_io_patch_example.bin");
+    /// fs::write(&path, b"asset 0001 blue").unwrap();
+    ///
+    /// let patch = patch::diff(&mut &b"asset 0001 blue"[..], &mut &b"asset 0002 red"[..]).unwrap();
+    /// let file = fs::OpenOptions::new().write(true).open(&path).unwrap();
+    /// patch.apply(&mut FileWrite::from_file(file)).unwrap();
+    /// assert_eq!(b"asset 0002 red".to_vec(), fs::read(&path).unwrap());
+    ///
+    /// fs::remove_file(&path).unwrap();
+    ///
+    /// ```
+    ///
+    pub fn apply(&self, writer: &mut FileWrite) -> io::Result<()> {
+        check_len(writer.get_ref().metadata()?.len(), self.old_len)?;
+        trace_event!(DEBUG, hunks = self.hunks.len(), old_len = self.old_len, new_len = self.new_len, "apply patch");
+        for hunk in &self.hunks {
+            writer.write_all_at(hunk.offset, &hunk.new)?;
+        }
+        io::Write::flush(writer)?;
+        if self.new_len < self.old_len {
+            writer.get_ref().set_len(self.new_len)?;
+        }
+        Ok(())
+    }
+
+    /// Applies this patch to `bytes` of the old stream.
+    ///
+    /// # Errors
+    ///
+    /// If the length of `bytes` is not `old_len`, returns Err(io::Error) of `InvalidInput` and
+    /// changes nothing.
+    ///
+    pub fn apply_to_vec(&self, bytes: &mut Vec<u8>) -> io::Result<()> {
+        check_len(bytes.len() as u64, self.old_len)?;
+        bytes.resize(cmp::max(self.old_len, self.new_len) as usize, 0);
+        for hunk in &self.hunks {
+            let start = hunk.offset as usize;
+            bytes[start..start + hunk.new.len()].copy_from_slice(&hunk.new);
+        }
+        bytes.truncate(self.new_len as usize);
+        Ok(())
+    }
+}
+
+/// Compares the streams `old` and `new` to their ends, and returns the patch from `old` to `new`.
+///
+/// # Errors
+///
+/// If the function succeeds then Ok(Patch), otherwise Err(io::Error).
+///
+pub fn diff<TOld, TNew>(old: &mut TOld, new: &mut TNew) -> io::Result<Patch>
+    where TOld: io::Read,
+          TNew: io::Read {
+    let mut patch = Patch::default();
+    let mut old_chunk = vec![0_u8; CHUNK_SIZE];
+    let mut new_chunk = vec![0_u8; CHUNK_SIZE];
+    let mut open: Option<Hunk> = None;
+    loop {
+        let old_read = read_fully(old, &mut old_chunk)?;
+        let new_read = read_fully(new, &mut new_chunk)?;
+        if old_read == 0 && new_read == 0 {
+            break;
+        }
+        // The streams are compared while both have bytes, as a short read is the end.
+        let offset = cmp::max(patch.old_len, patch.new_len);
+        let common = cmp::min(old_read, new_read);
+        for (i, (&a, &b)) in old_chunk[..common].iter().zip(&new_chunk[..common]).enumerate() {
+            if a == b {
+                patch.hunks.extend(open.take());
+                continue;
+            }
+            let hunk = open.get_or_insert_with(|| Hunk { offset: offset + i as u64, old: vec![], new: vec![] });
+            hunk.old.push(a);
+            hunk.new.push(b);
+        }
+        if old_read != new_read {
+            let hunk = open.get_or_insert_with(|| Hunk { offset: offset + common as u64, old: vec![], new: vec![] });
+            hunk.old.extend_from_slice(&old_chunk[common..old_read]);
+            hunk.new.extend_from_slice(&new_chunk[common..new_read]);
+        }
+        patch.old_len += old_read as u64;
+        patch.new_len += new_read as u64;
+    }
+    patch.hunks.extend(open);
+    Ok(patch)
+}
+
+fn check_len(len: u64, old_len: u64) -> io::Result<()> {
+    if len != old_len {
+        let message = format!("patch is of {} bytes, but the target is {} bytes", old_len, len);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+    }
+    Ok(())
+}
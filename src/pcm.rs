@@ -0,0 +1,157 @@
+//! Provides the features to read and write the arrays of 24-bit PCM samples.
+//!
+//! The 24-bit samples of WAV, AIFF and FLAC are 3-byte signed integers, which are sign-extended
+//! to `i32`. The arrays are decoded by chunks staged in the scratch buffer, which is much faster
+//! than reading the samples one by one.
+
+use std::io;
+
+use endian::{self, Endian};
+use scratch;
+
+/// The sample count staged by one read or write.
+const CHUNK_SAMPLES: usize = 16 * 1024;
+
+/// The smallest value of a 24-bit sample.
+pub const I24_MIN: i32 = -0x80_0000;
+
+/// The largest value of a 24-bit sample.
+pub const I24_MAX: i32 = 0x7F_FFFF;
+
+/// Decodes the 3-byte signed samples of `bytes` into `destination`, sign-extending them.
+///
+/// # Panics
+///
+/// If the length of `bytes` is not 3 times the length of `destination`, the function panics.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::endian::{BigEndian, LittleEndian};
+/// use mm_binary_io::pcm;
+///
+/// let mut samples = [0_i32; 2];
+/// pcm::decode_i24::<LittleEndian>(&[0x56, 0x34, 0x12, 0xFF, 0xFF, 0xFF], &mut samples);
+/// assert_eq!([0x12_3456, -1], samples);
+///
+/// pcm::decode_i24::<BigEndian>(&[0x80, 0x00, 0x00, 0x7F, 0xFF, 0xFF], &mut samples);
+/// assert_eq!([pcm::I24_MIN, pcm::I24_MAX], samples);
+///
+/// let mut bytes = [0_u8; 6];
+/// pcm::encode_i24::<BigEndian>(&samples, &mut bytes);
+/// assert_eq!([0x80, 0x00, 0x00, 0x7F, 0xFF, 0xFF], bytes);
+///
+/// ```
+///
+pub fn decode_i24<TEndian>(bytes: &[u8], destination: &mut [i32])
+    where TEndian: Endian {
+    assert_eq!(bytes.len(), destination.len() * 3, "bytes must be 3 times the samples");
+    let samples = bytes.chunks_exact(3).zip(destination.iter_mut());
+    if endian::is_big_endian::<TEndian>() {
+        for (b, sample) in samples {
+            *sample = (i32::from(b[0]) << 24 | i32::from(b[1]) << 16 | i32::from(b[2]) << 8) >> 8;
+        }
+    } else {
+        for (b, sample) in samples {
+            *sample = (i32::from(b[2]) << 24 | i32::from(b[1]) << 16 | i32::from(b[0]) << 8) >> 8;
+        }
+    }
+}
+
+/// Encodes `samples` into the 3-byte signed samples of `destination`, keeping the low 24 bits.
+///
+/// # Panics
+///
+/// If the length of `destination` is not 3 times the length of `samples`, the function panics.
+///
+pub fn encode_i24<TEndian>(samples: &[i32], destination: &mut [u8])
+    where TEndian: Endian {
+    assert_eq!(destination.len(), samples.len() * 3, "destination must be 3 times the samples");
+    let samples = destination.chunks_exact_mut(3).zip(samples);
+    if endian::is_big_endian::<TEndian>() {
+        for (b, &sample) in samples {
+            b.copy_from_slice(&[(sample >> 16) as u8, (sample >> 8) as u8, sample as u8]);
+        }
+    } else {
+        for (b, &sample) in samples {
+            b.copy_from_slice(&[sample as u8, (sample >> 8) as u8, (sample >> 16) as u8]);
+        }
+    }
+}
+
+/// Provides the features to read the PCM samples.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::pcm::{PcmRead, PcmWrite};
+///
+/// let samples = vec![0, 1, -1, 0x7F_FFFF, -0x80_0000];
+///
+/// let mut writer = vec![];
+/// writer.write_i24_samples::<LittleEndian>(&samples).unwrap();
+/// assert_eq!(vec![0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0xFF, 0xFF, 0xFF], writer[..9].to_vec());
+/// assert!(writer.write_i24_samples::<LittleEndian>(&[0x80_0000]).is_err());
+///
+/// let mut reader = io::Cursor::new(writer);
+/// assert_eq!(samples, reader.read_i24_samples::<LittleEndian>(5).unwrap());
+///
+/// ```
+///
+pub trait PcmRead: io::Read {
+    /// Reads `sample_count` 3-byte signed samples, sign-extended to `i32`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the samples), otherwise Err(io::Error).
+    ///
+    fn read_i24_samples<TEndian>(&mut self, sample_count: usize) -> io::Result<Vec<i32>>
+        where Self: Sized,
+              TEndian: Endian {
+        let mut samples = Vec::with_capacity(sample_count.min(CHUNK_SAMPLES));
+        while samples.len() < sample_count {
+            let start = samples.len();
+            let n = (sample_count - start).min(CHUNK_SAMPLES);
+            samples.resize(start + n, 0);
+            scratch::with_scratch(n * 3, |buf| -> io::Result<()> {
+                self.read_exact(buf)?;
+                decode_i24::<TEndian>(buf, &mut samples[start..]);
+                Ok(())
+            })?;
+        }
+        Ok(samples)
+    }
+}
+
+impl<T> PcmRead for T
+    where T: io::Read {}
+
+/// Provides the features to write the PCM samples.
+pub trait PcmWrite: io::Write {
+    /// Writes `samples` as 3-byte signed samples.
+    ///
+    /// # Errors
+    ///
+    /// If a sample is outside `I24_MIN` to `I24_MAX`, returns Err(io::Error) of `InvalidInput`
+    /// before writing. If writing fails, returns Err(io::Error).
+    ///
+    fn write_i24_samples<TEndian>(&mut self, samples: &[i32]) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian {
+        if samples.iter().any(|&sample| !(I24_MIN..=I24_MAX).contains(&sample)) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "sample does not fit in 24 bits"));
+        }
+        for chunk in samples.chunks(CHUNK_SAMPLES) {
+            scratch::with_scratch(chunk.len() * 3, |buf| {
+                encode_i24::<TEndian>(chunk, buf);
+                self.write_all(buf)
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> PcmWrite for T
+    where T: io::Write {}
@@ -0,0 +1,235 @@
+//! Provides the features to read and write the arrays of packed pixels, converting them to and
+//! from `Rgba8`.
+//!
+//! A pixel is packed into an integer of 2, 3 or 4 bytes in the byte order of `TEndian`, with the
+//! first channel of the format name in the highest bits. So `Rgb888` in `LittleEndian` is the
+//! `B, G, R` byte order of BMP, and `Argb8888` in `LittleEndian` is the `B, G, R, A` byte order of
+//! the Windows and the Direct3D framebuffers. `ChannelOrder::Bgr` swaps the red and the blue.
+
+use std::io;
+
+use binary_read::BinaryRead;
+use endian::{self, Endian};
+
+/// A pixel of 8-bit red, green, blue and alpha channels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgba8 {
+    /// The red channel.
+    pub r: u8,
+    /// The green channel.
+    pub g: u8,
+    /// The blue channel.
+    pub b: u8,
+    /// The alpha channel, where 0xFF is opaque.
+    pub a: u8,
+}
+
+impl Rgba8 {
+    /// Creates a new `Rgba8` of the channels.
+    pub fn new(r: u8, g: u8, b: u8, a: u8) -> Rgba8 {
+        Rgba8 { r, g, b, a }
+    }
+}
+
+/// The layout of a packed pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PixelFormat {
+    /// 16 bits of 5-bit red, 6-bit green and 5-bit blue. The alpha is opaque.
+    Rgb565,
+    /// 24 bits of 8-bit red, green and blue. The alpha is opaque.
+    Rgb888,
+    /// 32 bits of 8-bit red, green, blue and alpha.
+    Rgba8888,
+    /// 32 bits of 8-bit alpha, red, green and blue.
+    Argb8888,
+}
+
+/// The order of the color channels in a packed pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChannelOrder {
+    /// The red, green and blue order of the format name.
+    Rgb,
+    /// The red and the blue are swapped: `Rgb565` is BGR565, and `Argb8888` is ABGR8888.
+    Bgr,
+}
+
+impl PixelFormat {
+    /// Returns the byte size of a pixel.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Rgb565 => 2,
+            PixelFormat::Rgb888 => 3,
+            PixelFormat::Rgba8888 | PixelFormat::Argb8888 => 4,
+        }
+    }
+
+    /// Converts the packed `value` to `Rgba8`.
+    ///
+    /// The 5-bit and 6-bit channels are scaled by repeating their high bits, so that the maximum
+    /// becomes 0xFF.
+    pub fn decode(self, value: u32, order: ChannelOrder) -> Rgba8 {
+        let (first, g, last, a) = match self {
+            PixelFormat::Rgb565 => {
+                let (r, g, b) = (value >> 11 & 0x1F, value >> 5 & 0x3F, value & 0x1F);
+                ((r << 3 | r >> 2) as u8, (g << 2 | g >> 4) as u8, (b << 3 | b >> 2) as u8, 0xFF)
+            }
+            PixelFormat::Rgb888 => ((value >> 16) as u8, (value >> 8) as u8, value as u8, 0xFF),
+            PixelFormat::Rgba8888 => ((value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8),
+            PixelFormat::Argb8888 => ((value >> 16) as u8, (value >> 8) as u8, value as u8, (value >> 24) as u8),
+        };
+        match order {
+            ChannelOrder::Rgb => Rgba8::new(first, g, last, a),
+            ChannelOrder::Bgr => Rgba8::new(last, g, first, a),
+        }
+    }
+
+    /// Converts `pixel` to the packed value.
+    ///
+    /// The channels narrower than 8 bits keep the high bits, and the alpha is dropped by the
+    /// formats without it.
+    pub fn encode(self, pixel: Rgba8, order: ChannelOrder) -> u32 {
+        let (first, g, last) = match order {
+            ChannelOrder::Rgb => (u32::from(pixel.r), u32::from(pixel.g), u32::from(pixel.b)),
+            ChannelOrder::Bgr => (u32::from(pixel.b), u32::from(pixel.g), u32::from(pixel.r)),
+        };
+        let a = u32::from(pixel.a);
+        match self {
+            PixelFormat::Rgb565 => (first >> 3) << 11 | (g >> 2) << 5 | last >> 3,
+            PixelFormat::Rgb888 => first << 16 | g << 8 | last,
+            PixelFormat::Rgba8888 => first << 24 | g << 16 | last << 8 | a,
+            PixelFormat::Argb8888 => a << 24 | first << 16 | g << 8 | last,
+        }
+    }
+}
+
+/// Decodes the packed pixels of `bytes`.
+///
+/// Returns None if the length of `bytes` is not a multiple of the pixel size.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::endian::{BigEndian, LittleEndian};
+/// use mm_binary_io::pixels::{self, ChannelOrder, PixelFormat, Rgba8};
+///
+/// // The red and the blue of RGB565, stored little-endian.
+/// let pixels = pixels::decode_pixels::<LittleEndian>(&[0x00, 0xF8, 0x1F, 0x00], PixelFormat::Rgb565, ChannelOrder::Rgb);
+/// assert_eq!(Some(vec![Rgba8::new(0xFF, 0, 0, 0xFF), Rgba8::new(0, 0, 0xFF, 0xFF)]), pixels);
+///
+/// // The B, G, R bytes of BMP.
+/// let pixels = pixels::decode_pixels::<LittleEndian>(&[0x30, 0x20, 0x10], PixelFormat::Rgb888, ChannelOrder::Rgb);
+/// assert_eq!(Some(vec![Rgba8::new(0x10, 0x20, 0x30, 0xFF)]), pixels);
+///
+/// let pixel = Rgba8::new(0x10, 0x20, 0x30, 0x80);
+/// let bytes = pixels::encode_pixels::<BigEndian>(&[pixel], PixelFormat::Argb8888, ChannelOrder::Bgr);
+/// assert_eq!(vec![0x80, 0x30, 0x20, 0x10], bytes);
+/// assert_eq!(Some(vec![pixel]), pixels::decode_pixels::<BigEndian>(&bytes, PixelFormat::Argb8888, ChannelOrder::Bgr));
+///
+/// assert_eq!(None, pixels::decode_pixels::<BigEndian>(&bytes[..3], PixelFormat::Rgba8888, ChannelOrder::Rgb));
+///
+/// ```
+///
+pub fn decode_pixels<TEndian>(bytes: &[u8], format: PixelFormat, order: ChannelOrder) -> Option<Vec<Rgba8>>
+    where TEndian: Endian {
+    let size = format.bytes_per_pixel();
+    if !bytes.len().is_multiple_of(size) {
+        return None;
+    }
+    Some(bytes.chunks_exact(size)
+        .map(|chunk| format.decode(packed_from_bytes::<TEndian>(chunk), order))
+        .collect())
+}
+
+/// Encodes `pixels` as the packed pixels.
+pub fn encode_pixels<TEndian>(pixels: &[Rgba8], format: PixelFormat, order: ChannelOrder) -> Vec<u8>
+    where TEndian: Endian {
+    let size = format.bytes_per_pixel();
+    let mut bytes = vec![0_u8; pixels.len() * size];
+    for (chunk, pixel) in bytes.chunks_exact_mut(size).zip(pixels) {
+        packed_to_bytes::<TEndian>(format.encode(*pixel, order), chunk);
+    }
+    bytes
+}
+
+/// Provides the features to read the packed pixels.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::pixels::{ChannelOrder, PixelFormat, PixelRead, PixelWrite, Rgba8};
+///
+/// let pixels = vec![Rgba8::new(0x10, 0x20, 0x30, 0x40), Rgba8::new(0xFF, 0xFF, 0xFF, 0xFF)];
+///
+/// let mut writer = vec![];
+/// writer.write_pixels::<LittleEndian>(&pixels, PixelFormat::Rgba8888, ChannelOrder::Rgb).unwrap();
+/// assert_eq!(vec![0x40, 0x30, 0x20, 0x10], writer[..4].to_vec());
+///
+/// let mut reader = io::Cursor::new(writer);
+/// assert_eq!(pixels, reader.read_pixels::<LittleEndian>(2, PixelFormat::Rgba8888, ChannelOrder::Rgb).unwrap());
+///
+/// ```
+///
+pub trait PixelRead: io::Read {
+    /// Reads `pixel_count` packed pixels.
+    ///
+    /// # Errors
+    ///
+    /// If the byte size of the pixels overflows, returns Err(io::Error) of `InvalidInput`.
+    /// If reading fails, returns Err(io::Error).
+    ///
+    fn read_pixels<TEndian>(&mut self, pixel_count: usize, format: PixelFormat, order: ChannelOrder) -> io::Result<Vec<Rgba8>>
+        where Self: Sized,
+              TEndian: Endian {
+        let len = pixel_count.checked_mul(format.bytes_per_pixel())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "pixel count is too large"))?;
+        let bytes = self.read_byte_array(len)?;
+        Ok(decode_pixels::<TEndian>(&bytes, format, order).unwrap_or_default())
+    }
+}
+
+impl<T> PixelRead for T
+    where T: io::Read {}
+
+/// Provides the features to write the packed pixels.
+pub trait PixelWrite: io::Write {
+    /// Writes `pixels` as the packed pixels.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    fn write_pixels<TEndian>(&mut self, pixels: &[Rgba8], format: PixelFormat, order: ChannelOrder) -> io::Result<()>
+        where TEndian: Endian {
+        self.write_all(&encode_pixels::<TEndian>(pixels, format, order))
+    }
+}
+
+impl<T> PixelWrite for T
+    where T: io::Write {}
+
+/// Converts the packed pixel of 2 to 4 `bytes` to the value.
+fn packed_from_bytes<TEndian>(bytes: &[u8]) -> u32
+    where TEndian: Endian {
+    let mut buf = [0_u8; 4];
+    if endian::is_big_endian::<TEndian>() {
+        buf[4 - bytes.len()..].copy_from_slice(bytes);
+    } else {
+        buf[..bytes.len()].copy_from_slice(bytes);
+    }
+    TEndian::u32_from_bytes(&buf)
+}
+
+/// Converts the packed pixel `value` to the bytes of the length of `destination`.
+fn packed_to_bytes<TEndian>(value: u32, destination: &mut [u8])
+    where TEndian: Endian {
+    let mut buf = [0_u8; 4];
+    TEndian::u32_to_bytes(value, &mut buf);
+    let len = destination.len();
+    if endian::is_big_endian::<TEndian>() {
+        destination.copy_from_slice(&buf[4 - len..]);
+    } else {
+        destination.copy_from_slice(&buf[..len]);
+    }
+}
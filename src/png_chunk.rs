@@ -0,0 +1,219 @@
+//! Provides the features to read and write PNG-style chunks.
+//!
+//! A chunk is a big-endian u32 data length, a 4-byte chunk type, the data, and the big-endian
+//! CRC-32 of the chunk type and the data.
+
+use std::io;
+use std::io::Read;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use checksum_frame::ChecksumMismatch;
+use crc32::Crc32;
+use endian::BigEndian;
+
+/// The PNG file signature.
+pub const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// The maximum data length allowed by the PNG specification.
+pub const MAX_CHUNK_LEN: u32 = 0x7FFF_FFFF;
+
+/// A PNG-style chunk.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Chunk {
+    /// The chunk type (e.g. `*b"IHDR"`).
+    pub chunk_type: [u8; 4],
+    /// The chunk data.
+    pub data: Vec<u8>,
+}
+
+impl Chunk {
+    /// Creates a new `Chunk`.
+    pub fn new(chunk_type: [u8; 4], data: Vec<u8>) -> Chunk {
+        Chunk {
+            chunk_type,
+            data,
+        }
+    }
+
+    /// Returns true if the chunk is critical (the first letter of the type is uppercase).
+    pub fn is_critical(&self) -> bool {
+        self.chunk_type[0] & 0x20 == 0
+    }
+
+    /// Calculates the CRC-32 of the chunk.
+    pub fn crc(&self) -> u32 {
+        let mut crc = Crc32::new();
+        crc.update(&self.chunk_type);
+        crc.update(&self.data);
+        crc.digest()
+    }
+}
+
+/// Reads a chunk and verifies its CRC.
+///
+/// # Errors
+///
+/// If the stream ends cleanly before the chunk, returns Ok(None).
+/// If the length exceeds `MAX_CHUNK_LEN`, returns Err(io::Error) of `InvalidData`.
+/// If the CRC does not match, returns Err(io::Error) of `InvalidData` whose inner error is
+/// `ChecksumMismatch<u32>`.
+/// If the chunk is cut off, returns Err(io::Error) of `UnexpectedEof`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::png_chunk::{self, Chunk};
+///
+/// let mut writer = vec![];
+/// png_chunk::write_chunk(&mut writer, &Chunk::new(*b"IEND", vec![])).unwrap();
+/// assert_eq!(vec![0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82], writer);
+///
+/// let mut reader = io::Cursor::new(writer);
+/// assert_eq!(Some(Chunk::new(*b"IEND", vec![])), png_chunk::read_chunk(&mut reader).unwrap());
+/// assert_eq!(None, png_chunk::read_chunk(&mut reader).unwrap());
+///
+/// ```
+///
+pub fn read_chunk<TRead>(reader: &mut TRead) -> io::Result<Option<Chunk>>
+    where TRead: io::Read {
+    let mut first = [0_u8; 1];
+    loop {
+        match reader.read(&mut first) {
+            Ok(0) => return Ok(None),
+            Ok(_) => break,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    let mut reader = io::Cursor::new(first).chain(reader);
+    let len = reader.read_integer::<BigEndian, u32>()?;
+    if len > MAX_CHUNK_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "chunk is too long"));
+    }
+    let mut chunk_type = [0_u8; 4];
+    reader.read_exact(&mut chunk_type)?;
+    let mut data = vec![];
+    (&mut reader).take(len as u64).read_to_end(&mut data)?;
+    if data.len() < len as usize {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "chunk is cut off"));
+    }
+    let expected = reader.read_integer::<BigEndian, u32>()?;
+
+    let chunk = Chunk::new(chunk_type, data);
+    trace_event!(TRACE, chunk_type = %String::from_utf8_lossy(&chunk_type), len, "read PNG chunk");
+    let actual = chunk.crc();
+    if actual != expected {
+        trace_event!(DEBUG, expected, actual, "PNG chunk CRC mismatch");
+        return Err(io::Error::new(io::ErrorKind::InvalidData, ChecksumMismatch { expected, actual }));
+    }
+    Ok(Some(chunk))
+}
+
+/// Writes a chunk with its CRC.
+///
+/// # Errors
+///
+/// If the data is longer than `MAX_CHUNK_LEN`, returns Err(io::Error) of `InvalidInput`.
+/// If writing fails, returns Err(io::Error).
+///
+pub fn write_chunk<TWrite>(writer: &mut TWrite, chunk: &Chunk) -> io::Result<()>
+    where TWrite: io::Write {
+    if chunk.data.len() > MAX_CHUNK_LEN as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "chunk is too long"));
+    }
+    writer.write_integer::<BigEndian, _>(chunk.data.len() as u32)?;
+    writer.write_all(&chunk.chunk_type)?;
+    writer.write_all(&chunk.data)?;
+    writer.write_integer::<BigEndian, _>(chunk.crc())
+}
+
+/// Reads and verifies the PNG file signature.
+///
+/// # Errors
+///
+/// If the signature does not match, returns Err(io::Error) of `InvalidData`.
+///
+pub fn read_signature<TRead>(reader: &mut TRead) -> io::Result<()>
+    where TRead: io::Read {
+    let mut signature = [0_u8; 8];
+    reader.read_exact(&mut signature)?;
+    if signature != PNG_SIGNATURE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid PNG signature"));
+    }
+    Ok(())
+}
+
+/// Iterates the chunks in a stream.
+///
+/// The iteration stops at the clean end of the stream or after the `IEND` chunk.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::png_chunk::{self, Chunk, ChunkReader};
+///
+/// let mut data = png_chunk::PNG_SIGNATURE.to_vec();
+/// png_chunk::write_chunk(&mut data, &Chunk::new(*b"tEXt", b"a\0b".to_vec())).unwrap();
+/// png_chunk::write_chunk(&mut data, &Chunk::new(*b"IEND", vec![])).unwrap();
+/// data.extend_from_slice(b"trailing garbage");
+///
+/// let mut reader = io::Cursor::new(data);
+/// png_chunk::read_signature(&mut reader).unwrap();
+///
+/// let types = ChunkReader::new(reader)
+///     .map(|c| c.map(|c| c.chunk_type))
+///     .collect::<io::Result<Vec<_>>>()
+///     .unwrap();
+/// assert_eq!(vec![*b"tEXt", *b"IEND"], types);
+///
+/// ```
+///
+pub struct ChunkReader<TRead>
+    where TRead: io::Read {
+    reader: TRead,
+    finished: bool,
+}
+
+impl<TRead> ChunkReader<TRead>
+    where TRead: io::Read {
+    /// Creates a new `ChunkReader`.
+    pub fn new(reader: TRead) -> ChunkReader<TRead> {
+        ChunkReader {
+            reader,
+            finished: false,
+        }
+    }
+
+    /// Unwraps this `ChunkReader`, returning the underlying reader.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+}
+
+impl<TRead> Iterator for ChunkReader<TRead>
+    where TRead: io::Read {
+    type Item = io::Result<Chunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match read_chunk(&mut self.reader) {
+            Ok(Some(chunk)) => {
+                self.finished = chunk.chunk_type == *b"IEND";
+                Some(Ok(chunk))
+            }
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Provides the features to read and write `bytemuck::Pod` types, such as plain `#[repr(C)]`
+//! structs and arrays of them, directly.
+//!
+//! The bytes are read into or written from the value as is. If `TEndian` is not the native
+//! endianness, the fields are converted by `EndianSwap`, so the conversion costs nothing on the
+//! matching platforms.
+//!
+//! This module is available with the `bytemuck` feature.
+
+use std::io;
+
+use bytemuck::{self, Pod};
+
+use endian::Endian;
+
+/// Reverses the byte order of the fields of a value.
+///
+/// Implement this for a `#[repr(C)]` struct by swapping each field. Padding bytes are not
+/// allowed by `Pod`, so all bytes belong to a field.
+pub trait EndianSwap {
+    /// Reverses the byte order of each field.
+    fn swap_endian(&mut self);
+}
+
+macro_rules! impl_endian_swap_for_integer {
+    ($($t:ty),*) => {
+        $(
+            impl EndianSwap for $t {
+                fn swap_endian(&mut self) {
+                    *self = <$t>::swap_bytes(*self);
+                }
+            }
+        )*
+    }
+}
+
+impl_endian_swap_for_integer!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128);
+
+impl EndianSwap for f32 {
+    fn swap_endian(&mut self) {
+        *self = f32::from_bits(self.to_bits().swap_bytes());
+    }
+}
+
+impl EndianSwap for f64 {
+    fn swap_endian(&mut self) {
+        *self = f64::from_bits(self.to_bits().swap_bytes());
+    }
+}
+
+impl<T, const N: usize> EndianSwap for [T; N]
+    where T: EndianSwap {
+    fn swap_endian(&mut self) {
+        for value in self.iter_mut() {
+            value.swap_endian();
+        }
+    }
+}
+
+/// Provides the features to read `Pod` values.
+///
+/// # Examples
+///
+/// ```
+/// extern crate bytemuck;
+/// extern crate mm_binary_io;
+///
+/// use std::io;
+/// use mm_binary_io::endian::{BigEndian, LittleEndian};
+/// use mm_binary_io::pod::{EndianSwap, PodRead, PodWrite};
+///
+/// #[repr(C)]
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct Point { x: i16, y: i16, weight: f32 }
+///
+/// unsafe impl bytemuck::Zeroable for Point {}
+/// unsafe impl bytemuck::Pod for Point {}
+///
+/// impl EndianSwap for Point {
+///     fn swap_endian(&mut self) {
+///         self.x.swap_endian();
+///         self.y.swap_endian();
+///         self.weight.swap_endian();
+///     }
+/// }
+///
+/// # fn main() {
+/// let points = [Point { x: 1, y: -2, weight: 0.5 }, Point { x: 3, y: 4, weight: 2.0 }];
+///
+/// let mut writer = vec![];
+/// writer.write_pod_array::<BigEndian, _>(&points).unwrap();
+/// assert_eq!(vec![0x00, 0x01, 0xFF, 0xFE, 0x3F, 0x00, 0x00, 0x00], writer[..8].to_vec());
+///
+/// let mut reader = io::Cursor::new(writer);
+/// assert_eq!(points[0], reader.read_pod::<BigEndian, Point>().unwrap());
+/// assert_eq!(vec![points[1]], reader.read_pod_array::<BigEndian, Point>(1).unwrap());
+///
+/// let mut writer = vec![];
+/// writer.write_pod::<LittleEndian, _>(&[0x1234_u16, 0x5678]).unwrap();
+/// assert_eq!(vec![0x34, 0x12, 0x78, 0x56], writer);
+/// # }
+///
+/// ```
+///
+pub trait PodRead: io::Read {
+    /// Reads a value.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(T), otherwise Err(io::Error).
+    ///
+    fn read_pod<TEndian, T>(&mut self) -> io::Result<T>
+        where TEndian: Endian,
+              T: Pod + EndianSwap {
+        let mut value = T::zeroed();
+        self.read_exact(bytemuck::bytes_of_mut(&mut value))?;
+        if !TEndian::is_native() {
+            value.swap_endian();
+        }
+        Ok(value)
+    }
+
+    /// Reads an array of `element_count` values.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(Vec<T>), otherwise Err(io::Error).
+    ///
+    fn read_pod_array<TEndian, T>(&mut self, element_count: usize) -> io::Result<Vec<T>>
+        where TEndian: Endian,
+              T: Pod + EndianSwap {
+        let mut values = vec![T::zeroed(); element_count];
+        self.read_exact(bytemuck::cast_slice_mut(&mut values))?;
+        if !TEndian::is_native() {
+            for value in values.iter_mut() {
+                value.swap_endian();
+            }
+        }
+        Ok(values)
+    }
+}
+
+impl<T> PodRead for T
+    where T: io::Read {}
+
+/// Provides the features to write `Pod` values.
+pub trait PodWrite: io::Write {
+    /// Writes the `value`.
+    fn write_pod<TEndian, T>(&mut self, value: &T) -> io::Result<()>
+        where TEndian: Endian,
+              T: Pod + EndianSwap {
+        if TEndian::is_native() {
+            return self.write_all(bytemuck::bytes_of(value));
+        }
+        let mut value = *value;
+        value.swap_endian();
+        self.write_all(bytemuck::bytes_of(&value))
+    }
+
+    /// Writes the array of `values`.
+    fn write_pod_array<TEndian, T>(&mut self, values: &[T]) -> io::Result<()>
+        where TEndian: Endian,
+              T: Pod + EndianSwap {
+        if TEndian::is_native() {
+            return self.write_all(bytemuck::cast_slice(values));
+        }
+        for value in values {
+            self.write_pod::<TEndian, T>(value)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> PodWrite for T
+    where T: io::Write {}
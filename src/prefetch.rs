@@ -0,0 +1,177 @@
+//! Provides the reader which prefetches the next block on a background thread.
+//!
+//! While the current block is being parsed, the background thread reads the next one, hiding the
+//! IO latency of the streaming parsers of huge files. The blocks are double-buffered: one is
+//! parsed and one is read ahead, and their buffers are reused.
+
+use std::io;
+use std::mem;
+use std::sync::mpsc;
+use std::thread;
+
+/// The default block size of `PrefetchReader`.
+pub const DEFAULT_BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Reads the inner reader by blocks on a background thread, one block ahead of the caller.
+///
+/// `PrefetchReader` implements `io::Read` and `io::BufRead`, so all `BinaryRead` methods can be
+/// used. The background thread stops when the reader is dropped, after its current read.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Read};
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::prefetch::PrefetchReader;
+///
+/// let data: Vec<u8> = (0..100_u8).collect();
+/// let mut reader = PrefetchReader::with_block_size(16, io::Cursor::new(data));
+///
+/// assert_eq!(0x0001, reader.read_integer::<BigEndian, u16>().unwrap());
+/// assert_eq!((2..20).collect::<Vec<u8>>(), reader.read_byte_array(18).unwrap());
+/// assert_eq!(20, reader.position());
+///
+/// let mut rest = vec![];
+/// reader.read_to_end(&mut rest).unwrap();
+/// assert_eq!(80, rest.len());
+///
+/// let inner = reader.into_inner().unwrap();
+/// assert_eq!(100, inner.position());
+///
+/// ```
+///
+pub struct PrefetchReader<TRead>
+    where TRead: io::Read + Send + 'static {
+    blocks: mpsc::Receiver<io::Result<Vec<u8>>>,
+    recycled: mpsc::Sender<Vec<u8>>,
+    current: Vec<u8>,
+    start: usize,
+    eof: bool,
+    position: u64,
+    worker: thread::JoinHandle<TRead>,
+}
+
+impl<TRead> PrefetchReader<TRead>
+    where TRead: io::Read + Send + 'static {
+    /// Creates a new `PrefetchReader` with the blocks of `DEFAULT_BLOCK_SIZE`, and starts
+    /// prefetching.
+    pub fn new(reader: TRead) -> PrefetchReader<TRead> {
+        PrefetchReader::with_block_size(DEFAULT_BLOCK_SIZE, reader)
+    }
+
+    /// Creates a new `PrefetchReader` with the blocks of `block_size` bytes, and starts
+    /// prefetching.
+    ///
+    /// # Panics
+    ///
+    /// If `block_size` is 0, the function panics.
+    ///
+    pub fn with_block_size(block_size: usize, reader: TRead) -> PrefetchReader<TRead> {
+        assert!(block_size > 0, "block size must not be 0");
+        let (block_sender, blocks) = mpsc::sync_channel(1);
+        let (recycled, recycled_receiver) = mpsc::channel();
+        let worker = thread::spawn(move || prefetch(reader, block_size, block_sender, recycled_receiver));
+        PrefetchReader {
+            blocks,
+            recycled,
+            current: vec![],
+            start: 0,
+            eof: false,
+            position: 0,
+            worker,
+        }
+    }
+
+    /// Returns the byte count read through this reader.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Stops prefetching, and returns the inner reader.
+    ///
+    /// The function waits for the current read of the background thread. The prefetched data
+    /// is lost, so the position of the inner reader may be ahead of `position()`.
+    ///
+    /// # Errors
+    ///
+    /// If the background thread panicked, returns Err(io::Error).
+    ///
+    pub fn into_inner(self) -> io::Result<TRead> {
+        let PrefetchReader { blocks, worker, .. } = self;
+        drop(blocks);
+        worker.join().map_err(|_| io::Error::other("the prefetch thread panicked"))
+    }
+}
+
+impl<TRead> io::Read for PrefetchReader<TRead>
+    where TRead: io::Read + Send + 'static {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = {
+            let available = io::BufRead::fill_buf(self)?;
+            let len = available.len().min(buf.len());
+            buf[..len].copy_from_slice(&available[..len]);
+            len
+        };
+        io::BufRead::consume(self, len);
+        Ok(len)
+    }
+}
+
+impl<TRead> io::BufRead for PrefetchReader<TRead>
+    where TRead: io::Read + Send + 'static {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.start == self.current.len() && !self.eof {
+            let block = match self.blocks.recv() {
+                Ok(block) => block?,
+                Err(_) => return Err(io::Error::other("the prefetch thread stopped")),
+            };
+            self.eof = block.is_empty();
+            let used = mem::replace(&mut self.current, block);
+            let _ = self.recycled.send(used);
+            self.start = 0;
+        }
+        Ok(&self.current[self.start..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = amt.min(self.current.len() - self.start);
+        self.start += amt;
+        self.position += amt as u64;
+    }
+}
+
+/// Reads the blocks of `reader` until the end or an error, and returns the reader.
+///
+/// The end is sent as an empty block. The loop also stops when `PrefetchReader` is dropped.
+fn prefetch<TRead>(mut reader: TRead, block_size: usize, blocks: mpsc::SyncSender<io::Result<Vec<u8>>>, recycled: mpsc::Receiver<Vec<u8>>) -> TRead
+    where TRead: io::Read {
+    loop {
+        let mut block = recycled.try_recv().unwrap_or_default();
+        block.resize(block_size, 0);
+        let mut len = 0;
+        let result = loop {
+            match reader.read(&mut block[len..]) {
+                Ok(0) => break Ok(()),
+                Ok(n) => {
+                    len += n;
+                    if len == block_size {
+                        break Ok(());
+                    }
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => break Err(e),
+            }
+        };
+        block.truncate(len);
+        let last = len == 0 || result.is_err();
+        // A partial block is sent before the error, which is sent next.
+        if len > 0 && blocks.send(Ok(block)).is_err() {
+            return reader;
+        }
+        if last {
+            let _ = blocks.send(result.map(|_| vec![]));
+            return reader;
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Provides the features to report the progress of long streaming operations.
+
+/// The number of bytes processed between two progress reports.
+pub const REPORT_INTERVAL: u64 = 64 * 1024;
+
+/// Receives the progress of a long streaming operation.
+///
+/// Any `FnMut(u64, Option<u64>)` closure can be used as a `Progress`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+///
+/// let data = vec![0_u8; 100_000];
+/// let mut reader = io::Cursor::new(data);
+///
+/// let mut reports = vec![];
+/// let result = reader.read_byte_array_with_progress(100_000, &mut |processed, total| {
+///     reports.push((processed, total));
+/// }).unwrap();
+///
+/// assert_eq!(100_000, result.len());
+/// assert_eq!(vec![(65_536, Some(100_000)), (100_000, Some(100_000))], reports);
+///
+/// ```
+///
+pub trait Progress {
+    /// Reports the progress.
+    ///
+    /// # Arguments
+    ///
+    /// * processed - the number of bytes processed so far.
+    /// * total - the total number of bytes, if known.
+    ///
+    fn report(&mut self, processed: u64, total: Option<u64>);
+}
+
+impl<F> Progress for F
+    where F: FnMut(u64, Option<u64>) {
+    fn report(&mut self, processed: u64, total: Option<u64>) {
+        self(processed, total)
+    }
+}
+
+/// The `Progress` which ignores all reports.
+pub struct NoProgress {}
+
+impl Progress for NoProgress {
+    fn report(&mut self, _processed: u64, _total: Option<u64>) {}
+}
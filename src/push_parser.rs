@@ -0,0 +1,245 @@
+//! Provides the sans-io parser, which is fed the byte slices as they arrive and returns the
+//! parsed items when they are complete, for the nonblocking sockets and the event loops without
+//! an async runtime.
+//!
+//! A `Decoder` parses an item from the head of the buffered bytes, or tells that it needs more.
+//! `PushParser` keeps the partial bytes between the calls.
+
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+
+use binary_read::BinaryRead;
+use endian::Endian;
+use read_integer::ReadInteger;
+
+/// The result of `Decoder::decode`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Decoded<T> {
+    /// An item, and the byte count consumed by it.
+    Item(T, usize),
+    /// The bytes end before the item does.
+    NeedMore,
+}
+
+/// A parser of an item from the head of a byte slice.
+///
+/// This is implemented for the closures of `FnMut(&mut io::Cursor<&[u8]>) -> io::Result<T>`,
+/// which parse with the reading functions of this crate, and whose `UnexpectedEof` means that
+/// more bytes are needed. Such a closure parses an item again from its head for each slice fed
+/// until it is complete.
+pub trait Decoder {
+    /// The type of the parsed items.
+    type Item;
+
+    /// Parses an item from the head of `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the item or NeedMore), otherwise Err(io::Error), after
+    /// which the stream can not be parsed any further.
+    ///
+    fn decode(&mut self, bytes: &[u8]) -> io::Result<Decoded<Self::Item>>;
+}
+
+impl<T, F> Decoder for F
+    where F: FnMut(&mut io::Cursor<&[u8]>) -> io::Result<T> {
+    type Item = T;
+
+    fn decode(&mut self, bytes: &[u8]) -> io::Result<Decoded<T>> {
+        let mut cursor = io::Cursor::new(bytes);
+        match self(&mut cursor) {
+            Ok(item) => Ok(Decoded::Item(item, cursor.position() as usize)),
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(Decoded::NeedMore),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// The decoder of the frames of a `TLen` length prefix in `TEndian` and the payload, as
+/// `BinaryRead::read_frame`.
+///
+/// The length is checked against the maximum as soon as the prefix arrives, so that a hostile
+/// peer can not make the parser buffer more than a frame.
+pub struct FrameDecoder<TEndian, TLen>
+    where TEndian: Endian {
+    max_len: usize,
+    _marker: PhantomData<(TEndian, TLen)>,
+}
+
+impl<TEndian, TLen> FrameDecoder<TEndian, TLen>
+    where TEndian: Endian {
+    /// Creates a new `FrameDecoder`.
+    ///
+    /// # Arguments
+    ///
+    /// * max_len - the maximum payload length accepted.
+    ///
+    pub fn new(max_len: usize) -> FrameDecoder<TEndian, TLen> {
+        FrameDecoder { max_len, _marker: PhantomData }
+    }
+
+    /// Returns the maximum payload length accepted.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+}
+
+impl<TEndian, TLen> Decoder for FrameDecoder<TEndian, TLen>
+    where TEndian: Endian,
+          TLen: ReadInteger<OutputType=TLen> + Into<u64> {
+    type Item = Vec<u8>;
+
+    /// Parses a frame, and returns the payload.
+    ///
+    /// If the length exceeds the maximum, returns Err(io::Error) of `InvalidData`.
+    fn decode(&mut self, bytes: &[u8]) -> io::Result<Decoded<Vec<u8>>> {
+        let header_len = mem::size_of::<TLen>();
+        if bytes.len() < header_len {
+            return Ok(Decoded::NeedMore);
+        }
+        let len: u64 = (&bytes[..header_len]).read_integer::<TEndian, TLen>()?.into();
+        if len > self.max_len as u64 {
+            trace_event!(DEBUG, len, max_len = self.max_len, "frame is too long");
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "frame is too long"));
+        }
+        let frame_len = header_len + len as usize;
+        if bytes.len() < frame_len {
+            return Ok(Decoded::NeedMore);
+        }
+        Ok(Decoded::Item(bytes[header_len..frame_len].to_vec(), frame_len))
+    }
+}
+
+/// Buffers the bytes pushed into it, and parses the items with `TDecoder`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::push_parser::{FrameDecoder, PushParser};
+///
+/// let mut parser = PushParser::new(FrameDecoder::<BigEndian, u16>::new(1024));
+///
+/// // The bytes arrive split at arbitrary points.
+/// parser.push(&[0x00]);
+/// assert_eq!(None, parser.next_item().unwrap());
+/// parser.push(&[0x03, b'a', b'b']);
+/// assert_eq!(None, parser.next_item().unwrap());
+/// parser.push(&[b'c', 0x00, 0x01, b'd', 0x00]);
+/// assert_eq!(Some(b"abc".to_vec()), parser.next_item().unwrap());
+/// assert_eq!(Some(b"d".to_vec()), parser.next_item().unwrap());
+/// assert_eq!(None, parser.next_item().unwrap());
+/// assert_eq!(1, parser.buffered().len());
+/// assert_eq!(io::ErrorKind::UnexpectedEof, parser.finish().err().unwrap().kind());
+///
+/// // A closure parses with the reading functions.
+/// let mut parser = PushParser::new(|reader: &mut io::Cursor<&[u8]>| {
+///     let id = reader.read_integer::<BigEndian, u16>()?;
+///     let value = reader.read_integer::<BigEndian, u32>()?;
+///     Ok((id, value))
+/// });
+/// parser.push(&[0x00, 0x07, 0x00, 0x00]);
+/// assert_eq!(None, parser.next_item().unwrap());
+/// parser.push(&[0x00, 0x2A]);
+/// assert_eq!(Some((7, 42)), parser.next_item().unwrap());
+/// assert!(parser.finish().is_ok());
+///
+/// ```
+///
+pub struct PushParser<TDecoder>
+    where TDecoder: Decoder {
+    decoder: TDecoder,
+    buffer: Vec<u8>,
+    start: usize,
+}
+
+impl<TDecoder> PushParser<TDecoder>
+    where TDecoder: Decoder {
+    /// Creates a new `PushParser` of `decoder`.
+    pub fn new(decoder: TDecoder) -> PushParser<TDecoder> {
+        PushParser {
+            decoder,
+            buffer: vec![],
+            start: 0,
+        }
+    }
+
+    /// Appends the bytes arrived.
+    pub fn push(&mut self, bytes: &[u8]) {
+        // The consumed bytes are dropped when they are the most, so that a push moves each byte
+        // a limited number of times.
+        if self.start > 0 && self.start >= self.buffer.len() - self.start {
+            self.buffer.drain(..self.start);
+            self.start = 0;
+        }
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Parses the next item from the bytes buffered, or returns Ok(None) if more bytes are needed.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the item or None), otherwise Err(io::Error) of the
+    /// decoder, after which the buffered bytes are left as they are.
+    ///
+    pub fn next_item(&mut self) -> io::Result<Option<TDecoder::Item>> {
+        match self.decoder.decode(&self.buffer[self.start..])? {
+            Decoded::Item(item, consumed) => {
+                debug_assert!(consumed <= self.buffer.len() - self.start);
+                self.start += consumed;
+                Ok(Some(item))
+            }
+            Decoded::NeedMore => Ok(None),
+        }
+    }
+
+    /// Returns the bytes buffered but not parsed yet.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer[self.start..]
+    }
+
+    /// Gets a reference to the decoder.
+    pub fn get_ref(&self) -> &TDecoder {
+        &self.decoder
+    }
+
+    /// Checks that the stream has ended between the items, and returns the decoder.
+    ///
+    /// # Errors
+    ///
+    /// If bytes of an incomplete item are buffered, returns Err(io::Error) of `UnexpectedEof`.
+    ///
+    pub fn finish(self) -> io::Result<TDecoder> {
+        if self.start < self.buffer.len() {
+            let message = format!("stream ends in the middle of an item, {} bytes buffered", self.buffer.len() - self.start);
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, message));
+        }
+        Ok(self.decoder)
+    }
+}
+
+impl<TDecoder> io::Write for PushParser<TDecoder>
+    where TDecoder: Decoder {
+    /// Appends the bytes, as `push`.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.push(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<TDecoder> Iterator for PushParser<TDecoder>
+    where TDecoder: Decoder {
+    type Item = io::Result<TDecoder::Item>;
+
+    /// Parses the next item, or returns None if more bytes are needed.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_item().transpose()
+    }
+}
@@ -1,7 +1,14 @@
 //! Provides the features for reading integers from binary data.
 
 use std::io;
+use std::mem;
+use std::slice;
+
 use endian::Endian;
+use scratch;
+
+/// The byte count staged by one read of `read_integer_array` for the non-native endianness.
+const ARRAY_STAGING_SIZE: usize = 64 * 1024;
 
 /// Provides features for reading binary data.
 ///
@@ -48,6 +55,25 @@ pub trait ReadInteger {
     ///
     fn read_integer<TEndian>(reader: &mut io::Read) -> io::Result<Self::OutputType>
         where TEndian: Endian;
+
+    /// Reads an array of `element_count` integers from the reader.
+    ///
+    /// The integers of this crate read the whole array by one `read_exact` without conversion,
+    /// if `TEndian` is the native endianness.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds, returns Ok(Vec<Self::OutputType>),
+    /// otherwise returns Err(io::Error)
+    ///
+    fn read_integer_array<TEndian>(reader: &mut dyn io::Read, element_count: usize) -> io::Result<Vec<Self::OutputType>>
+        where TEndian: Endian {
+        let mut result = Vec::with_capacity(element_count);
+        for _i in 0..element_count {
+            result.push(Self::read_integer::<TEndian>(reader)?);
+        }
+        Ok(result)
+    }
 }
 
 impl ReadInteger for u8 {
@@ -62,6 +88,11 @@ impl ReadInteger for u8 {
                 Ok(TEndian::u8_from_bytes(&buf))
             })
     }
+
+    fn read_integer_array<TEndian>(reader: &mut dyn io::Read, element_count: usize) -> io::Result<Vec<Self::OutputType>>
+        where TEndian: Endian {
+        read_integer_array::<TEndian, Self>(reader, element_count)
+    }
 }
 
 impl ReadInteger for i8 {
@@ -74,6 +105,11 @@ impl ReadInteger for i8 {
                 Ok(x as i8)
             })
     }
+
+    fn read_integer_array<TEndian>(reader: &mut dyn io::Read, element_count: usize) -> io::Result<Vec<Self::OutputType>>
+        where TEndian: Endian {
+        read_integer_array::<TEndian, Self>(reader, element_count)
+    }
 }
 
 
@@ -89,6 +125,11 @@ impl ReadInteger for u16 {
                 Ok(TEndian::u16_from_bytes(&buf))
             })
     }
+
+    fn read_integer_array<TEndian>(reader: &mut dyn io::Read, element_count: usize) -> io::Result<Vec<Self::OutputType>>
+        where TEndian: Endian {
+        read_integer_array::<TEndian, Self>(reader, element_count)
+    }
 }
 
 impl ReadInteger for i16 {
@@ -101,6 +142,11 @@ impl ReadInteger for i16 {
                 Ok(x as i16)
             })
     }
+
+    fn read_integer_array<TEndian>(reader: &mut dyn io::Read, element_count: usize) -> io::Result<Vec<Self::OutputType>>
+        where TEndian: Endian {
+        read_integer_array::<TEndian, Self>(reader, element_count)
+    }
 }
 
 impl ReadInteger for u32 {
@@ -115,6 +161,11 @@ impl ReadInteger for u32 {
                 Ok(TEndian::u32_from_bytes(&buf))
             })
     }
+
+    fn read_integer_array<TEndian>(reader: &mut dyn io::Read, element_count: usize) -> io::Result<Vec<Self::OutputType>>
+        where TEndian: Endian {
+        read_integer_array::<TEndian, Self>(reader, element_count)
+    }
 }
 
 impl ReadInteger for i32 {
@@ -127,6 +178,11 @@ impl ReadInteger for i32 {
                 Ok(x as i32)
             })
     }
+
+    fn read_integer_array<TEndian>(reader: &mut dyn io::Read, element_count: usize) -> io::Result<Vec<Self::OutputType>>
+        where TEndian: Endian {
+        read_integer_array::<TEndian, Self>(reader, element_count)
+    }
 }
 
 impl ReadInteger for u64 {
@@ -141,6 +197,11 @@ impl ReadInteger for u64 {
                 Ok(TEndian::u64_from_bytes(&buf))
             })
     }
+
+    fn read_integer_array<TEndian>(reader: &mut dyn io::Read, element_count: usize) -> io::Result<Vec<Self::OutputType>>
+        where TEndian: Endian {
+        read_integer_array::<TEndian, Self>(reader, element_count)
+    }
 }
 
 impl ReadInteger for i64 {
@@ -153,4 +214,66 @@ impl ReadInteger for i64 {
                 Ok(x as i64)
             })
     }
+
+    fn read_integer_array<TEndian>(reader: &mut dyn io::Read, element_count: usize) -> io::Result<Vec<Self::OutputType>>
+        where TEndian: Endian {
+        read_integer_array::<TEndian, Self>(reader, element_count)
+    }
+}
+
+fn read_integer_array<TEndian, TInt>(reader: &mut dyn io::Read, element_count: usize) -> io::Result<Vec<TInt>>
+    where TEndian: Endian,
+          TInt: ReadInteger<OutputType=TInt> + Copy + Default {
+    let element_size = mem::size_of::<TInt>();
+    if !TEndian::is_native() && element_size > 1 {
+        // The bytes are staged in the scratch buffer by chunks, and swapped element by element.
+        let chunk_elements = (ARRAY_STAGING_SIZE / element_size).max(1);
+        let mut result = Vec::with_capacity(element_count);
+        let mut remaining = element_count;
+        while remaining > 0 {
+            let n = remaining.min(chunk_elements);
+            scratch::with_scratch(n * element_size, |buf| -> io::Result<()> {
+                reader.read_exact(buf)?;
+                for bytes in buf.chunks_exact(element_size) {
+                    result.push(TInt::read_integer::<TEndian>(&mut &bytes[..])?);
+                }
+                Ok(())
+            })?;
+            remaining -= n;
+        }
+        return Ok(result);
+    }
+    let byte_count = element_count.checked_mul(element_size)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "array is too large"))?;
+    read_native_array(reader, element_count, byte_count)
+}
+
+/// Reads `element_count` integers of the native endianness, `byte_count` bytes in total.
+///
+/// With the `nightly` feature, the bytes are read into the spare capacity by `read_buf`, so the
+/// array is not zero-initialized before being overwritten.
+#[cfg(feature = "nightly")]
+fn read_native_array<TInt>(reader: &mut dyn io::Read, element_count: usize, byte_count: usize) -> io::Result<Vec<TInt>> {
+    let mut result = Vec::<TInt>::with_capacity(element_count);
+    {
+        let spare = result.spare_capacity_mut();
+        let bytes = unsafe { slice::from_raw_parts_mut(spare.as_mut_ptr() as *mut mem::MaybeUninit<u8>, byte_count) };
+        let mut borrowed = io::BorrowedBuf::from(bytes);
+        reader.read_buf_exact(borrowed.unfilled())?;
+    }
+    // `read_buf_exact` succeeded, so all bytes are initialized, and `TInt` is one of the
+    // integers, for which every bit pattern is valid.
+    unsafe { result.set_len(element_count); }
+    Ok(result)
+}
+
+/// Reads `element_count` integers of the native endianness, `byte_count` bytes in total.
+#[cfg(not(feature = "nightly"))]
+fn read_native_array<TInt>(reader: &mut dyn io::Read, element_count: usize, byte_count: usize) -> io::Result<Vec<TInt>>
+    where TInt: Copy + Default {
+    let mut result = vec![TInt::default(); element_count];
+    // `TInt` is one of the integers, for which every bit pattern is valid.
+    let bytes = unsafe { slice::from_raw_parts_mut(result.as_mut_ptr() as *mut u8, byte_count) };
+    reader.read_exact(bytes)?;
+    Ok(result)
 }
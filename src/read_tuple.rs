@@ -0,0 +1,77 @@
+//! Provides the reads of the tuples of the integers and the arrays, for the small fixed headers.
+
+use std::io;
+
+use binary_read::BinaryRead;
+use endian::Endian;
+
+/// A value read in the endianness `TEndian`, as a component of `BinaryRead::read_tuple`.
+///
+/// It is implemented for the integers, the arrays of them, and the tuples of up to 12 of them,
+/// which are read component by component in order.
+pub trait ReadTuple: Sized {
+    /// Reads a value from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(Self), otherwise Err(io::Error).
+    ///
+    fn read_tuple<TEndian, TRead>(reader: &mut TRead) -> io::Result<Self>
+        where TEndian: Endian,
+              TRead: io::Read;
+}
+
+macro_rules! impl_read_tuple_integer {
+    ($($t:ty),*) => {
+        $(
+            impl ReadTuple for $t {
+                fn read_tuple<TEndian, TRead>(reader: &mut TRead) -> io::Result<$t>
+                    where TEndian: Endian,
+                          TRead: io::Read {
+                    reader.read_integer::<TEndian, $t>()
+                }
+            }
+        )*
+    };
+}
+
+impl_read_tuple_integer!(u8, i8, u16, i16, u32, i32, u64, i64);
+
+impl<T, const N: usize> ReadTuple for [T; N]
+    where T: ReadTuple + Copy + Default {
+    fn read_tuple<TEndian, TRead>(reader: &mut TRead) -> io::Result<[T; N]>
+        where TEndian: Endian,
+              TRead: io::Read {
+        let mut array = [T::default(); N];
+        for x in array.iter_mut() {
+            *x = T::read_tuple::<TEndian, TRead>(reader)?;
+        }
+        Ok(array)
+    }
+}
+
+macro_rules! impl_read_tuple {
+    ($($name:ident),*) => {
+        impl<$($name),*> ReadTuple for ($($name,)*)
+            where $($name: ReadTuple),* {
+            fn read_tuple<TEndian, TRead>(reader: &mut TRead) -> io::Result<($($name,)*)>
+                where TEndian: Endian,
+                      TRead: io::Read {
+                Ok(($($name::read_tuple::<TEndian, TRead>(reader)?,)*))
+            }
+        }
+    };
+}
+
+impl_read_tuple!(A);
+impl_read_tuple!(A, B);
+impl_read_tuple!(A, B, C);
+impl_read_tuple!(A, B, C, D);
+impl_read_tuple!(A, B, C, D, E);
+impl_read_tuple!(A, B, C, D, E, F);
+impl_read_tuple!(A, B, C, D, E, F, G);
+impl_read_tuple!(A, B, C, D, E, F, G, H);
+impl_read_tuple!(A, B, C, D, E, F, G, H, I);
+impl_read_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_read_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_read_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
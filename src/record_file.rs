@@ -0,0 +1,340 @@
+//! Provides the features to read and write files of a typed header followed by fixed-size records.
+//!
+//! The header holds the record count. Appended records are written before the count is updated,
+//! so records torn by a crash are beyond the count and never visible.
+
+use std::fs;
+use std::io;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use file::{self, FILE_BUFFER_SIZE};
+
+/// A value encoded in a fixed number of bytes.
+pub trait FixedRecord: Sized {
+    /// The encoded size in bytes.
+    const SIZE: usize;
+
+    /// Reads a value from `reader`.
+    fn read_record<TRead>(reader: &mut TRead) -> io::Result<Self>
+        where TRead: io::Read;
+
+    /// Writes the value to `writer`. Exactly `SIZE` bytes must be written.
+    fn write_record<TWrite>(&self, writer: &mut TWrite) -> io::Result<()>
+        where TWrite: io::Write;
+}
+
+/// The header of a `RecordFile`, which holds the record count.
+pub trait RecordFileHeader: FixedRecord {
+    /// Returns the record count.
+    fn record_count(&self) -> u64;
+
+    /// Sets the record count.
+    fn set_record_count(&mut self, count: u64);
+}
+
+/// A file of a typed header followed by fixed-size records.
+///
+/// # Examples
+///
+/// ```
+/// use std::env;
+/// use std::fs;
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::record_file::{FixedRecord, RecordFile, RecordFileHeader};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Header { version: u32, count: u64 }
+///
+/// impl FixedRecord for Header {
+///     const SIZE: usize = 12;
+///     fn read_record<TRead: io::Read>(reader: &mut TRead) -> io::Result<Header> {
+///         let version = reader.read_integer::<LittleEndian, u32>()?;
+///         let count = reader.read_integer::<LittleEndian, u64>()?;
+///         Ok(Header { version, count })
+///     }
+///     fn write_record<TWrite: io::Write>(&self, writer: &mut TWrite) -> io::Result<()> {
+///         writer.write_integer::<LittleEndian, _>(self.version)?;
+///         writer.write_integer::<LittleEndian, _>(self.count)
+///     }
+/// }
+///
+/// impl RecordFileHeader for Header {
+///     fn record_count(&self) -> u64 { self.count }
+///     fn set_record_count(&mut self, count: u64) { self.count = count; }
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Entry { key: u32, offset: u64 }
+///
+/// impl FixedRecord for Entry {
+///     const SIZE: usize = 12;
+///     fn read_record<TRead: io::Read>(reader: &mut TRead) -> io::Result<Entry> {
+///         let key = reader.read_integer::<LittleEndian, u32>()?;
+///         let offset = reader.read_integer::<LittleEndian, u64>()?;
+///         Ok(Entry { key, offset })
+///     }
+///     fn write_record<TWrite: io::Write>(&self, writer: &mut TWrite) -> io::Result<()> {
+///         writer.write_integer::<LittleEndian, _>(self.key)?;
+///         writer.write_integer::<LittleEndian, _>(self.offset)
+///     }
+/// }
+///
+/// let path = env::temp_dir().join("mm_binary_io_record_file_example.idx");
+/// {
+///     let mut records = RecordFile::<Header, Entry>::create(&path, Header { version: 1, count: 0 }).unwrap();
+///     records.append(&Entry { key: 1, offset: 0 }).unwrap();
+///
+///     let mut appender = records.appender();
+///     appender.push(&Entry { key: 2, offset: 100 }).unwrap();
+///     appender.push(&Entry { key: 3, offset: 250 }).unwrap();
+///     appender.finish().unwrap();
+/// }
+///
+/// let records = RecordFile::<Header, Entry>::open(&path).unwrap();
+/// assert_eq!(&Header { version: 1, count: 3 }, records.header());
+/// assert_eq!(Some(Entry { key: 2, offset: 100 }), records.get(1).unwrap());
+/// assert_eq!(None, records.get(3).unwrap());
+///
+/// let keys = records.iter().map(|e| e.map(|e| e.key)).collect::<io::Result<Vec<_>>>().unwrap();
+/// assert_eq!(vec![1, 2, 3], keys);
+///
+/// fs::remove_file(&path).unwrap();
+///
+/// ```
+///
+pub struct RecordFile<THeader, TRecord>
+    where THeader: RecordFileHeader,
+          TRecord: FixedRecord {
+    file: fs::File,
+    header: THeader,
+    _record: PhantomData<TRecord>,
+}
+
+impl<THeader, TRecord> RecordFile<THeader, TRecord>
+    where THeader: RecordFileHeader,
+          TRecord: FixedRecord {
+    /// Creates a new file at `path` with `header` and no records, truncating the existing file.
+    pub fn create<TPath>(path: TPath, header: THeader) -> io::Result<RecordFile<THeader, TRecord>>
+        where TPath: AsRef<Path> {
+        let file = fs::OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path)?;
+        let mut header = header;
+        header.set_record_count(0);
+        file::write_all_at_file(&file, 0, &encode(&header)?)?;
+        Ok(RecordFile {
+            file,
+            header,
+            _record: PhantomData,
+        })
+    }
+
+    /// Opens the file at `path` for reading and appending.
+    ///
+    /// # Errors
+    ///
+    /// If the file is shorter than the header and the counted records, returns Err(io::Error) of
+    /// `InvalidData`.
+    ///
+    pub fn open<TPath>(path: TPath) -> io::Result<RecordFile<THeader, TRecord>>
+        where TPath: AsRef<Path> {
+        let file = fs::OpenOptions::new().read(true).write(true).open(path)?;
+        let mut buf = vec![0_u8; THeader::SIZE];
+        file::read_exact_at_file(&file, 0, &mut buf)?;
+        let header = THeader::read_record(&mut &buf[..])?;
+
+        let required = header.record_count().checked_mul(TRecord::SIZE as u64)
+            .and_then(|len| len.checked_add(THeader::SIZE as u64));
+        match required {
+            Some(required) if required <= file.metadata()?.len() => {}
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "record file is cut off")),
+        }
+        Ok(RecordFile {
+            file,
+            header,
+            _record: PhantomData,
+        })
+    }
+
+    /// Returns the header.
+    pub fn header(&self) -> &THeader {
+        &self.header
+    }
+
+    /// Replaces the header, keeping the record count.
+    pub fn set_header(&mut self, header: THeader) -> io::Result<()> {
+        let mut header = header;
+        header.set_record_count(self.header.record_count());
+        file::write_all_at_file(&self.file, 0, &encode(&header)?)?;
+        self.header = header;
+        Ok(())
+    }
+
+    /// Returns the number of the records.
+    pub fn len(&self) -> u64 {
+        self.header.record_count()
+    }
+
+    /// Returns true if there is no record.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads the record at `index` by a positional read.
+    ///
+    /// # Errors
+    ///
+    /// If `index` is out of range, returns Ok(None).
+    /// If reading fails, returns Err(io::Error).
+    ///
+    pub fn get(&self, index: u64) -> io::Result<Option<TRecord>> {
+        if index >= self.len() {
+            return Ok(None);
+        }
+        let mut buf = vec![0_u8; TRecord::SIZE];
+        file::read_exact_at_file(&self.file, self.offset_of(index), &mut buf)?;
+        TRecord::read_record(&mut &buf[..]).map(Some)
+    }
+
+    /// Returns an iterator over the records.
+    pub fn iter<'a>(&'a self) -> Records<'a, TRecord> {
+        Records {
+            file: &self.file,
+            offset: self.offset_of(0),
+            remaining: self.len(),
+            buffer: vec![],
+            position: 0,
+            _record: PhantomData,
+        }
+    }
+
+    /// Appends a record and updates the record count.
+    pub fn append(&mut self, record: &TRecord) -> io::Result<()> {
+        let mut appender = self.appender();
+        appender.push(record)?;
+        appender.finish()
+    }
+
+    /// Returns an appender which writes records in batches and updates the record count once
+    /// at `finish`.
+    pub fn appender<'a>(&'a mut self) -> RecordAppender<'a, THeader, TRecord> {
+        RecordAppender {
+            file: self,
+            buffer: vec![],
+            written: 0,
+            pending: 0,
+        }
+    }
+
+    /// Synchronizes the file to the storage.
+    pub fn sync(&self) -> io::Result<()> {
+        self.file.sync_all()
+    }
+
+    /// Unwraps this `RecordFile`, returning the underlying file.
+    pub fn into_inner(self) -> fs::File {
+        self.file
+    }
+
+    fn offset_of(&self, index: u64) -> u64 {
+        THeader::SIZE as u64 + index * TRecord::SIZE as u64
+    }
+}
+
+/// Iterates the records of a `RecordFile`, reading them in batches.
+pub struct Records<'a, TRecord>
+    where TRecord: FixedRecord {
+    file: &'a fs::File,
+    offset: u64,
+    remaining: u64,
+    buffer: Vec<u8>,
+    position: usize,
+    _record: PhantomData<TRecord>,
+}
+
+impl<'a, TRecord> Iterator for Records<'a, TRecord>
+    where TRecord: FixedRecord {
+    type Item = io::Result<TRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == self.buffer.len() {
+            if self.remaining == 0 {
+                return None;
+            }
+            let count = self.remaining.min((FILE_BUFFER_SIZE / TRecord::SIZE.max(1)).max(1) as u64);
+            self.buffer.resize(count as usize * TRecord::SIZE, 0);
+            self.position = 0;
+            if let Err(e) = file::read_exact_at_file(self.file, self.offset, &mut self.buffer) {
+                self.remaining = 0;
+                self.buffer.clear();
+                return Some(Err(e));
+            }
+            self.offset += self.buffer.len() as u64;
+            self.remaining -= count;
+        }
+        let record = TRecord::read_record(&mut &self.buffer[self.position..self.position + TRecord::SIZE]);
+        self.position += TRecord::SIZE;
+        Some(record)
+    }
+}
+
+/// Appends records to a `RecordFile` in batches.
+///
+/// The record count is updated at `finish`. The records pushed without `finish` are not counted,
+/// and are overwritten by the next append.
+pub struct RecordAppender<'a, THeader, TRecord>
+    where THeader: RecordFileHeader + 'a,
+          TRecord: FixedRecord + 'a {
+    file: &'a mut RecordFile<THeader, TRecord>,
+    buffer: Vec<u8>,
+    written: u64,
+    pending: u64,
+}
+
+impl<'a, THeader, TRecord> RecordAppender<'a, THeader, TRecord>
+    where THeader: RecordFileHeader,
+          TRecord: FixedRecord {
+    /// Pushes a record.
+    pub fn push(&mut self, record: &TRecord) -> io::Result<()> {
+        self.buffer.extend_from_slice(&encode(record)?);
+        self.pending += 1;
+        if self.buffer.len() >= FILE_BUFFER_SIZE {
+            self.write_pending()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the pushed records and updates the record count.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.write_pending()?;
+        let previous = self.file.len();
+        self.file.header.set_record_count(previous + self.written);
+        let result = encode(&self.file.header)
+            .and_then(|header| file::write_all_at_file(&self.file.file, 0, &header));
+        if result.is_err() {
+            self.file.header.set_record_count(previous);
+        }
+        result
+    }
+
+    fn write_pending(&mut self) -> io::Result<()> {
+        let offset = self.file.offset_of(self.file.len() + self.written);
+        file::write_all_at_file(&self.file.file, offset, &self.buffer)?;
+        self.written += self.pending;
+        self.pending = 0;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+fn encode<TRecord>(record: &TRecord) -> io::Result<Vec<u8>>
+    where TRecord: FixedRecord {
+    let mut bytes = Vec::with_capacity(TRecord::SIZE);
+    record.write_record(&mut bytes)?;
+    if bytes.len() != TRecord::SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "encoded record size does not match SIZE"));
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,231 @@
+//! Provides an append-only log of framed records.
+//!
+//! Each record is stored as a little-endian u32 payload length, a little-endian u32 complemented
+//! CRC-32 of the payload, and the payload itself.
+//!
+//! The CRC-32 of an empty payload is 0, so it is complemented to keep a zero-filled tail, e.g.
+//! left by a crash before the file system wrote the data, from passing as valid empty records.
+
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+use binary_read::read_fully;
+use binary_write::BinaryWrite;
+use crc32;
+use endian::{Endian, LittleEndian};
+
+/// The size of the header preceding each record payload.
+pub const RECORD_HEADER_SIZE: usize = 8;
+
+/// Appends records to a record log.
+///
+/// Each record is written with a single `write_all` call, so an interrupted append leaves at most
+/// one torn record at the tail, which `RecordLogReader` skips.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::record_log::{RecordLogReader, RecordLogWriter};
+///
+/// let mut writer = RecordLogWriter::new(vec![]);
+/// writer.append(b"first").unwrap();
+/// writer.append(b"second").unwrap();
+/// let mut data = writer.into_inner();
+///
+/// // Simulate a crash in the middle of the third append.
+/// data.extend_from_slice(&[0x05, 0x00, 0x00]);
+///
+/// let mut reader = RecordLogReader::new(io::Cursor::new(data));
+/// let records = reader.by_ref().collect::<io::Result<Vec<_>>>().unwrap();
+///
+/// assert_eq!(vec![b"first".to_vec(), b"second".to_vec()], records);
+/// assert_eq!(27, reader.valid_len());
+/// assert_eq!(3, reader.torn_len());
+///
+/// ```
+///
+pub struct RecordLogWriter<TWrite>
+    where TWrite: io::Write {
+    writer: TWrite,
+    buffer: Vec<u8>,
+}
+
+impl<TWrite> RecordLogWriter<TWrite>
+    where TWrite: io::Write {
+    /// Creates a new `RecordLogWriter` which appends records to `writer`.
+    pub fn new(writer: TWrite) -> RecordLogWriter<TWrite> {
+        RecordLogWriter {
+            writer,
+            buffer: vec![],
+        }
+    }
+
+    /// Appends a record.
+    ///
+    /// # Errors
+    ///
+    /// If the payload is longer than `u32::MAX` bytes, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    pub fn append(&mut self, payload: &[u8]) -> io::Result<()> {
+        if payload.len() > u32::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "record is too long"));
+        }
+        self.buffer.clear();
+        self.buffer.write_integer::<LittleEndian, _>(payload.len() as u32)?;
+        self.buffer.write_integer::<LittleEndian, _>(record_checksum(payload))?;
+        self.buffer.extend_from_slice(payload);
+        self.writer.write_all(&self.buffer)
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &TWrite {
+        &self.writer
+    }
+
+    /// Unwraps this `RecordLogWriter`, returning the underlying writer.
+    pub fn into_inner(self) -> TWrite {
+        self.writer
+    }
+}
+
+impl RecordLogWriter<fs::File> {
+    /// Opens the log file at `path` for appending, creating it if it does not exist.
+    pub fn open<TPath>(path: TPath) -> io::Result<RecordLogWriter<fs::File>>
+        where TPath: AsRef<Path> {
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map(RecordLogWriter::new)
+    }
+
+    /// Flushes the appended records and synchronizes them to the disk.
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.flush()?;
+        self.writer.sync_data()
+    }
+}
+
+/// Iterates the records of a record log.
+///
+/// The iteration stops cleanly at the end of the log or at a torn (incomplete) record at the tail.
+/// A complete record whose checksum does not match is reported as Err(io::Error) of `InvalidData`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::record_log::{RecordLogReader, RecordLogWriter};
+///
+/// let mut writer = RecordLogWriter::new(vec![]);
+/// writer.append(b"first").unwrap();
+/// writer.append(b"").unwrap();
+/// let mut data = writer.into_inner();
+///
+/// // Simulate a crash which extended the file without writing the data.
+/// data.extend_from_slice(&[0_u8; 16]);
+///
+/// let mut reader = RecordLogReader::new(io::Cursor::new(data));
+/// assert_eq!(b"first".to_vec(), reader.next().unwrap().unwrap());
+/// assert_eq!(b"".to_vec(), reader.next().unwrap().unwrap());
+/// assert_eq!(io::ErrorKind::InvalidData, reader.next().unwrap().unwrap_err().kind());
+/// assert_eq!(21, reader.valid_len());
+///
+/// ```
+///
+pub struct RecordLogReader<TRead>
+    where TRead: io::Read {
+    reader: TRead,
+    valid_len: u64,
+    torn_len: u64,
+    finished: bool,
+}
+
+impl<TRead> RecordLogReader<TRead>
+    where TRead: io::Read {
+    /// Creates a new `RecordLogReader` which reads records from `reader`.
+    pub fn new(reader: TRead) -> RecordLogReader<TRead> {
+        RecordLogReader {
+            reader,
+            valid_len: 0,
+            torn_len: 0,
+            finished: false,
+        }
+    }
+
+    /// Returns the byte length of the valid records read so far.
+    ///
+    /// After the iteration finished, a damaged log can be repaired by truncating it to this length.
+    pub fn valid_len(&self) -> u64 {
+        self.valid_len
+    }
+
+    /// Returns the byte length of the torn record at the tail, or 0 if there is none.
+    pub fn torn_len(&self) -> u64 {
+        self.torn_len
+    }
+
+    /// Unwraps this `RecordLogReader`, returning the underlying reader.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut header = [0_u8; RECORD_HEADER_SIZE];
+        let header_len = read_fully(&mut self.reader, &mut header)?;
+        if header_len < RECORD_HEADER_SIZE {
+            self.torn_len = header_len as u64;
+            return Ok(None);
+        }
+        let len = LittleEndian::u32_from_bytes(&header[0..4]) as u64;
+        let expected = LittleEndian::u32_from_bytes(&header[4..8]);
+
+        let mut payload = vec![];
+        (&mut self.reader).take(len).read_to_end(&mut payload)?;
+        if (payload.len() as u64) < len {
+            self.torn_len = (RECORD_HEADER_SIZE + payload.len()) as u64;
+            return Ok(None);
+        }
+        if record_checksum(&payload) != expected {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "record checksum mismatch"));
+        }
+        self.valid_len += RECORD_HEADER_SIZE as u64 + len;
+        Ok(Some(payload))
+    }
+}
+
+impl<TRead> Iterator for RecordLogReader<TRead>
+    where TRead: io::Read {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.read_record() {
+            Ok(Some(payload)) => Some(Ok(payload)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Returns the checksum stored in the header of `payload`.
+fn record_checksum(payload: &[u8]) -> u32 {
+    !crc32::crc32(payload)
+}
@@ -0,0 +1,271 @@
+//! Provides the features to continue parsing a damaged stream after a broken record.
+//!
+//! `RecoveryReader` keeps the bytes of the current record until it is parsed. If the parser
+//! fails, the error is collected, and the reader skips to the end of the record declared by the
+//! parser, or to the next sync pattern after the record start, so that the following records are
+//! still read. This is what the forensic and repair tools need on truncated or corrupted files.
+
+use std::io;
+use std::mem;
+
+/// The byte count read from the inner reader at once.
+const FILL_SIZE: usize = 8 * 1024;
+
+/// An error collected by `RecoveryReader`.
+#[derive(Debug)]
+pub struct RecoveredError {
+    /// The stream offset of the start of the broken record.
+    pub offset: u64,
+    /// The byte count skipped to resynchronize.
+    pub skipped: u64,
+    /// The error returned by the parser.
+    pub error: io::Error,
+}
+
+/// Reads records by a parser, recovering from the parse errors.
+///
+/// On a parse error, the reader skips to the record end declared by `declare_record_len`, or if
+/// none was declared, to the next sync pattern after the record start. The errors of the inner
+/// reader itself are not recovered, and are returned.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::recovery::RecoveryReader;
+///
+/// let data = [
+///     &b"RC\x02ab"[..],
+///     &b"RC\x02\xFFx"[..],
+///     &b"XX"[..],
+///     &b"RC\x01c"[..],
+///     &b"RC\x05d"[..]].concat();
+/// let mut reader = RecoveryReader::with_sync_pattern(&data[..], b"RC");
+///
+/// let records = reader.read_all(|reader| {
+///     if reader.read_byte_array(2)? != b"RC" {
+///         return Err(io::Error::new(io::ErrorKind::InvalidData, "sync pattern is missing"));
+///     }
+///     let len = reader.read_integer::<BigEndian, u8>()?;
+///     reader.declare_record_len(3 + len as u64);
+///     let payload = reader.read_byte_array(len as usize)?;
+///     if payload[0] == 0xFF {
+///         return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid payload"));
+///     }
+///     Ok(payload)
+/// }).unwrap();
+///
+/// assert_eq!(vec![b"ab".to_vec(), b"c".to_vec()], records);
+///
+/// let errors: Vec<_> = reader.errors().iter()
+///     .map(|e| (e.offset, e.skipped, e.error.kind()))
+///     .collect();
+/// assert_eq!(vec![
+///     (5, 5, io::ErrorKind::InvalidData),
+///     (10, 2, io::ErrorKind::InvalidData),
+///     (16, 4, io::ErrorKind::UnexpectedEof)], errors);
+///
+/// ```
+///
+pub struct RecoveryReader<TRead>
+    where TRead: io::Read {
+    reader: TRead,
+    pattern: Option<Vec<u8>>,
+    buffer: Vec<u8>,
+    cursor: usize,
+    start: u64,
+    record_len: Option<u64>,
+    failed: bool,
+    errors: Vec<RecoveredError>,
+}
+
+impl<TRead> RecoveryReader<TRead>
+    where TRead: io::Read {
+    /// Creates a new `RecoveryReader`, which resynchronizes only by the declared record lengths.
+    pub fn new(reader: TRead) -> RecoveryReader<TRead> {
+        RecoveryReader {
+            reader,
+            pattern: None,
+            buffer: vec![],
+            cursor: 0,
+            start: 0,
+            record_len: None,
+            failed: false,
+            errors: vec![],
+        }
+    }
+
+    /// Creates a new `RecoveryReader`, which resynchronizes by `pattern` at the start of the
+    /// records, unless a record length is declared.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is empty, the function panics.
+    ///
+    pub fn with_sync_pattern(reader: TRead, pattern: &[u8]) -> RecoveryReader<TRead> {
+        assert!(!pattern.is_empty(), "sync pattern must not be empty");
+        let mut reader = RecoveryReader::new(reader);
+        reader.pattern = Some(pattern.to_vec());
+        reader
+    }
+
+    /// Returns the current stream offset.
+    pub fn position(&self) -> u64 {
+        self.start + self.cursor as u64
+    }
+
+    /// Declares the byte length of the current record from its start, so that a failure of the
+    /// record skips to its end.
+    ///
+    /// Call this from the parser as soon as the record header is read.
+    pub fn declare_record_len(&mut self, len: u64) {
+        self.record_len = Some(len);
+    }
+
+    /// Reads the next record by `parse`, skipping the broken records.
+    ///
+    /// # Errors
+    ///
+    /// If the stream ends before the record, returns Ok(None).
+    /// If `parse` fails and the reader can not resynchronize, returns Err(io::Error) of the parser.
+    /// If the inner reader fails, returns Err(io::Error).
+    ///
+    pub fn read_record<T, TParse>(&mut self, mut parse: TParse) -> io::Result<Option<T>>
+        where TParse: FnMut(&mut RecoveryReader<TRead>) -> io::Result<T> {
+        loop {
+            if self.buffer.is_empty() && self.fill()? == 0 {
+                return Ok(None);
+            }
+            self.record_len = None;
+            self.failed = false;
+            trace_span!(TRACE, "read record", offset = self.start);
+            let error = match parse(self) {
+                Ok(record) => {
+                    let len = self.cursor;
+                    self.discard(len);
+                    return Ok(Some(record));
+                }
+                Err(e) => e,
+            };
+            if self.failed || (self.record_len.is_none() && self.pattern.is_none()) {
+                return Err(error);
+            }
+            let offset = self.start;
+            self.cursor = 0;
+            let skipped = match self.record_len {
+                Some(len) => self.skip(len.max(1))?,
+                None => self.skip_to_pattern()?,
+            };
+            trace_event!(WARN, offset, skipped, error = %error, "skipped broken record");
+            self.errors.push(RecoveredError { offset, skipped, error });
+        }
+    }
+
+    /// Reads all records by `parse`, skipping the broken records.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the records), otherwise Err(io::Error) as `read_record`.
+    ///
+    pub fn read_all<T, TParse>(&mut self, mut parse: TParse) -> io::Result<Vec<T>>
+        where TParse: FnMut(&mut RecoveryReader<TRead>) -> io::Result<T> {
+        let mut records = vec![];
+        while let Some(record) = self.read_record(&mut parse)? {
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    /// Returns the errors collected so far.
+    pub fn errors(&self) -> &[RecoveredError] {
+        &self.errors
+    }
+
+    /// Returns and clears the errors collected so far.
+    pub fn take_errors(&mut self) -> Vec<RecoveredError> {
+        mem::take(&mut self.errors)
+    }
+
+    /// Unwraps this `RecoveryReader`, returning the underlying reader.
+    ///
+    /// The buffered bytes are lost.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+
+    /// Skips `len` bytes from the record start, and returns the byte count skipped.
+    fn skip(&mut self, len: u64) -> io::Result<u64> {
+        let mut skipped = 0;
+        while skipped < len {
+            if self.buffer.is_empty() && self.fill()? == 0 {
+                break;
+            }
+            let n = (len - skipped).min(self.buffer.len() as u64) as usize;
+            self.discard(n);
+            skipped += n as u64;
+        }
+        Ok(skipped)
+    }
+
+    /// Skips to the next sync pattern after the record start, and returns the byte count skipped.
+    fn skip_to_pattern(&mut self) -> io::Result<u64> {
+        let pattern = self.pattern.take().unwrap_or_default();
+        let mut skipped = self.skip(1)?;
+        let result = loop {
+            if let Some(offset) = self.buffer.windows(pattern.len()).position(|w| w == &pattern[..]) {
+                self.discard(offset);
+                break Ok(skipped + offset as u64);
+            }
+            let n = self.buffer.len().saturating_sub(pattern.len() - 1);
+            self.discard(n);
+            skipped += n as u64;
+            match self.fill() {
+                Ok(0) => {
+                    let n = self.buffer.len();
+                    self.discard(n);
+                    break Ok(skipped + n as u64);
+                }
+                Ok(_) => {}
+                Err(e) => break Err(e),
+            }
+        };
+        self.pattern = Some(pattern);
+        result
+    }
+
+    fn discard(&mut self, len: usize) {
+        self.buffer.drain(..len);
+        self.cursor -= len.min(self.cursor);
+        self.start += len as u64;
+    }
+
+    /// Appends the bytes of the inner reader to the buffer, and returns the byte count read.
+    fn fill(&mut self) -> io::Result<usize> {
+        let len = self.buffer.len();
+        self.buffer.resize(len + FILL_SIZE, 0);
+        let result = loop {
+            match self.reader.read(&mut self.buffer[len..]) {
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                result => break result,
+            }
+        };
+        self.buffer.truncate(len + *result.as_ref().unwrap_or(&0));
+        self.failed |= result.is_err();
+        result
+    }
+}
+
+impl<TRead> io::Read for RecoveryReader<TRead>
+    where TRead: io::Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.cursor == self.buffer.len() && self.fill()? == 0 {
+            return Ok(0);
+        }
+        let n = (self.buffer.len() - self.cursor).min(buf.len());
+        buf[..n].copy_from_slice(&self.buffer[self.cursor..self.cursor + n]);
+        self.cursor += n;
+        Ok(n)
+    }
+}
@@ -0,0 +1,228 @@
+//! Provides the reader and the writer retrying the operations failed with `Interrupted`, and
+//! optionally with `WouldBlock`, so that the parsers built on `read_exact` do not fail on the
+//! signals or on the nonblocking sockets.
+
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// The policy of the retries of `RetryReader` and `RetryWriter`.
+///
+/// `Interrupted` is always retried. `WouldBlock` is retried only with `with_backoff`, after a
+/// sleep doubling from `initial` up to `max`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use mm_binary_io::retry::RetryPolicy;
+///
+/// let policy = RetryPolicy::new()
+///     .with_max_retries(100)
+///     .with_backoff(Duration::from_millis(1), Duration::from_millis(50));
+/// assert_eq!(Some(100), policy.max_retries());
+///
+/// ```
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: Option<u32>,
+    backoff: Option<(Duration, Duration)>,
+}
+
+impl RetryPolicy {
+    /// Creates a new `RetryPolicy` retrying `Interrupted` without limit, and not `WouldBlock`.
+    pub fn new() -> RetryPolicy {
+        RetryPolicy::default()
+    }
+
+    /// Limits the consecutive retries of an operation to `max_retries`, after which the last
+    /// error is returned.
+    pub fn with_max_retries(mut self, max_retries: u32) -> RetryPolicy {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// Retries `WouldBlock` too, sleeping `initial` before the first retry and doubling the
+    /// sleep up to `max`.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> RetryPolicy {
+        self.backoff = Some((initial, max.max(initial)));
+        self
+    }
+
+    /// Returns the limit of the consecutive retries, or None if it is unlimited.
+    pub fn max_retries(&self) -> Option<u32> {
+        self.max_retries
+    }
+
+    /// Returns the initial and the maximum sleeps of the retries of `WouldBlock`, or None if it
+    /// is not retried.
+    pub fn backoff(&self) -> Option<(Duration, Duration)> {
+        self.backoff
+    }
+
+    /// Calls `operation` until it succeeds or fails with an error not to be retried.
+    fn retry<T, F>(&self, mut operation: F) -> io::Result<T>
+        where F: FnMut() -> io::Result<T> {
+        let mut retries = 0_u32;
+        let mut sleep = self.backoff.map(|(initial, _)| initial);
+        loop {
+            let error = match operation() {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+            let wait = match error.kind() {
+                io::ErrorKind::Interrupted => None,
+                io::ErrorKind::WouldBlock if self.backoff.is_some() => sleep,
+                _ => return Err(error),
+            };
+            if self.max_retries.is_some_and(|max_retries| retries >= max_retries) {
+                trace_event!(DEBUG, retries, "retries are exhausted");
+                return Err(error);
+            }
+            retries += 1;
+            if let Some(wait) = wait {
+                thread::sleep(wait);
+                let max = self.backoff.map_or(wait, |(_, max)| max);
+                sleep = Some((wait * 2).min(max));
+            }
+        }
+    }
+}
+
+/// Wraps a reader, and retries its reads as `RetryPolicy`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Read};
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::retry::{RetryPolicy, RetryReader};
+///
+/// // A reader interrupted before every byte.
+/// struct Interrupting<'a>(&'a [u8], bool);
+///
+/// impl<'a> Read for Interrupting<'a> {
+///     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+///         self.1 = !self.1;
+///         if self.1 {
+///             return Err(io::Error::new(io::ErrorKind::Interrupted, "signal"));
+///         }
+///         (&mut self.0).take(1).read(buf)
+///     }
+/// }
+///
+/// let mut reader = RetryReader::new(Interrupting(&[0x12, 0x34], false), RetryPolicy::new());
+/// assert_eq!(0x1234, reader.read_integer::<BigEndian, u16>().unwrap());
+///
+/// // The retries are exhausted.
+/// let policy = RetryPolicy::new().with_max_retries(0);
+/// let mut reader = RetryReader::new(Interrupting(&[0x12, 0x34], false), policy);
+/// assert_eq!(io::ErrorKind::Interrupted, reader.read(&mut [0; 2]).unwrap_err().kind());
+///
+/// ```
+///
+pub struct RetryReader<TRead>
+    where TRead: io::Read {
+    reader: TRead,
+    policy: RetryPolicy,
+}
+
+impl<TRead> RetryReader<TRead>
+    where TRead: io::Read {
+    /// Creates a new `RetryReader` of `policy`.
+    pub fn new(reader: TRead, policy: RetryPolicy) -> RetryReader<TRead> {
+        RetryReader { reader, policy }
+    }
+
+    /// Returns the retry policy.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &TRead {
+        &self.reader
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut TRead {
+        &mut self.reader
+    }
+
+    /// Unwraps this `RetryReader`, returning the underlying reader.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+}
+
+impl<TRead> io::Read for RetryReader<TRead>
+    where TRead: io::Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let reader = &mut self.reader;
+        self.policy.retry(|| reader.read(buf))
+    }
+}
+
+impl<TRead> io::Seek for RetryReader<TRead>
+    where TRead: io::Read + io::Seek {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.reader.seek(pos)
+    }
+}
+
+/// Wraps a writer, and retries its writes and flushes as `RetryPolicy`.
+pub struct RetryWriter<TWrite>
+    where TWrite: io::Write {
+    writer: TWrite,
+    policy: RetryPolicy,
+}
+
+impl<TWrite> RetryWriter<TWrite>
+    where TWrite: io::Write {
+    /// Creates a new `RetryWriter` of `policy`.
+    pub fn new(writer: TWrite, policy: RetryPolicy) -> RetryWriter<TWrite> {
+        RetryWriter { writer, policy }
+    }
+
+    /// Returns the retry policy.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &TWrite {
+        &self.writer
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut TWrite {
+        &mut self.writer
+    }
+
+    /// Unwraps this `RetryWriter`, returning the underlying writer.
+    pub fn into_inner(self) -> TWrite {
+        self.writer
+    }
+}
+
+impl<TWrite> io::Write for RetryWriter<TWrite>
+    where TWrite: io::Write {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let writer = &mut self.writer;
+        self.policy.retry(|| writer.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let writer = &mut self.writer;
+        self.policy.retry(|| writer.flush())
+    }
+}
+
+impl<TWrite> io::Seek for RetryWriter<TWrite>
+    where TWrite: io::Write + io::Seek {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.writer.seek(pos)
+    }
+}
@@ -0,0 +1,186 @@
+//! Provides the ring buffer of a fixed capacity, to which a producer appends the protocol bytes
+//! and from which a consumer parses them, both through the reading and writing functions of this
+//! crate.
+
+use std::io;
+
+use slice::OutOfBounds;
+
+/// A byte queue of a fixed capacity, which is written at the tail and read from the head.
+///
+/// A write exceeding the free space is an error of `WriteZero` whose inner error is
+/// `OutOfBounds`, and writes nothing, so that no item is queued half. A read of the empty buffer
+/// returns 0 bytes, the end of the stream, so that a parser should be run on `peek_reader` and
+/// the bytes consumed only after an item is parsed whole.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::ring_buffer::RingBuffer;
+///
+/// let mut ring = RingBuffer::new(6);
+/// ring.write_integer::<BigEndian, _>(0x1234_5678_u32).unwrap();
+/// assert_eq!(0x1234, ring.read_integer::<BigEndian, u16>().unwrap());
+///
+/// // The tail wraps around to the head.
+/// ring.write_integer::<BigEndian, _>(0x9ABC_DEF0_u32).unwrap();
+/// assert_eq!(0, ring.available());
+/// assert_eq!(io::ErrorKind::WriteZero, ring.write_integer::<BigEndian, _>(0_u8).unwrap_err().kind());
+///
+/// // An item is consumed only after it is parsed whole.
+/// let mut reader = ring.peek_reader();
+/// assert_eq!(0x5678_9ABC, reader.read_integer::<BigEndian, u32>().unwrap());
+/// let consumed = reader.position();
+/// ring.consume(consumed);
+/// assert!(ring.peek_reader().read_integer::<BigEndian, u32>().is_err());
+/// assert_eq!(2, ring.len());
+///
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct RingBuffer {
+    buffer: Box<[u8]>,
+    head: usize,
+    len: usize,
+}
+
+impl RingBuffer {
+    /// Creates a new empty `RingBuffer` of `capacity` bytes.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0, the function panics.
+    ///
+    pub fn new(capacity: usize) -> RingBuffer {
+        assert!(capacity > 0, "ring buffer capacity must not be 0");
+        RingBuffer {
+            buffer: vec![0_u8; capacity].into_boxed_slice(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the byte count of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the byte count queued.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if nothing is queued.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the byte count which can be written.
+    pub fn available(&self) -> usize {
+        self.buffer.len() - self.len
+    }
+
+    /// Discards all the bytes queued.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Returns the bytes queued, in the two slices before and after the wrap-around.
+    pub fn as_slices(&self) -> (&[u8], &[u8]) {
+        let end = self.head + self.len;
+        if end <= self.buffer.len() {
+            (&self.buffer[self.head..end], &[])
+        } else {
+            (&self.buffer[self.head..], &self.buffer[..end - self.buffer.len()])
+        }
+    }
+
+    /// Returns a reader of the bytes queued, which consumes nothing.
+    pub fn peek_reader(&self) -> PeekReader<'_> {
+        let (first, second) = self.as_slices();
+        PeekReader { first, second, position: 0 }
+    }
+
+    /// Discards the `len` bytes at the head.
+    ///
+    /// # Panics
+    ///
+    /// If `len` exceeds the byte count queued, the function panics.
+    ///
+    pub fn consume(&mut self, len: usize) {
+        assert!(len <= self.len, "consumed more bytes than queued");
+        self.head = (self.head + len) % self.buffer.len();
+        self.len -= len;
+        if self.len == 0 {
+            self.head = 0;
+        }
+    }
+}
+
+impl io::Read for RingBuffer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = io::Read::read(&mut self.peek_reader(), buf)?;
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl io::BufRead for RingBuffer {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.as_slices().0)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        RingBuffer::consume(self, amt.min(self.len));
+    }
+}
+
+impl io::Write for RingBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.available() {
+            let error = OutOfBounds { required: buf.len(), remaining: self.available() };
+            return Err(io::Error::new(io::ErrorKind::WriteZero, error));
+        }
+        let tail = (self.head + self.len) % self.buffer.len();
+        let first = buf.len().min(self.buffer.len() - tail);
+        self.buffer[tail..tail + first].copy_from_slice(&buf[..first]);
+        self.buffer[..buf.len() - first].copy_from_slice(&buf[first..]);
+        self.len += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads the bytes queued in a `RingBuffer` without consuming them.
+#[derive(Clone, Debug)]
+pub struct PeekReader<'a> {
+    first: &'a [u8],
+    second: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PeekReader<'a> {
+    /// Returns the byte count read, to be passed to `RingBuffer::consume`.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl<'a> io::Read for PeekReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = io::Read::read(&mut self.first, buf)?;
+        if self.first.is_empty() {
+            n += io::Read::read(&mut self.second, &mut buf[n..])?;
+        }
+        self.position += n;
+        Ok(n)
+    }
+}
@@ -0,0 +1,242 @@
+//! Provides an rsync-style rolling checksum and content-defined chunking.
+
+use std::io;
+
+/// The size of the read buffer used by `ContentChunker`.
+const CHUNKER_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Calculates an rsync-style (Adler-like) checksum over a sliding window of bytes.
+///
+/// Pushing a byte to a full window slides the window by one byte in constant time.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::rolling::RollingChecksum;
+///
+/// let data = b"the quick brown fox";
+///
+/// let mut rolling = RollingChecksum::new(4);
+/// for &b in data.iter() {
+///     rolling.push(b);
+/// }
+///
+/// assert!(rolling.is_full());
+/// assert_eq!(RollingChecksum::checksum(b" fox"), rolling.digest());
+///
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct RollingChecksum {
+    window: Vec<u8>,
+    position: usize,
+    len: usize,
+    a: u32,
+    b: u32,
+}
+
+impl RollingChecksum {
+    /// Creates a new `RollingChecksum` with an empty window of `window_size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// If `window_size` is 0, the function panics.
+    ///
+    pub fn new(window_size: usize) -> RollingChecksum {
+        assert!(window_size > 0, "window size must not be 0");
+        RollingChecksum {
+            window: vec![0; window_size],
+            position: 0,
+            len: 0,
+            a: 0,
+            b: 0,
+        }
+    }
+
+    /// Calculates the checksum of `bytes` as a single window.
+    pub fn checksum(bytes: &[u8]) -> u32 {
+        let mut a = 0_u32;
+        let mut b = 0_u32;
+        let len = bytes.len() as u32;
+        for (i, &x) in bytes.iter().enumerate() {
+            a = a.wrapping_add(x as u32);
+            b = b.wrapping_add((len - i as u32).wrapping_mul(x as u32));
+        }
+        (a & 0xFFFF) | (b << 16)
+    }
+
+    /// Returns the size of the window.
+    pub fn window_size(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Returns true if the window is filled.
+    pub fn is_full(&self) -> bool {
+        self.len == self.window.len()
+    }
+
+    /// Pushes `byte` into the window, removing the oldest byte if the window is full.
+    pub fn push(&mut self, byte: u8) {
+        if self.is_full() {
+            let old = self.window[self.position] as u32;
+            self.a = self.a.wrapping_sub(old).wrapping_add(byte as u32);
+            self.b = self.b.wrapping_sub((self.len as u32).wrapping_mul(old)).wrapping_add(self.a);
+        } else {
+            self.len += 1;
+            self.a = self.a.wrapping_add(byte as u32);
+            self.b = self.b.wrapping_add(self.a);
+        }
+        self.window[self.position] = byte;
+        self.position = (self.position + 1) % self.window.len();
+    }
+
+    /// Returns the checksum of the bytes in the window.
+    pub fn digest(&self) -> u32 {
+        (self.a & 0xFFFF) | (self.b << 16)
+    }
+
+    /// Empties the window.
+    pub fn reset(&mut self) {
+        self.position = 0;
+        self.len = 0;
+        self.a = 0;
+        self.b = 0;
+    }
+}
+
+/// Splits a stream into content-defined chunks.
+///
+/// A chunk ends where the rolling checksum of the last `window_size` bytes satisfies
+/// `digest & mask == 0`, so that identical content produces identical chunks regardless of
+/// its offset in the stream. The chunk sizes are kept within `min_size..=max_size`,
+/// except for the last chunk which may be shorter.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::rolling::ContentChunker;
+///
+/// let data = (0..100_000_u32).map(|x| (x.wrapping_mul(2_654_435_761) >> 24) as u8).collect::<Vec<_>>();
+///
+/// let chunker = ContentChunker::new(io::Cursor::new(data.clone()), 32, 0x3FF, 256, 8192);
+/// let chunks = chunker.collect::<io::Result<Vec<_>>>().unwrap();
+///
+/// assert!(chunks.len() > 1);
+/// assert!(chunks[..chunks.len() - 1].iter().all(|c| c.len() >= 256 && c.len() <= 8192));
+/// assert_eq!(data, chunks.concat());
+///
+/// ```
+///
+pub struct ContentChunker<TRead>
+    where TRead: io::Read {
+    reader: TRead,
+    rolling: RollingChecksum,
+    mask: u32,
+    min_size: usize,
+    max_size: usize,
+    buffer: Vec<u8>,
+    buffer_position: usize,
+    buffer_len: usize,
+    finished: bool,
+}
+
+impl<TRead> ContentChunker<TRead>
+    where TRead: io::Read {
+    /// Creates a new `ContentChunker`.
+    ///
+    /// # Arguments
+    ///
+    /// * window_size - the size of the rolling checksum window.
+    /// * mask - the mask of the checksum bits which must be 0 at a boundary.
+    ///   The average chunk size is about `mask + 1` bytes if `mask` is `2^n - 1`.
+    /// * min_size - the minimum chunk size.
+    /// * max_size - the maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// If `window_size` is 0 or `max_size` is less than `min_size` or 0, the function panics.
+    ///
+    pub fn new(reader: TRead, window_size: usize, mask: u32, min_size: usize, max_size: usize) -> ContentChunker<TRead> {
+        assert!(max_size > 0 && min_size <= max_size, "invalid chunk size range");
+        ContentChunker {
+            reader,
+            rolling: RollingChecksum::new(window_size),
+            mask,
+            min_size,
+            max_size,
+            buffer: vec![0; CHUNKER_BUFFER_SIZE],
+            buffer_position: 0,
+            buffer_len: 0,
+            finished: false,
+        }
+    }
+
+    /// Unwraps this `ContentChunker`, returning the underlying reader.
+    ///
+    /// The bytes buffered but not returned as chunks yet are lost.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+
+    fn next_chunk(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut chunk = vec![];
+        self.rolling.reset();
+        loop {
+            if self.buffer_position == self.buffer_len {
+                let n = match self.reader.read(&mut self.buffer) {
+                    Ok(n) => n,
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                if n == 0 {
+                    return Ok(if chunk.is_empty() { None } else { Some(chunk) });
+                }
+                self.buffer_position = 0;
+                self.buffer_len = n;
+            }
+
+            let start = self.buffer_position;
+            let mut end = start;
+            let mut boundary = false;
+            while end < self.buffer_len {
+                let b = self.buffer[end];
+                end += 1;
+                self.rolling.push(b);
+                let size = chunk.len() + end - start;
+                if size >= self.max_size
+                    || (size >= self.min_size && self.rolling.is_full() && self.rolling.digest() & self.mask == 0) {
+                    boundary = true;
+                    break;
+                }
+            }
+            chunk.extend_from_slice(&self.buffer[start..end]);
+            self.buffer_position = end;
+            if boundary {
+                return Ok(Some(chunk));
+            }
+        }
+    }
+}
+
+impl<TRead> Iterator for ContentChunker<TRead>
+    where TRead: io::Read {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.next_chunk() {
+            Ok(Some(chunk)) => Some(Ok(chunk)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Provides the savepoints of a seekable stream, for the backtracking parsers of the ambiguous
+//! formats.
+
+use std::io;
+
+/// A position saved by `SavepointRead::savepoint`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Savepoint {
+    position: u64,
+}
+
+impl Savepoint {
+    /// Returns the saved position.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+/// Provides the features to save and restore the position of a seekable stream.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::savepoint::SavepointRead;
+///
+/// // A record is either a tagged u32, or an untagged u16.
+/// let mut reader = io::Cursor::new(vec![0x02, 0x01, 0xFF, 0x03, 0x04]);
+///
+/// let tagged = |r: &mut io::Cursor<Vec<u8>>| {
+///     if r.read_integer::<LittleEndian, u8>()? != 0xFF {
+///         return Err(io::Error::new(io::ErrorKind::InvalidData, "not tagged"));
+///     }
+///     r.read_integer::<LittleEndian, u32>()
+/// };
+/// let untagged = |r: &mut io::Cursor<Vec<u8>>| r.read_integer::<LittleEndian, u16>().map(u32::from);
+///
+/// let value = reader.try_parse(tagged).or_else(|_| reader.try_parse(untagged)).unwrap();
+/// assert_eq!(0x0102, value);
+///
+/// // The failed attempt is rewound.
+/// let saved = reader.savepoint().unwrap();
+/// assert!(reader.try_parse(tagged).is_err());
+/// assert_eq!(saved.position(), reader.position());
+/// assert_eq!(0xFF, reader.read_integer::<LittleEndian, u8>().unwrap());
+///
+/// reader.restore(saved).unwrap();
+/// assert_eq!(0x03FF, reader.try_parse(untagged).unwrap());
+///
+/// ```
+///
+pub trait SavepointRead: io::Read + io::Seek {
+    /// Saves the current position.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(Savepoint), otherwise Err(io::Error).
+    ///
+    fn savepoint(&mut self) -> io::Result<Savepoint>
+        where Self: Sized {
+        self.stream_position().map(|position| Savepoint { position })
+    }
+
+    /// Seeks back to `savepoint`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    fn restore(&mut self, savepoint: Savepoint) -> io::Result<()>
+        where Self: Sized {
+        self.seek(io::SeekFrom::Start(savepoint.position)).map(|_| ())
+    }
+
+    /// Parses by `f`, and seeks back to the current position if `f` fails.
+    ///
+    /// # Errors
+    ///
+    /// If `f` succeeds then Ok(T), otherwise Err(io::Error) of `f`, after which the position is
+    /// restored. If the restoring seek fails, returns its Err(io::Error) instead.
+    ///
+    fn try_parse<T, F>(&mut self, f: F) -> io::Result<T>
+        where Self: Sized,
+              F: FnOnce(&mut Self) -> io::Result<T> {
+        let savepoint = self.savepoint()?;
+        let result = f(self);
+        if result.is_err() {
+            trace_event!(TRACE, position = savepoint.position, "rewind failed parse");
+            self.restore(savepoint)?;
+        }
+        result
+    }
+}
+
+impl<T> SavepointRead for T
+    where T: io::Read + io::Seek {}
@@ -0,0 +1,368 @@
+//! Provides the parser driven by a format described as data, which reads the fields into a
+//! dynamic `Value` tree, so that a tool can load the user-supplied format descriptions without
+//! being recompiled.
+//!
+//! A `Schema` is a list of the `Field`s, each of which has a name, a `FieldType`, and optionally
+//! the byte order, the repeat count and the condition. The counts and the conditions refer to
+//! the integer fields read before them, in the same or an enclosing structure.
+
+use std::convert::TryFrom;
+use std::io;
+
+use binary_read::BinaryRead;
+use endian::{BigEndian, LittleEndian};
+use error::Context;
+use read_integer::ReadInteger;
+
+/// The maximum nesting depth of the structures accepted by `Schema::read`.
+pub const MAX_DEPTH: usize = 64;
+
+/// The items of a repeated field allocated ahead, so that a broken count allocates nothing huge.
+const MAX_PREALLOCATED_ITEMS: usize = 4096;
+
+/// The byte order of the multi-byte fields.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ByteOrder {
+    /// Big-endian.
+    Big,
+    /// Little-endian.
+    Little,
+}
+
+/// A value read by a `Schema`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// An unsigned integer.
+    UInt(u64),
+    /// A signed integer.
+    Int(i64),
+    /// A floating point number.
+    Float(f64),
+    /// A byte array.
+    Bytes(Vec<u8>),
+    /// A UTF-8 string.
+    Str(String),
+    /// The items of a repeated field.
+    List(Vec<Value>),
+    /// The fields of a structure in the read order, without the fields skipped by the conditions.
+    Struct(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Returns the field `name` of a structure, or None if it is not a structure or has no such
+    /// field.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        match *self {
+            Value::Struct(ref fields) => fields.iter().rev().find(|field| field.0 == name).map(|field| &field.1),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an integer fitting in `u64`, or None otherwise.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::UInt(value) => Some(value),
+            Value::Int(value) if value >= 0 => Some(value as u64),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an integer fitting in `i64`, or None otherwise.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::UInt(value) if value <= i64::MAX as u64 => Some(value as i64),
+            Value::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_i128(&self) -> Option<i128> {
+        match *self {
+            Value::UInt(value) => Some(i128::from(value)),
+            Value::Int(value) => Some(i128::from(value)),
+            _ => None,
+        }
+    }
+}
+
+/// The count of the bytes, the characters or the items of a field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Count {
+    /// A fixed count.
+    Fixed(u64),
+    /// The value of the integer field of the name read before.
+    Field(String),
+}
+
+/// The condition of a field to be read, on the integer field of the name read before.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Condition {
+    /// The field equals the value.
+    Equals(String, i64),
+    /// The field does not equal the value.
+    NotEquals(String, i64),
+    /// The field has all the bits of the mask set.
+    BitsSet(String, u64),
+}
+
+/// The type of a field.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldType {
+    /// `u8`, read as `Value::UInt`.
+    U8,
+    /// `u16`, read as `Value::UInt`.
+    U16,
+    /// `u32`, read as `Value::UInt`.
+    U32,
+    /// `u64`, read as `Value::UInt`.
+    U64,
+    /// `i8`, read as `Value::Int`.
+    I8,
+    /// `i16`, read as `Value::Int`.
+    I16,
+    /// `i32`, read as `Value::Int`.
+    I32,
+    /// `i64`, read as `Value::Int`.
+    I64,
+    /// `f32`, read as `Value::Float`.
+    F32,
+    /// `f64`, read as `Value::Float`.
+    F64,
+    /// A byte array of the count, read as `Value::Bytes`.
+    Bytes(Count),
+    /// A UTF-8 string of the byte count, read as `Value::Str`.
+    Str(Count),
+    /// A structure of the fields, read as `Value::Struct`.
+    Struct(Vec<Field>),
+}
+
+/// A field of a `Schema`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    name: String,
+    field_type: FieldType,
+    byte_order: Option<ByteOrder>,
+    repeat: Option<Count>,
+    condition: Option<Condition>,
+}
+
+impl Field {
+    /// Creates a new `Field` read once, in the byte order of the schema.
+    pub fn new<TName>(name: TName, field_type: FieldType) -> Field
+        where TName: Into<String> {
+        Field {
+            name: name.into(),
+            field_type,
+            byte_order: None,
+            repeat: None,
+            condition: None,
+        }
+    }
+
+    /// Reads this field, and the fields nested in it, in `byte_order`.
+    pub fn with_byte_order(mut self, byte_order: ByteOrder) -> Field {
+        self.byte_order = Some(byte_order);
+        self
+    }
+
+    /// Reads this field `count` times into `Value::List`.
+    pub fn repeated(mut self, count: Count) -> Field {
+        self.repeat = Some(count);
+        self
+    }
+
+    /// Reads this field only if `condition` holds, and skips it otherwise.
+    pub fn when(mut self, condition: Condition) -> Field {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the type.
+    pub fn field_type(&self) -> &FieldType {
+        &self.field_type
+    }
+}
+
+/// A format described as data.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::schema::{ByteOrder, Condition, Count, Field, FieldType, Schema, Value};
+///
+/// let schema = Schema::new(ByteOrder::Little, vec![
+///     Field::new("magic", FieldType::Str(Count::Fixed(4))),
+///     Field::new("flags", FieldType::U8),
+///     Field::new("count", FieldType::U8),
+///     Field::new("entries", FieldType::Struct(vec![
+///         Field::new("id", FieldType::U16).with_byte_order(ByteOrder::Big),
+///         Field::new("len", FieldType::U8),
+///         Field::new("data", FieldType::Bytes(Count::Field("len".to_string()))),
+///     ])).repeated(Count::Field("count".to_string())),
+///     Field::new("extra", FieldType::I16).when(Condition::BitsSet("flags".to_string(), 0x01)),
+///     Field::new("ignored", FieldType::U8).when(Condition::Equals("count".to_string(), 0)),
+/// ]);
+///
+/// let data = b"DEMO\x01\x02\x00\x07\x01\xAA\x00\x08\x00\xFE\xFF";
+/// let value = schema.read(&mut io::Cursor::new(&data[..])).unwrap();
+///
+/// assert_eq!(Some(&Value::Str("DEMO".to_string())), value.get("magic"));
+/// let entries = match value.get("entries") {
+///     Some(&Value::List(ref entries)) => entries,
+///     _ => panic!(),
+/// };
+/// assert_eq!(Some(7), entries[0].get("id").and_then(Value::as_u64));
+/// assert_eq!(Some(&Value::Bytes(vec![0xAA])), entries[0].get("data"));
+/// assert_eq!(Some(&Value::Bytes(vec![])), entries[1].get("data"));
+/// assert_eq!(Some(-2), value.get("extra").and_then(Value::as_i64));
+/// assert_eq!(None, value.get("ignored"));
+///
+/// // The error tells the path of the failed field.
+/// let error = schema.read(&mut io::Cursor::new(&data[..10])).unwrap_err();
+/// assert_eq!(io::ErrorKind::UnexpectedEof, error.kind());
+/// assert!(error.to_string().contains("entries[1].id"));
+///
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schema {
+    byte_order: ByteOrder,
+    fields: Vec<Field>,
+}
+
+impl Schema {
+    /// Creates a new `Schema` of the `fields` read in `byte_order` by default.
+    pub fn new(byte_order: ByteOrder, fields: Vec<Field>) -> Schema {
+        Schema { byte_order, fields }
+    }
+
+    /// Returns the default byte order.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// Returns the fields.
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Reads the fields into `Value::Struct`.
+    ///
+    /// # Errors
+    ///
+    /// If a count or a condition refers to a field not read before or not an integer, returns
+    /// Err(io::Error) of `InvalidInput`. If a string is not UTF-8 or the structures are nested
+    /// deeper than `MAX_DEPTH`, returns Err(io::Error) of `InvalidData`. If reading fails,
+    /// returns Err(io::Error). The errors carry the path of the failed field, as
+    /// `error::Context`.
+    ///
+    pub fn read<TRead>(&self, reader: &mut TRead) -> io::Result<Value>
+        where TRead: io::Read {
+        let mut scopes = vec![];
+        read_struct(&self.fields, self.byte_order, reader, &mut scopes)
+    }
+}
+
+/// The fields read so far, of the structure being read and its enclosing structures.
+type Scopes = Vec<Vec<(String, Value)>>;
+
+fn read_struct<TRead>(fields: &[Field], byte_order: ByteOrder, reader: &mut TRead, scopes: &mut Scopes) -> io::Result<Value>
+    where TRead: io::Read {
+    if scopes.len() >= MAX_DEPTH {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "structures are nested too deep"));
+    }
+    scopes.push(vec![]);
+    for field in fields {
+        if let Some(value) = read_field(field, byte_order, reader, scopes).with_ctx(|| field.name.clone())? {
+            scopes.last_mut().unwrap().push((field.name.clone(), value));
+        }
+    }
+    Ok(Value::Struct(scopes.pop().unwrap()))
+}
+
+fn read_field<TRead>(field: &Field, byte_order: ByteOrder, reader: &mut TRead, scopes: &mut Scopes) -> io::Result<Option<Value>>
+    where TRead: io::Read {
+    if let Some(ref condition) = field.condition {
+        if !evaluate(condition, scopes)? {
+            return Ok(None);
+        }
+    }
+    let byte_order = field.byte_order.unwrap_or(byte_order);
+    let count = match field.repeat {
+        Some(ref count) => resolve(count, scopes)?,
+        None => return read_type(&field.field_type, byte_order, reader, scopes).map(Some),
+    };
+    let mut items = Vec::with_capacity(count.min(MAX_PREALLOCATED_ITEMS));
+    for i in 0..count {
+        items.push(read_type(&field.field_type, byte_order, reader, scopes).with_ctx(|| format!("[{}]", i))?);
+    }
+    Ok(Some(Value::List(items)))
+}
+
+fn read_type<TRead>(field_type: &FieldType, byte_order: ByteOrder, reader: &mut TRead, scopes: &mut Scopes) -> io::Result<Value>
+    where TRead: io::Read {
+    Ok(match *field_type {
+        FieldType::U8 => Value::UInt(read_integer::<_, u8>(reader, byte_order)?.into()),
+        FieldType::U16 => Value::UInt(read_integer::<_, u16>(reader, byte_order)?.into()),
+        FieldType::U32 => Value::UInt(read_integer::<_, u32>(reader, byte_order)?.into()),
+        FieldType::U64 => Value::UInt(read_integer::<_, u64>(reader, byte_order)?),
+        FieldType::I8 => Value::Int(read_integer::<_, i8>(reader, byte_order)?.into()),
+        FieldType::I16 => Value::Int(read_integer::<_, i16>(reader, byte_order)?.into()),
+        FieldType::I32 => Value::Int(read_integer::<_, i32>(reader, byte_order)?.into()),
+        FieldType::I64 => Value::Int(read_integer::<_, i64>(reader, byte_order)?),
+        FieldType::F32 => Value::Float(f32::from_bits(read_integer::<_, u32>(reader, byte_order)?).into()),
+        FieldType::F64 => Value::Float(f64::from_bits(read_integer::<_, u64>(reader, byte_order)?)),
+        FieldType::Bytes(ref count) => Value::Bytes(reader.read_byte_array(resolve(count, scopes)?)?),
+        FieldType::Str(ref count) => {
+            let bytes = reader.read_byte_array(resolve(count, scopes)?)?;
+            let value = String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            Value::Str(value)
+        }
+        FieldType::Struct(ref fields) => read_struct(fields, byte_order, reader, scopes)?,
+    })
+}
+
+fn read_integer<TRead, TInt>(reader: &mut TRead, byte_order: ByteOrder) -> io::Result<TInt>
+    where TRead: io::Read,
+          TInt: ReadInteger<OutputType=TInt> {
+    match byte_order {
+        ByteOrder::Big => reader.read_integer::<BigEndian, TInt>(),
+        ByteOrder::Little => reader.read_integer::<LittleEndian, TInt>(),
+    }
+}
+
+fn resolve(count: &Count, scopes: &Scopes) -> io::Result<usize> {
+    let count = match *count {
+        Count::Fixed(count) => count,
+        Count::Field(ref name) => lookup(name, scopes)?.as_u64()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("count field `{}` is not a non-negative integer", name)))?,
+    };
+    usize::try_from(count).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "count does not fit in usize"))
+}
+
+fn evaluate(condition: &Condition, scopes: &Scopes) -> io::Result<bool> {
+    let name = match *condition {
+        Condition::Equals(ref name, _) | Condition::NotEquals(ref name, _) | Condition::BitsSet(ref name, _) => name,
+    };
+    let value = lookup(name, scopes)?.as_i128()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("condition field `{}` is not an integer", name)))?;
+    Ok(match *condition {
+        Condition::Equals(_, expected) => value == i128::from(expected),
+        Condition::NotEquals(_, expected) => value != i128::from(expected),
+        Condition::BitsSet(_, mask) => value as u64 & mask == mask,
+    })
+}
+
+fn lookup<'a>(name: &str, scopes: &'a Scopes) -> io::Result<&'a Value> {
+    scopes.iter().rev()
+        .flat_map(|scope| scope.iter().rev())
+        .find(|field| field.0 == name)
+        .map(|field| &field.1)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("field `{}` is not read before", name)))
+}
@@ -0,0 +1,96 @@
+//! Provides the reusable scratch buffers for the transient bytes of the reads.
+//!
+//! The string reads, `skip_bytes`, the checksum frame reads, the array staging of
+//! `read_integer_array` and the chunked copies borrow a thread-local buffer by `with_scratch`,
+//! instead of allocating one per call in hot parsing loops. `ScratchBuffer` can also be owned and
+//! passed around explicitly.
+
+use std::cell::RefCell;
+
+/// The largest buffer kept by `with_scratch` after use, so that one large read does not hold
+/// the memory of the thread forever.
+pub const MAX_RETAINED_SIZE: usize = 1024 * 1024;
+
+/// A reusable buffer which grows to the largest length requested.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::scratch::ScratchBuffer;
+///
+/// let mut scratch = ScratchBuffer::new();
+/// scratch.get(16).copy_from_slice(&[0xAB; 16]);
+/// assert_eq!(4, scratch.get(4).len());
+/// assert!(scratch.capacity() >= 16);
+///
+/// ```
+///
+#[derive(Debug, Default)]
+pub struct ScratchBuffer {
+    buf: Vec<u8>,
+}
+
+impl ScratchBuffer {
+    /// Creates a new empty `ScratchBuffer`.
+    pub fn new() -> ScratchBuffer {
+        ScratchBuffer { buf: Vec::new() }
+    }
+
+    /// Returns the buffer of `len` bytes, growing it if needed.
+    ///
+    /// The contents are left from the previous use.
+    pub fn get(&mut self, len: usize) -> &mut [u8] {
+        if self.buf.len() < len {
+            self.buf.resize(len, 0);
+        }
+        &mut self.buf[..len]
+    }
+
+    /// Returns the byte count allocated.
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Frees the memory if more than `max` bytes are allocated.
+    pub fn shrink_to(&mut self, max: usize) {
+        if self.buf.capacity() > max {
+            self.buf = Vec::new();
+        }
+    }
+}
+
+thread_local! {
+    static SCRATCH: RefCell<ScratchBuffer> = RefCell::new(ScratchBuffer::new());
+}
+
+/// Calls `f` with a scratch buffer of `len` bytes borrowed from the thread-local pool.
+///
+/// The contents of the buffer are left from the previous use. If the pool is already borrowed
+/// by the caller, e.g. by a reader which reads through another, a temporary buffer is used.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::scratch;
+///
+/// let sum = scratch::with_scratch(4, |buf| {
+///     buf.copy_from_slice(&[1, 2, 3, 4]);
+///     buf.iter().map(|&b| b as u32).sum::<u32>()
+/// });
+/// assert_eq!(10, sum);
+///
+/// ```
+///
+pub fn with_scratch<T, F>(len: usize, f: F) -> T
+    where F: FnOnce(&mut [u8]) -> T {
+    SCRATCH.with(|scratch| {
+        match scratch.try_borrow_mut() {
+            Ok(mut scratch) => {
+                let result = f(scratch.get(len));
+                scratch.shrink_to(MAX_RETAINED_SIZE);
+                result
+            }
+            Err(_) => f(ScratchBuffer::new().get(len)),
+        }
+    })
+}
@@ -0,0 +1,173 @@
+//! Provides the search of a byte pattern over a large stream, for the carving and the signature
+//! scanning.
+//!
+//! The stream is read in blocks, keeping the tail of the previous block, so that the matches
+//! straddling the block boundaries are found. The blocks are searched by Boyer-Moore-Horspool.
+
+use std::io;
+
+use binary_read::read_fully;
+
+/// The byte count read at a time.
+const BLOCK_SIZE: usize = 64 * 1024;
+
+/// A precomputed byte pattern to search for.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::search::Finder;
+///
+/// let finder = Finder::new(b"PK\x03\x04");
+/// assert_eq!(Some(3), finder.find(b"abcPK\x03\x04"));
+/// assert_eq!(None, finder.find(b"PK\x03"));
+///
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct Finder {
+    pattern: Vec<u8>,
+    skip: Vec<usize>,
+}
+
+impl Finder {
+    /// Creates a new `Finder` of `pattern`.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is empty, the function panics.
+    ///
+    pub fn new(pattern: &[u8]) -> Finder {
+        assert!(!pattern.is_empty(), "search pattern must not be empty");
+        let last = pattern.len() - 1;
+        let mut skip = vec![pattern.len(); 256];
+        for (i, &byte) in pattern[..last].iter().enumerate() {
+            skip[byte as usize] = last - i;
+        }
+        Finder { pattern: pattern.to_vec(), skip }
+    }
+
+    /// Returns the pattern.
+    pub fn pattern(&self) -> &[u8] {
+        &self.pattern
+    }
+
+    /// Returns the offset of the first match in `haystack`, or None if nothing matches.
+    pub fn find(&self, haystack: &[u8]) -> Option<usize> {
+        let last = self.pattern.len() - 1;
+        let mut i = 0;
+        while i + last < haystack.len() {
+            let byte = haystack[i + last];
+            if byte == self.pattern[last] && haystack[i..i + last] == self.pattern[..last] {
+                return Some(i);
+            }
+            i += self.skip[byte as usize];
+        }
+        None
+    }
+}
+
+/// Provides the features to search a seekable stream for a byte pattern.
+///
+/// The search starts at the current position, and the offsets are from the stream head.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Seek, SeekFrom};
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::search::SearchRead;
+///
+/// let mut data = vec![0_u8; 200_000];
+/// data[65_534..65_538].copy_from_slice(b"\xFF\xD8\xFF\xE0");
+/// data[100_000..100_004].copy_from_slice(b"\xFF\xD8\xFF\xE0");
+/// let mut reader = io::Cursor::new(data);
+///
+/// // The first match straddles the block boundary.
+/// assert_eq!(vec![65_534, 100_000], reader.find_all(b"\xFF\xD8\xFF\xE0").unwrap());
+///
+/// reader.seek(SeekFrom::Start(70_000)).unwrap();
+/// assert_eq!(Some(100_000), reader.find_first(b"\xFF\xD8").unwrap());
+/// assert_eq!(0xFFD8, reader.read_integer::<BigEndian, u16>().unwrap());
+///
+/// ```
+///
+pub trait SearchRead: io::Read + io::Seek {
+    /// Searches to the end of the stream, and returns the offsets of all the matches of `pattern`,
+    /// including the overlapping ones.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is empty, the function panics.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the offsets), otherwise Err(io::Error).
+    ///
+    fn find_all(&mut self, pattern: &[u8]) -> io::Result<Vec<u64>>
+        where Self: Sized {
+        let mut matches = vec![];
+        search(self, &Finder::new(pattern), |offset| {
+            matches.push(offset);
+            true
+        })?;
+        Ok(matches)
+    }
+
+    /// Searches for the first match of `pattern`, and seeks to it, or to the end of the stream
+    /// if nothing matches.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is empty, the function panics.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the offset or None), otherwise Err(io::Error).
+    ///
+    fn find_first(&mut self, pattern: &[u8]) -> io::Result<Option<u64>>
+        where Self: Sized {
+        let found = search(self, &Finder::new(pattern), |_| false)?;
+        if let Some(offset) = found {
+            self.seek(io::SeekFrom::Start(offset))?;
+        }
+        Ok(found)
+    }
+}
+
+impl<T> SearchRead for T
+    where T: io::Read + io::Seek {}
+
+/// Passes the offsets of the matches to `on_match` until it returns false, and returns the offset
+/// where it stopped, or None at the end of the stream.
+fn search<TRead, F>(reader: &mut TRead, finder: &Finder, mut on_match: F) -> io::Result<Option<u64>>
+    where TRead: io::Read + io::Seek,
+          F: FnMut(u64) -> bool {
+    trace_span!(DEBUG, "search stream", pattern_len = finder.pattern.len());
+    let keep = finder.pattern.len() - 1;
+    let mut base = reader.stream_position()?;
+    let mut buffer = Vec::with_capacity(BLOCK_SIZE + keep);
+    loop {
+        let kept = buffer.len();
+        buffer.resize(kept + BLOCK_SIZE, 0);
+        let read = read_fully(reader, &mut buffer[kept..])?;
+        buffer.truncate(kept + read);
+
+        let mut from = 0;
+        while let Some(i) = finder.find(&buffer[from..]) {
+            let offset = base + (from + i) as u64;
+            if !on_match(offset) {
+                return Ok(Some(offset));
+            }
+            from += i + 1;
+        }
+        if read < BLOCK_SIZE {
+            return Ok(None);
+        }
+        // A match starting in the kept tail ends in the next block.
+        let dropped = buffer.len() - keep.min(buffer.len());
+        buffer.drain(..dropped);
+        base += dropped as u64;
+    }
+}
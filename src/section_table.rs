@@ -0,0 +1,311 @@
+//! Provides the writer which records the offset and the length of each named section, and then
+//! writes the index of the sections, for the container formats.
+//!
+//! The index is written either after the sections, or into a slot reserved in the header before
+//! them. Its encoding is given by a function, and `write_table` and `read_table` provide a
+//! default one.
+
+use std::convert::TryFrom;
+use std::io;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::Endian;
+use error::Context;
+use slice::OutOfBounds;
+
+/// The section count preallocated at most, so that a corrupt count does not exhaust the memory.
+const MAX_PREALLOCATED_SECTIONS: usize = 4096;
+
+/// A named section written by `SectionWriter`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Section {
+    /// The name.
+    pub name: String,
+    /// The offset from the stream head.
+    pub offset: u64,
+    /// The byte count.
+    pub len: u64,
+}
+
+/// A slot reserved for the index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexSlot {
+    offset: u64,
+    len: usize,
+}
+
+impl IndexSlot {
+    /// Returns the offset from the stream head.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the byte count.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the slot is of 0 bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// The writer which records the sections written to `TWrite`.
+///
+/// The sections are written one at a time, between `begin_section` and `end_section`, or in
+/// `section`. The bytes written outside the sections, such as the header, are not recorded.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Write};
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::section_table::{self, Section, SectionWriter};
+///
+/// let mut writer = SectionWriter::new(io::Cursor::new(vec![]));
+/// writer.get_mut().write_all(b"PACK").unwrap();
+/// let slot = writer.reserve_slot(64).unwrap();
+///
+/// writer.section("meta", |w| w.write_all(b"{}")).unwrap();
+/// writer.begin_section("data").unwrap();
+/// writer.get_mut().write_integer_array::<LittleEndian, u32>(&[1, 2, 3]).unwrap();
+/// writer.end_section().unwrap();
+///
+/// writer.write_index_at(slot, section_table::write_table::<LittleEndian>).unwrap();
+/// let bytes = writer.into_inner().into_inner();
+///
+/// let mut reader = io::Cursor::new(&bytes[..]);
+/// reader.set_position(4);
+/// let sections = section_table::read_table::<LittleEndian, _>(&mut reader).unwrap();
+/// assert_eq!(vec![
+///     Section { name: "meta".to_string(), offset: 68, len: 2 },
+///     Section { name: "data".to_string(), offset: 70, len: 12 },
+/// ], sections);
+///
+/// reader.set_position(sections[1].offset);
+/// assert_eq!(vec![1, 2, 3], reader.read_integer_array::<LittleEndian, u32>(3).unwrap());
+///
+/// ```
+///
+pub struct SectionWriter<TWrite> {
+    writer: TWrite,
+    sections: Vec<Section>,
+    open: Option<(String, u64)>,
+}
+
+impl<TWrite> SectionWriter<TWrite>
+    where TWrite: io::Write + io::Seek {
+    /// Creates a new `SectionWriter` writing to `writer` from its current position.
+    pub fn new(writer: TWrite) -> SectionWriter<TWrite> {
+        SectionWriter {
+            writer,
+            sections: vec![],
+            open: None,
+        }
+    }
+
+    /// Returns the sections ended, in the written order.
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    /// Starts the section `name` at the current position.
+    ///
+    /// # Errors
+    ///
+    /// If a section is open, returns Err(io::Error) of `InvalidInput`.
+    /// If getting the position fails, returns Err(io::Error).
+    ///
+    pub fn begin_section<TName>(&mut self, name: TName) -> io::Result<()>
+        where TName: Into<String> {
+        check_closed(&self.open)?;
+        let offset = self.writer.stream_position()?;
+        self.open = Some((name.into(), offset));
+        Ok(())
+    }
+
+    /// Ends the open section at the current position, and returns it.
+    ///
+    /// # Errors
+    ///
+    /// If no section is open, or the position is before the section start, returns
+    /// Err(io::Error) of `InvalidInput`. If getting the position fails, returns Err(io::Error).
+    ///
+    pub fn end_section(&mut self) -> io::Result<&Section> {
+        let end = self.writer.stream_position()?;
+        let (name, offset) = match self.open.take() {
+            Some(open) => open,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "no section is open")),
+        };
+        if end < offset {
+            let message = format!("position {} is before the start of section {}", end, name);
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        trace_event!(TRACE, name = %name, offset, len = end - offset, "end section");
+        self.sections.push(Section { name, offset, len: end - offset });
+        Ok(&self.sections[self.sections.len() - 1])
+    }
+
+    /// Writes the section `name` by `f`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the result of `f`), otherwise Err(io::Error) whose
+    /// field path names the section. If `f` fails, the section is left open.
+    ///
+    pub fn section<TName, F, R>(&mut self, name: TName, f: F) -> io::Result<R>
+        where TName: Into<String>,
+              F: FnOnce(&mut TWrite) -> io::Result<R> {
+        let name = name.into();
+        self.begin_section(name.clone())?;
+        let result = f(&mut self.writer).ctx(name)?;
+        self.end_section()?;
+        Ok(result)
+    }
+
+    /// Writes `len` zeros at the current position, and returns the slot of them for
+    /// `write_index_at`.
+    ///
+    /// # Errors
+    ///
+    /// If a section is open, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    pub fn reserve_slot(&mut self, len: usize) -> io::Result<IndexSlot> {
+        check_closed(&self.open)?;
+        let offset = self.writer.stream_position()?;
+        self.writer.write_all(&vec![0_u8; len])?;
+        Ok(IndexSlot { offset, len })
+    }
+
+    /// Writes the index of the sections encoded by `encode` at the current position, and
+    /// returns its offset.
+    ///
+    /// # Errors
+    ///
+    /// If a section is open, returns Err(io::Error) of `InvalidInput`.
+    /// If encoding or writing fails, returns Err(io::Error).
+    ///
+    pub fn write_index<F>(&mut self, encode: F) -> io::Result<u64>
+        where F: FnOnce(&mut Vec<u8>, &[Section]) -> io::Result<()> {
+        check_closed(&self.open)?;
+        let index = self.encode_index(encode)?;
+        let offset = self.writer.stream_position()?;
+        self.writer.write_all(&index)?;
+        Ok(offset)
+    }
+
+    /// Writes the index of the sections encoded by `encode` into `slot`, padding it with zeros,
+    /// and returns to the current position.
+    ///
+    /// # Errors
+    ///
+    /// If a section is open, returns Err(io::Error) of `InvalidInput`.
+    /// If the index exceeds the slot, returns Err(io::Error) of `WriteZero` whose inner error is
+    /// `OutOfBounds`, and writes nothing. If encoding or writing fails, returns Err(io::Error).
+    ///
+    pub fn write_index_at<F>(&mut self, slot: IndexSlot, encode: F) -> io::Result<()>
+        where F: FnOnce(&mut Vec<u8>, &[Section]) -> io::Result<()> {
+        check_closed(&self.open)?;
+        let mut index = self.encode_index(encode)?;
+        if index.len() > slot.len {
+            let error = OutOfBounds { required: index.len(), remaining: slot.len };
+            return Err(io::Error::new(io::ErrorKind::WriteZero, error));
+        }
+        index.resize(slot.len, 0);
+        let position = self.writer.stream_position()?;
+        self.writer.seek(io::SeekFrom::Start(slot.offset))?;
+        self.writer.write_all(&index)?;
+        self.writer.seek(io::SeekFrom::Start(position))?;
+        Ok(())
+    }
+
+    fn encode_index<F>(&self, encode: F) -> io::Result<Vec<u8>>
+        where F: FnOnce(&mut Vec<u8>, &[Section]) -> io::Result<()> {
+        trace_event!(DEBUG, sections = self.sections.len(), "write section index");
+        let mut index = vec![];
+        encode(&mut index, &self.sections)?;
+        Ok(index)
+    }
+}
+
+impl<TWrite> SectionWriter<TWrite> {
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &TWrite {
+        &self.writer
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut TWrite {
+        &mut self.writer
+    }
+
+    /// Unwraps this `SectionWriter`, returning the underlying writer.
+    pub fn into_inner(self) -> TWrite {
+        self.writer
+    }
+}
+
+/// Writes `sections` in the default encoding: the section count in u32, and then the name
+/// length in u16, the UTF-8 name, the offset in u64 and the length in u64 of each section.
+///
+/// # Errors
+///
+/// If a name is longer than `u16::MAX` bytes, or there are more than `u32::MAX` sections,
+/// returns Err(io::Error) of `InvalidInput`.
+///
+pub fn write_table<TEndian>(writer: &mut Vec<u8>, sections: &[Section]) -> io::Result<()>
+    where TEndian: Endian {
+    let count = u32::try_from(sections.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many sections"))?;
+    writer.write_integer::<TEndian, _>(count)?;
+    for section in sections {
+        let name_len = u16::try_from(section.name.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "section name is too long"))?;
+        writer.write_integer::<TEndian, _>(name_len)?;
+        writer.write_string(&section.name)?;
+        writer.write_integer::<TEndian, _>(section.offset)?;
+        writer.write_integer::<TEndian, _>(section.len)?;
+    }
+    Ok(())
+}
+
+/// Reads the sections written by `write_table`.
+///
+/// # Errors
+///
+/// If the function succeeds then Ok(the sections), otherwise Err(io::Error) whose field path
+/// names the section index.
+///
+pub fn read_table<TEndian, TRead>(reader: &mut TRead) -> io::Result<Vec<Section>>
+    where TEndian: Endian,
+          TRead: io::Read {
+    let count = reader.read_integer::<TEndian, u32>().ctx("count")? as usize;
+    let mut sections = Vec::with_capacity(count.min(MAX_PREALLOCATED_SECTIONS));
+    for i in 0..count {
+        sections.push(read_section::<TEndian, _>(reader).with_ctx(|| format!("[{}]", i))?);
+    }
+    Ok(sections)
+}
+
+fn read_section<TEndian, TRead>(reader: &mut TRead) -> io::Result<Section>
+    where TEndian: Endian,
+          TRead: io::Read {
+    let name_len = reader.read_integer::<TEndian, u16>()?;
+    let name = reader.read_string(name_len as usize).ctx("name")?;
+    let offset = reader.read_integer::<TEndian, u64>().ctx("offset")?;
+    let len = reader.read_integer::<TEndian, u64>().ctx("len")?;
+    Ok(Section { name, offset, len })
+}
+
+fn check_closed(open: &Option<(String, u64)>) -> io::Result<()> {
+    if let Some((ref name, _)) = *open {
+        let message = format!("section {} is open", name);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+    }
+    Ok(())
+}
@@ -0,0 +1,769 @@
+//! Provides a serde `Serializer` and `Deserializer` for a plain binary format.
+//!
+//! Integers and floats are written in fixed width in the chosen endianness. Strings, byte arrays,
+//! sequences and maps are prefixed by the u64 length. `bool` and the `Option` tag are one byte,
+//! `char` is u32, and enum variants are the u32 variant index. Structs and tuples are their fields
+//! in order, without names or lengths. The format is not self-describing, so
+//! `deserialize_any` is not supported, and the borrowed `&str` or `&[u8]` are not supported as the
+//! data is read from `io::Read`.
+//!
+//! This module is available with the `serde_binary` feature.
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::Read;
+use std::marker::PhantomData;
+
+use serde::{de, ser};
+use serde::de::IntoDeserializer;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::Endian;
+
+/// The error of the serialization and the deserialization, which wraps `io::Error`.
+#[derive(Debug)]
+pub struct Error(io::Error);
+
+impl Error {
+    /// Unwraps this `Error`, returning the underlying `io::Error`.
+    pub fn into_inner(self) -> io::Error {
+        self.0
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T>(msg: T) -> Error
+        where T: fmt::Display {
+        Error(io::Error::new(io::ErrorKind::InvalidInput, msg.to_string()))
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T>(msg: T) -> Error
+        where T: fmt::Display {
+        Error(io::Error::new(io::ErrorKind::InvalidData, msg.to_string()))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error(e)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> io::Error {
+        e.0
+    }
+}
+
+/// Serializes `value` to `writer`.
+///
+/// # Errors
+///
+/// If `value` can not be serialized (a sequence or a map of unknown length), returns
+/// Err(io::Error) of `InvalidInput`.
+/// If writing fails, returns Err(io::Error).
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate mm_binary_io;
+///
+/// use std::io;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::serde_binary;
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// enum Shape { Point, Circle(u16), Rect { width: u8, height: u8 } }
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Drawing { name: String, shapes: Vec<Shape>, scale: Option<i16> }
+///
+/// # fn main() {
+/// let drawing = Drawing {
+///     name: "ab".to_string(),
+///     shapes: vec![Shape::Point, Shape::Circle(3), Shape::Rect { width: 4, height: 5 }],
+///     scale: Some(-2),
+/// };
+///
+/// let mut writer = vec![];
+/// serde_binary::to_writer::<LittleEndian, _, _>(&mut writer, &drawing).unwrap();
+/// assert_eq!(
+///     vec![2_u8, 0, 0, 0, 0, 0, 0, 0, b'a', b'b',
+///          3, 0, 0, 0, 0, 0, 0, 0,
+///          0, 0, 0, 0,
+///          1, 0, 0, 0, 3, 0,
+///          2, 0, 0, 0, 4, 5,
+///          1, 0xFE, 0xFF],
+///     writer);
+///
+/// let mut reader = io::Cursor::new(writer);
+/// assert_eq!(drawing, serde_binary::from_reader::<LittleEndian, _, Drawing>(&mut reader).unwrap());
+/// # }
+///
+/// ```
+///
+pub fn to_writer<TEndian, TWrite, TValue>(writer: TWrite, value: &TValue) -> io::Result<()>
+    where TEndian: Endian,
+          TWrite: io::Write,
+          TValue: ser::Serialize + ?Sized {
+    let mut serializer = Serializer::<TEndian, TWrite>::new(writer);
+    value.serialize(&mut serializer).map_err(Error::into_inner)
+}
+
+/// Serializes `value` to a new `Vec<u8>`.
+pub fn to_vec<TEndian, TValue>(value: &TValue) -> io::Result<Vec<u8>>
+    where TEndian: Endian,
+          TValue: ser::Serialize + ?Sized {
+    let mut bytes = vec![];
+    to_writer::<TEndian, _, _>(&mut bytes, value)?;
+    Ok(bytes)
+}
+
+/// Deserializes a value from `reader`.
+///
+/// # Errors
+///
+/// If the data is broken (an invalid bool, char or UTF-8 string, or an unknown variant index),
+/// returns Err(io::Error) of `InvalidData`.
+/// If the stream ends in the middle of the value, returns Err(io::Error) of `UnexpectedEof`.
+///
+pub fn from_reader<TEndian, TRead, TValue>(reader: TRead) -> io::Result<TValue>
+    where TEndian: Endian,
+          TRead: io::Read,
+          TValue: de::DeserializeOwned {
+    let mut deserializer = Deserializer::<TEndian, TRead>::new(reader);
+    TValue::deserialize(&mut deserializer).map_err(Error::into_inner)
+}
+
+/// Deserializes a value from `bytes`, which must be consumed entirely.
+///
+/// # Errors
+///
+/// If bytes remain after the value, returns Err(io::Error) of `InvalidData`.
+/// Otherwise returns the errors of `from_reader`.
+///
+pub fn from_slice<TEndian, TValue>(bytes: &[u8]) -> io::Result<TValue>
+    where TEndian: Endian,
+          TValue: de::DeserializeOwned {
+    let mut reader = bytes;
+    let value = from_reader::<TEndian, _, _>(&mut reader)?;
+    if !reader.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "trailing bytes after the value"));
+    }
+    Ok(value)
+}
+
+/// A serde `Serializer` which writes the plain binary format to `io::Write`.
+pub struct Serializer<TEndian, TWrite>
+    where TEndian: Endian,
+          TWrite: io::Write {
+    writer: TWrite,
+    _endian: PhantomData<TEndian>,
+}
+
+impl<TEndian, TWrite> Serializer<TEndian, TWrite>
+    where TEndian: Endian,
+          TWrite: io::Write {
+    /// Creates a new `Serializer` which writes to `writer`.
+    pub fn new(writer: TWrite) -> Serializer<TEndian, TWrite> {
+        Serializer {
+            writer,
+            _endian: PhantomData,
+        }
+    }
+
+    /// Unwraps this `Serializer`, returning the underlying writer.
+    pub fn into_inner(self) -> TWrite {
+        self.writer
+    }
+
+    fn write_len(&mut self, len: usize) -> Result<(), Error> {
+        Ok(self.writer.write_integer::<TEndian, _>(len as u64)?)
+    }
+
+    fn write_variant_index(&mut self, variant_index: u32) -> Result<(), Error> {
+        Ok(self.writer.write_integer::<TEndian, _>(variant_index)?)
+    }
+}
+
+impl<TEndian, TWrite> ser::Serializer for &mut Serializer<TEndian, TWrite>
+    where TEndian: Endian,
+          TWrite: io::Write {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.serialize_u8(v as u8)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        Ok(self.writer.write_integer::<TEndian, _>(v)?)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        Ok(self.writer.write_integer::<TEndian, _>(v)?)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        Ok(self.writer.write_integer::<TEndian, _>(v)?)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        Ok(self.writer.write_integer::<TEndian, _>(v)?)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        Ok(self.writer.write_integer::<TEndian, _>(v)?)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        Ok(self.writer.write_integer::<TEndian, _>(v)?)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        Ok(self.writer.write_integer::<TEndian, _>(v)?)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        Ok(self.writer.write_integer::<TEndian, _>(v)?)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.serialize_u32(v.to_bits())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.serialize_u64(v.to_bits())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.serialize_u32(v as u32)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.write_len(v.len())?;
+        Ok(self.writer.write_all(v)?)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.serialize_u8(0)
+    }
+
+    fn serialize_some<TValue>(self, value: &TValue) -> Result<(), Error>
+        where TValue: ser::Serialize + ?Sized {
+        self.serialize_u8(1)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str) -> Result<(), Error> {
+        self.write_variant_index(variant_index)
+    }
+
+    fn serialize_newtype_struct<TValue>(self, _name: &'static str, value: &TValue) -> Result<(), Error>
+        where TValue: ser::Serialize + ?Sized {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<TValue>(self, _name: &'static str, variant_index: u32, _variant: &'static str, value: &TValue) -> Result<(), Error>
+        where TValue: ser::Serialize + ?Sized {
+        self.write_variant_index(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, Error> {
+        let len = len.ok_or_else(|| <Error as ser::Error>::custom("sequence length is required"))?;
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str, _len: usize) -> Result<Self, Error> {
+        self.write_variant_index(variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, Error> {
+        let len = len.ok_or_else(|| <Error as ser::Error>::custom("map length is required"))?;
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str, _len: usize) -> Result<Self, Error> {
+        self.write_variant_index(variant_index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<TEndian, TWrite> ser::SerializeSeq for &mut Serializer<TEndian, TWrite>
+    where TEndian: Endian,
+          TWrite: io::Write {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<TValue>(&mut self, value: &TValue) -> Result<(), Error>
+        where TValue: ser::Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<TEndian, TWrite> ser::SerializeTuple for &mut Serializer<TEndian, TWrite>
+    where TEndian: Endian,
+          TWrite: io::Write {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<TValue>(&mut self, value: &TValue) -> Result<(), Error>
+        where TValue: ser::Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<TEndian, TWrite> ser::SerializeTupleStruct for &mut Serializer<TEndian, TWrite>
+    where TEndian: Endian,
+          TWrite: io::Write {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<TValue>(&mut self, value: &TValue) -> Result<(), Error>
+        where TValue: ser::Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<TEndian, TWrite> ser::SerializeTupleVariant for &mut Serializer<TEndian, TWrite>
+    where TEndian: Endian,
+          TWrite: io::Write {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<TValue>(&mut self, value: &TValue) -> Result<(), Error>
+        where TValue: ser::Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<TEndian, TWrite> ser::SerializeMap for &mut Serializer<TEndian, TWrite>
+    where TEndian: Endian,
+          TWrite: io::Write {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<TValue>(&mut self, key: &TValue) -> Result<(), Error>
+        where TValue: ser::Serialize + ?Sized {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<TValue>(&mut self, value: &TValue) -> Result<(), Error>
+        where TValue: ser::Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<TEndian, TWrite> ser::SerializeStruct for &mut Serializer<TEndian, TWrite>
+    where TEndian: Endian,
+          TWrite: io::Write {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<TValue>(&mut self, _key: &'static str, value: &TValue) -> Result<(), Error>
+        where TValue: ser::Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<TEndian, TWrite> ser::SerializeStructVariant for &mut Serializer<TEndian, TWrite>
+    where TEndian: Endian,
+          TWrite: io::Write {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<TValue>(&mut self, _key: &'static str, value: &TValue) -> Result<(), Error>
+        where TValue: ser::Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// A serde `Deserializer` which reads the plain binary format from `io::Read`.
+pub struct Deserializer<TEndian, TRead>
+    where TEndian: Endian,
+          TRead: io::Read {
+    reader: TRead,
+    _endian: PhantomData<TEndian>,
+}
+
+impl<TEndian, TRead> Deserializer<TEndian, TRead>
+    where TEndian: Endian,
+          TRead: io::Read {
+    /// Creates a new `Deserializer` which reads from `reader`.
+    pub fn new(reader: TRead) -> Deserializer<TEndian, TRead> {
+        Deserializer {
+            reader,
+            _endian: PhantomData,
+        }
+    }
+
+    /// Unwraps this `Deserializer`, returning the underlying reader.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+
+    fn read_len(&mut self) -> Result<u64, Error> {
+        Ok(self.reader.read_integer::<TEndian, u64>()?)
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>, Error> {
+        let len = self.read_len()?;
+        let mut bytes = vec![];
+        (&mut self.reader).take(len).read_to_end(&mut bytes)?;
+        if (bytes.len() as u64) < len {
+            return Err(Error(io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer")));
+        }
+        Ok(bytes)
+    }
+
+    fn read_string(&mut self) -> Result<String, Error> {
+        String::from_utf8(self.read_bytes()?)
+            .map_err(|_| <Error as de::Error>::custom("invalid UTF-8 string"))
+    }
+
+    fn read_tag(&mut self) -> Result<bool, Error> {
+        match self.reader.read_integer::<TEndian, u8>()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(<Error as de::Error>::custom("invalid bool or Option tag")),
+        }
+    }
+}
+
+impl<'de, TEndian, TRead> de::Deserializer<'de> for &mut Deserializer<TEndian, TRead>
+    where TEndian: Endian,
+          TRead: io::Read {
+    type Error = Error;
+
+    fn deserialize_any<TVisitor>(self, _visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        Err(<Error as de::Error>::custom("the plain binary format is not self-describing"))
+    }
+
+    fn deserialize_bool<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_bool(self.read_tag()?)
+    }
+
+    fn deserialize_i8<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_i8(self.reader.read_integer::<TEndian, i8>()?)
+    }
+
+    fn deserialize_i16<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_i16(self.reader.read_integer::<TEndian, i16>()?)
+    }
+
+    fn deserialize_i32<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_i32(self.reader.read_integer::<TEndian, i32>()?)
+    }
+
+    fn deserialize_i64<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_i64(self.reader.read_integer::<TEndian, i64>()?)
+    }
+
+    fn deserialize_u8<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_u8(self.reader.read_integer::<TEndian, u8>()?)
+    }
+
+    fn deserialize_u16<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_u16(self.reader.read_integer::<TEndian, u16>()?)
+    }
+
+    fn deserialize_u32<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_u32(self.reader.read_integer::<TEndian, u32>()?)
+    }
+
+    fn deserialize_u64<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_u64(self.reader.read_integer::<TEndian, u64>()?)
+    }
+
+    fn deserialize_f32<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_f32(f32::from_bits(self.reader.read_integer::<TEndian, u32>()?))
+    }
+
+    fn deserialize_f64<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_f64(f64::from_bits(self.reader.read_integer::<TEndian, u64>()?))
+    }
+
+    fn deserialize_char<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        let code = self.reader.read_integer::<TEndian, u32>()?;
+        let c = ::std::char::from_u32(code).ok_or_else(|| <Error as de::Error>::custom("invalid char"))?;
+        visitor.visit_char(c)
+    }
+
+    fn deserialize_str<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_string(self.read_string()?)
+    }
+
+    fn deserialize_string<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_string(self.read_string()?)
+    }
+
+    fn deserialize_bytes<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_byte_buf(self.read_bytes()?)
+    }
+
+    fn deserialize_byte_buf<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_byte_buf(self.read_bytes()?)
+    }
+
+    fn deserialize_option<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        if self.read_tag()? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<TVisitor>(self, _name: &'static str, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<TVisitor>(self, _name: &'static str, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        let len = self.read_len()?;
+        visitor.visit_seq(Elements { deserializer: self, remaining: len })
+    }
+
+    fn deserialize_tuple<TVisitor>(self, len: usize, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_seq(Elements { deserializer: self, remaining: len as u64 })
+    }
+
+    fn deserialize_tuple_struct<TVisitor>(self, _name: &'static str, len: usize, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<TVisitor>(self, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        let len = self.read_len()?;
+        visitor.visit_map(Elements { deserializer: self, remaining: len })
+    }
+
+    fn deserialize_struct<TVisitor>(self, _name: &'static str, fields: &'static [&'static str], visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<TVisitor>(self, _name: &'static str, _variants: &'static [&'static str], visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<TVisitor>(self, _visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        Err(<Error as de::Error>::custom("the plain binary format has no identifiers"))
+    }
+
+    fn deserialize_ignored_any<TVisitor>(self, _visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        Err(<Error as de::Error>::custom("the plain binary format can not skip values"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<'de, TEndian, TRead> de::EnumAccess<'de> for &mut Deserializer<TEndian, TRead>
+    where TEndian: Endian,
+          TRead: io::Read {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<TSeed>(self, seed: TSeed) -> Result<(TSeed::Value, Self), Error>
+        where TSeed: de::DeserializeSeed<'de> {
+        let variant_index = self.reader.read_integer::<TEndian, u32>()?;
+        let value = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(variant_index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, TEndian, TRead> de::VariantAccess<'de> for &mut Deserializer<TEndian, TRead>
+    where TEndian: Endian,
+          TRead: io::Read {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<TSeed>(self, seed: TSeed) -> Result<TSeed::Value, Error>
+        where TSeed: de::DeserializeSeed<'de> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<TVisitor>(self, len: usize, visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<TVisitor>(self, fields: &'static [&'static str], visitor: TVisitor) -> Result<TVisitor::Value, Error>
+        where TVisitor: de::Visitor<'de> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
+
+/// Reads the elements of a sequence, a tuple or a map.
+struct Elements<'a, TEndian, TRead>
+    where TEndian: Endian + 'a,
+          TRead: io::Read + 'a {
+    deserializer: &'a mut Deserializer<TEndian, TRead>,
+    remaining: u64,
+}
+
+impl<'de, 'a, TEndian, TRead> de::SeqAccess<'de> for Elements<'a, TEndian, TRead>
+    where TEndian: Endian,
+          TRead: io::Read {
+    type Error = Error;
+
+    fn next_element_seed<TSeed>(&mut self, seed: TSeed) -> Result<Option<TSeed::Value>, Error>
+        where TSeed: de::DeserializeSeed<'de> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.min(usize::MAX as u64) as usize)
+    }
+}
+
+impl<'de, 'a, TEndian, TRead> de::MapAccess<'de> for Elements<'a, TEndian, TRead>
+    where TEndian: Endian,
+          TRead: io::Read {
+    type Error = Error;
+
+    fn next_key_seed<TSeed>(&mut self, seed: TSeed) -> Result<Option<TSeed::Value>, Error>
+        where TSeed: de::DeserializeSeed<'de> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn next_value_seed<TSeed>(&mut self, seed: TSeed) -> Result<TSeed::Value, Error>
+        where TSeed: de::DeserializeSeed<'de> {
+        seed.deserialize(&mut *self.deserializer)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.min(usize::MAX as u64) as usize)
+    }
+}
@@ -0,0 +1,278 @@
+//! Provides the readers and writers over fixed buffers, which work without `std` and `alloc`.
+
+use std::fmt;
+use std::mem;
+
+use endian::Endian;
+use from_bytes::FromBytes;
+use to_bytes::ToBytes;
+
+/// The error of `SliceReader` and `SliceWriter`, returned when the buffer is too short.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfBounds {
+    /// The byte count required by the operation.
+    pub required: usize,
+    /// The byte count left in the buffer.
+    pub remaining: usize,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bytes are required, but {} bytes remain", self.required, self.remaining)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for OutOfBounds {}
+
+/// Reads binary data from a byte slice.
+///
+/// The byte arrays are borrowed from the slice, so nothing is copied or allocated. A failed read
+/// consumes nothing.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::endian::{BigEndian, LittleEndian};
+/// use mm_binary_io::slice::{OutOfBounds, SliceReader};
+///
+/// let data = [0x12_u8, 0x34, 0xFE, 0xFF, b'a', b'b', 0x01];
+/// let mut reader = SliceReader::new(&data);
+///
+/// assert_eq!(Ok(0x1234_u16), reader.read_integer::<BigEndian, u16>());
+/// assert_eq!(Ok(-2_i16), reader.read_integer::<LittleEndian, i16>());
+/// assert_eq!(Ok(&b"ab"[..]), reader.read_bytes(2));
+/// assert_eq!(Err(OutOfBounds { required: 2, remaining: 1 }), reader.read_integer::<BigEndian, u16>());
+/// assert_eq!(6, reader.position());
+/// assert_eq!(&[0x01], reader.remaining_bytes());
+///
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct SliceReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> SliceReader<'a> {
+    /// Creates a new `SliceReader` reading `bytes` from the head.
+    pub fn new(bytes: &'a [u8]) -> SliceReader<'a> {
+        SliceReader { bytes, position: 0 }
+    }
+
+    /// Returns the byte count read.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the byte count left.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    /// Returns the bytes left.
+    pub fn remaining_bytes(&self) -> &'a [u8] {
+        &self.bytes[self.position..]
+    }
+
+    /// Reads an integer.
+    pub fn read_integer<TEndian, TInt>(&mut self) -> Result<TInt, OutOfBounds>
+        where TEndian: Endian,
+              TInt: FromBytes<OutputType=TInt> {
+        self.read_bytes(mem::size_of::<TInt>()).map(TInt::from_bytes::<TEndian>)
+    }
+
+    /// Reads `len` bytes, borrowing them from the slice.
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], OutOfBounds> {
+        if len > self.remaining() {
+            return Err(OutOfBounds { required: len, remaining: self.remaining() });
+        }
+        let bytes = &self.bytes[self.position..self.position + len];
+        self.position += len;
+        Ok(bytes)
+    }
+
+    /// Reads exactly `buf.len()` bytes into `buf`.
+    pub fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), OutOfBounds> {
+        self.read_bytes(buf.len()).map(|bytes| buf.copy_from_slice(bytes))
+    }
+
+    /// Skips `len` bytes.
+    pub fn skip(&mut self, len: usize) -> Result<(), OutOfBounds> {
+        self.read_bytes(len).map(|_| ())
+    }
+}
+
+/// The error of the `heapless` reads of `SliceReader`.
+#[cfg(feature = "heapless")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeaplessReadError {
+    /// The slice is too short.
+    OutOfBounds(OutOfBounds),
+    /// The value does not fit in the capacity of the collection.
+    CapacityExceeded,
+    /// The bytes are not valid UTF-8.
+    InvalidUtf8,
+}
+
+#[cfg(feature = "heapless")]
+impl From<OutOfBounds> for HeaplessReadError {
+    fn from(e: OutOfBounds) -> HeaplessReadError {
+        HeaplessReadError::OutOfBounds(e)
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl fmt::Display for HeaplessReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HeaplessReadError::OutOfBounds(ref e) => e.fmt(f),
+            HeaplessReadError::CapacityExceeded => write!(f, "the value exceeds the capacity"),
+            HeaplessReadError::InvalidUtf8 => write!(f, "invalid UTF-8 string"),
+        }
+    }
+}
+
+#[cfg(all(feature = "heapless", feature = "std"))]
+impl ::std::error::Error for HeaplessReadError {}
+
+/// Reads into `heapless` collections, whose capacity `N` is bounded at compile time.
+///
+/// These are available with the `heapless` feature, and work without `alloc`. A failed read
+/// consumes nothing.
+///
+/// # Examples
+///
+/// ```
+/// extern crate heapless;
+/// extern crate mm_binary_io;
+///
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::slice::{HeaplessReadError, SliceReader};
+///
+/// # fn main() {
+/// let data = [0x00_u8, 0x01, 0x00, 0x02, b'a', b'b', 0xFF];
+/// let mut reader = SliceReader::new(&data);
+///
+/// let values: heapless::Vec<u16, 4> = reader.read_integer_vec::<BigEndian, u16, 4>(2).unwrap();
+/// assert_eq!(&[1, 2], &values[..]);
+///
+/// assert_eq!(Err(HeaplessReadError::CapacityExceeded), reader.read_string::<1>(2));
+/// let text: heapless::String<8> = reader.read_string(2).unwrap();
+/// assert_eq!("ab", text);
+///
+/// assert_eq!(Err(HeaplessReadError::InvalidUtf8), reader.read_string::<8>(1));
+/// assert_eq!(6, reader.position());
+/// # }
+///
+/// ```
+///
+#[cfg(feature = "heapless")]
+impl<'a> SliceReader<'a> {
+    /// Reads an integer array of `element_count` elements.
+    pub fn read_integer_vec<TEndian, TInt, const N: usize>(&mut self, element_count: usize) -> Result<::heapless::Vec<TInt, N>, HeaplessReadError>
+        where TEndian: Endian,
+              TInt: FromBytes<OutputType=TInt> {
+        if element_count > N {
+            return Err(HeaplessReadError::CapacityExceeded);
+        }
+        let element_size = mem::size_of::<TInt>();
+        let bytes = self.read_bytes(element_count * element_size)?;
+        let mut values = ::heapless::Vec::new();
+        for chunk in bytes.chunks(element_size.max(1)) {
+            if values.push(TInt::from_bytes::<TEndian>(chunk)).is_err() {
+                unreachable!("the element count is checked");
+            }
+        }
+        Ok(values)
+    }
+
+    /// Reads a UTF-8 string of `byte_count` bytes.
+    pub fn read_string<const N: usize>(&mut self, byte_count: usize) -> Result<::heapless::String<N>, HeaplessReadError> {
+        if byte_count > N {
+            return Err(HeaplessReadError::CapacityExceeded);
+        }
+        let bytes = self.remaining_bytes().get(..byte_count)
+            .ok_or(OutOfBounds { required: byte_count, remaining: self.remaining() })?;
+        let text = ::std::str::from_utf8(bytes).map_err(|_| HeaplessReadError::InvalidUtf8)?;
+        let mut string = ::heapless::String::new();
+        string.push_str(text).map_err(|_| HeaplessReadError::CapacityExceeded)?;
+        self.position += byte_count;
+        Ok(string)
+    }
+}
+
+/// Writes binary data to a mutable byte slice.
+///
+/// A failed write writes nothing.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::endian::{BigEndian, LittleEndian};
+/// use mm_binary_io::slice::{OutOfBounds, SliceWriter};
+///
+/// let mut buf = [0_u8; 7];
+/// let mut writer = SliceWriter::new(&mut buf);
+///
+/// writer.write_integer::<BigEndian, _>(0x1234_u16).unwrap();
+/// writer.write_integer::<LittleEndian, _>(-2_i16).unwrap();
+/// writer.write_bytes(b"ab").unwrap();
+/// assert_eq!(Err(OutOfBounds { required: 2, remaining: 1 }), writer.write_integer::<BigEndian, _>(0_u16));
+/// assert_eq!(&[0x12, 0x34, 0xFE, 0xFF, b'a', b'b'], writer.written());
+///
+/// ```
+///
+#[derive(Debug)]
+pub struct SliceWriter<'a> {
+    bytes: &'a mut [u8],
+    position: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Creates a new `SliceWriter` writing `bytes` from the head.
+    pub fn new(bytes: &'a mut [u8]) -> SliceWriter<'a> {
+        SliceWriter { bytes, position: 0 }
+    }
+
+    /// Returns the byte count written.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the byte count left.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    /// Returns the bytes written.
+    pub fn written(&self) -> &[u8] {
+        &self.bytes[..self.position]
+    }
+
+    /// Unwraps this `SliceWriter`, returning the bytes written.
+    pub fn into_written(self) -> &'a mut [u8] {
+        &mut self.bytes[..self.position]
+    }
+
+    /// Writes the `value`.
+    pub fn write_integer<TEndian, TInt>(&mut self, value: TInt) -> Result<(), OutOfBounds>
+        where TEndian: Endian,
+              TInt: ToBytes {
+        self.reserve(mem::size_of::<TInt>()).map(|destination| value.to_bytes::<TEndian>(destination))
+    }
+
+    /// Writes `bytes`.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), OutOfBounds> {
+        self.reserve(bytes.len()).map(|destination| destination.copy_from_slice(bytes))
+    }
+
+    fn reserve(&mut self, len: usize) -> Result<&mut [u8], OutOfBounds> {
+        if len > self.remaining() {
+            return Err(OutOfBounds { required: len, remaining: self.remaining() });
+        }
+        let start = self.position;
+        self.position += len;
+        Ok(&mut self.bytes[start..start + len])
+    }
+}
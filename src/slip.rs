@@ -0,0 +1,275 @@
+//! Provides the features to read and write SLIP (RFC 1055) frames.
+//!
+//! A frame ends with `END`, and the `END` and `ESC` bytes in the frame are escaped.
+
+use std::io;
+
+use delimited::{self, FrameDecoder};
+
+/// The frame delimiter.
+pub const END: u8 = 0xC0;
+/// The escape byte.
+pub const ESC: u8 = 0xDB;
+/// The escaped `END`, following `ESC`.
+pub const ESC_END: u8 = 0xDC;
+/// The escaped `ESC`, following `ESC`.
+pub const ESC_ESC: u8 = 0xDD;
+
+/// Encodes `data` as a frame, including the trailing `END`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::slip;
+///
+/// assert_eq!(vec![0x01, 0xDB, 0xDC, 0xDB, 0xDD, 0xC0], slip::encode(&[0x01, 0xC0, 0xDB]));
+///
+/// ```
+///
+pub fn encode(data: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(data.len() + 2);
+    write_escaped(&mut encoded, data).unwrap();
+    encoded.push(END);
+    encoded
+}
+
+/// Decodes a frame, with or without the `END` bytes around it.
+///
+/// # Errors
+///
+/// If the frame contains an invalid escape sequence or `END` in the middle, returns
+/// Err(io::Error) of `InvalidData`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::slip;
+///
+/// assert_eq!(vec![0x01, 0xC0, 0xDB], slip::decode(&[0xC0, 0x01, 0xDB, 0xDC, 0xDB, 0xDD, 0xC0]).unwrap());
+/// assert!(slip::decode(&[0x01, 0xDB, 0x02]).is_err());
+///
+/// ```
+///
+pub fn decode(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut data = data;
+    if let Some((&END, rest)) = data.split_first() {
+        data = rest;
+    }
+    if let Some((&END, rest)) = data.split_last() {
+        data = rest;
+    }
+    let mut decoder = Decoder::new();
+    for &byte in data {
+        if byte == END {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected END in SLIP frame"));
+        }
+        decoder.push(byte);
+    }
+    decoder.finish()
+}
+
+/// Escapes the written bytes as SLIP frames.
+///
+/// The bytes written until `end_frame` form a frame.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+/// use mm_binary_io::slip::SlipWriter;
+///
+/// let mut writer = SlipWriter::new(vec![]);
+/// writer.write_all(&[0x01, 0xC0]).unwrap();
+/// writer.end_frame().unwrap();
+///
+/// assert_eq!(vec![0x01, 0xDB, 0xDC, 0xC0], writer.into_inner());
+///
+/// ```
+///
+pub struct SlipWriter<TWrite>
+    where TWrite: io::Write {
+    writer: TWrite,
+}
+
+impl<TWrite> SlipWriter<TWrite>
+    where TWrite: io::Write {
+    /// Creates a new `SlipWriter`.
+    pub fn new(writer: TWrite) -> SlipWriter<TWrite> {
+        SlipWriter {
+            writer,
+        }
+    }
+
+    /// Writes `END` to start a frame, flushing out the line noise received by the peer.
+    ///
+    /// RFC 1055 recommends this before each frame on noisy lines.
+    pub fn begin_frame(&mut self) -> io::Result<()> {
+        self.writer.write_all(&[END])
+    }
+
+    /// Ends the current frame.
+    pub fn end_frame(&mut self) -> io::Result<()> {
+        self.writer.write_all(&[END])
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &TWrite {
+        &self.writer
+    }
+
+    /// Unwraps this `SlipWriter`, returning the underlying writer.
+    pub fn into_inner(self) -> TWrite {
+        self.writer
+    }
+}
+
+impl<TWrite> io::Write for SlipWriter<TWrite>
+    where TWrite: io::Write {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        write_escaped(&mut self.writer, buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Reads SLIP frames from a stream.
+///
+/// Empty frames (consecutive `END` bytes) are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::slip::SlipReader;
+///
+/// let data = vec![0xC0_u8, 0x01, 0xDB, 0xDC, 0xC0, 0xC0, 0x02, 0xDB, 0x03, 0xC0, 0x04, 0xC0];
+/// let mut reader = SlipReader::new(io::Cursor::new(data), 16);
+///
+/// assert_eq!(Some(vec![0x01, 0xC0]), reader.read_frame().unwrap());
+/// assert_eq!(io::ErrorKind::InvalidData, reader.read_frame().unwrap_err().kind());
+/// assert_eq!(Some(vec![0x04]), reader.read_frame().unwrap());
+/// assert_eq!(None, reader.read_frame().unwrap());
+///
+/// ```
+///
+pub struct SlipReader<TRead>
+    where TRead: io::BufRead {
+    reader: TRead,
+    max_len: usize,
+    finished: bool,
+}
+
+impl<TRead> SlipReader<TRead>
+    where TRead: io::BufRead {
+    /// Creates a new `SlipReader`.
+    ///
+    /// # Arguments
+    ///
+    /// * max_len - the maximum length of a decoded frame.
+    pub fn new(reader: TRead, max_len: usize) -> SlipReader<TRead> {
+        SlipReader {
+            reader,
+            max_len,
+            finished: false,
+        }
+    }
+
+    /// Reads and decodes the next frame.
+    ///
+    /// # Errors
+    ///
+    /// If the stream ends cleanly before the frame, returns Ok(None).
+    /// If the frame is broken or longer than `max_len`, the frame is skipped and
+    /// Err(io::Error) of `InvalidData` is returned.
+    /// If the stream ends in the middle of the frame, returns Err(io::Error) of `UnexpectedEof`.
+    ///
+    pub fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        delimited::read_frame(&mut self.reader, END, self.max_len, Decoder::new(), "SLIP")
+    }
+
+    /// Unwraps this `SlipReader`, returning the underlying reader.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+}
+
+impl<TRead> Iterator for SlipReader<TRead>
+    where TRead: io::BufRead {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.read_frame() {
+            Ok(Some(frame)) => Some(Ok(frame)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = e.kind() != io::ErrorKind::InvalidData;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn write_escaped<TWrite>(writer: &mut TWrite, data: &[u8]) -> io::Result<()>
+    where TWrite: io::Write {
+    let mut rest = data;
+    while let Some(i) = rest.iter().position(|&b| b == END || b == ESC) {
+        writer.write_all(&rest[..i])?;
+        let escaped = if rest[i] == END { ESC_END } else { ESC_ESC };
+        writer.write_all(&[ESC, escaped])?;
+        rest = &rest[i + 1..];
+    }
+    writer.write_all(rest)
+}
+
+struct Decoder {
+    frame: Vec<u8>,
+    escaping: bool,
+    broken: bool,
+}
+
+impl Decoder {
+    fn new() -> Decoder {
+        Decoder {
+            frame: vec![],
+            escaping: false,
+            broken: false,
+        }
+    }
+}
+
+impl FrameDecoder for Decoder {
+    fn push(&mut self, byte: u8) {
+        if self.escaping {
+            self.escaping = false;
+            match byte {
+                ESC_END => self.frame.push(END),
+                ESC_ESC => self.frame.push(ESC),
+                _ => self.broken = true,
+            }
+        } else if byte == ESC {
+            self.escaping = true;
+        } else {
+            self.frame.push(byte);
+        }
+    }
+
+    fn decoded_len(&self) -> usize {
+        self.frame.len()
+    }
+
+    fn finish(self) -> io::Result<Vec<u8>> {
+        if self.broken || self.escaping {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid SLIP escape sequence"));
+        }
+        Ok(self.frame)
+    }
+}
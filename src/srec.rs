@@ -0,0 +1,147 @@
+//! Provides the features to read and write Motorola S-record (SREC) files.
+//!
+//! The payload is exposed as a `MemoryImage`. The 16-bit (S1), 24-bit (S2) and 32-bit (S3)
+//! addresses are supported.
+
+use std::io;
+
+use memory_image::{self, MemoryImage};
+
+/// The number of data bytes in a data record written by `write`.
+pub const RECORD_DATA_LEN: usize = 16;
+
+const INVALID_RECORD: &str = "invalid S-record";
+
+/// Reads an S-record file.
+///
+/// The header (S0) and the count (S5, S6) records are ignored. The reading stops at the
+/// termination (S7, S8, S9) record, whose address is stored as the entry point.
+///
+/// # Errors
+///
+/// If a record is broken, returns Err(io::Error) of `InvalidData`. If the checksum of a record does
+/// not match, the inner error is `ChecksumMismatch<u8>`.
+/// If the termination record is missing, returns Err(io::Error) of `UnexpectedEof`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::memory_image::Segment;
+/// use mm_binary_io::srec;
+///
+/// let text = "S0030000FC\nS107100001020304DE\nS5030001FB\nS9031000EC\n";
+/// let image = srec::read(io::Cursor::new(text)).unwrap();
+///
+/// assert_eq!(&[Segment::new(0x1000, vec![1, 2, 3, 4])], image.segments());
+/// assert_eq!(Some(0x1000), image.entry_point());
+///
+/// let mut written = vec![];
+/// srec::write(&mut written, &image).unwrap();
+/// assert_eq!(text.as_bytes(), &written[..]);
+///
+/// let image = srec::read(io::Cursor::new("S208012345AABBCCDD80\nS80401234592\n")).unwrap();
+/// assert_eq!(&[Segment::new(0x012345, vec![0xAA, 0xBB, 0xCC, 0xDD])], image.segments());
+///
+/// ```
+///
+pub fn read<TRead>(reader: TRead) -> io::Result<MemoryImage>
+    where TRead: io::BufRead {
+    let mut image = MemoryImage::new();
+    let mut ended = false;
+    memory_image::read_records(reader, |record| {
+        if record.len() < 2 || record[0] != b'S' {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, INVALID_RECORD));
+        }
+        let bytes = memory_image::decode_hex(&record[2..], INVALID_RECORD)?;
+        if bytes.len() < 2 || bytes.len() != bytes[0] as usize + 1 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, INVALID_RECORD));
+        }
+        let (body, expected) = bytes.split_at(bytes.len() - 1);
+        let actual = checksum(body);
+        if actual != expected[0] {
+            return Err(memory_image::checksum_mismatch(expected[0], actual));
+        }
+
+        let record_type = record[1];
+        let address_len = match record_type {
+            b'0' | b'1' | b'5' | b'9' => 2,
+            b'2' | b'6' | b'8' => 3,
+            b'3' | b'7' => 4,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, INVALID_RECORD)),
+        };
+        if body.len() < 1 + address_len {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, INVALID_RECORD));
+        }
+        let address = body[1..1 + address_len].iter().fold(0_u32, |a, &b| a << 8 | b as u32);
+        let data = &body[1 + address_len..];
+        match record_type {
+            b'1' | b'2' | b'3' => {
+                image.write_at(address, data).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, INVALID_RECORD))?;
+            }
+            b'7' | b'8' | b'9' => {
+                image.set_entry_point(Some(address));
+                ended = true;
+                return Ok(false);
+            }
+            _ => {}
+        }
+        Ok(true)
+    })?;
+    if !ended {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "S-record termination record is missing"));
+    }
+    Ok(image)
+}
+
+/// Writes `image` as an S-record file.
+///
+/// The narrowest address width which holds all the addresses is used. If the image has no entry
+/// point, the termination record has the address 0.
+///
+pub fn write<TWrite>(writer: &mut TWrite, image: &MemoryImage) -> io::Result<()>
+    where TWrite: io::Write {
+    let max_address = image.segments().last().map_or(0, |s| s.end().saturating_sub(1))
+        .max(image.entry_point().unwrap_or(0) as u64);
+    let address_len = if max_address <= 0xFFFF {
+        2
+    } else if max_address <= 0xFF_FFFF {
+        3
+    } else {
+        4
+    };
+
+    write_record(writer, b'0', 2, 0, &[])?;
+    let mut count = 0_u32;
+    for segment in image.segments() {
+        for (i, data) in segment.data.chunks(RECORD_DATA_LEN).enumerate() {
+            let address = segment.address + (i * RECORD_DATA_LEN) as u32;
+            write_record(writer, b'0' + address_len as u8 - 1, address_len, address, data)?;
+            count += 1;
+        }
+    }
+    if count <= 0xFFFF {
+        write_record(writer, b'5', 2, count, &[])?;
+    } else if count <= 0xFF_FFFF {
+        write_record(writer, b'6', 3, count, &[])?;
+    }
+    write_record(writer, b'0' + 11 - address_len as u8, address_len, image.entry_point().unwrap_or(0), &[])
+}
+
+fn write_record<TWrite>(writer: &mut TWrite, record_type: u8, address_len: usize, address: u32, data: &[u8]) -> io::Result<()>
+    where TWrite: io::Write {
+    let mut body = vec![(address_len + data.len() + 1) as u8];
+    body.extend_from_slice(&address.to_be_bytes()[4 - address_len..]);
+    body.extend_from_slice(data);
+    body.push(checksum(&body));
+
+    let mut line = String::from("S");
+    line.push(record_type as char);
+    memory_image::encode_hex(&body, &mut line);
+    line.push('\n');
+    writer.write_all(line.as_bytes())
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    !bytes.iter().fold(0_u8, |sum, &b| sum.wrapping_add(b))
+}
@@ -0,0 +1,137 @@
+//! Provides the reads of an array of small structs, either into the array of structs or into the
+//! struct of arrays.
+//!
+//! The records are stored one after another, and are read record by record. The caller selects
+//! the layout in memory by the type read into: a `Vec` of the records keeps each record together,
+//! and a struct of a vector per field, which implements `StructArray`, stores each field
+//! contiguously, as the columnar analyses process them.
+
+use std::io;
+
+use error::Context;
+use record_file::FixedRecord;
+
+/// The record count preallocated at most, so that a corrupt count does not exhaust the memory.
+const MAX_PREALLOCATED_RECORDS: usize = 4096;
+
+/// A collection of the records of `Record`, into which an array of structs is read.
+///
+/// It is implemented for `Vec<T>`, the array of structs. A struct of arrays implements it by
+/// pushing each field to its vector.
+pub trait StructArray: Default {
+    /// The record type.
+    type Record: FixedRecord;
+
+    /// Returns the record count.
+    fn len(&self) -> usize;
+
+    /// Returns true if there are no records.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reserves the capacity for `additional` more records.
+    fn reserve(&mut self, additional: usize);
+
+    /// Appends `record`.
+    fn push(&mut self, record: Self::Record);
+}
+
+impl<T> StructArray for Vec<T>
+    where T: FixedRecord {
+    type Record = T;
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+
+    fn push(&mut self, record: T) {
+        Vec::push(self, record);
+    }
+}
+
+/// Provides the features to read an array of structs.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::record_file::FixedRecord;
+/// use mm_binary_io::struct_array::{StructArray, StructArrayRead};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Trade { price: u32, volume: u16 }
+///
+/// impl FixedRecord for Trade {
+///     const SIZE: usize = 6;
+///     fn read_record<TRead: io::Read>(reader: &mut TRead) -> io::Result<Trade> {
+///         let price = reader.read_integer::<LittleEndian, u32>()?;
+///         let volume = reader.read_integer::<LittleEndian, u16>()?;
+///         Ok(Trade { price, volume })
+///     }
+///     fn write_record<TWrite: io::Write>(&self, writer: &mut TWrite) -> io::Result<()> {
+///         writer.write_integer::<LittleEndian, _>(self.price)?;
+///         writer.write_integer::<LittleEndian, _>(self.volume)
+///     }
+/// }
+///
+/// #[derive(Default)]
+/// struct Trades { price: Vec<u32>, volume: Vec<u16> }
+///
+/// impl StructArray for Trades {
+///     type Record = Trade;
+///     fn len(&self) -> usize { self.price.len() }
+///     fn reserve(&mut self, additional: usize) {
+///         self.price.reserve(additional);
+///         self.volume.reserve(additional);
+///     }
+///     fn push(&mut self, record: Trade) {
+///         self.price.push(record.price);
+///         self.volume.push(record.volume);
+///     }
+/// }
+///
+/// let bytes = [100, 0, 0, 0, 5, 0, 101, 0, 0, 0, 7, 0];
+///
+/// let trades: Vec<Trade> = (&bytes[..]).read_struct_array(2).unwrap();
+/// assert_eq!(vec![Trade { price: 100, volume: 5 }, Trade { price: 101, volume: 7 }], trades);
+///
+/// let trades: Trades = (&bytes[..]).read_struct_array(2).unwrap();
+/// assert_eq!(vec![100, 101], trades.price);
+/// assert_eq!(12_u16, trades.volume.iter().sum());
+///
+/// let error = (&bytes[..]).read_struct_array::<Trades>(3).err().unwrap();
+/// assert_eq!(io::ErrorKind::UnexpectedEof, error.kind());
+///
+/// ```
+///
+pub trait StructArrayRead: io::Read {
+    /// Reads `count` records into `TArray`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(TArray), otherwise Err(io::Error) whose field path
+    /// names the record index.
+    ///
+    fn read_struct_array<TArray>(&mut self, count: usize) -> io::Result<TArray>
+        where Self: Sized,
+              TArray: StructArray {
+        trace_span!(DEBUG, "read struct array", count = count, record_size = TArray::Record::SIZE);
+        let mut array = TArray::default();
+        array.reserve(count.min(MAX_PREALLOCATED_RECORDS));
+        for i in 0..count {
+            array.push(TArray::Record::read_record(self).with_ctx(|| format!("[{}]", i))?);
+        }
+        Ok(array)
+    }
+}
+
+impl<T> StructArrayRead for T
+    where T: io::Read {}
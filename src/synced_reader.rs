@@ -0,0 +1,171 @@
+//! Provides the features to read fixed-size packets aligned to a recurring sync pattern.
+
+use std::io;
+use std::mem;
+
+/// Reads fixed-size packets which start with a sync pattern (e.g. MPEG-TS `0x47` every 188 bytes).
+///
+/// The reader aligns to the pattern at startup, and whenever a packet does not start with the
+/// pattern, it skips the garbage until it finds the pattern recurring at the stride again.
+/// The skipped bytes are counted by `discarded()`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::synced_reader::SyncedReader;
+///
+/// let data = vec![
+///     0xFF, 0xFF,
+///     0x47, 0x01, 0x02, 0x03,
+///     0x47, 0x04, 0x05, 0x06,
+///     0xAA,
+///     0x47, 0x07, 0x08, 0x09,
+///     0x47, 0x0A];
+/// let mut reader = SyncedReader::new(&data[..], &[0x47], 4, 2);
+///
+/// assert_eq!(Some(vec![0x47, 0x01, 0x02, 0x03]), reader.read_packet().unwrap());
+/// assert_eq!(Some(vec![0x47, 0x04, 0x05, 0x06]), reader.read_packet().unwrap());
+/// assert_eq!(2, reader.discarded());
+///
+/// assert_eq!(Some(vec![0x47, 0x07, 0x08, 0x09]), reader.read_packet().unwrap());
+/// assert_eq!(3, reader.discarded());
+///
+/// assert_eq!(None, reader.read_packet().unwrap());
+/// assert_eq!(5, reader.discarded());
+///
+/// ```
+///
+pub struct SyncedReader<TRead>
+    where TRead: io::Read {
+    reader: TRead,
+    pattern: Vec<u8>,
+    stride: usize,
+    confirmations: usize,
+    buffer: Vec<u8>,
+    synced: bool,
+    eof: bool,
+    discarded: u64,
+}
+
+impl<TRead> SyncedReader<TRead>
+    where TRead: io::Read {
+    /// Creates a new `SyncedReader`.
+    ///
+    /// # Arguments
+    ///
+    /// * pattern - the sync pattern at the start of each packet.
+    /// * stride - the packet size.
+    /// * confirmations - the number of consecutive patterns required to (re)gain the sync.
+    ///   Near the end of the stream, only the patterns within the stream are required.
+    ///
+    /// # Panics
+    ///
+    /// If `pattern` is empty or longer than `stride`, or `confirmations` is 0, the function panics.
+    ///
+    pub fn new(reader: TRead, pattern: &[u8], stride: usize, confirmations: usize) -> SyncedReader<TRead> {
+        assert!(!pattern.is_empty() && pattern.len() <= stride, "invalid sync pattern length");
+        assert!(confirmations > 0, "confirmations must not be 0");
+        SyncedReader {
+            reader,
+            pattern: pattern.to_vec(),
+            stride,
+            confirmations,
+            buffer: vec![],
+            synced: false,
+            eof: false,
+            discarded: 0,
+        }
+    }
+
+    /// Reads the next packet.
+    ///
+    /// # Errors
+    ///
+    /// If no more packet is found, returns Ok(None). The bytes left at the end of the stream are
+    /// counted as discarded.
+    /// If reading fails, returns Err(io::Error).
+    ///
+    pub fn read_packet(&mut self) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            if self.synced {
+                self.fill(self.stride)?;
+                if self.buffer.len() >= self.stride && self.buffer.starts_with(&self.pattern) {
+                    let rest = self.buffer.split_off(self.stride);
+                    return Ok(Some(mem::replace(&mut self.buffer, rest)));
+                }
+                self.synced = false;
+            }
+
+            let needed = self.stride * self.confirmations + self.pattern.len() - 1;
+            self.fill(needed)?;
+            if self.buffer.len() < self.stride {
+                self.discard(self.buffer.len());
+                return Ok(None);
+            }
+            match self.find_sync() {
+                Some(offset) => {
+                    self.discard(offset);
+                    self.synced = true;
+                }
+                None => self.discard(self.stride.min(self.buffer.len())),
+            }
+        }
+    }
+
+    /// Returns the total number of the bytes skipped to find the sync.
+    pub fn discarded(&self) -> u64 {
+        self.discarded
+    }
+
+    /// Returns true if the reader is aligned to the sync pattern.
+    pub fn is_synced(&self) -> bool {
+        self.synced
+    }
+
+    /// Unwraps this `SyncedReader`, returning the underlying reader.
+    ///
+    /// The buffered bytes are lost.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+
+    fn find_sync(&self) -> Option<usize> {
+        (0..self.stride).find(|&offset| {
+            if offset + self.stride > self.buffer.len() {
+                return false;
+            }
+            (0..self.confirmations)
+                .map(|k| offset + k * self.stride)
+                .take_while(|&start| start + self.pattern.len() <= self.buffer.len())
+                .all(|start| self.buffer[start..].starts_with(&self.pattern))
+        })
+    }
+
+    fn discard(&mut self, len: usize) {
+        self.buffer.drain(..len);
+        self.discarded += len as u64;
+    }
+
+    fn fill(&mut self, len: usize) -> io::Result<()> {
+        let mut buf = [0_u8; 4096];
+        while !self.eof && self.buffer.len() < len {
+            let n = (len - self.buffer.len()).min(buf.len());
+            match self.reader.read(&mut buf[..n]) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.buffer.extend_from_slice(&buf[..n]),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<TRead> Iterator for SyncedReader<TRead>
+    where TRead: io::Read {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_packet().transpose()
+    }
+}
@@ -0,0 +1,316 @@
+//! Provides the features to read and write the 512-byte ustar (POSIX tar) headers.
+//!
+//! The numeric fields are octal ASCII. The values which do not fit in octal are written in the
+//! base-256 form (GNU extension), which is also accepted when reading.
+
+use std::convert::TryFrom;
+use std::io;
+use std::io::Write;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use checksum_frame::ChecksumMismatch;
+
+/// The size of a tar block.
+pub const BLOCK_SIZE: usize = 512;
+
+/// The type flag of a regular file.
+pub const ENTRY_REGULAR: u8 = b'0';
+/// The type flag of a hard link.
+pub const ENTRY_HARD_LINK: u8 = b'1';
+/// The type flag of a symbolic link.
+pub const ENTRY_SYMLINK: u8 = b'2';
+/// The type flag of a character device.
+pub const ENTRY_CHAR_DEVICE: u8 = b'3';
+/// The type flag of a block device.
+pub const ENTRY_BLOCK_DEVICE: u8 = b'4';
+/// The type flag of a directory.
+pub const ENTRY_DIRECTORY: u8 = b'5';
+/// The type flag of a FIFO.
+pub const ENTRY_FIFO: u8 = b'6';
+
+const CHECKSUM_RANGE: std::ops::Range<usize> = 148..156;
+
+/// A ustar header.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Header {
+    /// The file name (up to 100 bytes).
+    pub name: Vec<u8>,
+    /// The file mode.
+    pub mode: u32,
+    /// The user ID of the owner.
+    pub uid: u64,
+    /// The group ID of the owner.
+    pub gid: u64,
+    /// The size of the file data.
+    pub size: u64,
+    /// The modification time in seconds since the Unix epoch.
+    pub mtime: u64,
+    /// The type flag (e.g. `ENTRY_REGULAR`).
+    pub entry_type: u8,
+    /// The target of a link (up to 100 bytes).
+    pub link_name: Vec<u8>,
+    /// The user name of the owner (up to 32 bytes).
+    pub uname: Vec<u8>,
+    /// The group name of the owner (up to 32 bytes).
+    pub gname: Vec<u8>,
+    /// The major device number.
+    pub dev_major: u32,
+    /// The minor device number.
+    pub dev_minor: u32,
+    /// The path prefix (up to 155 bytes).
+    pub prefix: Vec<u8>,
+}
+
+impl Header {
+    /// Returns the full path, joining `prefix` and `name`.
+    pub fn path(&self) -> Vec<u8> {
+        if self.prefix.is_empty() {
+            return self.name.clone();
+        }
+        let mut path = self.prefix.clone();
+        path.push(b'/');
+        path.extend_from_slice(&self.name);
+        path
+    }
+
+    /// Sets `name` and `prefix` from `path`, splitting it at a `/` if it exceeds 100 bytes.
+    ///
+    /// # Errors
+    ///
+    /// If `path` cannot be split into a 155-byte prefix and a 100-byte name, returns
+    /// Err(io::Error) of `InvalidInput`.
+    ///
+    pub fn set_path(&mut self, path: &[u8]) -> io::Result<()> {
+        if path.len() <= 100 {
+            self.name = path.to_vec();
+            self.prefix.clear();
+            return Ok(());
+        }
+        let split = path.iter().enumerate()
+            .filter(|&(i, &b)| b == b'/' && i <= 155 && path.len() - i - 1 <= 100 && i + 1 < path.len())
+            .map(|(i, _)| i)
+            .next();
+        match split {
+            Some(i) => {
+                self.prefix = path[..i].to_vec();
+                self.name = path[i + 1..].to_vec();
+                Ok(())
+            }
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "path is too long for a ustar header")),
+        }
+    }
+
+    /// Encodes this header into a block.
+    ///
+    /// # Errors
+    ///
+    /// If a field does not fit, returns Err(io::Error) of `InvalidInput`.
+    ///
+    pub fn to_block(&self) -> io::Result<[u8; BLOCK_SIZE]> {
+        let mut block = Vec::with_capacity(BLOCK_SIZE);
+        block.write_padded_bytes(&self.name, 100, 0)?;
+        write_number(&mut block, self.mode as u64, 8)?;
+        write_number(&mut block, self.uid, 8)?;
+        write_number(&mut block, self.gid, 8)?;
+        write_number(&mut block, self.size, 12)?;
+        write_number(&mut block, self.mtime, 12)?;
+        block.write_all(b"        ")?;
+        block.push(self.entry_type);
+        block.write_padded_bytes(&self.link_name, 100, 0)?;
+        block.write_all(b"ustar\x0000")?;
+        block.write_padded_bytes(&self.uname, 32, 0)?;
+        block.write_padded_bytes(&self.gname, 32, 0)?;
+        write_number(&mut block, self.dev_major as u64, 8)?;
+        write_number(&mut block, self.dev_minor as u64, 8)?;
+        block.write_padded_bytes(&self.prefix, 155, 0)?;
+        block.resize(BLOCK_SIZE, 0);
+
+        let checksum = unsigned_checksum(&block);
+        block[CHECKSUM_RANGE].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+        let mut result = [0_u8; BLOCK_SIZE];
+        result.copy_from_slice(&block);
+        Ok(result)
+    }
+
+    /// Decodes a header from a block, verifying its checksum.
+    ///
+    /// # Errors
+    ///
+    /// If the checksum does not match, returns Err(io::Error) of `InvalidData` whose inner error
+    /// is `ChecksumMismatch<u32>`.
+    /// If a numeric field is broken, returns Err(io::Error) of `InvalidData`.
+    ///
+    pub fn from_block(block: &[u8; BLOCK_SIZE]) -> io::Result<Header> {
+        let expected = u32::try_from(parse_number(&block[CHECKSUM_RANGE])?).map_err(|_| invalid_field())?;
+        let actual = unsigned_checksum(block);
+        if expected != actual && expected as i64 != signed_checksum(block) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, ChecksumMismatch { expected, actual }));
+        }
+
+        let mut reader = io::Cursor::new(&block[..]);
+        let name = reader.read_padded_bytes(100, 0)?;
+        let mode = u32::try_from(read_number(&mut reader, 8)?).map_err(|_| invalid_field())?;
+        let uid = read_number(&mut reader, 8)?;
+        let gid = read_number(&mut reader, 8)?;
+        let size = read_number(&mut reader, 12)?;
+        let mtime = read_number(&mut reader, 12)?;
+        let entry_type = block[156];
+        reader.set_position(157);
+        let link_name = reader.read_padded_bytes(100, 0)?;
+        let magic = reader.read_byte_array(8)?;
+        let uname = reader.read_padded_bytes(32, 0)?;
+        let gname = reader.read_padded_bytes(32, 0)?;
+        let dev_major = u32::try_from(read_number(&mut reader, 8)?).map_err(|_| invalid_field())?;
+        let dev_minor = u32::try_from(read_number(&mut reader, 8)?).map_err(|_| invalid_field())?;
+        let prefix = if magic == b"ustar\x0000" {
+            reader.read_padded_bytes(155, 0)?
+        } else {
+            vec![]
+        };
+        Ok(Header {
+            name,
+            mode,
+            uid,
+            gid,
+            size,
+            mtime,
+            entry_type,
+            link_name,
+            uname,
+            gname,
+            dev_major,
+            dev_minor,
+            prefix,
+        })
+    }
+}
+
+/// Reads a header block.
+///
+/// # Errors
+///
+/// If the block is all zero (the end-of-archive marker), returns Ok(None).
+/// If the header is broken, returns Err(io::Error) of `InvalidData`.
+/// If reading fails, returns Err(io::Error).
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::tar::{self, Header};
+///
+/// let mut header = Header::default();
+/// header.set_path(b"dir/file.txt").unwrap();
+/// header.mode = 0o644;
+/// header.size = 10;
+/// header.entry_type = tar::ENTRY_REGULAR;
+///
+/// let mut writer = vec![];
+/// tar::write_header(&mut writer, &header).unwrap();
+/// tar::write_end_of_archive(&mut writer).unwrap();
+/// assert_eq!(3 * tar::BLOCK_SIZE, writer.len());
+/// assert_eq!(b"0000644\0", &writer[100..108]);
+/// assert_eq!(b"00000000012\0", &writer[124..136]);
+/// assert_eq!(b"ustar\x0000", &writer[257..265]);
+///
+/// let mut reader = io::Cursor::new(writer.clone());
+/// assert_eq!(Some(header), tar::read_header(&mut reader).unwrap());
+/// assert_eq!(None, tar::read_header(&mut reader).unwrap());
+///
+/// writer[0] = b'x';
+/// let error = tar::read_header(&mut io::Cursor::new(writer)).unwrap_err();
+/// assert_eq!(io::ErrorKind::InvalidData, error.kind());
+///
+/// ```
+///
+pub fn read_header<TRead>(reader: &mut TRead) -> io::Result<Option<Header>>
+    where TRead: io::Read {
+    let mut block = [0_u8; BLOCK_SIZE];
+    reader.read_exact(&mut block)?;
+    if block.iter().all(|&b| b == 0) {
+        return Ok(None);
+    }
+    let header = Header::from_block(&block)?;
+    trace_event!(TRACE, path = %String::from_utf8_lossy(&header.path()), size = header.size, "read tar header");
+    Ok(Some(header))
+}
+
+/// Writes a header block.
+///
+/// # Errors
+///
+/// If a field does not fit, returns Err(io::Error) of `InvalidInput`.
+/// If writing fails, returns Err(io::Error).
+///
+pub fn write_header<TWrite>(writer: &mut TWrite, header: &Header) -> io::Result<()>
+    where TWrite: io::Write {
+    writer.write_all(&header.to_block()?)
+}
+
+/// Writes the end-of-archive marker (two zero blocks).
+pub fn write_end_of_archive<TWrite>(writer: &mut TWrite) -> io::Result<()>
+    where TWrite: io::Write {
+    writer.write_all(&[0_u8; BLOCK_SIZE * 2])
+}
+
+/// Returns the length of the padding after `size` bytes of file data.
+pub fn padding_len(size: u64) -> u64 {
+    (BLOCK_SIZE as u64 - size % BLOCK_SIZE as u64) % BLOCK_SIZE as u64
+}
+
+fn read_number<TRead>(reader: &mut TRead, len: usize) -> io::Result<u64>
+    where TRead: io::Read {
+    let field = reader.read_byte_array(len)?;
+    parse_number(&field)
+}
+
+fn parse_number(field: &[u8]) -> io::Result<u64> {
+    if field.first().is_some_and(|&b| b & 0x80 != 0) {
+        let high = field.len().saturating_sub(8).max(1);
+        if field[0] != 0x80 || field[1..high].iter().any(|&b| b != 0) {
+            return Err(invalid_field());
+        }
+        return Ok(field[1..].iter().fold(0_u64, |value, &b| value << 8 | b as u64));
+    }
+    let digits = field.iter()
+        .skip_while(|&&b| b == b' ')
+        .take_while(|&&b| b != 0 && b != b' ');
+    let mut value = 0_u64;
+    for &digit in digits {
+        if !(b'0'..=b'7').contains(&digit) || value >> 61 != 0 {
+            return Err(invalid_field());
+        }
+        value = value << 3 | (digit - b'0') as u64;
+    }
+    Ok(value)
+}
+
+fn write_number(block: &mut Vec<u8>, value: u64, len: usize) -> io::Result<()> {
+    let digits = len - 1;
+    if value < 1 << (3 * digits) {
+        block.write_all(format!("{:0width$o}\0", value, width = digits).as_bytes())
+    } else if len > 8 || value < 1 << (8 * (len - 1)) {
+        block.push(0x80);
+        block.write_all(&vec![0; (len - 1).saturating_sub(8)])?;
+        block.write_all(&value.to_be_bytes()[8 - (len - 1).min(8)..])
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidInput, "value does not fit in the tar field"))
+    }
+}
+
+fn unsigned_checksum(block: &[u8]) -> u32 {
+    block.iter().enumerate()
+        .map(|(i, &b)| if CHECKSUM_RANGE.contains(&i) { b' ' } else { b } as u32)
+        .sum()
+}
+
+fn signed_checksum(block: &[u8]) -> i64 {
+    block.iter().enumerate()
+        .map(|(i, &b)| if CHECKSUM_RANGE.contains(&i) { b' ' as i64 } else { b as i8 as i64 })
+        .sum()
+}
+
+fn invalid_field() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid tar header field")
+}
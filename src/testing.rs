@@ -0,0 +1,530 @@
+//! Provides the test support for the code reading and writing binary data.
+//!
+//! The types here are meant for the unit tests of the downstream crates: `FaultyReader`
+//! exercises their error paths against realistic IO failures, and `RecordingWriter` shows
+//! exactly what their serializers wrote and where. The hex helpers build the inputs and check the
+//! outputs as readable hex strings.
+//!
+//! With the `test-helpers` feature, `assert_round_trip` property-tests by proptest that the values
+//! are read back equal after being written, and `Tlv` and `Chunk` implement `Arbitrary`.
+
+#[cfg(feature = "test-helpers")]
+use std::fmt;
+use std::io;
+
+#[cfg(feature = "test-helpers")]
+use proptest::arbitrary::{any, Arbitrary};
+#[cfg(feature = "test-helpers")]
+use proptest::array::uniform4;
+#[cfg(feature = "test-helpers")]
+use proptest::collection::vec;
+#[cfg(feature = "test-helpers")]
+use proptest::strategy::{BoxedStrategy, Strategy};
+#[cfg(feature = "test-helpers")]
+use proptest::test_runner::{TestCaseError, TestRunner};
+
+#[cfg(feature = "test-helpers")]
+use endian::Endian;
+#[cfg(feature = "test-helpers")]
+use png_chunk::Chunk;
+#[cfg(feature = "test-helpers")]
+use read_integer::ReadInteger;
+#[cfg(feature = "test-helpers")]
+use tlv::Tlv;
+#[cfg(feature = "test-helpers")]
+use write_integer::WriteInteger;
+
+/// The byte count of a row of `assert_hex_eq`.
+const HEX_ROW_LEN: usize = 16;
+
+/// A fault injected by `FaultyReader`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Returns an error of the kind.
+    Error(io::ErrorKind),
+    /// Returns at most the byte count. 0 simulates a premature end of the stream.
+    ShortRead(usize),
+    /// Returns an error of `Interrupted`, which the callers are expected to retry.
+    Interrupted,
+}
+
+/// Wraps a reader, and injects the programmed faults at the stream offsets.
+///
+/// A read is split at the offset of the next fault, so that the fault happens exactly at the
+/// offset. Each fault happens once, in the order of the offsets, and of the programming for the
+/// same offset.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Read};
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::testing::{Fault, FaultyReader};
+///
+/// let data = vec![0x00_u8, 0x01, 0x00, 0x02, 0x00, 0x03];
+/// let mut reader = FaultyReader::new(&data[..])
+///     .fault_at(1, Fault::Interrupted)
+///     .fault_at(2, Fault::ShortRead(1))
+///     .fault_at(4, Fault::Error(io::ErrorKind::ConnectionReset));
+///
+/// // `read_exact` retries `Interrupted` and continues after a short read.
+/// assert_eq!(1, reader.read_integer::<BigEndian, u16>().unwrap());
+/// assert_eq!(2, reader.read_integer::<BigEndian, u16>().unwrap());
+///
+/// let error = reader.read_integer::<BigEndian, u16>().unwrap_err();
+/// assert_eq!(io::ErrorKind::ConnectionReset, error.kind());
+/// assert_eq!(4, reader.position());
+///
+/// let mut buf = [0_u8; 8];
+/// assert_eq!(2, reader.read(&mut buf).unwrap());
+///
+/// ```
+///
+#[derive(Debug)]
+pub struct FaultyReader<TRead>
+    where TRead: io::Read {
+    reader: TRead,
+    position: u64,
+    faults: Vec<(u64, Fault)>,
+}
+
+impl<TRead> FaultyReader<TRead>
+    where TRead: io::Read {
+    /// Creates a new `FaultyReader` without faults.
+    pub fn new(reader: TRead) -> FaultyReader<TRead> {
+        FaultyReader { reader, position: 0, faults: vec![] }
+    }
+
+    /// Programs `fault` at the stream `offset`.
+    pub fn fault_at(mut self, offset: u64, fault: Fault) -> FaultyReader<TRead> {
+        let index = self.faults.iter().position(|&(o, _)| o > offset).unwrap_or(self.faults.len());
+        self.faults.insert(index, (offset, fault));
+        self
+    }
+
+    /// Returns the byte count read through this reader.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the number of the faults which have not happened yet.
+    pub fn pending_faults(&self) -> usize {
+        self.faults.len()
+    }
+
+    /// Unwraps this `FaultyReader`, returning the underlying reader.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+}
+
+impl<TRead> io::Read for FaultyReader<TRead>
+    where TRead: io::Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // The faults programmed after a short read at the same offset are passed by it.
+        while self.faults.first().is_some_and(|&(offset, _)| offset < self.position) {
+            self.faults.remove(0);
+        }
+        let mut len = buf.len();
+        match self.faults.first().copied() {
+            Some((offset, fault)) if offset == self.position => {
+                self.faults.remove(0);
+                match fault {
+                    Fault::Error(kind) => return Err(io::Error::new(kind, "injected fault")),
+                    Fault::Interrupted => return Err(io::Error::new(io::ErrorKind::Interrupted, "injected fault")),
+                    Fault::ShortRead(max) => len = len.min(max),
+                }
+            }
+            Some((offset, _)) => len = len.min((offset - self.position) as usize),
+            None => {}
+        }
+        let n = self.reader.read(&mut buf[..len])?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+/// A write captured by `RecordingWriter`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WriteRecord {
+    /// The offset of the write.
+    pub offset: u64,
+    /// The byte count written.
+    pub len: usize,
+    /// The label set when the write happened.
+    pub label: Option<String>,
+}
+
+/// Writes to memory, and records every write with its offset and the current label.
+///
+/// It implements `io::Seek`, so the serializers which patch the headers afterwards are recorded
+/// as well. Writing after the end fills the gap with zeros.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Seek, Write};
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::testing::{RecordingWriter, WriteRecord};
+///
+/// let mut writer = RecordingWriter::new();
+/// writer.labeled("header", |w| {
+///     w.write_all(b"MM")?;
+///     w.write_integer::<BigEndian, u16>(0)
+/// }).unwrap();
+/// writer.write_string("body").unwrap();
+/// writer.seek(io::SeekFrom::Start(2)).unwrap();
+/// writer.labeled("header.len", |w| w.write_integer::<BigEndian, u16>(4)).unwrap();
+///
+/// assert_eq!(b"MM\x00\x04body", writer.buffer());
+/// let header = Some("header".to_string());
+/// assert_eq!(&[
+///     WriteRecord { offset: 0, len: 2, label: header.clone() },
+///     WriteRecord { offset: 2, len: 2, label: header },
+///     WriteRecord { offset: 4, len: 4, label: None },
+///     WriteRecord { offset: 2, len: 2, label: Some("header.len".to_string()) }], writer.records());
+///
+/// ```
+///
+#[derive(Debug, Default)]
+pub struct RecordingWriter {
+    buffer: Vec<u8>,
+    position: u64,
+    label: Option<String>,
+    records: Vec<WriteRecord>,
+}
+
+impl RecordingWriter {
+    /// Creates a new empty `RecordingWriter`.
+    pub fn new() -> RecordingWriter {
+        RecordingWriter::default()
+    }
+
+    /// Sets the label of the following writes. None clears it.
+    pub fn set_label(&mut self, label: Option<&str>) {
+        self.label = label.map(str::to_owned);
+    }
+
+    /// Calls `f` with the writes labeled `label`, and restores the previous label.
+    pub fn labeled<T, F>(&mut self, label: &str, f: F) -> T
+        where F: FnOnce(&mut RecordingWriter) -> T {
+        let previous = self.label.replace(label.to_owned());
+        let result = f(self);
+        self.label = previous;
+        result
+    }
+
+    /// Returns the bytes written.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Returns the writes recorded, in the order of the calls.
+    pub fn records(&self) -> &[WriteRecord] {
+        &self.records
+    }
+
+    /// Returns the current position.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Unwraps this `RecordingWriter`, returning the bytes written.
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+impl io::Write for RecordingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let start = self.position as usize;
+        let end = start + buf.len();
+        if self.buffer.len() < end {
+            self.buffer.resize(end, 0);
+        }
+        self.buffer[start..end].copy_from_slice(buf);
+        self.records.push(WriteRecord { offset: self.position, len: buf.len(), label: self.label.clone() });
+        self.position = end as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl io::Seek for RecordingWriter {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            io::SeekFrom::Start(offset) => Some(offset),
+            io::SeekFrom::End(delta) => (self.buffer.len() as u64).checked_add_signed(delta),
+            io::SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        self.position = position
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative position"))?;
+        Ok(self.position)
+    }
+}
+
+/// Decodes a hex string with whitespace and `#` comments into bytes.
+///
+/// # Panics
+///
+/// If the string has a non-hex character outside the comments, or an odd number of digits,
+/// the function panics.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::testing;
+///
+/// let bytes = testing::hex_bytes("
+///     DE AD BE EF  # magic
+///     00 02        # version
+/// ");
+/// assert_eq!(vec![0xDE, 0xAD, 0xBE, 0xEF, 0x00, 0x02], bytes);
+///
+/// ```
+///
+#[track_caller]
+pub fn hex_bytes(hex: &str) -> Vec<u8> {
+    let digits: Vec<u32> = hex.lines()
+        .flat_map(|line| line.split('#').next().unwrap_or("").chars())
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_digit(16).unwrap_or_else(|| panic!("invalid hex digit {:?}", c)))
+        .collect();
+    assert!(digits.len().is_multiple_of(2), "odd number of hex digits");
+    digits.chunks(2).map(|pair| (pair[0] << 4 | pair[1]) as u8).collect()
+}
+
+/// Creates a reader of the bytes of a hex string with whitespace and `#` comments.
+///
+/// # Panics
+///
+/// If the string is not valid as `hex_bytes`, the function panics.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::testing;
+///
+/// let mut reader = testing::from_hex("DE AD BE EF  # magic");
+/// assert_eq!(0xDEADBEEF, reader.read_integer::<BigEndian, u32>().unwrap());
+///
+/// ```
+///
+#[track_caller]
+pub fn from_hex(hex: &str) -> io::Cursor<Vec<u8>> {
+    io::Cursor::new(hex_bytes(hex))
+}
+
+/// Asserts that `actual` equals the bytes of the hex string `expected`.
+///
+/// On a mismatch, the panic message shows the rows of 16 bytes, marking the expected rows by
+/// `-` and the actual rows by `+` where they differ.
+///
+/// # Panics
+///
+/// If the bytes differ, or `expected` is not valid as `hex_bytes`, the function panics.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::testing;
+///
+/// let mut writer = vec![];
+/// writer.write_integer::<LittleEndian, u32>(0xDEADBEEF).unwrap();
+/// testing::assert_hex_eq(&writer, "EF BE AD DE");
+///
+/// let message = std::panic::catch_unwind(|| testing::assert_hex_eq(&writer, "EF BE AD 00"))
+///     .unwrap_err()
+///     .downcast::<String>()
+///     .unwrap();
+/// assert_eq!("bytes differ at offset 3 (expected 4 bytes, actual 4 bytes)\n\
+///             - 0000: EF BE AD 00\n\
+///             + 0000: EF BE AD DE\n", *message);
+///
+/// ```
+///
+#[track_caller]
+pub fn assert_hex_eq(actual: &[u8], expected: &str) {
+    let expected = hex_bytes(expected);
+    if actual == &expected[..] {
+        return;
+    }
+    let offset = actual.iter().zip(&expected).take_while(|&(a, e)| a == e).count();
+    let mut message = format!("bytes differ at offset {} (expected {} bytes, actual {} bytes)\n",
+                              offset, expected.len(), actual.len());
+    let rows = expected.len().max(actual.len()).div_ceil(HEX_ROW_LEN);
+    for row in 0..rows {
+        let expected_row = hex_row(&expected, row);
+        let actual_row = hex_row(actual, row);
+        if expected_row == actual_row {
+            message.push_str(&format!("  {:04X}: {}\n", row * HEX_ROW_LEN, expected_row));
+        } else {
+            message.push_str(&format!("- {:04X}: {}\n", row * HEX_ROW_LEN, expected_row));
+            message.push_str(&format!("+ {:04X}: {}\n", row * HEX_ROW_LEN, actual_row));
+        }
+    }
+    panic!("{}", message);
+}
+
+/// Formats the `row`-th row of `bytes` as spaced hex digits.
+fn hex_row(bytes: &[u8], row: usize) -> String {
+    let start = (row * HEX_ROW_LEN).min(bytes.len());
+    let end = (start + HEX_ROW_LEN).min(bytes.len());
+    bytes[start..end].iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ")
+}
+
+/// A value which is read and written in the endianness `TEndian`, for the round-trip tests.
+///
+/// It is implemented for the integers. The downstream format crates implement it for their
+/// types to use `assert_round_trip`.
+///
+/// This trait is available with the `test-helpers` feature.
+#[cfg(feature = "test-helpers")]
+pub trait EndianCodec: Sized {
+    /// Reads a value from `reader`.
+    fn decode<TEndian>(reader: &mut dyn io::Read) -> io::Result<Self>
+        where TEndian: Endian;
+
+    /// Writes the value to `writer`.
+    fn encode<TEndian>(&self, writer: &mut dyn io::Write) -> io::Result<()>
+        where TEndian: Endian;
+}
+
+#[cfg(feature = "test-helpers")]
+macro_rules! impl_endian_codec {
+    ($($t:ty),*) => {
+        $(
+            impl EndianCodec for $t {
+                fn decode<TEndian>(reader: &mut dyn io::Read) -> io::Result<$t>
+                    where TEndian: Endian {
+                    <$t as ReadInteger>::read_integer::<TEndian>(reader)
+                }
+
+                fn encode<TEndian>(&self, writer: &mut dyn io::Write) -> io::Result<()>
+                    where TEndian: Endian {
+                    WriteInteger::write_integer::<TEndian>(self, writer)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "test-helpers")]
+impl_endian_codec!(u8, i8, u16, i16, u32, i32, u64, i64);
+
+/// Checks that `value` is read back equal after being written in `TEndian`, and that the read
+/// consumes all bytes written.
+///
+/// It is meant for the bodies of the `proptest!` tests.
+///
+/// This function is available with the `test-helpers` feature.
+///
+/// # Errors
+///
+/// If the value is not read back, returns Err(TestCaseError) describing the failure.
+///
+#[cfg(feature = "test-helpers")]
+pub fn check_round_trip<T, TEndian>(value: &T) -> Result<(), TestCaseError>
+    where T: EndianCodec + PartialEq + fmt::Debug,
+          TEndian: Endian {
+    let mut bytes = vec![];
+    value.encode::<TEndian>(&mut bytes)
+        .map_err(|e| TestCaseError::fail(format!("failed to write {:?}: {}", value, e)))?;
+    let mut reader = &bytes[..];
+    let read = T::decode::<TEndian>(&mut reader)
+        .map_err(|e| TestCaseError::fail(format!("failed to read {:?} from {:02X?}: {}", value, bytes, e)))?;
+    if read != *value {
+        return Err(TestCaseError::fail(format!("{:?} was read back as {:?} from {:02X?}", value, read, bytes)));
+    }
+    if !reader.is_empty() {
+        return Err(TestCaseError::fail(format!("{} of {} bytes of {:?} were not read", reader.len(), bytes.len(), value)));
+    }
+    Ok(())
+}
+
+/// Property-tests that the arbitrary values of `T` are read back equal after being written in
+/// `TEndian`.
+///
+/// This function is available with the `test-helpers` feature.
+///
+/// # Panics
+///
+/// If a value is not read back, the function panics with the minimal failing value.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::endian::{BigEndian, LittleEndian};
+/// use mm_binary_io::testing;
+///
+/// testing::assert_round_trip::<u32, BigEndian>();
+/// testing::assert_round_trip::<i64, LittleEndian>();
+///
+/// ```
+///
+#[cfg(feature = "test-helpers")]
+#[track_caller]
+pub fn assert_round_trip<T, TEndian>()
+    where T: EndianCodec + Arbitrary + PartialEq + fmt::Debug,
+          TEndian: Endian {
+    assert_round_trip_with::<T, TEndian, _>(any::<T>());
+}
+
+/// Property-tests that the values of `strategy` are read back equal after being written in
+/// `TEndian`.
+///
+/// This function is available with the `test-helpers` feature.
+///
+/// # Panics
+///
+/// If a value is not read back, the function panics with the minimal failing value.
+///
+#[cfg(feature = "test-helpers")]
+#[track_caller]
+pub fn assert_round_trip_with<T, TEndian, TStrategy>(strategy: TStrategy)
+    where T: EndianCodec + PartialEq + fmt::Debug,
+          TEndian: Endian,
+          TStrategy: Strategy<Value=T> {
+    let mut runner = TestRunner::default();
+    if let Err(e) = runner.run(&strategy, |value| check_round_trip::<T, TEndian>(&value)) {
+        panic!("{}", e);
+    }
+}
+
+/// The maximum length of the byte arrays of the arbitrary values.
+#[cfg(feature = "test-helpers")]
+const MAX_ARBITRARY_LEN: usize = 256;
+
+#[cfg(feature = "test-helpers")]
+impl Arbitrary for Tlv {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Tlv>;
+
+    fn arbitrary_with(_args: ()) -> BoxedStrategy<Tlv> {
+        (any::<u64>(), vec(any::<u8>(), 0..MAX_ARBITRARY_LEN))
+            .prop_map(|(tag, value)| Tlv { tag, value })
+            .boxed()
+    }
+}
+
+#[cfg(feature = "test-helpers")]
+impl Arbitrary for Chunk {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Chunk>;
+
+    fn arbitrary_with(_args: ()) -> BoxedStrategy<Chunk> {
+        let letter = (0_u8..52).prop_map(|i| if i < 26 { b'A' + i } else { b'a' + i - 26 });
+        (uniform4(letter), vec(any::<u8>(), 0..MAX_ARBITRARY_LEN))
+            .prop_map(|(chunk_type, data)| Chunk::new(chunk_type, data))
+            .boxed()
+    }
+}
@@ -0,0 +1,193 @@
+//! Provides the reader applying the timeouts and the deadlines to the blocking reads of a socket,
+//! so that the protocol loops can handle a slow or stalled peer.
+//!
+//! The wrapper configures the read timeout of the socket before each read, and converts an
+//! expired timeout into an error of `TimedOut`, whichever of `WouldBlock` and `TimedOut` the
+//! platform reports.
+
+use std::io;
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::time::{Duration, Instant};
+
+/// A reader whose blocking reads can time out, such as a socket.
+pub trait ReadTimeout {
+    /// Sets the timeout of the following reads, or blocks them indefinitely if `timeout` is None.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl ReadTimeout for TcpStream {
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+}
+
+impl ReadTimeout for &TcpStream {
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+}
+
+#[cfg(unix)]
+impl ReadTimeout for UnixStream {
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        UnixStream::set_read_timeout(self, timeout)
+    }
+}
+
+#[cfg(unix)]
+impl ReadTimeout for &UnixStream {
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        UnixStream::set_read_timeout(self, timeout)
+    }
+}
+
+/// Wraps a socket, and applies a timeout to each read and a deadline to a whole operation.
+///
+/// The timeout limits each read, so that a peer sending nothing is detected. The deadline, set by
+/// `with_deadline` or `set_deadline`, limits all the reads until it is cleared, so that a peer
+/// sending one byte at a time can not hold a `read_exact` forever. A read past either is an error
+/// of `TimedOut`.
+///
+/// The underlying socket is left with the last timeout set.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::net::{TcpListener, TcpStream};
+/// use std::time::Duration;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::timeout::TimeoutReader;
+///
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+/// let _peer = listener.accept().unwrap();
+///
+/// // The peer sends nothing.
+/// let mut reader = TimeoutReader::new(stream, None);
+/// let result = reader.with_deadline(Duration::from_millis(50), |reader| {
+///     reader.read_integer::<BigEndian, u32>()
+/// });
+/// assert_eq!(io::ErrorKind::TimedOut, result.unwrap_err().kind());
+/// assert_eq!(None, reader.deadline());
+///
+/// ```
+///
+pub struct TimeoutReader<TRead>
+    where TRead: io::Read + ReadTimeout {
+    reader: TRead,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    applied: Option<Option<Duration>>,
+}
+
+impl<TRead> TimeoutReader<TRead>
+    where TRead: io::Read + ReadTimeout {
+    /// Creates a new `TimeoutReader` of the per-read `timeout`, or of no timeout if it is None.
+    pub fn new(reader: TRead, timeout: Option<Duration>) -> TimeoutReader<TRead> {
+        TimeoutReader {
+            reader,
+            timeout,
+            deadline: None,
+            applied: None,
+        }
+    }
+
+    /// Returns the per-read timeout.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Sets the per-read timeout, or clears it if `timeout` is None.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Returns the deadline of the reads.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Sets the deadline of the reads, or clears it if `deadline` is None.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+
+    /// Calls `f` under the deadline `timeout` from now, and restores the previous deadline
+    /// after it returns.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the result of `f`), otherwise Err(io::Error) of `f`,
+    /// which is of `TimedOut` if the deadline has passed.
+    ///
+    pub fn with_deadline<T, F>(&mut self, timeout: Duration, f: F) -> io::Result<T>
+        where F: FnOnce(&mut TimeoutReader<TRead>) -> io::Result<T> {
+        let previous = self.deadline;
+        self.deadline = Some(Instant::now() + timeout);
+        let result = f(self);
+        self.deadline = previous;
+        result
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &TRead {
+        &self.reader
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut TRead {
+        self.applied = None;
+        &mut self.reader
+    }
+
+    /// Unwraps this `TimeoutReader`, returning the underlying reader.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+
+    /// Returns the timeout of the next read, or Err(io::Error) of `TimedOut` if the deadline has
+    /// passed.
+    fn next_timeout(&self) -> io::Result<Option<Duration>> {
+        let deadline = match self.deadline {
+            Some(deadline) => deadline,
+            None => return Ok(self.timeout),
+        };
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(timed_out());
+        }
+        let left = deadline - now;
+        Ok(Some(self.timeout.map_or(left, |timeout| timeout.min(left))))
+    }
+}
+
+impl<TRead> io::Read for TimeoutReader<TRead>
+    where TRead: io::Read + ReadTimeout {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let timeout = self.next_timeout()?;
+        if self.applied != Some(timeout) {
+            self.reader.set_read_timeout(timeout)?;
+            self.applied = Some(timeout);
+        }
+        match self.reader.read(buf) {
+            Err(ref e) if timeout.is_some() && (e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut) => {
+                trace_event!(DEBUG, ?timeout, "read timed out");
+                Err(timed_out())
+            }
+            result => result,
+        }
+    }
+}
+
+fn timed_out() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "read timed out")
+}
@@ -0,0 +1,812 @@
+//! Provides the features to read and write timestamps stored as the count of a unit since the
+//! Unix epoch, in a chosen integer width and endianness.
+//!
+//! `std::time::SystemTime` is always supported, `chrono::DateTime<Utc>` with the `chrono` feature,
+//! and `time::OffsetDateTime` with the `time` feature. The precision finer than the unit is
+//! truncated toward the past when writing.
+//!
+//! The Windows `FILETIME`, the 100-nanosecond intervals since 1601-01-01 of NTFS, PE, LNK and
+//! the registry, is also read and written as `SystemTime`. The packed MS-DOS date and time
+//! fields of ZIP and FAT are read and written as `DosDateTime`, a local time without a zone.
+//! The 64-bit NTP timestamps are read and written as `NtpTimestamp`, whose 32-bit seconds wrap
+//! every era of about 136 years.
+
+use std::convert::TryFrom;
+use std::io;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Timelike, Utc};
+#[cfg(feature = "time")]
+use time::OffsetDateTime;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::Endian;
+use read_integer::ReadInteger;
+use write_integer::WriteInteger;
+
+/// The nanoseconds from the `FILETIME` epoch (1601-01-01) to the Unix epoch.
+const FILETIME_EPOCH_OFFSET_NANOS: i128 = 11_644_473_600 * 1_000_000_000;
+
+/// The nanoseconds of one `FILETIME` interval.
+const FILETIME_INTERVAL_NANOS: i128 = 100;
+
+/// The seconds from the NTP epoch (1900-01-01) to the Unix epoch.
+const NTP_EPOCH_OFFSET_SECS: i128 = 2_208_988_800;
+
+/// The seconds of one NTP era.
+const NTP_ERA_SECS: i128 = 1 << 32;
+
+/// The unit of a timestamp.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TimestampUnit {
+    /// Seconds.
+    Seconds,
+    /// Milliseconds.
+    Millis,
+    /// Microseconds.
+    Micros,
+    /// Nanoseconds.
+    Nanos,
+}
+
+impl TimestampUnit {
+    /// Returns the nanoseconds of one unit.
+    pub fn nanos(self) -> i128 {
+        match self {
+            TimestampUnit::Seconds => 1_000_000_000,
+            TimestampUnit::Millis => 1_000_000,
+            TimestampUnit::Micros => 1_000,
+            TimestampUnit::Nanos => 1,
+        }
+    }
+
+    /// Converts `value` of this unit to nanoseconds.
+    pub fn to_nanos(self, value: i128) -> Option<i128> {
+        value.checked_mul(self.nanos())
+    }
+
+    /// Converts `nanos` to this unit, truncating toward the past.
+    pub fn from_nanos(self, nanos: i128) -> i128 {
+        nanos.div_euclid(self.nanos())
+    }
+}
+
+fn read_nanos<TEndian, TInt, TRead>(reader: &mut TRead, unit: TimestampUnit) -> io::Result<i128>
+    where TEndian: Endian,
+          TInt: ReadInteger<OutputType=TInt> + Into<i128>,
+          TRead: io::Read {
+    unit.to_nanos(reader.read_integer::<TEndian, TInt>()?.into())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "timestamp is out of range"))
+}
+
+fn write_nanos<TEndian, TInt, TWrite>(writer: &mut TWrite, nanos: i128, unit: TimestampUnit) -> io::Result<()>
+    where TEndian: Endian,
+          TInt: WriteInteger + TryFrom<i128>,
+          TWrite: io::Write {
+    let value = TInt::try_from(unit.from_nanos(nanos))
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "timestamp does not fit in the integer"))?;
+    writer.write_integer::<TEndian, _>(value)
+}
+
+/// Converts `nanos` since the Unix epoch to `SystemTime`, or None if it is out of the range.
+pub(crate) fn system_time_from_nanos(nanos: i128) -> Option<SystemTime> {
+    let duration = |nanos: u128| {
+        u64::try_from(nanos / 1_000_000_000).ok()
+            .map(|secs| Duration::new(secs, (nanos % 1_000_000_000) as u32))
+    };
+    if nanos >= 0 {
+        duration(nanos as u128).and_then(|d| UNIX_EPOCH.checked_add(d))
+    } else {
+        duration(nanos.unsigned_abs()).and_then(|d| UNIX_EPOCH.checked_sub(d))
+    }
+}
+
+/// Converts `time` to the nanoseconds since the Unix epoch, negative before it.
+pub(crate) fn system_time_to_nanos(time: &SystemTime) -> i128 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_nanos() as i128,
+        Err(e) => -(e.duration().as_nanos() as i128),
+    }
+}
+
+/// Provides the features to read `std::time::SystemTime`.
+///
+/// The timestamps before 1970 are read from the negative values of the signed integers.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use mm_binary_io::endian::{BigEndian, LittleEndian};
+/// use mm_binary_io::timestamp::{SystemTimeRead, SystemTimeWrite, TimestampUnit};
+///
+/// let value = UNIX_EPOCH + Duration::new(1_600_000_000, 123_456_789);
+/// let before_1970 = UNIX_EPOCH - Duration::from_millis(1_500);
+///
+/// let mut writer = vec![];
+/// writer.write_unix_timestamp_secs::<BigEndian>(&value).unwrap();
+/// writer.write_unix_timestamp_millis::<LittleEndian>(&before_1970).unwrap();
+/// writer.write_system_time::<BigEndian, u32>(&value, TimestampUnit::Seconds).unwrap();
+/// assert_eq!(vec![0x00, 0x00, 0x00, 0x00, 0x5F, 0x5E, 0x10, 0x00], writer[..8].to_vec());
+///
+/// let mut reader = io::Cursor::new(writer);
+/// assert_eq!(UNIX_EPOCH + Duration::from_secs(1_600_000_000), reader.read_unix_timestamp_secs::<BigEndian>().unwrap());
+/// assert_eq!(before_1970, reader.read_unix_timestamp_millis::<LittleEndian>().unwrap());
+/// assert_eq!(UNIX_EPOCH + Duration::from_secs(1_600_000_000), reader.read_system_time::<BigEndian, u32>(TimestampUnit::Seconds).unwrap());
+///
+/// ```
+///
+pub trait SystemTimeRead: io::Read {
+    /// Reads a timestamp of `TInt` counting `unit` since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// If the timestamp is out of the range of `SystemTime`, returns Err(io::Error) of
+    /// `InvalidData`. If reading fails, returns Err(io::Error).
+    ///
+    fn read_system_time<TEndian, TInt>(&mut self, unit: TimestampUnit) -> io::Result<SystemTime>
+        where Self: Sized,
+              TEndian: Endian,
+              TInt: ReadInteger<OutputType=TInt> + Into<i128> {
+        let nanos = read_nanos::<TEndian, TInt, _>(self, unit)?;
+        system_time_from_nanos(nanos)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "timestamp is out of range"))
+    }
+
+    /// Reads a timestamp of `i64` seconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(SystemTime), otherwise Err(io::Error) as `read_system_time`.
+    ///
+    fn read_unix_timestamp_secs<TEndian>(&mut self) -> io::Result<SystemTime>
+        where Self: Sized,
+              TEndian: Endian {
+        self.read_system_time::<TEndian, i64>(TimestampUnit::Seconds)
+    }
+
+    /// Reads a timestamp of `i64` milliseconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(SystemTime), otherwise Err(io::Error) as `read_system_time`.
+    ///
+    fn read_unix_timestamp_millis<TEndian>(&mut self) -> io::Result<SystemTime>
+        where Self: Sized,
+              TEndian: Endian {
+        self.read_system_time::<TEndian, i64>(TimestampUnit::Millis)
+    }
+
+    /// Reads a timestamp of `i64` nanoseconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(SystemTime), otherwise Err(io::Error) as `read_system_time`.
+    ///
+    fn read_unix_timestamp_nanos<TEndian>(&mut self) -> io::Result<SystemTime>
+        where Self: Sized,
+              TEndian: Endian {
+        self.read_system_time::<TEndian, i64>(TimestampUnit::Nanos)
+    }
+
+    /// Reads a Windows `FILETIME` of `u64` 100-nanosecond intervals since 1601-01-01.
+    ///
+    /// `FILETIME` is stored little-endian by Windows, but `TEndian` is chosen for the formats
+    /// converting it.
+    ///
+    /// # Errors
+    ///
+    /// If the timestamp is out of the range of `SystemTime`, returns Err(io::Error) of
+    /// `InvalidData`. If reading fails, returns Err(io::Error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use mm_binary_io::endian::LittleEndian;
+    /// use mm_binary_io::timestamp::{SystemTimeRead, SystemTimeWrite};
+    ///
+    /// // 1970-01-01 00:00:00 is 116444736000000000 intervals after 1601.
+    /// let data = 116_444_736_000_000_000_u64.to_le_bytes();
+    /// let mut reader = io::Cursor::new(data);
+    /// assert_eq!(UNIX_EPOCH, reader.read_filetime::<LittleEndian>().unwrap());
+    ///
+    /// let value = UNIX_EPOCH + Duration::new(1_600_000_000, 123_456_789);
+    /// let mut writer = vec![];
+    /// writer.write_filetime::<LittleEndian>(&value).unwrap();
+    /// assert_eq!(132_444_736_001_234_567_u64.to_le_bytes().to_vec(), writer);
+    ///
+    /// let before_1601 = UNIX_EPOCH - Duration::from_secs(11_644_473_601);
+    /// assert_eq!(io::ErrorKind::InvalidInput, writer.write_filetime::<LittleEndian>(&before_1601).unwrap_err().kind());
+    ///
+    /// ```
+    ///
+    fn read_filetime<TEndian>(&mut self) -> io::Result<SystemTime>
+        where Self: Sized,
+              TEndian: Endian {
+        let intervals = self.read_integer::<TEndian, u64>()?;
+        let nanos = intervals as i128 * FILETIME_INTERVAL_NANOS - FILETIME_EPOCH_OFFSET_NANOS;
+        system_time_from_nanos(nanos)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "timestamp is out of range"))
+    }
+}
+
+impl<T> SystemTimeRead for T
+    where T: io::Read {}
+
+/// Provides the features to write `std::time::SystemTime`.
+pub trait SystemTimeWrite: io::Write {
+    /// Writes the `value` as `TInt` counting `unit` since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// If the timestamp does not fit in `TInt`, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    fn write_system_time<TEndian, TInt>(&mut self, value: &SystemTime, unit: TimestampUnit) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian,
+              TInt: WriteInteger + TryFrom<i128> {
+        write_nanos::<TEndian, TInt, _>(self, system_time_to_nanos(value), unit)
+    }
+
+    /// Writes the `value` as `i64` seconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error) as `write_system_time`.
+    ///
+    fn write_unix_timestamp_secs<TEndian>(&mut self, value: &SystemTime) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian {
+        self.write_system_time::<TEndian, i64>(value, TimestampUnit::Seconds)
+    }
+
+    /// Writes the `value` as `i64` milliseconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error) as `write_system_time`.
+    ///
+    fn write_unix_timestamp_millis<TEndian>(&mut self, value: &SystemTime) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian {
+        self.write_system_time::<TEndian, i64>(value, TimestampUnit::Millis)
+    }
+
+    /// Writes the `value` as `i64` nanoseconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// If the value does not fit in `i64` (around the years 1677 to 2262), returns
+    /// Err(io::Error) of `InvalidInput`. If writing fails, returns Err(io::Error).
+    ///
+    fn write_unix_timestamp_nanos<TEndian>(&mut self, value: &SystemTime) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian {
+        self.write_system_time::<TEndian, i64>(value, TimestampUnit::Nanos)
+    }
+
+    /// Writes the `value` as a Windows `FILETIME` of `u64` 100-nanosecond intervals since
+    /// 1601-01-01.
+    ///
+    /// # Errors
+    ///
+    /// If the value is before 1601 or does not fit in `u64`, returns Err(io::Error) of
+    /// `InvalidInput`. If writing fails, returns Err(io::Error).
+    ///
+    fn write_filetime<TEndian>(&mut self, value: &SystemTime) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian {
+        let nanos = system_time_to_nanos(value) + FILETIME_EPOCH_OFFSET_NANOS;
+        let intervals = u64::try_from(nanos.div_euclid(FILETIME_INTERVAL_NANOS))
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "timestamp does not fit in FILETIME"))?;
+        self.write_integer::<TEndian, _>(intervals)
+    }
+}
+
+impl<T> SystemTimeWrite for T
+    where T: io::Write {}
+
+/// A date and time of the packed 16-bit MS-DOS date and time fields.
+///
+/// The date holds the year since 1980 in the bits 9-15, the month in 5-8 and the day in 0-4.
+/// The time holds the hour in the bits 11-15, the minute in 5-10 and the second divided by 2 in
+/// 0-4. The fields are decoded as they are, so a value read from a broken file may be invalid.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::timestamp::DosDateTime;
+///
+/// let value = DosDateTime::from_dos(0x5A8E, 0x6B3D);
+/// assert_eq!(DosDateTime { year: 2025, month: 4, day: 14, hour: 13, minute: 25, second: 58 }, value);
+/// assert_eq!(Some((0x5A8E, 0x6B3D)), value.to_dos());
+///
+/// // The seconds are stored in the units of 2 seconds.
+/// let odd = DosDateTime { second: 59, ..value };
+/// assert_eq!(Some((0x5A8E, 0x6B3D)), odd.to_dos());
+///
+/// // The years before 1980 can not be stored.
+/// assert_eq!(None, DosDateTime { year: 1979, ..value }.to_dos());
+///
+/// ```
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DosDateTime {
+    /// The year, from 1980 to 2107.
+    pub year: u16,
+    /// The month, from 1 to 12.
+    pub month: u8,
+    /// The day of the month, from 1 to 31.
+    pub day: u8,
+    /// The hour, from 0 to 23.
+    pub hour: u8,
+    /// The minute, from 0 to 59.
+    pub minute: u8,
+    /// The second, from 0 to 59. The stored value has the resolution of 2 seconds.
+    pub second: u8,
+}
+
+impl DosDateTime {
+    /// Decodes the packed `date` and `time` fields.
+    pub fn from_dos(date: u16, time: u16) -> DosDateTime {
+        DosDateTime {
+            year: 1980 + (date >> 9),
+            month: (date >> 5 & 0x0F) as u8,
+            day: (date & 0x1F) as u8,
+            hour: (time >> 11) as u8,
+            minute: (time >> 5 & 0x3F) as u8,
+            second: (time & 0x1F) as u8 * 2,
+        }
+    }
+
+    /// Encodes the packed `(date, time)` fields, truncating the second to even.
+    ///
+    /// Returns None if a field is out of the range, including the years outside 1980-2107.
+    /// The day is not checked against the month.
+    pub fn to_dos(&self) -> Option<(u16, u16)> {
+        if !(1980..=2107).contains(&self.year) || !(1..=12).contains(&self.month) || !(1..=31).contains(&self.day)
+            || self.hour > 23 || self.minute > 59 || self.second > 59 {
+            return None;
+        }
+        let date = (self.year - 1980) << 9 | u16::from(self.month) << 5 | u16::from(self.day);
+        let time = u16::from(self.hour) << 11 | u16::from(self.minute) << 5 | u16::from(self.second / 2);
+        Some((date, time))
+    }
+
+    /// Converts this to `chrono::NaiveDateTime`, or None if it is not a valid date and time.
+    ///
+    /// This is available with the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn to_naive_date_time(&self) -> Option<NaiveDateTime> {
+        NaiveDate::from_ymd_opt(i32::from(self.year), u32::from(self.month), u32::from(self.day))?
+            .and_hms_opt(u32::from(self.hour), u32::from(self.minute), u32::from(self.second))
+    }
+
+    /// Converts `value` to `DosDateTime`, or None if the year is outside 1980-2107.
+    ///
+    /// The fraction of the second and a leap second are dropped. This is available with the
+    /// `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn from_naive_date_time(value: &NaiveDateTime) -> Option<DosDateTime> {
+        if !(1980..=2107).contains(&value.year()) {
+            return None;
+        }
+        Some(DosDateTime {
+            year: value.year() as u16,
+            month: value.month() as u8,
+            day: value.day() as u8,
+            hour: value.hour() as u8,
+            minute: value.minute() as u8,
+            second: value.second().min(59) as u8,
+        })
+    }
+}
+
+/// Provides the features to read `DosDateTime`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::timestamp::{DosDateTime, DosDateTimeRead, DosDateTimeWrite};
+///
+/// let value = DosDateTime { year: 2025, month: 4, day: 14, hour: 13, minute: 25, second: 58 };
+///
+/// let mut writer = vec![];
+/// writer.write_dos_date_time::<LittleEndian>(&value).unwrap();
+/// assert_eq!(vec![0x3D, 0x6B, 0x8E, 0x5A], writer);
+///
+/// let mut reader = io::Cursor::new(writer);
+/// assert_eq!(value, reader.read_dos_date_time::<LittleEndian>().unwrap());
+///
+/// ```
+///
+pub trait DosDateTimeRead: io::Read {
+    /// Reads the 16-bit time field followed by the 16-bit date field, in the order of ZIP and FAT.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(DosDateTime), otherwise Err(io::Error).
+    ///
+    fn read_dos_date_time<TEndian>(&mut self) -> io::Result<DosDateTime>
+        where Self: Sized,
+              TEndian: Endian {
+        let time = self.read_integer::<TEndian, u16>()?;
+        let date = self.read_integer::<TEndian, u16>()?;
+        Ok(DosDateTime::from_dos(date, time))
+    }
+}
+
+impl<T> DosDateTimeRead for T
+    where T: io::Read {}
+
+/// Provides the features to write `DosDateTime`.
+pub trait DosDateTimeWrite: io::Write {
+    /// Writes the 16-bit time field followed by the 16-bit date field, in the order of ZIP and FAT.
+    ///
+    /// # Errors
+    ///
+    /// If a field of the `value` is out of the range, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    fn write_dos_date_time<TEndian>(&mut self, value: &DosDateTime) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian {
+        let (date, time) = value.to_dos()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "date and time do not fit in the MS-DOS format"))?;
+        self.write_integer::<TEndian, _>(time)?;
+        self.write_integer::<TEndian, _>(date)
+    }
+}
+
+impl<T> DosDateTimeWrite for T
+    where T: io::Write {}
+
+/// A 64-bit NTP timestamp of the 32-bit seconds and the 32-bit fraction of a second.
+///
+/// The seconds count from 1900-01-01 in era 0, and wrap to 0 on 2036-02-07 when era 1 starts.
+/// The era itself is not stored, so converting to `SystemTime` takes it or a time near the value.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use mm_binary_io::timestamp::NtpTimestamp;
+///
+/// let value = UNIX_EPOCH + Duration::new(1_600_000_000, 500_000_000);
+/// let ntp = NtpTimestamp::from_system_time(&value);
+/// assert_eq!(NtpTimestamp { seconds: 3_808_988_800, fraction: 0x8000_0000 }, ntp);
+/// assert_eq!(Some(value), ntp.to_system_time(0));
+///
+/// // A time after 2036 wraps to era 1, and is recovered from a time near it.
+/// let later = UNIX_EPOCH + Duration::from_secs(2_100_000_000);
+/// let ntp = NtpTimestamp::from_system_time(&later);
+/// assert_eq!(2_100_000_000 + 2_208_988_800 - (1_u64 << 32), u64::from(ntp.seconds));
+/// assert_eq!(Some(later), ntp.to_system_time_near(&(later - Duration::from_secs(1_000_000))));
+///
+/// assert_eq!(Duration::from_millis(1_250), NtpTimestamp::from_duration(Duration::from_millis(1_250)).unwrap().to_duration());
+///
+/// ```
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NtpTimestamp {
+    /// The seconds since the start of the era.
+    pub seconds: u32,
+    /// The fraction of a second in the units of 2^-32 seconds.
+    pub fraction: u32,
+}
+
+impl NtpTimestamp {
+    /// Creates a new `NtpTimestamp` of the 64-bit value, whose high 32 bits are the seconds.
+    pub fn from_bits(bits: u64) -> NtpTimestamp {
+        NtpTimestamp { seconds: (bits >> 32) as u32, fraction: bits as u32 }
+    }
+
+    /// Returns the 64-bit value, whose high 32 bits are the seconds.
+    pub fn to_bits(&self) -> u64 {
+        u64::from(self.seconds) << 32 | u64::from(self.fraction)
+    }
+
+    /// Converts `duration` to `NtpTimestamp`, or None if it is 2^32 seconds or longer.
+    ///
+    /// The fraction is rounded up, so that `to_duration` returns the same nanoseconds.
+    pub fn from_duration(duration: Duration) -> Option<NtpTimestamp> {
+        let seconds = u32::try_from(duration.as_secs()).ok()?;
+        Some(NtpTimestamp { seconds, fraction: fraction_from_nanos(duration.subsec_nanos()) })
+    }
+
+    /// Converts this to `Duration` since the start of the era, truncating to nanoseconds.
+    pub fn to_duration(&self) -> Duration {
+        Duration::new(u64::from(self.seconds), nanos_from_fraction(self.fraction))
+    }
+
+    /// Converts `value` to `NtpTimestamp`, dropping the era.
+    ///
+    /// The fraction is rounded up, so that `to_system_time` returns the same nanoseconds.
+    pub fn from_system_time(value: &SystemTime) -> NtpTimestamp {
+        let nanos = system_time_to_nanos(value);
+        let seconds = (nanos.div_euclid(1_000_000_000) + NTP_EPOCH_OFFSET_SECS).rem_euclid(NTP_ERA_SECS);
+        NtpTimestamp {
+            seconds: seconds as u32,
+            fraction: fraction_from_nanos(nanos.rem_euclid(1_000_000_000) as u32),
+        }
+    }
+
+    /// Converts this to `SystemTime` in `era`, or None if it is out of the range of `SystemTime`.
+    pub fn to_system_time(&self, era: i32) -> Option<SystemTime> {
+        let seconds = i128::from(era) * NTP_ERA_SECS + i128::from(self.seconds) - NTP_EPOCH_OFFSET_SECS;
+        system_time_from_nanos(seconds * 1_000_000_000 + i128::from(nanos_from_fraction(self.fraction)))
+    }
+
+    /// Converts this to `SystemTime` in the era which makes it the nearest to `pivot`, or None
+    /// if it is out of the range of `SystemTime`.
+    ///
+    /// The result is within about 68 years of `pivot`.
+    pub fn to_system_time_near(&self, pivot: &SystemTime) -> Option<SystemTime> {
+        let pivot = system_time_to_nanos(pivot).div_euclid(1_000_000_000) + NTP_EPOCH_OFFSET_SECS;
+        let era = (pivot - i128::from(self.seconds) + NTP_ERA_SECS / 2).div_euclid(NTP_ERA_SECS);
+        self.to_system_time(i32::try_from(era).ok()?)
+    }
+
+    /// Returns the era by the rule of RFC 4330: era 1 if the highest bit of the seconds is 0,
+    /// which places the value between 1968 and 2104.
+    pub fn rfc4330_era(&self) -> i32 {
+        if self.seconds & 0x8000_0000 != 0 { 0 } else { 1 }
+    }
+}
+
+fn fraction_from_nanos(nanos: u32) -> u32 {
+    ((u64::from(nanos) << 32).div_ceil(1_000_000_000)) as u32
+}
+
+fn nanos_from_fraction(fraction: u32) -> u32 {
+    ((u64::from(fraction) * 1_000_000_000) >> 32) as u32
+}
+
+/// Provides the features to read the NTP timestamps.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::timestamp::{NtpRead, NtpTimestamp, NtpWrite};
+///
+/// let value = UNIX_EPOCH + Duration::new(1_600_000_000, 250_000_000);
+///
+/// let mut writer = vec![];
+/// writer.write_ntp_system_time::<BigEndian>(&value).unwrap();
+/// assert_eq!(vec![0xE3, 0x08, 0x8E, 0x80, 0x40, 0x00, 0x00, 0x00], writer);
+///
+/// let mut reader = io::Cursor::new(writer);
+/// assert_eq!(value, reader.read_ntp_system_time::<BigEndian>().unwrap());
+///
+/// let mut reader = io::Cursor::new(vec![0x00, 0x00, 0x00, 0x01, 0x80, 0x00, 0x00, 0x00]);
+/// assert_eq!(NtpTimestamp { seconds: 1, fraction: 0x8000_0000 }, reader.read_ntp_timestamp::<BigEndian>().unwrap());
+///
+/// ```
+///
+pub trait NtpRead: io::Read {
+    /// Reads a 64-bit NTP timestamp.
+    ///
+    /// NTP itself stores it big-endian, but `TEndian` is chosen for the formats converting it.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(NtpTimestamp), otherwise Err(io::Error).
+    ///
+    fn read_ntp_timestamp<TEndian>(&mut self) -> io::Result<NtpTimestamp>
+        where Self: Sized,
+              TEndian: Endian {
+        Ok(NtpTimestamp::from_bits(self.read_integer::<TEndian, u64>()?))
+    }
+
+    /// Reads a 64-bit NTP timestamp as `SystemTime`, in the era of `NtpTimestamp::rfc4330_era`.
+    ///
+    /// # Errors
+    ///
+    /// If the timestamp is out of the range of `SystemTime`, returns Err(io::Error) of
+    /// `InvalidData`. If reading fails, returns Err(io::Error).
+    ///
+    fn read_ntp_system_time<TEndian>(&mut self) -> io::Result<SystemTime>
+        where Self: Sized,
+              TEndian: Endian {
+        let timestamp = self.read_ntp_timestamp::<TEndian>()?;
+        timestamp.to_system_time(timestamp.rfc4330_era())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "timestamp is out of range"))
+    }
+}
+
+impl<T> NtpRead for T
+    where T: io::Read {}
+
+/// Provides the features to write the NTP timestamps.
+pub trait NtpWrite: io::Write {
+    /// Writes a 64-bit NTP timestamp.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    fn write_ntp_timestamp<TEndian>(&mut self, value: &NtpTimestamp) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian {
+        self.write_integer::<TEndian, _>(value.to_bits())
+    }
+
+    /// Writes the `value` as a 64-bit NTP timestamp, dropping the era.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    fn write_ntp_system_time<TEndian>(&mut self, value: &SystemTime) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian {
+        self.write_ntp_timestamp::<TEndian>(&NtpTimestamp::from_system_time(value))
+    }
+}
+
+impl<T> NtpWrite for T
+    where T: io::Write {}
+
+/// Provides the features to read `chrono::DateTime<Utc>`.
+///
+/// These are available with the `chrono` feature.
+///
+/// # Examples
+///
+/// ```
+/// extern crate chrono;
+/// extern crate mm_binary_io;
+///
+/// use std::io;
+/// use mm_binary_io::endian::{BigEndian, LittleEndian};
+/// use mm_binary_io::timestamp::{ChronoRead, ChronoWrite, TimestampUnit};
+///
+/// # fn main() {
+/// let value = chrono::DateTime::from_timestamp(1_600_000_000, 123_456_789).unwrap();
+///
+/// let mut writer = vec![];
+/// writer.write_chrono_timestamp::<BigEndian, u32>(&value, TimestampUnit::Seconds).unwrap();
+/// writer.write_chrono_timestamp::<LittleEndian, i64>(&value, TimestampUnit::Millis).unwrap();
+/// assert_eq!(vec![0x5F, 0x5E, 0x10, 0x00], writer[..4].to_vec());
+///
+/// let mut reader = io::Cursor::new(writer);
+/// assert_eq!(1_600_000_000, reader.read_chrono_timestamp::<BigEndian, u32>(TimestampUnit::Seconds).unwrap().timestamp());
+/// assert_eq!(1_600_000_000_123, reader.read_chrono_timestamp::<LittleEndian, i64>(TimestampUnit::Millis).unwrap().timestamp_millis());
+/// # }
+///
+/// ```
+///
+#[cfg(feature = "chrono")]
+pub trait ChronoRead: io::Read {
+    /// Reads a timestamp of `TInt` counting `unit` since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// If the timestamp is out of the range of `DateTime<Utc>`, returns Err(io::Error) of
+    /// `InvalidData`. If reading fails, returns Err(io::Error).
+    ///
+    fn read_chrono_timestamp<TEndian, TInt>(&mut self, unit: TimestampUnit) -> io::Result<DateTime<Utc>>
+        where Self: Sized,
+              TEndian: Endian,
+              TInt: ReadInteger<OutputType=TInt> + Into<i128> {
+        let nanos = read_nanos::<TEndian, TInt, _>(self, unit)?;
+        let secs = i64::try_from(nanos.div_euclid(1_000_000_000)).ok();
+        secs.and_then(|secs| DateTime::from_timestamp(secs, nanos.rem_euclid(1_000_000_000) as u32))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "timestamp is out of range"))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<T> ChronoRead for T
+    where T: io::Read {}
+
+/// Provides the features to write `chrono::DateTime<Utc>`.
+///
+/// These are available with the `chrono` feature.
+#[cfg(feature = "chrono")]
+pub trait ChronoWrite: io::Write {
+    /// Writes the `value` as `TInt` counting `unit` since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// If the timestamp does not fit in `TInt`, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    fn write_chrono_timestamp<TEndian, TInt>(&mut self, value: &DateTime<Utc>, unit: TimestampUnit) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian,
+              TInt: WriteInteger + TryFrom<i128> {
+        let nanos = i128::from(value.timestamp()) * 1_000_000_000 + i128::from(value.timestamp_subsec_nanos());
+        write_nanos::<TEndian, TInt, _>(self, nanos, unit)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<T> ChronoWrite for T
+    where T: io::Write {}
+
+/// Provides the features to read `time::OffsetDateTime`.
+///
+/// These are available with the `time` feature. The values are read in UTC.
+///
+/// # Examples
+///
+/// ```
+/// extern crate time;
+/// extern crate mm_binary_io;
+///
+/// use std::io;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::timestamp::{TimeRead, TimeWrite, TimestampUnit};
+///
+/// # fn main() {
+/// let value = time::OffsetDateTime::from_unix_timestamp_nanos(-1_500).unwrap();
+///
+/// let mut writer = vec![];
+/// writer.write_offset_date_time::<BigEndian, i64>(&value, TimestampUnit::Micros).unwrap();
+/// assert!(writer.write_offset_date_time::<BigEndian, u32>(&value, TimestampUnit::Seconds).is_err());
+///
+/// let mut reader = io::Cursor::new(writer);
+/// let value = reader.read_offset_date_time::<BigEndian, i64>(TimestampUnit::Micros).unwrap();
+/// assert_eq!(-2_000, value.unix_timestamp_nanos());
+/// # }
+///
+/// ```
+///
+#[cfg(feature = "time")]
+pub trait TimeRead: io::Read {
+    /// Reads a timestamp of `TInt` counting `unit` since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// If the timestamp is out of the range of `OffsetDateTime`, returns Err(io::Error) of
+    /// `InvalidData`. If reading fails, returns Err(io::Error).
+    ///
+    fn read_offset_date_time<TEndian, TInt>(&mut self, unit: TimestampUnit) -> io::Result<OffsetDateTime>
+        where Self: Sized,
+              TEndian: Endian,
+              TInt: ReadInteger<OutputType=TInt> + Into<i128> {
+        let nanos = read_nanos::<TEndian, TInt, _>(self, unit)?;
+        OffsetDateTime::from_unix_timestamp_nanos(nanos)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "timestamp is out of range"))
+    }
+}
+
+#[cfg(feature = "time")]
+impl<T> TimeRead for T
+    where T: io::Read {}
+
+/// Provides the features to write `time::OffsetDateTime`.
+///
+/// These are available with the `time` feature.
+#[cfg(feature = "time")]
+pub trait TimeWrite: io::Write {
+    /// Writes the `value` as `TInt` counting `unit` since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// If the timestamp does not fit in `TInt`, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    fn write_offset_date_time<TEndian, TInt>(&mut self, value: &OffsetDateTime, unit: TimestampUnit) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian,
+              TInt: WriteInteger + TryFrom<i128> {
+        write_nanos::<TEndian, TInt, _>(self, value.unix_timestamp_nanos(), unit)
+    }
+}
+
+#[cfg(feature = "time")]
+impl<T> TimeWrite for T
+    where T: io::Write {}
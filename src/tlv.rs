@@ -0,0 +1,364 @@
+//! Provides the features to read and write TLV (type-length-value) records.
+//!
+//! The widths of the tag and the length are configured by `TlvFormat`, and the fixed-width
+//! fields are stored in `TEndian`.
+
+use std::io;
+use std::io::Read;
+use std::marker::PhantomData;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::Endian;
+use varint;
+
+/// The encoding of a tag or a length field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Width {
+    /// 1 byte.
+    U8,
+    /// 2 bytes.
+    U16,
+    /// 4 bytes.
+    U32,
+    /// 8 bytes.
+    U64,
+    /// Unsigned LEB128 variable-length integer.
+    Varint,
+}
+
+impl Width {
+    /// Returns the maximum value which can be stored.
+    pub fn max_value(self) -> u64 {
+        match self {
+            Width::U8 => u8::MAX as u64,
+            Width::U16 => u16::MAX as u64,
+            Width::U32 => u32::MAX as u64,
+            Width::U64 | Width::Varint => u64::MAX,
+        }
+    }
+
+    /// Reads a value of this width.
+    pub fn read<TEndian, TRead>(self, reader: &mut TRead) -> io::Result<u64>
+        where TEndian: Endian,
+              TRead: io::Read {
+        match self {
+            Width::U8 => reader.read_integer::<TEndian, u8>().map(|x| x as u64),
+            Width::U16 => reader.read_integer::<TEndian, u16>().map(|x| x as u64),
+            Width::U32 => reader.read_integer::<TEndian, u32>().map(|x| x as u64),
+            Width::U64 => reader.read_integer::<TEndian, u64>(),
+            Width::Varint => varint::read_u64(reader),
+        }
+    }
+
+    /// Writes `value` in this width.
+    ///
+    /// # Errors
+    ///
+    /// If `value` exceeds `max_value()`, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    pub fn write<TEndian, TWrite>(self, writer: &mut TWrite, value: u64) -> io::Result<()>
+        where TEndian: Endian,
+              TWrite: io::Write {
+        if value > self.max_value() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "value does not fit in the field width"));
+        }
+        match self {
+            Width::U8 => writer.write_integer::<TEndian, _>(value as u8),
+            Width::U16 => writer.write_integer::<TEndian, _>(value as u16),
+            Width::U32 => writer.write_integer::<TEndian, _>(value as u32),
+            Width::U64 => writer.write_integer::<TEndian, _>(value),
+            Width::Varint => varint::write_u64(writer, value),
+        }
+    }
+}
+
+/// A TLV record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tlv {
+    /// The tag (type).
+    pub tag: u64,
+    /// The value.
+    pub value: Vec<u8>,
+}
+
+impl Tlv {
+    /// Returns a reader which iterates the records nested in the value.
+    pub fn children<TEndian>(&self, format: TlvFormat<TEndian>) -> TlvReader<io::Cursor<&[u8]>, TEndian>
+        where TEndian: Endian {
+        TlvReader::new(io::Cursor::new(&self.value[..]), format)
+    }
+}
+
+/// The layout of TLV records.
+pub struct TlvFormat<TEndian>
+    where TEndian: Endian {
+    tag_width: Width,
+    length_width: Width,
+    _endian: PhantomData<TEndian>,
+}
+
+impl<TEndian> TlvFormat<TEndian>
+    where TEndian: Endian {
+    /// Creates a new `TlvFormat`.
+    pub fn new(tag_width: Width, length_width: Width) -> TlvFormat<TEndian> {
+        TlvFormat {
+            tag_width,
+            length_width,
+            _endian: PhantomData,
+        }
+    }
+
+    /// Returns the width of the tag.
+    pub fn tag_width(&self) -> Width {
+        self.tag_width
+    }
+
+    /// Returns the width of the length.
+    pub fn length_width(&self) -> Width {
+        self.length_width
+    }
+
+    /// Reads the tag and the length of a record.
+    ///
+    /// # Errors
+    ///
+    /// If the stream ends cleanly before the record, returns Ok(None).
+    /// If the stream ends in the middle of the header, returns Err(io::Error) of `UnexpectedEof`.
+    ///
+    pub fn read_header<TRead>(&self, reader: &mut TRead) -> io::Result<Option<(u64, u64)>>
+        where TRead: io::Read {
+        let mut first = [0_u8; 1];
+        loop {
+            match reader.read(&mut first) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let mut reader = io::Cursor::new(first).chain(reader);
+        let tag = self.tag_width.read::<TEndian, _>(&mut reader)?;
+        let len = self.length_width.read::<TEndian, _>(&mut reader)?;
+        Ok(Some((tag, len)))
+    }
+
+    /// Writes the tag and the length of a record.
+    pub fn write_header<TWrite>(&self, writer: &mut TWrite, tag: u64, len: u64) -> io::Result<()>
+        where TWrite: io::Write {
+        self.tag_width.write::<TEndian, _>(writer, tag)?;
+        self.length_width.write::<TEndian, _>(writer, len)
+    }
+}
+
+impl<TEndian> Clone for TlvFormat<TEndian>
+    where TEndian: Endian {
+    fn clone(&self) -> TlvFormat<TEndian> {
+        *self
+    }
+}
+
+impl<TEndian> Copy for TlvFormat<TEndian>
+    where TEndian: Endian {}
+
+/// Iterates TLV records in a stream.
+///
+/// The iteration stops at the clean end of the stream. A record cut off in the middle is reported
+/// as Err(io::Error) of `UnexpectedEof`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::tlv::{Tlv, TlvFormat, TlvReader, Width};
+///
+/// let format = TlvFormat::<BigEndian>::new(Width::U8, Width::U16);
+/// let data = vec![
+///     0x01, 0x00, 0x02, 0xAB, 0xCD,
+///     0x02, 0x00, 0x07,
+///           0x03, 0x00, 0x01, 0xEF,
+///           0x04, 0x00, 0x00];
+///
+/// let reader = TlvReader::new(io::Cursor::new(data), format);
+/// let records = reader.collect::<io::Result<Vec<_>>>().unwrap();
+///
+/// assert_eq!(Tlv { tag: 1, value: vec![0xAB, 0xCD] }, records[0]);
+/// assert_eq!(2, records[1].tag);
+///
+/// let children = records[1].children(format).collect::<io::Result<Vec<_>>>().unwrap();
+/// assert_eq!(vec![Tlv { tag: 3, value: vec![0xEF] }, Tlv { tag: 4, value: vec![] }], children);
+///
+/// ```
+///
+pub struct TlvReader<TRead, TEndian>
+    where TRead: io::Read,
+          TEndian: Endian {
+    reader: TRead,
+    format: TlvFormat<TEndian>,
+    finished: bool,
+}
+
+impl<TRead, TEndian> TlvReader<TRead, TEndian>
+    where TRead: io::Read,
+          TEndian: Endian {
+    /// Creates a new `TlvReader`.
+    pub fn new(reader: TRead, format: TlvFormat<TEndian>) -> TlvReader<TRead, TEndian> {
+        TlvReader {
+            reader,
+            format,
+            finished: false,
+        }
+    }
+
+    /// Reads the next record.
+    ///
+    /// # Errors
+    ///
+    /// If the stream ends cleanly before the record, returns Ok(None).
+    /// If the record is cut off, returns Err(io::Error) of `UnexpectedEof`.
+    ///
+    pub fn read_record(&mut self) -> io::Result<Option<Tlv>> {
+        let (tag, len) = match self.format.read_header(&mut self.reader)? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        trace_event!(TRACE, tag, len, "read TLV record");
+        let mut value = vec![];
+        (&mut self.reader).take(len).read_to_end(&mut value)?;
+        if (value.len() as u64) < len {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "TLV value is cut off"));
+        }
+        Ok(Some(Tlv { tag, value }))
+    }
+
+    /// Unwraps this `TlvReader`, returning the underlying reader.
+    pub fn into_inner(self) -> TRead {
+        self.reader
+    }
+}
+
+impl<TRead, TEndian> Iterator for TlvReader<TRead, TEndian>
+    where TRead: io::Read,
+          TEndian: Endian {
+    type Item = io::Result<Tlv>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.read_record() {
+            Ok(Some(record)) => Some(Ok(record)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Writes TLV records, filling the lengths of nested records automatically.
+///
+/// The records between `begin` and `end` are buffered until `end` is called,
+/// then written as the value of the record opened by `begin`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::tlv::{TlvFormat, TlvWriter, Width};
+///
+/// let format = TlvFormat::<LittleEndian>::new(Width::U16, Width::Varint);
+/// let mut writer = TlvWriter::new(vec![], format);
+///
+/// writer.write_record(0x0001, &[0xAB]).unwrap();
+/// writer.begin(0x0002);
+/// writer.write_record(0x0003, &[0xCD, 0xEF]).unwrap();
+/// writer.end().unwrap();
+///
+/// assert_eq!(
+///     vec![0x01, 0x00, 0x01, 0xAB,
+///          0x02, 0x00, 0x05,
+///                0x03, 0x00, 0x02, 0xCD, 0xEF],
+///     writer.into_inner().unwrap());
+///
+/// ```
+///
+pub struct TlvWriter<TWrite, TEndian>
+    where TWrite: io::Write,
+          TEndian: Endian {
+    writer: TWrite,
+    format: TlvFormat<TEndian>,
+    open: Vec<(u64, Vec<u8>)>,
+}
+
+impl<TWrite, TEndian> TlvWriter<TWrite, TEndian>
+    where TWrite: io::Write,
+          TEndian: Endian {
+    /// Creates a new `TlvWriter`.
+    pub fn new(writer: TWrite, format: TlvFormat<TEndian>) -> TlvWriter<TWrite, TEndian> {
+        TlvWriter {
+            writer,
+            format,
+            open: vec![],
+        }
+    }
+
+    /// Writes a record.
+    pub fn write_record(&mut self, tag: u64, value: &[u8]) -> io::Result<()> {
+        let format = self.format;
+        match self.open.last_mut() {
+            Some(&mut (_, ref mut buffer)) => write_record(buffer, format, tag, value),
+            None => write_record(&mut self.writer, format, tag, value),
+        }
+    }
+
+    /// Opens a record whose value is the records written until the matching `end`.
+    pub fn begin(&mut self, tag: u64) {
+        self.open.push((tag, vec![]));
+    }
+
+    /// Closes the record opened by the last `begin` and writes it.
+    ///
+    /// # Errors
+    ///
+    /// If no record is open, returns Err(io::Error) of `InvalidInput`.
+    /// If writing fails, returns Err(io::Error).
+    ///
+    pub fn end(&mut self) -> io::Result<()> {
+        match self.open.pop() {
+            Some((tag, value)) => self.write_record(tag, &value),
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "no TLV record is open")),
+        }
+    }
+
+    /// Returns the number of the records opened by `begin` and not closed yet.
+    pub fn depth(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Unwraps this `TlvWriter`, returning the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// If a record is still open, returns Err(io::Error) of `InvalidInput`.
+    ///
+    pub fn into_inner(self) -> io::Result<TWrite> {
+        if !self.open.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "a TLV record is still open"));
+        }
+        Ok(self.writer)
+    }
+}
+
+fn write_record<TWrite, TEndian>(writer: &mut TWrite, format: TlvFormat<TEndian>, tag: u64, value: &[u8]) -> io::Result<()>
+    where TWrite: io::Write,
+          TEndian: Endian {
+    format.write_header(writer, tag, value.len() as u64)?;
+    writer.write_all(value)
+}
@@ -0,0 +1,87 @@
+//! Provides the features to convert integers to bytes without allocation.
+
+use endian::Endian;
+
+/// Converts an integer to bytes, the counterpart of `FromBytes`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::endian::{BigEndian, LittleEndian};
+/// use mm_binary_io::from_bytes::FromBytes;
+/// use mm_binary_io::to_bytes::ToBytes;
+///
+/// let mut buf = [0_u8; 4];
+/// 0x12345678_u32.to_bytes::<BigEndian>(&mut buf);
+/// assert_eq!([0x12, 0x34, 0x56, 0x78], buf);
+///
+/// (-2_i16).to_bytes::<LittleEndian>(&mut buf);
+/// assert_eq!(-2, i16::from_bytes::<LittleEndian>(&buf));
+///
+/// ```
+///
+/// # Panics
+///
+/// If `destination` is shorter than the integer, the functions panic.
+///
+pub trait ToBytes {
+    /// Writes the integer to the head of `destination`.
+    fn to_bytes<TEndian>(&self, destination: &mut [u8])
+        where TEndian: Endian;
+}
+
+impl ToBytes for u8 {
+    fn to_bytes<TEndian>(&self, destination: &mut [u8])
+        where TEndian: Endian {
+        TEndian::u8_to_bytes(*self, destination)
+    }
+}
+
+impl ToBytes for i8 {
+    fn to_bytes<TEndian>(&self, destination: &mut [u8])
+        where TEndian: Endian {
+        TEndian::i8_to_bytes(*self, destination)
+    }
+}
+
+impl ToBytes for u16 {
+    fn to_bytes<TEndian>(&self, destination: &mut [u8])
+        where TEndian: Endian {
+        TEndian::u16_to_bytes(*self, destination)
+    }
+}
+
+impl ToBytes for i16 {
+    fn to_bytes<TEndian>(&self, destination: &mut [u8])
+        where TEndian: Endian {
+        TEndian::i16_to_bytes(*self, destination)
+    }
+}
+
+impl ToBytes for u32 {
+    fn to_bytes<TEndian>(&self, destination: &mut [u8])
+        where TEndian: Endian {
+        TEndian::u32_to_bytes(*self, destination)
+    }
+}
+
+impl ToBytes for i32 {
+    fn to_bytes<TEndian>(&self, destination: &mut [u8])
+        where TEndian: Endian {
+        TEndian::i32_to_bytes(*self, destination)
+    }
+}
+
+impl ToBytes for u64 {
+    fn to_bytes<TEndian>(&self, destination: &mut [u8])
+        where TEndian: Endian {
+        TEndian::u64_to_bytes(*self, destination)
+    }
+}
+
+impl ToBytes for i64 {
+    fn to_bytes<TEndian>(&self, destination: &mut [u8])
+        where TEndian: Endian {
+        TEndian::i64_to_bytes(*self, destination)
+    }
+}
@@ -0,0 +1,257 @@
+//! Provides the reader and the writer applying a byte transform on the fly, such as the
+//! repeating-key XOR obfuscation of game data files.
+//!
+//! The transform receives the stream offset of the bytes, so that it gives the same result for
+//! the same bytes however the reads and the writes are split, and after a seek.
+
+use std::io;
+
+use scratch;
+
+/// A transform of the bytes at a stream offset.
+///
+/// The result must depend only on the offset and the bytes, as a write may be transformed again
+/// when the underlying writer accepts only a part of it. This is implemented for the closures of
+/// `FnMut(u64, &mut [u8])`.
+pub trait ByteTransform {
+    /// Transforms `bytes` in place, whose first byte is at `offset` of the stream.
+    fn transform(&mut self, offset: u64, bytes: &mut [u8]);
+}
+
+impl<F> ByteTransform for F
+    where F: FnMut(u64, &mut [u8]) {
+    fn transform(&mut self, offset: u64, bytes: &mut [u8]) {
+        self(offset, bytes)
+    }
+}
+
+/// The XOR of a repeating key, which is its own inverse.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::transform::{ByteTransform, XorKey};
+///
+/// let mut key = XorKey::new(b"\x01\x02\x03");
+/// let mut bytes = [0x10, 0x10, 0x10, 0x10];
+/// key.transform(1, &mut bytes);
+/// assert_eq!([0x12, 0x13, 0x11, 0x12], bytes);
+///
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XorKey {
+    key: Vec<u8>,
+}
+
+impl XorKey {
+    /// Creates a new `XorKey` of `key`, whose first byte applies at the offset 0.
+    ///
+    /// # Panics
+    ///
+    /// If `key` is empty, the function panics.
+    ///
+    pub fn new(key: &[u8]) -> XorKey {
+        assert!(!key.is_empty(), "XOR key must not be empty");
+        XorKey { key: key.to_vec() }
+    }
+
+    /// Returns the key.
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+}
+
+impl ByteTransform for XorKey {
+    fn transform(&mut self, offset: u64, bytes: &mut [u8]) {
+        let start = (offset % self.key.len() as u64) as usize;
+        let key = self.key.iter().cycle().skip(start);
+        for (byte, k) in bytes.iter_mut().zip(key) {
+            *byte ^= k;
+        }
+    }
+}
+
+/// Wraps a reader, and transforms the bytes read through it.
+///
+/// The offset starts at 0, and follows the seeks if the underlying reader is `io::Seek`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Seek, SeekFrom};
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::transform::TransformReader;
+///
+/// let data = vec![0x12 ^ 0xAA, 0x34 ^ 0x55, 0x56 ^ 0xAA, 0x78 ^ 0x55];
+/// let mut reader = TransformReader::xor(io::Cursor::new(data), b"\xAA\x55");
+/// assert_eq!(0x1234_5678, reader.read_integer::<BigEndian, u32>().unwrap());
+///
+/// reader.seek(SeekFrom::Start(3)).unwrap();
+/// assert_eq!(0x78, reader.read_integer::<BigEndian, u8>().unwrap());
+///
+/// // A closure is a transform too.
+/// let mut reader = TransformReader::new(&[0x01_u8, 0x01][..], |offset: u64, bytes: &mut [u8]| {
+///     for (i, byte) in bytes.iter_mut().enumerate() {
+///         *byte = byte.wrapping_add((offset + i as u64) as u8);
+///     }
+/// });
+/// assert_eq!(vec![0x01, 0x02], reader.read_byte_array(2).unwrap());
+///
+/// ```
+///
+pub struct TransformReader<TRead, TTransform>
+    where TRead: io::Read,
+          TTransform: ByteTransform {
+    reader: TRead,
+    transform: TTransform,
+    offset: u64,
+}
+
+impl<TRead> TransformReader<TRead, XorKey>
+    where TRead: io::Read {
+    /// Creates a new `TransformReader` of the XOR of the repeating `key`.
+    ///
+    /// # Panics
+    ///
+    /// If `key` is empty, the function panics.
+    ///
+    pub fn xor(reader: TRead, key: &[u8]) -> TransformReader<TRead, XorKey> {
+        TransformReader::new(reader, XorKey::new(key))
+    }
+}
+
+impl<TRead, TTransform> TransformReader<TRead, TTransform>
+    where TRead: io::Read,
+          TTransform: ByteTransform {
+    /// Creates a new `TransformReader` of `transform`.
+    pub fn new(reader: TRead, transform: TTransform) -> TransformReader<TRead, TTransform> {
+        TransformReader { reader, transform, offset: 0 }
+    }
+
+    /// Returns the stream offset passed to the transform.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &TRead {
+        &self.reader
+    }
+
+    /// Unwraps this `TransformReader`, returning the underlying reader and the transform.
+    pub fn into_inner(self) -> (TRead, TTransform) {
+        (self.reader, self.transform)
+    }
+}
+
+impl<TRead, TTransform> io::Read for TransformReader<TRead, TTransform>
+    where TRead: io::Read,
+          TTransform: ByteTransform {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.transform.transform(self.offset, &mut buf[..n]);
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+impl<TRead, TTransform> io::Seek for TransformReader<TRead, TTransform>
+    where TRead: io::Read + io::Seek,
+          TTransform: ByteTransform {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.offset = self.reader.seek(pos)?;
+        Ok(self.offset)
+    }
+}
+
+/// Wraps a writer, and transforms the bytes written through it.
+///
+/// The offset starts at 0, and follows the seeks if the underlying writer is `io::Seek`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::transform::TransformWriter;
+///
+/// let mut writer = TransformWriter::xor(vec![], b"\xFF");
+/// writer.write_integer::<BigEndian, _>(0x1234_u16).unwrap();
+/// assert_eq!(vec![0xED, 0xCB], writer.into_inner().0);
+///
+/// ```
+///
+pub struct TransformWriter<TWrite, TTransform>
+    where TWrite: io::Write,
+          TTransform: ByteTransform {
+    writer: TWrite,
+    transform: TTransform,
+    offset: u64,
+}
+
+impl<TWrite> TransformWriter<TWrite, XorKey>
+    where TWrite: io::Write {
+    /// Creates a new `TransformWriter` of the XOR of the repeating `key`.
+    ///
+    /// # Panics
+    ///
+    /// If `key` is empty, the function panics.
+    ///
+    pub fn xor(writer: TWrite, key: &[u8]) -> TransformWriter<TWrite, XorKey> {
+        TransformWriter::new(writer, XorKey::new(key))
+    }
+}
+
+impl<TWrite, TTransform> TransformWriter<TWrite, TTransform>
+    where TWrite: io::Write,
+          TTransform: ByteTransform {
+    /// Creates a new `TransformWriter` of `transform`.
+    pub fn new(writer: TWrite, transform: TTransform) -> TransformWriter<TWrite, TTransform> {
+        TransformWriter { writer, transform, offset: 0 }
+    }
+
+    /// Returns the stream offset passed to the transform.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &TWrite {
+        &self.writer
+    }
+
+    /// Unwraps this `TransformWriter`, returning the underlying writer and the transform.
+    pub fn into_inner(self) -> (TWrite, TTransform) {
+        (self.writer, self.transform)
+    }
+}
+
+impl<TWrite, TTransform> io::Write for TransformWriter<TWrite, TTransform>
+    where TWrite: io::Write,
+          TTransform: ByteTransform {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = scratch::with_scratch(buf.len(), |staged| {
+            staged.copy_from_slice(buf);
+            self.transform.transform(self.offset, staged);
+            self.writer.write(staged)
+        })?;
+        self.offset += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<TWrite, TTransform> io::Seek for TransformWriter<TWrite, TTransform>
+    where TWrite: io::Write + io::Seek,
+          TTransform: ByteTransform {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.offset = self.writer.seek(pos)?;
+        Ok(self.offset)
+    }
+}
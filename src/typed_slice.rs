@@ -0,0 +1,104 @@
+//! Provides the zero-copy views of byte slices as integer slices.
+//!
+//! If the bytes are in the native endianness and aligned for the integer, such as the numeric
+//! tables of a memory-mapped file, they are borrowed as `&[TInt]` with no per-element work.
+//! Otherwise `view_or_decode` falls back to decoding them.
+
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+use std::mem;
+
+use endian::Endian;
+use from_bytes::FromBytes;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// The integer types which can be viewed from bytes, as every bit pattern is valid for them.
+///
+/// This is sealed, and implemented for the integers from `u8` to `i64`.
+pub trait ViewElement: FromBytes<OutputType=Self> + Copy + private::Sealed {}
+
+macro_rules! impl_view_element {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+            impl ViewElement for $t {}
+        )*
+    }
+}
+
+impl_view_element!(u8, i8, u16, i16, u32, i32, u64, i64);
+
+/// Borrows `bytes` as a slice of `TInt`, without copying.
+///
+/// Returns None unless `TEndian` is the native endianness, `bytes` is aligned for `TInt`, and
+/// its length is a multiple of the size of `TInt`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::endian::{BigEndian, Endian, LittleEndian};
+/// use mm_binary_io::typed_slice;
+///
+/// let values = [0x1234_u16, 0x5678];
+/// let bytes = unsafe { std::slice::from_raw_parts(values.as_ptr() as *const u8, 4) };
+///
+/// if LittleEndian::is_native() {
+///     assert_eq!(Some(&values[..]), typed_slice::try_view::<LittleEndian, u16>(bytes));
+///     assert_eq!(None, typed_slice::try_view::<BigEndian, u16>(bytes));
+/// }
+/// assert_eq!(None, typed_slice::try_view::<LittleEndian, u16>(&bytes[..3]));
+///
+/// ```
+///
+pub fn try_view<TEndian, TInt>(bytes: &[u8]) -> Option<&[TInt]>
+    where TEndian: Endian,
+          TInt: ViewElement {
+    if !TEndian::is_native() || !bytes.len().is_multiple_of(mem::size_of::<TInt>()) {
+        return None;
+    }
+    // `ViewElement` is implemented only for the integers, for which every bit pattern is valid.
+    let (prefix, values, suffix) = unsafe { bytes.align_to::<TInt>() };
+    if prefix.is_empty() && suffix.is_empty() {
+        Some(values)
+    } else {
+        None
+    }
+}
+
+/// Borrows `bytes` as a slice of `TInt` if possible, otherwise decodes them.
+///
+/// Returns None if the length of `bytes` is not a multiple of the size of `TInt`.
+///
+/// This is available with the `std` feature.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::typed_slice;
+///
+/// let bytes = [0x12_u8, 0x34, 0x56, 0x78];
+/// let values = typed_slice::view_or_decode::<BigEndian, u16>(&bytes).unwrap();
+/// assert_eq!(&[0x1234, 0x5678], &values[..]);
+///
+/// assert_eq!(None, typed_slice::view_or_decode::<BigEndian, u16>(&bytes[..3]));
+///
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn view_or_decode<TEndian, TInt>(bytes: &[u8]) -> Option<Cow<'_, [TInt]>>
+    where TEndian: Endian,
+          TInt: ViewElement {
+    let element_size = mem::size_of::<TInt>();
+    if !bytes.len().is_multiple_of(element_size) {
+        return None;
+    }
+    if let Some(values) = try_view::<TEndian, TInt>(bytes) {
+        return Some(Cow::Borrowed(values));
+    }
+    Some(Cow::Owned(bytes.chunks(element_size).map(TInt::from_bytes::<TEndian>).collect()))
+}
@@ -0,0 +1,365 @@
+//! Provides the file reader and writer whose positional IO is backed by io_uring.
+//!
+//! `UringFileRead` and `UringFileWrite` wrap `FileRead` and `FileWrite`, so the sequential IO is
+//! buffered as usual. The batched positional reads and writes are submitted to the ring at once,
+//! and the kernel completes them concurrently, in any order. The short reads and writes are
+//! resubmitted until the buffers are done.
+//!
+//! This module is available with the `io-uring` feature on Linux.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::env;
+//! use std::fs;
+//! use mm_binary_io::binary_read::BinaryRead;
+//! use mm_binary_io::binary_write::BinaryWrite;
+//! use mm_binary_io::endian::BigEndian;
+//! use mm_binary_io::uring::{UringFileRead, UringFileWrite};
+//!
+//! let path = env::temp_dir().join("mm_binary_io_uring_example.bin");
+//!
+//! let mut writer = UringFileWrite::create(&path).unwrap();
+//! writer.write_integer::<BigEndian, _>(0_u32).unwrap();
+//! writer.write_all_batch_at(&[(0, &[0x12, 0x34]), (2, &[0x56, 0x78]), (4, &[0x9A])]).unwrap();
+//! writer.sync_all().unwrap();
+//!
+//! let mut reader = UringFileRead::open(&path).unwrap();
+//! let mut a = [0_u8; 2];
+//! let mut b = [0_u8; 3];
+//! reader.read_exact_batch_at(&mut [(3, &mut a[..]), (0, &mut b[..])]).unwrap();
+//! assert_eq!([0x78, 0x9A], a);
+//! assert_eq!([0x12, 0x34, 0x56], b);
+//!
+//! assert_eq!(0x1234, reader.read_integer::<BigEndian, u16>().unwrap());
+//! assert!(reader.read_exact_batch_at(&mut [(4, &mut a[..])]).is_err());
+//!
+//! fs::remove_file(&path).unwrap();
+//!
+//! ```
+
+use std::fs;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::thread;
+
+use io_uring::{opcode, squeue, types, IoUring};
+
+use file::{FileRead, FileWrite};
+
+/// The default number of the submission queue entries of the ring.
+pub const DEFAULT_RING_ENTRIES: u32 = 64;
+
+/// The user data of the cancellations, which is not an operation index.
+const CANCEL_USER_DATA: u64 = u64::MAX;
+
+/// The buffered file reader whose positional reads are backed by io_uring.
+///
+/// `UringFileRead` implements `io::Read`, `io::BufRead` and `io::Seek` as `FileRead`, so all
+/// `BinaryRead` methods can be used. The positional reads do not change the current position.
+pub struct UringFileRead {
+    reader: FileRead,
+    ring: IoUring,
+}
+
+impl UringFileRead {
+    /// Opens the file at `path` for reading.
+    pub fn open<TPath>(path: TPath) -> io::Result<UringFileRead>
+        where TPath: AsRef<Path> {
+        UringFileRead::from_file(fs::File::open(path)?, DEFAULT_RING_ENTRIES)
+    }
+
+    /// Creates a new `UringFileRead` reading `file` from its current position, with a ring of
+    /// `entries` submission queue entries.
+    ///
+    /// # Errors
+    ///
+    /// If the ring cannot be set up, returns Err(io::Error).
+    ///
+    pub fn from_file(file: fs::File, entries: u32) -> io::Result<UringFileRead> {
+        Ok(UringFileRead {
+            reader: FileRead::from_file(file),
+            ring: IoUring::new(entries)?,
+        })
+    }
+
+    /// Returns the length of the file.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> io::Result<u64> {
+        self.reader.len()
+    }
+
+    /// Returns the current position.
+    pub fn position(&mut self) -> io::Result<u64> {
+        self.reader.position()
+    }
+
+    /// Reads exactly `buf.len()` bytes at `offset` without changing the current position.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    /// If the file ends before `buf` is filled, returns Err(io::Error) of `UnexpectedEof`.
+    ///
+    pub fn read_exact_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.read_exact_batch_at(&mut [(offset, buf)])
+    }
+
+    /// Fills each buffer of `requests` with the bytes at its offset, submitting the reads at once.
+    ///
+    /// The current position is not changed.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error). The contents of the
+    /// buffers are unspecified on error.
+    /// If the file ends before a buffer is filled, returns Err(io::Error) of `UnexpectedEof`.
+    ///
+    pub fn read_exact_batch_at(&mut self, requests: &mut [(u64, &mut [u8])]) -> io::Result<()> {
+        let fd = types::Fd(self.reader.get_ref().as_raw_fd());
+        let lens: Vec<usize> = requests.iter().map(|r| r.1.len()).collect();
+        let eof = || io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer");
+        run_batch(&mut self.ring, &lens, eof, |i, done| {
+            let (offset, ref mut buf) = requests[i];
+            let buf = &mut buf[done..];
+            opcode::Read::new(fd, buf.as_mut_ptr(), chunk_len(buf.len()))
+                .offset(offset + done as u64)
+                .build()
+        })
+    }
+
+    /// Hints the OS that the file will be read sequentially, as `FileRead::advise_sequential`.
+    pub fn advise_sequential(&self) -> io::Result<()> {
+        self.reader.advise_sequential()
+    }
+
+    /// Hints the OS that the file will be read at random offsets, as `FileRead::advise_random`.
+    pub fn advise_random(&self) -> io::Result<()> {
+        self.reader.advise_random()
+    }
+
+    /// Hints the OS that `len` bytes at `offset` will be read soon, as
+    /// `FileRead::advise_willneed`.
+    pub fn advise_willneed(&self, offset: u64, len: u64) -> io::Result<()> {
+        self.reader.advise_willneed(offset, len)
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn get_ref(&self) -> &fs::File {
+        self.reader.get_ref()
+    }
+
+    /// Unwraps this `UringFileRead`, returning the underlying file.
+    ///
+    /// The buffered data is lost, so the position of the file may be ahead of `position()`.
+    pub fn into_inner(self) -> fs::File {
+        self.reader.into_inner()
+    }
+}
+
+impl io::Read for UringFileRead {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl io::BufRead for UringFileRead {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.reader.consume(amt)
+    }
+}
+
+impl io::Seek for UringFileRead {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.reader.seek(pos)
+    }
+}
+
+/// The buffered file writer whose positional writes are backed by io_uring.
+///
+/// `UringFileWrite` implements `io::Write` and `io::Seek` as `FileWrite`, so all `BinaryWrite`
+/// methods can be used. The positional writes do not change the current position.
+pub struct UringFileWrite {
+    writer: FileWrite,
+    ring: IoUring,
+}
+
+impl UringFileWrite {
+    /// Creates a new file at `path` for writing, truncating the existing file.
+    pub fn create<TPath>(path: TPath) -> io::Result<UringFileWrite>
+        where TPath: AsRef<Path> {
+        UringFileWrite::from_file(fs::File::create(path)?, DEFAULT_RING_ENTRIES)
+    }
+
+    /// Creates a new `UringFileWrite` writing `file` from its current position, with a ring of
+    /// `entries` submission queue entries.
+    ///
+    /// # Errors
+    ///
+    /// If the ring cannot be set up, returns Err(io::Error).
+    ///
+    pub fn from_file(file: fs::File, entries: u32) -> io::Result<UringFileWrite> {
+        Ok(UringFileWrite {
+            writer: FileWrite::from_file(file),
+            ring: IoUring::new(entries)?,
+        })
+    }
+
+    /// Returns the current position.
+    pub fn position(&mut self) -> io::Result<u64> {
+        self.writer.position()
+    }
+
+    /// Writes all of `buf` at `offset` without changing the current position.
+    ///
+    /// The buffered data is written before `buf`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    pub fn write_all_at(&mut self, offset: u64, buf: &[u8]) -> io::Result<()> {
+        self.write_all_batch_at(&[(offset, buf)])
+    }
+
+    /// Writes each buffer of `requests` at its offset, submitting the writes at once.
+    ///
+    /// The buffered data is written before the requests, and the current position is not
+    /// changed. The order of the overlapping writes is unspecified.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    pub fn write_all_batch_at(&mut self, requests: &[(u64, &[u8])]) -> io::Result<()> {
+        io::Write::flush(&mut self.writer)?;
+        let fd = types::Fd(self.writer.get_ref().as_raw_fd());
+        let lens: Vec<usize> = requests.iter().map(|r| r.1.len()).collect();
+        let write_zero = || io::Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer");
+        run_batch(&mut self.ring, &lens, write_zero, |i, done| {
+            let (offset, buf) = requests[i];
+            let buf = &buf[done..];
+            opcode::Write::new(fd, buf.as_ptr(), chunk_len(buf.len()))
+                .offset(offset + done as u64)
+                .build()
+        })
+    }
+
+    /// Writes the buffered data and synchronizes the file to the storage.
+    pub fn sync_all(&mut self) -> io::Result<()> {
+        self.writer.sync_all()
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn get_ref(&self) -> &fs::File {
+        self.writer.get_ref()
+    }
+
+    /// Writes the buffered data and unwraps this `UringFileWrite`, returning the underlying file.
+    pub fn into_inner(self) -> io::Result<fs::File> {
+        self.writer.into_inner()
+    }
+}
+
+impl io::Write for UringFileWrite {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl io::Seek for UringFileWrite {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.writer.seek(pos)
+    }
+}
+
+fn chunk_len(len: usize) -> u32 {
+    len.min(u32::MAX as usize) as u32
+}
+
+/// Submits the operations of `lens` bytes built by `build(index, done)`, and waits for them,
+/// resubmitting the short ones with the byte count done so far.
+///
+/// A completion of 0 bytes fails with `eof()`. All operations in flight are completed before
+/// returning, so the buffers are not used by the kernel after the function returns.
+fn run_batch<TEof, TBuild>(ring: &mut IoUring, lens: &[usize], eof: TEof, mut build: TBuild) -> io::Result<()>
+    where TEof: Fn() -> io::Error,
+          TBuild: FnMut(usize, usize) -> squeue::Entry {
+    let mut done = vec![0_usize; lens.len()];
+    let mut pending: Vec<usize> = (0..lens.len()).filter(|&i| lens[i] > 0).collect();
+    let capacity = ring.params().sq_entries() as usize;
+    let mut error = None;
+    while !pending.is_empty() && error.is_none() {
+        let batch: Vec<usize> = pending.drain(..pending.len().min(capacity)).collect();
+        for &i in &batch {
+            let entry = build(i, done[i]).user_data(i as u64);
+            // The buffers outlive the operations, which are all completed below.
+            unsafe { ring.submission().push(&entry) }
+                .expect("the batch does not exceed the submission queue");
+        }
+        let mut outstanding = batch;
+        while !outstanding.is_empty() {
+            match ring.submit_and_wait(outstanding.len()) {
+                Ok(_) => {}
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted || e.raw_os_error() == Some(libc::EBUSY) => {}
+                Err(e) => {
+                    cancel_and_wait(ring, &outstanding);
+                    return Err(e);
+                }
+            }
+            for entry in ring.completion() {
+                let i = entry.user_data() as usize;
+                if let Some(position) = outstanding.iter().position(|&j| j == i) {
+                    outstanding.swap_remove(position);
+                }
+                let result = entry.result();
+                if result == -libc::EINTR || result == -libc::EAGAIN {
+                    pending.push(i);
+                } else if result < 0 {
+                    error = Some(io::Error::from_raw_os_error(-result));
+                } else if result == 0 {
+                    error = Some(eof());
+                } else {
+                    done[i] += result as usize;
+                    if done[i] < lens[i] {
+                        pending.push(i);
+                    }
+                }
+            }
+        }
+    }
+    error.map_or(Ok(()), Err)
+}
+
+/// Cancels the operations of the indices `outstanding`, and waits until they and the
+/// cancellations complete.
+///
+/// The kernel may still use the buffers of the operations until they complete, so this keeps
+/// waiting even if the ring fails.
+fn cancel_and_wait(ring: &mut IoUring, outstanding: &[usize]) {
+    trace_event!(DEBUG, outstanding = outstanding.len(), "cancel io_uring operations");
+    let mut remaining = outstanding.len();
+    for &i in outstanding {
+        let cancel = opcode::AsyncCancel::new(i as u64).build().user_data(CANCEL_USER_DATA);
+        // The cancellation uses no buffer. If the queue is full, the operation is waited for.
+        if unsafe { ring.submission().push(&cancel) }.is_ok() {
+            remaining += 1;
+        }
+    }
+    while remaining > 0 {
+        if ring.submit_and_wait(1).is_err() {
+            thread::yield_now();
+        }
+        let completed = ring.completion().count();
+        remaining = remaining.saturating_sub(completed);
+    }
+}
@@ -0,0 +1,174 @@
+//! Provides the features to read and write UUIDs (GUIDs) in the RFC 4122 and the Microsoft
+//! layouts.
+//!
+//! RFC 4122 stores all 16 bytes in the big-endian order of the string form. The Microsoft GUID
+//! layout, used by GPT, the registry and many Windows formats, stores the first three fields
+//! (32, 16 and 16 bits) little-endian, and the last 8 bytes as they are.
+
+use std::fmt;
+use std::io;
+use std::str;
+
+/// The byte layout of a stored UUID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UuidLayout {
+    /// All bytes in the order of the string form.
+    Rfc4122,
+    /// The first three fields little-endian, as the Windows `GUID` structure.
+    MicrosoftGuid,
+}
+
+impl UuidLayout {
+    /// Converts the stored `bytes` to the order of the string form, or back.
+    ///
+    /// The conversion is its own inverse.
+    pub fn swap(self, bytes: [u8; 16]) -> [u8; 16] {
+        let mut bytes = bytes;
+        if self == UuidLayout::MicrosoftGuid {
+            bytes[0..4].reverse();
+            bytes[4..6].reverse();
+            bytes[6..8].reverse();
+        }
+        bytes
+    }
+}
+
+/// A UUID, held in the order of its string form.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::uuid::Uuid;
+///
+/// let uuid: Uuid = "C12A7328-F81F-11D2-BA4B-00A0C93EC93B".parse().unwrap();
+/// assert_eq!(0xC1, uuid.as_bytes()[0]);
+/// assert_eq!("c12a7328-f81f-11d2-ba4b-00a0c93ec93b", uuid.to_string());
+/// assert_eq!(uuid, "{c12a7328-f81f-11d2-ba4b-00a0c93ec93b}".parse().unwrap());
+/// assert!("c12a7328".parse::<Uuid>().is_err());
+///
+/// ```
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uuid([u8; 16]);
+
+impl Uuid {
+    /// The nil UUID, whose bits are all 0.
+    pub const NIL: Uuid = Uuid([0; 16]);
+
+    /// Creates a new `Uuid` of `bytes` in the order of the string form.
+    pub fn from_bytes(bytes: [u8; 16]) -> Uuid {
+        Uuid(bytes)
+    }
+
+    /// Returns the bytes in the order of the string form.
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// Returns the version number (the high 4 bits of the 7th byte).
+    pub fn version(&self) -> u8 {
+        self.0[6] >> 4
+    }
+}
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if i == 4 || i == 6 || i == 8 || i == 10 {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// The error returned when a string is not a UUID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseUuidError;
+
+impl fmt::Display for ParseUuidError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid UUID string")
+    }
+}
+
+impl ::std::error::Error for ParseUuidError {}
+
+impl str::FromStr for Uuid {
+    type Err = ParseUuidError;
+
+    /// Parses the hyphenated form, optionally enclosed in braces.
+    fn from_str(s: &str) -> Result<Uuid, ParseUuidError> {
+        let s = s.strip_prefix('{').and_then(|s| s.strip_suffix('}')).unwrap_or(s);
+        let s = s.as_bytes();
+        if s.len() != 36 || [8, 13, 18, 23].iter().any(|&i| s[i] != b'-') {
+            return Err(ParseUuidError);
+        }
+        let mut digits = s.iter().filter(|&&c| c != b'-').map(|&c| (c as char).to_digit(16));
+        let mut bytes = [0_u8; 16];
+        for byte in bytes.iter_mut() {
+            match (digits.next(), digits.next()) {
+                (Some(Some(high)), Some(Some(low))) => *byte = (high << 4 | low) as u8,
+                _ => return Err(ParseUuidError),
+            }
+        }
+        Ok(Uuid(bytes))
+    }
+}
+
+/// Provides the features to read UUIDs.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::uuid::{Uuid, UuidLayout, UuidRead, UuidWrite};
+///
+/// // The GPT partition type of the EFI system partition.
+/// let uuid: Uuid = "c12a7328-f81f-11d2-ba4b-00a0c93ec93b".parse().unwrap();
+///
+/// let mut writer = vec![];
+/// writer.write_uuid(&uuid, UuidLayout::MicrosoftGuid).unwrap();
+/// writer.write_uuid(&uuid, UuidLayout::Rfc4122).unwrap();
+/// assert_eq!(vec![0x28, 0x73, 0x2A, 0xC1, 0x1F, 0xF8, 0xD2, 0x11, 0xBA, 0x4B], writer[..10].to_vec());
+/// assert_eq!(vec![0xC1, 0x2A, 0x73, 0x28], writer[16..20].to_vec());
+///
+/// let mut reader = io::Cursor::new(writer);
+/// assert_eq!(uuid, reader.read_uuid(UuidLayout::MicrosoftGuid).unwrap());
+/// assert_eq!(uuid, reader.read_uuid(UuidLayout::Rfc4122).unwrap());
+///
+/// ```
+///
+pub trait UuidRead: io::Read {
+    /// Reads a UUID stored in `layout`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(Uuid), otherwise Err(io::Error).
+    ///
+    fn read_uuid(&mut self, layout: UuidLayout) -> io::Result<Uuid> {
+        let mut bytes = [0_u8; 16];
+        self.read_exact(&mut bytes)?;
+        Ok(Uuid(layout.swap(bytes)))
+    }
+}
+
+impl<T> UuidRead for T
+    where T: io::Read {}
+
+/// Provides the features to write UUIDs.
+pub trait UuidWrite: io::Write {
+    /// Writes `uuid` in `layout`.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(()), otherwise Err(io::Error).
+    ///
+    fn write_uuid(&mut self, uuid: &Uuid, layout: UuidLayout) -> io::Result<()> {
+        self.write_all(&layout.swap(uuid.0))
+    }
+}
+
+impl<T> UuidWrite for T
+    where T: io::Write {}
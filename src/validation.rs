@@ -0,0 +1,182 @@
+//! Provides the strict and lenient modes of the validation of the parsed data.
+//!
+//! Real-world files often violate their own specifications: a padding is not zeroed, an enum
+//! has a value added by a later version, or a field duplicated in two headers disagrees.
+//! The parsers check them through `Validation`, which either fails (strict, the default) or
+//! reports a warning and coerces the value (lenient).
+
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+/// The kind of a specification violation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ViolationKind {
+    /// A padding has bytes other than the padding byte.
+    Padding,
+    /// A field has a value not defined by the specification.
+    UnknownValue,
+    /// The copies of a field duplicated in the data do not match.
+    FieldMismatch,
+}
+
+/// A specification violation found by `Validation`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// The kind of the violation.
+    pub kind: ViolationKind,
+    /// The description of the violation.
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.message.fmt(f)
+    }
+}
+
+impl ::std::error::Error for Violation {}
+
+/// The callback receiving the violations in the lenient mode.
+type WarningCallback = Arc<dyn Fn(&Violation) + Send + Sync>;
+
+/// The validation mode of the parsers.
+///
+/// In the strict mode, a violation is returned as `io::Error` of `InvalidData`, whose inner error
+/// is `Violation`. In the lenient mode, the violation is passed to the warning callback, and the
+/// parsing continues.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::sync::{Arc, Mutex};
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::validation::{Validation, ViolationKind};
+///
+/// let data = vec![0x00_u8, 0x00, 0x01, 0x00];
+///
+/// let mut reader = io::Cursor::new(data.clone());
+/// let error = reader.read_padding(4, 0x00, &Validation::strict()).unwrap_err();
+/// assert_eq!(io::ErrorKind::InvalidData, error.kind());
+///
+/// let warnings = Arc::new(Mutex::new(vec![]));
+/// let sink = warnings.clone();
+/// let validation = Validation::lenient().on_warning(move |v| sink.lock().unwrap().push(v.kind));
+///
+/// let mut reader = io::Cursor::new(data);
+/// reader.read_padding(4, 0x00, &validation).unwrap();
+/// assert_eq!(4, reader.position());
+///
+/// // An unknown enum value is coerced to the fallback.
+/// let compression = validation.check_known(7, None, "stored").unwrap();
+/// assert_eq!("stored", compression);
+///
+/// assert_eq!(vec![ViolationKind::Padding, ViolationKind::UnknownValue], *warnings.lock().unwrap());
+///
+/// ```
+///
+#[derive(Clone, Default)]
+pub struct Validation {
+    lenient: bool,
+    warning: Option<WarningCallback>,
+}
+
+impl Validation {
+    /// Creates a new `Validation` of the strict mode.
+    pub fn strict() -> Validation {
+        Validation::default()
+    }
+
+    /// Creates a new `Validation` of the lenient mode, which ignores the violations until
+    /// `on_warning` is set.
+    pub fn lenient() -> Validation {
+        Validation { lenient: true, warning: None }
+    }
+
+    /// Sets the callback receiving the violations in the lenient mode.
+    pub fn on_warning<F>(mut self, warning: F) -> Validation
+        where F: Fn(&Violation) + Send + Sync + 'static {
+        self.warning = Some(Arc::new(warning));
+        self
+    }
+
+    /// Returns true if this is the strict mode.
+    pub fn is_strict(&self) -> bool {
+        !self.lenient
+    }
+
+    /// Handles `violation`, returning `coerced` in the lenient mode.
+    ///
+    /// # Errors
+    ///
+    /// In the strict mode, returns Err(io::Error) of `InvalidData`, whose inner error is `violation`.
+    ///
+    pub fn report<T>(&self, violation: Violation, coerced: T) -> io::Result<T> {
+        if !self.lenient {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, violation));
+        }
+        if let Some(ref warning) = self.warning {
+            warning(&violation);
+        }
+        Ok(coerced)
+    }
+
+    /// Checks that all of `bytes` are `pad`.
+    ///
+    /// # Errors
+    ///
+    /// In the strict mode, if a byte is not `pad`, returns Err(io::Error) of `InvalidData`.
+    ///
+    pub fn check_padding(&self, bytes: &[u8], pad: u8) -> io::Result<()> {
+        match bytes.iter().position(|&b| b != pad) {
+            Some(i) => self.report(Violation {
+                kind: ViolationKind::Padding,
+                message: format!("padding byte {} is {:#04x}, not {:#04x}", i, bytes[i], pad),
+            }, ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the value `parsed` from the raw value `raw`, or `fallback` if it is unknown.
+    ///
+    /// # Errors
+    ///
+    /// In the strict mode, if `parsed` is None, returns Err(io::Error) of `InvalidData`.
+    ///
+    pub fn check_known<T>(&self, raw: u64, parsed: Option<T>, fallback: T) -> io::Result<T> {
+        match parsed {
+            Some(value) => Ok(value),
+            None => self.report(Violation {
+                kind: ViolationKind::UnknownValue,
+                message: format!("unknown value {}", raw),
+            }, fallback),
+        }
+    }
+
+    /// Checks that the copies `first` and `second` of the field `name` match.
+    ///
+    /// # Errors
+    ///
+    /// In the strict mode, if they differ, returns Err(io::Error) of `InvalidData`.
+    ///
+    pub fn check_match<T>(&self, name: &str, first: &T, second: &T) -> io::Result<()>
+        where T: PartialEq + fmt::Debug {
+        if first == second {
+            return Ok(());
+        }
+        self.report(Violation {
+            kind: ViolationKind::FieldMismatch,
+            message: format!("{} does not match: {:?} and {:?}", name, first, second),
+        }, ())
+    }
+}
+
+impl fmt::Debug for Validation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Validation")
+            .field("lenient", &self.lenient)
+            .field("warning", &self.warning.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,171 @@
+//! Provides the features to read and write variable-length integers (unsigned LEB128).
+//!
+//! Signed integers are ZigZag encoded before encoded as unsigned LEB128 (as in Protocol Buffers).
+//!
+//! The reads and writes over `io::Read` and `io::Write` need the `std` feature, and the encoding
+//! and decoding of the byte slices work without it.
+
+#[cfg(feature = "std")]
+use std::io;
+
+/// The maximum length of an encoded u64.
+pub const MAX_VARINT_LEN: usize = 10;
+
+/// Reads an unsigned LEB128 integer.
+///
+/// # Errors
+///
+/// If the encoded integer exceeds u64, returns Err(io::Error) of `InvalidData`.
+/// If reading fails, returns Err(io::Error).
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::varint;
+///
+/// let mut reader = io::Cursor::new(vec![0xE5_u8, 0x8E, 0x26, 0x7F]);
+/// assert_eq!(624_485, varint::read_u64(&mut reader).unwrap());
+/// assert_eq!(127, varint::read_u64(&mut reader).unwrap());
+///
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn read_u64<TRead>(reader: &mut TRead) -> io::Result<u64>
+    where TRead: io::Read {
+    let mut first = [0_u8; 1];
+    reader.read_exact(&mut first)?;
+    read_u64_continued(reader, first[0])
+}
+
+/// Reads the rest of an unsigned LEB128 integer whose first byte is `first`.
+#[cfg(feature = "std")]
+pub(crate) fn read_u64_continued<TRead>(reader: &mut TRead, first: u8) -> io::Result<u64>
+    where TRead: io::Read {
+    let mut result = (first & 0x7F) as u64;
+    let mut byte = first;
+    let mut shift = 7;
+    while byte & 0x80 != 0 {
+        let mut buf = [0_u8; 1];
+        reader.read_exact(&mut buf)?;
+        byte = buf[0];
+        if shift == 63 && byte > 1 || shift > 63 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "varint overflows u64"));
+        }
+        result |= ((byte & 0x7F) as u64) << shift;
+        shift += 7;
+    }
+    Ok(result)
+}
+
+/// Writes an unsigned LEB128 integer.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::varint;
+///
+/// let mut writer = vec![];
+/// varint::write_u64(&mut writer, 624_485).unwrap();
+/// varint::write_u64(&mut writer, u64::MAX).unwrap();
+/// assert_eq!(vec![0xE5, 0x8E, 0x26], writer[0..3].to_vec());
+/// assert_eq!(3 + varint::MAX_VARINT_LEN, writer.len());
+///
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn write_u64<TWrite>(writer: &mut TWrite, value: u64) -> io::Result<()>
+    where TWrite: io::Write {
+    let mut buf = [0_u8; MAX_VARINT_LEN];
+    let len = encode_u64(value, &mut buf);
+    writer.write_all(&buf[..len])
+}
+
+/// Encodes `value` as an unsigned LEB128 integer into `buf` and returns the encoded length.
+pub fn encode_u64(value: u64, buf: &mut [u8; MAX_VARINT_LEN]) -> usize {
+    let mut value = value;
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf[len] = byte;
+            return len + 1;
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+}
+
+/// Decodes the unsigned LEB128 integer of `bytes`, which ends at the first byte without the
+/// continuation bit.
+///
+/// Returns None if `bytes` ends before the integer, or it exceeds u64.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::varint;
+///
+/// assert_eq!(Some(624_485), varint::decode_u64(&[0xE5, 0x8E, 0x26]));
+/// assert_eq!(None, varint::decode_u64(&[0xE5, 0x8E]));
+///
+/// ```
+///
+pub fn decode_u64(bytes: &[u8]) -> Option<u64> {
+    let mut result = 0_u64;
+    for (i, &byte) in bytes.iter().enumerate() {
+        let shift = 7 * i;
+        if shift == 63 && byte > 1 || shift > 63 {
+            return None;
+        }
+        result |= ((byte & 0x7F) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(result);
+        }
+    }
+    None
+}
+
+/// Returns the encoded length of `value`.
+pub fn encoded_len(value: u64) -> usize {
+    let bits = 64 - (value | 1).leading_zeros() as usize;
+    bits.div_ceil(7)
+}
+
+/// Reads a ZigZag encoded signed LEB128 integer.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::varint;
+///
+/// let mut reader = io::Cursor::new(vec![0x03_u8, 0x04]);
+/// assert_eq!(-2, varint::read_i64(&mut reader).unwrap());
+/// assert_eq!(2, varint::read_i64(&mut reader).unwrap());
+///
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn read_i64<TRead>(reader: &mut TRead) -> io::Result<i64>
+    where TRead: io::Read {
+    read_u64(reader).map(zigzag_decode)
+}
+
+/// Writes a ZigZag encoded signed LEB128 integer.
+#[cfg(feature = "std")]
+pub fn write_i64<TWrite>(writer: &mut TWrite, value: i64) -> io::Result<()>
+    where TWrite: io::Write {
+    write_u64(writer, zigzag_encode(value))
+}
+
+/// Encodes a signed integer by ZigZag encoding.
+pub fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Decodes a ZigZag encoded integer.
+pub fn zigzag_decode(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
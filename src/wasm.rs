@@ -0,0 +1,70 @@
+//! Provides the adapters between the JavaScript buffers and the readers and writers, so that the
+//! same parsing code runs in the browser.
+//!
+//! The contents of `Uint8Array` and `ArrayBuffer` are copied into the Wasm memory once, and read by
+//! `BinaryRead` through `io::Cursor`. The bytes written to `Vec<u8>` by `BinaryWrite` are copied
+//! back into a new JavaScript buffer.
+//!
+//! This module is available with the `wasm` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! extern crate js_sys;
+//! extern crate mm_binary_io;
+//!
+//! use mm_binary_io::binary_read::BinaryRead;
+//! use mm_binary_io::binary_write::BinaryWrite;
+//! use mm_binary_io::endian::BigEndian;
+//! use mm_binary_io::wasm;
+//!
+//! # fn main() {
+//! let input = js_sys::Uint8Array::from(&[0x12_u8, 0x34][..]);
+//! let mut reader = wasm::reader_from_uint8_array(&input);
+//! let value = reader.read_integer::<BigEndian, u16>().unwrap();
+//!
+//! let mut writer = vec![];
+//! writer.write_integer::<BigEndian, _>(value + 1).unwrap();
+//! let output = wasm::to_uint8_array(&writer);
+//! assert_eq!(vec![0x12, 0x35], output.to_vec());
+//! # }
+//!
+//! ```
+
+use std::io;
+
+use js_sys::{ArrayBuffer, Uint8Array};
+
+/// Creates a reader of a copy of the contents of `array`.
+pub fn reader_from_uint8_array(array: &Uint8Array) -> io::Cursor<Vec<u8>> {
+    io::Cursor::new(array.to_vec())
+}
+
+/// Creates a reader of a copy of the contents of `buffer`.
+pub fn reader_from_array_buffer(buffer: &ArrayBuffer) -> io::Cursor<Vec<u8>> {
+    reader_from_uint8_array(&Uint8Array::new(buffer))
+}
+
+/// Copies `bytes` into a new `Uint8Array`.
+pub fn to_uint8_array(bytes: &[u8]) -> Uint8Array {
+    Uint8Array::from(bytes)
+}
+
+/// Copies `bytes` into a new `ArrayBuffer`.
+pub fn to_array_buffer(bytes: &[u8]) -> ArrayBuffer {
+    to_uint8_array(bytes).buffer()
+}
+
+/// Copies `bytes` into `array` from the head.
+///
+/// # Errors
+///
+/// If `array` is shorter than `bytes`, returns Err(io::Error) of `InvalidInput`.
+///
+pub fn copy_into_uint8_array(bytes: &[u8], array: &Uint8Array) -> io::Result<()> {
+    if (array.length() as usize) < bytes.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "the array is too short"));
+    }
+    array.subarray(0, bytes.len() as u32).copy_from(bytes);
+    Ok(())
+}
@@ -0,0 +1,250 @@
+//! Provides the xxHash (XXH32, XXH64 and XXH3) hashes as checksums.
+//!
+//! This module is available with the `xxhash` feature.
+
+use xxhash_rust::xxh3::Xxh3;
+use xxhash_rust::xxh32::Xxh32;
+use xxhash_rust::xxh64::Xxh64;
+
+use checksum::{Checksum, ChecksumReader, ChecksumWriter};
+
+/// Calculates the XXH32 hash incrementally.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::xxhash::{XxHash32, XxHash32Reader};
+///
+/// let mut hash = XxHash32::new();
+/// hash.update(b"");
+/// assert_eq!(0x02CC5D05, hash.digest());
+///
+/// let mut reader = XxHash32Reader::new(io::Cursor::new(b"123456789".to_vec()));
+/// reader.read_byte_array(9).unwrap();
+/// assert_eq!(0x937BAD67, reader.digest());
+///
+/// ```
+///
+#[derive(Clone)]
+pub struct XxHash32 {
+    hasher: Xxh32,
+    seed: u32,
+}
+
+impl XxHash32 {
+    /// Creates a new `XxHash32` with the seed 0.
+    pub fn new() -> XxHash32 {
+        XxHash32::with_seed(0)
+    }
+
+    /// Creates a new `XxHash32` with `seed`.
+    pub fn with_seed(seed: u32) -> XxHash32 {
+        XxHash32 {
+            hasher: Xxh32::new(seed),
+            seed,
+        }
+    }
+
+    /// Updates the hash with `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.hasher.update(bytes)
+    }
+
+    /// Returns the hash of the data so far.
+    pub fn digest(&self) -> u32 {
+        self.hasher.digest()
+    }
+
+    /// Resets the hash to the initial state with the same seed.
+    pub fn reset(&mut self) {
+        self.hasher.reset(self.seed)
+    }
+}
+
+impl Default for XxHash32 {
+    fn default() -> XxHash32 {
+        XxHash32::new()
+    }
+}
+
+impl Checksum for XxHash32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        XxHash32::update(self, bytes)
+    }
+
+    fn digest(&self) -> u32 {
+        XxHash32::digest(self)
+    }
+
+    fn reset(&mut self) {
+        XxHash32::reset(self)
+    }
+}
+
+/// Calculates the XXH64 hash incrementally.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::xxhash::XxHash64;
+///
+/// let mut hash = XxHash64::new();
+/// hash.update(b"");
+/// assert_eq!(0xEF46DB3751D8E999, hash.digest());
+///
+/// ```
+///
+#[derive(Clone)]
+pub struct XxHash64 {
+    hasher: Xxh64,
+    seed: u64,
+}
+
+impl XxHash64 {
+    /// Creates a new `XxHash64` with the seed 0.
+    pub fn new() -> XxHash64 {
+        XxHash64::with_seed(0)
+    }
+
+    /// Creates a new `XxHash64` with `seed`.
+    pub fn with_seed(seed: u64) -> XxHash64 {
+        XxHash64 {
+            hasher: Xxh64::new(seed),
+            seed,
+        }
+    }
+
+    /// Updates the hash with `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.hasher.update(bytes)
+    }
+
+    /// Returns the hash of the data so far.
+    pub fn digest(&self) -> u64 {
+        self.hasher.digest()
+    }
+
+    /// Resets the hash to the initial state with the same seed.
+    pub fn reset(&mut self) {
+        self.hasher.reset(self.seed)
+    }
+}
+
+impl Default for XxHash64 {
+    fn default() -> XxHash64 {
+        XxHash64::new()
+    }
+}
+
+impl Checksum for XxHash64 {
+    type Output = u64;
+
+    fn update(&mut self, bytes: &[u8]) {
+        XxHash64::update(self, bytes)
+    }
+
+    fn digest(&self) -> u64 {
+        XxHash64::digest(self)
+    }
+
+    fn reset(&mut self) {
+        XxHash64::reset(self)
+    }
+}
+
+/// Calculates the 64-bit XXH3 hash incrementally.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Write;
+/// use mm_binary_io::xxhash::XxHash3Writer;
+///
+/// let mut writer = XxHash3Writer::new(vec![]);
+/// writer.write_all(b"").unwrap();
+/// assert_eq!(0x2D06800538D394C2, writer.digest());
+///
+/// ```
+///
+#[derive(Clone)]
+pub struct XxHash3 {
+    hasher: Xxh3,
+}
+
+impl XxHash3 {
+    /// Creates a new `XxHash3` with the seed 0.
+    pub fn new() -> XxHash3 {
+        XxHash3::with_seed(0)
+    }
+
+    /// Creates a new `XxHash3` with `seed`.
+    pub fn with_seed(seed: u64) -> XxHash3 {
+        XxHash3 {
+            hasher: Xxh3::with_seed(seed),
+        }
+    }
+
+    /// Updates the hash with `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.hasher.update(bytes)
+    }
+
+    /// Returns the 64-bit hash of the data so far.
+    pub fn digest(&self) -> u64 {
+        self.hasher.digest()
+    }
+
+    /// Returns the 128-bit hash of the data so far.
+    pub fn digest128(&self) -> u128 {
+        self.hasher.digest128()
+    }
+
+    /// Resets the hash to the initial state with the same seed.
+    pub fn reset(&mut self) {
+        self.hasher.reset()
+    }
+}
+
+impl Default for XxHash3 {
+    fn default() -> XxHash3 {
+        XxHash3::new()
+    }
+}
+
+impl Checksum for XxHash3 {
+    type Output = u64;
+
+    fn update(&mut self, bytes: &[u8]) {
+        XxHash3::update(self, bytes)
+    }
+
+    fn digest(&self) -> u64 {
+        XxHash3::digest(self)
+    }
+
+    fn reset(&mut self) {
+        XxHash3::reset(self)
+    }
+}
+
+/// The reader which calculates the XXH32 hash of the bytes read.
+pub type XxHash32Reader<TRead> = ChecksumReader<TRead, XxHash32>;
+
+/// The writer which calculates the XXH32 hash of the bytes written.
+pub type XxHash32Writer<TWrite> = ChecksumWriter<TWrite, XxHash32>;
+
+/// The reader which calculates the XXH64 hash of the bytes read.
+pub type XxHash64Reader<TRead> = ChecksumReader<TRead, XxHash64>;
+
+/// The writer which calculates the XXH64 hash of the bytes written.
+pub type XxHash64Writer<TWrite> = ChecksumWriter<TWrite, XxHash64>;
+
+/// The reader which calculates the 64-bit XXH3 hash of the bytes read.
+pub type XxHash3Reader<TRead> = ChecksumReader<TRead, XxHash3>;
+
+/// The writer which calculates the 64-bit XXH3 hash of the bytes written.
+pub type XxHash3Writer<TWrite> = ChecksumWriter<TWrite, XxHash3>;
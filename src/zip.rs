@@ -0,0 +1,439 @@
+//! Provides the features to read and write the ZIP local file headers, central directory entries,
+//! and end of central directory (EOCD) records.
+//!
+//! All fields are little-endian. The ZIP64 extensions are not parsed; the fields which hold
+//! `0xFFFF` or `0xFFFF_FFFF` in a ZIP64 archive are returned as they are.
+
+use std::io;
+use std::io::SeekFrom;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::LittleEndian;
+use timestamp::DosDateTime;
+use validation::Validation;
+
+/// The signature of a local file header.
+pub const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4B50;
+/// The signature of a central directory entry.
+pub const CENTRAL_ENTRY_SIGNATURE: u32 = 0x0201_4B50;
+/// The signature of an end of central directory record.
+pub const EOCD_SIGNATURE: u32 = 0x0605_4B50;
+
+/// The size of an EOCD record without the comment.
+pub const EOCD_LEN: u64 = 22;
+
+/// The maximum distance of an EOCD record from the end of the archive.
+const MAX_EOCD_SEARCH: u64 = EOCD_LEN + u16::MAX as u64;
+
+/// A local file header.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LocalHeader {
+    /// The version needed to extract.
+    pub version_needed: u16,
+    /// The general purpose bit flags.
+    pub flags: u16,
+    /// The compression method (0 = stored, 8 = deflated).
+    pub compression: u16,
+    /// The modification time in the MS-DOS format.
+    pub mod_time: u16,
+    /// The modification date in the MS-DOS format.
+    pub mod_date: u16,
+    /// The CRC-32 of the uncompressed data.
+    pub crc32: u32,
+    /// The compressed size.
+    pub compressed_size: u32,
+    /// The uncompressed size.
+    pub uncompressed_size: u32,
+    /// The file name.
+    pub name: Vec<u8>,
+    /// The extra field.
+    pub extra: Vec<u8>,
+}
+
+/// A central directory entry.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CentralEntry {
+    /// The version made by.
+    pub version_made_by: u16,
+    /// The version needed to extract.
+    pub version_needed: u16,
+    /// The general purpose bit flags.
+    pub flags: u16,
+    /// The compression method (0 = stored, 8 = deflated).
+    pub compression: u16,
+    /// The modification time in the MS-DOS format.
+    pub mod_time: u16,
+    /// The modification date in the MS-DOS format.
+    pub mod_date: u16,
+    /// The CRC-32 of the uncompressed data.
+    pub crc32: u32,
+    /// The compressed size.
+    pub compressed_size: u32,
+    /// The uncompressed size.
+    pub uncompressed_size: u32,
+    /// The disk number where the file starts.
+    pub disk_number_start: u16,
+    /// The internal file attributes.
+    pub internal_attributes: u16,
+    /// The external file attributes.
+    pub external_attributes: u32,
+    /// The offset of the local file header.
+    pub local_header_offset: u32,
+    /// The file name.
+    pub name: Vec<u8>,
+    /// The extra field.
+    pub extra: Vec<u8>,
+    /// The file comment.
+    pub comment: Vec<u8>,
+}
+
+impl LocalHeader {
+    /// Returns the modification date and time decoded from `mod_date` and `mod_time`.
+    pub fn mod_date_time(&self) -> DosDateTime {
+        DosDateTime::from_dos(self.mod_date, self.mod_time)
+    }
+}
+
+impl CentralEntry {
+    /// Returns the modification date and time decoded from `mod_date` and `mod_time`.
+    pub fn mod_date_time(&self) -> DosDateTime {
+        DosDateTime::from_dos(self.mod_date, self.mod_time)
+    }
+}
+
+/// An end of central directory record.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EndOfCentralDirectory {
+    /// The number of this disk.
+    pub disk_number: u16,
+    /// The disk where the central directory starts.
+    pub central_directory_disk: u16,
+    /// The number of the central directory entries on this disk.
+    pub entries_on_disk: u16,
+    /// The total number of the central directory entries.
+    pub total_entries: u16,
+    /// The size of the central directory.
+    pub central_directory_size: u32,
+    /// The offset of the central directory.
+    pub central_directory_offset: u32,
+    /// The archive comment.
+    pub comment: Vec<u8>,
+}
+
+/// Reads a local file header.
+///
+/// # Errors
+///
+/// If the signature does not match, returns Err(io::Error) of `InvalidData`.
+/// If reading fails, returns Err(io::Error).
+///
+pub fn read_local_header<TRead>(reader: &mut TRead) -> io::Result<LocalHeader>
+    where TRead: io::Read {
+    read_signature(reader, LOCAL_HEADER_SIGNATURE, "invalid ZIP local header signature")?;
+    let version_needed = reader.read_integer::<LittleEndian, u16>()?;
+    let flags = reader.read_integer::<LittleEndian, u16>()?;
+    let compression = reader.read_integer::<LittleEndian, u16>()?;
+    let mod_time = reader.read_integer::<LittleEndian, u16>()?;
+    let mod_date = reader.read_integer::<LittleEndian, u16>()?;
+    let crc32 = reader.read_integer::<LittleEndian, u32>()?;
+    let compressed_size = reader.read_integer::<LittleEndian, u32>()?;
+    let uncompressed_size = reader.read_integer::<LittleEndian, u32>()?;
+    let name_len = reader.read_integer::<LittleEndian, u16>()?;
+    let extra_len = reader.read_integer::<LittleEndian, u16>()?;
+    let name = reader.read_byte_array(name_len as usize)?;
+    let extra = reader.read_byte_array(extra_len as usize)?;
+    Ok(LocalHeader {
+        version_needed,
+        flags,
+        compression,
+        mod_time,
+        mod_date,
+        crc32,
+        compressed_size,
+        uncompressed_size,
+        name,
+        extra,
+    })
+}
+
+/// Writes a local file header.
+///
+/// # Errors
+///
+/// If the name or the extra field is longer than 65535 bytes, returns Err(io::Error) of
+/// `InvalidInput`.
+/// If writing fails, returns Err(io::Error).
+///
+pub fn write_local_header<TWrite>(writer: &mut TWrite, header: &LocalHeader) -> io::Result<()>
+    where TWrite: io::Write {
+    let name_len = field_len(&header.name)?;
+    let extra_len = field_len(&header.extra)?;
+    writer.write_integer::<LittleEndian, _>(LOCAL_HEADER_SIGNATURE)?;
+    writer.write_integer::<LittleEndian, _>(header.version_needed)?;
+    writer.write_integer::<LittleEndian, _>(header.flags)?;
+    writer.write_integer::<LittleEndian, _>(header.compression)?;
+    writer.write_integer::<LittleEndian, _>(header.mod_time)?;
+    writer.write_integer::<LittleEndian, _>(header.mod_date)?;
+    writer.write_integer::<LittleEndian, _>(header.crc32)?;
+    writer.write_integer::<LittleEndian, _>(header.compressed_size)?;
+    writer.write_integer::<LittleEndian, _>(header.uncompressed_size)?;
+    writer.write_integer::<LittleEndian, _>(name_len)?;
+    writer.write_integer::<LittleEndian, _>(extra_len)?;
+    writer.write_all(&header.name)?;
+    writer.write_all(&header.extra)
+}
+
+/// Reads a central directory entry.
+///
+/// # Errors
+///
+/// If the signature does not match, returns Err(io::Error) of `InvalidData`.
+/// If reading fails, returns Err(io::Error).
+///
+pub fn read_central_entry<TRead>(reader: &mut TRead) -> io::Result<CentralEntry>
+    where TRead: io::Read {
+    read_signature(reader, CENTRAL_ENTRY_SIGNATURE, "invalid ZIP central directory signature")?;
+    let version_made_by = reader.read_integer::<LittleEndian, u16>()?;
+    let version_needed = reader.read_integer::<LittleEndian, u16>()?;
+    let flags = reader.read_integer::<LittleEndian, u16>()?;
+    let compression = reader.read_integer::<LittleEndian, u16>()?;
+    let mod_time = reader.read_integer::<LittleEndian, u16>()?;
+    let mod_date = reader.read_integer::<LittleEndian, u16>()?;
+    let crc32 = reader.read_integer::<LittleEndian, u32>()?;
+    let compressed_size = reader.read_integer::<LittleEndian, u32>()?;
+    let uncompressed_size = reader.read_integer::<LittleEndian, u32>()?;
+    let name_len = reader.read_integer::<LittleEndian, u16>()?;
+    let extra_len = reader.read_integer::<LittleEndian, u16>()?;
+    let comment_len = reader.read_integer::<LittleEndian, u16>()?;
+    let disk_number_start = reader.read_integer::<LittleEndian, u16>()?;
+    let internal_attributes = reader.read_integer::<LittleEndian, u16>()?;
+    let external_attributes = reader.read_integer::<LittleEndian, u32>()?;
+    let local_header_offset = reader.read_integer::<LittleEndian, u32>()?;
+    let name = reader.read_byte_array(name_len as usize)?;
+    let extra = reader.read_byte_array(extra_len as usize)?;
+    let comment = reader.read_byte_array(comment_len as usize)?;
+    Ok(CentralEntry {
+        version_made_by,
+        version_needed,
+        flags,
+        compression,
+        mod_time,
+        mod_date,
+        crc32,
+        compressed_size,
+        uncompressed_size,
+        disk_number_start,
+        internal_attributes,
+        external_attributes,
+        local_header_offset,
+        name,
+        extra,
+        comment,
+    })
+}
+
+/// Writes a central directory entry.
+///
+/// # Errors
+///
+/// If the name, the extra field or the comment is longer than 65535 bytes, returns
+/// Err(io::Error) of `InvalidInput`.
+/// If writing fails, returns Err(io::Error).
+///
+pub fn write_central_entry<TWrite>(writer: &mut TWrite, entry: &CentralEntry) -> io::Result<()>
+    where TWrite: io::Write {
+    let name_len = field_len(&entry.name)?;
+    let extra_len = field_len(&entry.extra)?;
+    let comment_len = field_len(&entry.comment)?;
+    writer.write_integer::<LittleEndian, _>(CENTRAL_ENTRY_SIGNATURE)?;
+    writer.write_integer::<LittleEndian, _>(entry.version_made_by)?;
+    writer.write_integer::<LittleEndian, _>(entry.version_needed)?;
+    writer.write_integer::<LittleEndian, _>(entry.flags)?;
+    writer.write_integer::<LittleEndian, _>(entry.compression)?;
+    writer.write_integer::<LittleEndian, _>(entry.mod_time)?;
+    writer.write_integer::<LittleEndian, _>(entry.mod_date)?;
+    writer.write_integer::<LittleEndian, _>(entry.crc32)?;
+    writer.write_integer::<LittleEndian, _>(entry.compressed_size)?;
+    writer.write_integer::<LittleEndian, _>(entry.uncompressed_size)?;
+    writer.write_integer::<LittleEndian, _>(name_len)?;
+    writer.write_integer::<LittleEndian, _>(extra_len)?;
+    writer.write_integer::<LittleEndian, _>(comment_len)?;
+    writer.write_integer::<LittleEndian, _>(entry.disk_number_start)?;
+    writer.write_integer::<LittleEndian, _>(entry.internal_attributes)?;
+    writer.write_integer::<LittleEndian, _>(entry.external_attributes)?;
+    writer.write_integer::<LittleEndian, _>(entry.local_header_offset)?;
+    writer.write_all(&entry.name)?;
+    writer.write_all(&entry.extra)?;
+    writer.write_all(&entry.comment)
+}
+
+/// Checks that the fields duplicated in `local` and its central directory `entry` match.
+///
+/// If the data descriptor flag (bit 3) is set, the CRC-32 and the sizes of `local` are not
+/// checked, because they are written after the data.
+///
+/// # Errors
+///
+/// If a field does not match in the strict mode, returns Err(io::Error) of `InvalidData`.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::validation::Validation;
+/// use mm_binary_io::zip::{self, CentralEntry, LocalHeader};
+///
+/// let local = LocalHeader { name: b"a.txt".to_vec(), crc32: 1, ..LocalHeader::default() };
+/// let entry = CentralEntry { name: b"a.txt".to_vec(), crc32: 2, ..CentralEntry::default() };
+///
+/// assert!(zip::validate_local_header(&local, &entry, &Validation::strict()).is_err());
+/// assert!(zip::validate_local_header(&local, &entry, &Validation::lenient()).is_ok());
+///
+/// ```
+///
+pub fn validate_local_header(local: &LocalHeader, entry: &CentralEntry, validation: &Validation) -> io::Result<()> {
+    validation.check_match("ZIP file name", &local.name, &entry.name)?;
+    validation.check_match("ZIP compression method", &local.compression, &entry.compression)?;
+    if local.flags & 0x0008 == 0 {
+        validation.check_match("ZIP CRC-32", &local.crc32, &entry.crc32)?;
+        validation.check_match("ZIP compressed size", &local.compressed_size, &entry.compressed_size)?;
+        validation.check_match("ZIP uncompressed size", &local.uncompressed_size, &entry.uncompressed_size)?;
+    }
+    Ok(())
+}
+
+/// Reads an end of central directory record.
+///
+/// # Errors
+///
+/// If the signature does not match, returns Err(io::Error) of `InvalidData`.
+/// If reading fails, returns Err(io::Error).
+///
+pub fn read_eocd<TRead>(reader: &mut TRead) -> io::Result<EndOfCentralDirectory>
+    where TRead: io::Read {
+    read_signature(reader, EOCD_SIGNATURE, "invalid ZIP end of central directory signature")?;
+    let disk_number = reader.read_integer::<LittleEndian, u16>()?;
+    let central_directory_disk = reader.read_integer::<LittleEndian, u16>()?;
+    let entries_on_disk = reader.read_integer::<LittleEndian, u16>()?;
+    let total_entries = reader.read_integer::<LittleEndian, u16>()?;
+    let central_directory_size = reader.read_integer::<LittleEndian, u32>()?;
+    let central_directory_offset = reader.read_integer::<LittleEndian, u32>()?;
+    let comment_len = reader.read_integer::<LittleEndian, u16>()?;
+    let comment = reader.read_byte_array(comment_len as usize)?;
+    Ok(EndOfCentralDirectory {
+        disk_number,
+        central_directory_disk,
+        entries_on_disk,
+        total_entries,
+        central_directory_size,
+        central_directory_offset,
+        comment,
+    })
+}
+
+/// Writes an end of central directory record.
+///
+/// # Errors
+///
+/// If the comment is longer than 65535 bytes, returns Err(io::Error) of `InvalidInput`.
+/// If writing fails, returns Err(io::Error).
+///
+pub fn write_eocd<TWrite>(writer: &mut TWrite, eocd: &EndOfCentralDirectory) -> io::Result<()>
+    where TWrite: io::Write {
+    let comment_len = field_len(&eocd.comment)?;
+    writer.write_integer::<LittleEndian, _>(EOCD_SIGNATURE)?;
+    writer.write_integer::<LittleEndian, _>(eocd.disk_number)?;
+    writer.write_integer::<LittleEndian, _>(eocd.central_directory_disk)?;
+    writer.write_integer::<LittleEndian, _>(eocd.entries_on_disk)?;
+    writer.write_integer::<LittleEndian, _>(eocd.total_entries)?;
+    writer.write_integer::<LittleEndian, _>(eocd.central_directory_size)?;
+    writer.write_integer::<LittleEndian, _>(eocd.central_directory_offset)?;
+    writer.write_integer::<LittleEndian, _>(comment_len)?;
+    writer.write_all(&eocd.comment)
+}
+
+/// Finds the end of central directory record by scanning backward from the end of the archive.
+///
+/// A candidate signature is accepted only if its comment extends exactly to the end of the
+/// archive, so a signature inside the comment is not mistaken for the record.
+///
+/// # Errors
+///
+/// If the function succeeds then Ok((the offset of the record, the record)).
+/// If no record is found, returns Err(io::Error) of `InvalidData`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use std::io::{Seek, SeekFrom};
+/// use mm_binary_io::zip::{self, CentralEntry, EndOfCentralDirectory, LocalHeader};
+///
+/// let mut archive = vec![];
+/// let local = LocalHeader { crc32: 0xD8932AAC, compressed_size: 2, uncompressed_size: 2, name: b"a.txt".to_vec(), ..LocalHeader::default() };
+/// zip::write_local_header(&mut archive, &local).unwrap();
+/// archive.extend_from_slice(b"hi");
+///
+/// let central_directory_offset = archive.len() as u32;
+/// let entry = CentralEntry { crc32: 0xD8932AAC, compressed_size: 2, uncompressed_size: 2, name: b"a.txt".to_vec(), ..CentralEntry::default() };
+/// zip::write_central_entry(&mut archive, &entry).unwrap();
+///
+/// let eocd = EndOfCentralDirectory {
+///     entries_on_disk: 1,
+///     total_entries: 1,
+///     central_directory_size: archive.len() as u32 - central_directory_offset,
+///     central_directory_offset,
+///     comment: b"PK\x05\x06 in the comment".to_vec(),
+///     ..EndOfCentralDirectory::default()
+/// };
+/// zip::write_eocd(&mut archive, &eocd).unwrap();
+///
+/// let mut reader = io::Cursor::new(archive);
+/// let (_, found) = zip::find_eocd(&mut reader).unwrap();
+/// assert_eq!(eocd, found);
+///
+/// reader.seek(SeekFrom::Start(found.central_directory_offset as u64)).unwrap();
+/// let entry = zip::read_central_entry(&mut reader).unwrap();
+/// reader.seek(SeekFrom::Start(entry.local_header_offset as u64)).unwrap();
+/// assert_eq!(local, zip::read_local_header(&mut reader).unwrap());
+///
+/// ```
+///
+pub fn find_eocd<TRead>(reader: &mut TRead) -> io::Result<(u64, EndOfCentralDirectory)>
+    where TRead: io::Read + io::Seek {
+    let len = reader.seek(SeekFrom::End(0))?;
+    if len < EOCD_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "ZIP end of central directory is not found"));
+    }
+    let start = len - len.min(MAX_EOCD_SEARCH);
+    reader.seek(SeekFrom::Start(start))?;
+    let tail = reader.read_byte_array((len - start) as usize)?;
+
+    let signature = EOCD_SIGNATURE.to_le_bytes();
+    for i in (0..=tail.len() - EOCD_LEN as usize).rev() {
+        if tail[i..i + 4] != signature {
+            continue;
+        }
+        let comment_len = u16::from_le_bytes([tail[i + 20], tail[i + 21]]) as usize;
+        if i + EOCD_LEN as usize + comment_len == tail.len() {
+            let eocd = read_eocd(&mut &tail[i..])?;
+            return Ok((start + i as u64, eocd));
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, "ZIP end of central directory is not found"))
+}
+
+fn read_signature<TRead>(reader: &mut TRead, expected: u32, message: &'static str) -> io::Result<()>
+    where TRead: io::Read {
+    if reader.read_integer::<LittleEndian, u32>()? != expected {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+    }
+    Ok(())
+}
+
+fn field_len(field: &[u8]) -> io::Result<u16> {
+    if field.len() > u16::MAX as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "ZIP field is longer than 65535 bytes"));
+    }
+    Ok(field.len() as u16)
+}
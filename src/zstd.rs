@@ -0,0 +1,219 @@
+//! Provides the adapters to read and write the Zstandard compressed data, so that the compressed
+//! sections of a format are parsed by the same field code.
+//!
+//! `ZstdReader` is an `io::Read`, so `BinaryRead` reads the decompressed data from it. The
+//! length-prefixed compressed blocks are read by `ZstdRead::read_zstd_block` into a buffer bounded
+//! by the caller, so that a hostile block can not exhaust the memory.
+//!
+//! This module is available with the `zstd` feature.
+
+use std::convert::TryFrom;
+use std::io;
+
+use zstd_crate::stream::read::Decoder;
+use zstd_crate::stream::write::Encoder;
+
+use binary_read::BinaryRead;
+use binary_write::BinaryWrite;
+use endian::Endian;
+use read_integer::ReadInteger;
+use write_integer::WriteInteger;
+
+/// Wraps a reader of the compressed data, and reads the decompressed data.
+///
+/// # Examples
+///
+/// ```
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::binary_write::BinaryWrite;
+/// use mm_binary_io::endian::LittleEndian;
+/// use mm_binary_io::zstd::{ZstdReader, ZstdWriter};
+///
+/// let mut writer = ZstdWriter::new(vec![], 3).unwrap();
+/// writer.write_integer_array::<LittleEndian, u32>(&[42; 1000]).unwrap();
+/// let compressed = writer.finish().unwrap();
+/// assert!(compressed.len() < 100);
+///
+/// let mut reader = ZstdReader::new(&compressed[..]).unwrap();
+/// assert_eq!(vec![42; 1000], reader.read_integer_array::<LittleEndian, u32>(1000).unwrap());
+///
+/// ```
+///
+pub struct ZstdReader<TRead>
+    where TRead: io::Read {
+    decoder: Decoder<'static, io::BufReader<TRead>>,
+}
+
+impl<TRead> ZstdReader<TRead>
+    where TRead: io::Read {
+    /// Creates a new `ZstdReader` of the data read from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// If the decompression context can not be created, returns Err(io::Error).
+    ///
+    pub fn new(reader: TRead) -> io::Result<ZstdReader<TRead>> {
+        Ok(ZstdReader { decoder: Decoder::new(reader)? })
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &TRead {
+        self.decoder.get_ref().get_ref()
+    }
+
+    /// Unwraps this `ZstdReader`, returning the underlying reader.
+    ///
+    /// The decoder may have read the underlying reader ahead of the decompressed data.
+    pub fn into_inner(self) -> TRead {
+        self.decoder.finish().into_inner()
+    }
+}
+
+impl<TRead> io::Read for ZstdReader<TRead>
+    where TRead: io::Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.decoder.read(buf)
+    }
+}
+
+/// Wraps a writer, and writes the data compressed.
+///
+/// `finish` must be called to write the end of the compressed data.
+pub struct ZstdWriter<TWrite>
+    where TWrite: io::Write {
+    encoder: Encoder<'static, TWrite>,
+}
+
+impl<TWrite> ZstdWriter<TWrite>
+    where TWrite: io::Write {
+    /// Creates a new `ZstdWriter` writing to `writer`, with the compression `level` from 1 to 22,
+    /// or 0 for the default level.
+    ///
+    /// # Errors
+    ///
+    /// If the compression context can not be created, returns Err(io::Error).
+    ///
+    pub fn new(writer: TWrite, level: i32) -> io::Result<ZstdWriter<TWrite>> {
+        Ok(ZstdWriter { encoder: Encoder::new(writer, level)? })
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &TWrite {
+        self.encoder.get_ref()
+    }
+
+    /// Writes the rest of the compressed data, and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// If the function succeeds then Ok(the underlying writer), otherwise Err(io::Error).
+    ///
+    pub fn finish(self) -> io::Result<TWrite> {
+        self.encoder.finish()
+    }
+}
+
+impl<TWrite> io::Write for ZstdWriter<TWrite>
+    where TWrite: io::Write {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.encoder.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.encoder.flush()
+    }
+}
+
+/// Provides the features to read the compressed regions and blocks.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use mm_binary_io::binary_read::BinaryRead;
+/// use mm_binary_io::endian::BigEndian;
+/// use mm_binary_io::zstd::{ZstdRead, ZstdWrite};
+///
+/// let mut writer = vec![];
+/// writer.write_zstd_block::<BigEndian, u32>(&[0xAB; 4096], 3).unwrap();
+/// writer.push(0xCD);
+///
+/// let mut reader = io::Cursor::new(writer.clone());
+/// assert_eq!(vec![0xAB; 4096], reader.read_zstd_block::<BigEndian, u32>(4096).unwrap());
+/// assert_eq!(0xCD, reader.read_integer::<BigEndian, u8>().unwrap());
+///
+/// // The block decompressing to more than the bound is rejected.
+/// let mut reader = io::Cursor::new(writer);
+/// let error = reader.read_zstd_block::<BigEndian, u32>(1024).unwrap_err();
+/// assert_eq!(io::ErrorKind::InvalidData, error.kind());
+///
+/// ```
+///
+pub trait ZstdRead: io::Read {
+    /// Returns a reader decompressing the next `compressed_len` bytes.
+    ///
+    /// The reader reads at most `compressed_len` bytes from this reader.
+    ///
+    /// # Errors
+    ///
+    /// If the decompression context can not be created, returns Err(io::Error).
+    ///
+    fn zstd_region(&mut self, compressed_len: u64) -> io::Result<ZstdReader<io::Take<&mut Self>>>
+        where Self: Sized {
+        ZstdReader::new(io::Read::take(self, compressed_len))
+    }
+
+    /// Reads a block of a `TLen` compressed length in `TEndian` followed by the compressed data,
+    /// and returns the decompressed data.
+    ///
+    /// The bytes of the block left after the compressed data are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * max_len - the maximum decompressed length, to prevent a broken or hostile block
+    ///   from exhausting the memory.
+    ///
+    /// # Errors
+    ///
+    /// If the decompressed data exceeds `max_len`, returns Err(io::Error) of `InvalidData`.
+    /// If the compressed data is broken or reading fails, returns Err(io::Error).
+    ///
+    fn read_zstd_block<TEndian, TLen>(&mut self, max_len: usize) -> io::Result<Vec<u8>>
+        where Self: Sized,
+              TEndian: Endian,
+              TLen: ReadInteger<OutputType=TLen> + Into<u64> {
+        let compressed_len: u64 = self.read_integer::<TEndian, TLen>()?.into();
+        let mut data = vec![];
+        let mut region = self.zstd_region(compressed_len)?;
+        io::Read::read_to_end(&mut io::Read::take(&mut region, max_len as u64 + 1), &mut data)?;
+        io::copy(&mut region.into_inner(), &mut io::sink())?;
+        if data.len() > max_len {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "decompressed block is too long"));
+        }
+        Ok(data)
+    }
+}
+
+impl<T> ZstdRead for T
+    where T: io::Read {}
+
+/// Provides the features to write the compressed blocks.
+pub trait ZstdWrite: io::Write {
+    /// Writes `data` compressed at `level`, prefixed by its `TLen` compressed length in `TEndian`.
+    ///
+    /// # Errors
+    ///
+    /// If the compressed length does not fit in `TLen`, returns Err(io::Error) of `InvalidInput`.
+    /// If the compression or writing fails, returns Err(io::Error).
+    ///
+    fn write_zstd_block<TEndian, TLen>(&mut self, data: &[u8], level: i32) -> io::Result<()>
+        where Self: Sized,
+              TEndian: Endian,
+              TLen: WriteInteger + TryFrom<u64> {
+        let compressed = zstd_crate::stream::encode_all(data, level)?;
+        self.write_frame::<TEndian, TLen>(&compressed)
+    }
+}
+
+impl<T> ZstdWrite for T
+    where T: io::Write {}